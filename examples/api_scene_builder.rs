@@ -0,0 +1,82 @@
+extern crate pbrt;
+
+use pbrt::core::builder::SceneBuilder;
+use pbrt::core::geometry::{Point3f, Vector3f};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::parser::World;
+use pbrt::core::pbrt::{Options, Spectrum};
+// std
+use std::process;
+
+fn main() {
+    // a red sphere on a gray disk, lit by a point light
+    let mut camera_params: ParamSet = ParamSet::default();
+    camera_params.add_float(String::from("fov"), 45.0);
+    let mut film_params: ParamSet = ParamSet::default();
+    film_params.add_int(String::from("xresolution"), 400);
+    film_params.add_int(String::from("yresolution"), 300);
+    film_params.add_string(String::from("filename"), String::from("builder.png"));
+    let mut sampler_params: ParamSet = ParamSet::default();
+    sampler_params.add_int(String::from("pixelsamples"), 16);
+    let mut light_params: ParamSet = ParamSet::default();
+    light_params.add_rgb_spectrum(String::from("I"), Spectrum::new(50.0));
+    light_params.add_point3f(String::from("from"),
+                             Point3f {
+                                 x: 2.0,
+                                 y: 4.0,
+                                 z: 3.0,
+                             });
+    let mut sphere_params: ParamSet = ParamSet::default();
+    sphere_params.add_float(String::from("radius"), 1.0);
+    let mut red: ParamSet = ParamSet::default();
+    red.add_rgb_spectrum(String::from("Kd"), Spectrum::rgb(0.8, 0.1, 0.1));
+    let mut disk_params: ParamSet = ParamSet::default();
+    disk_params.add_float(String::from("radius"), 5.0);
+    let mut gray: ParamSet = ParamSet::default();
+    gray.add_rgb_spectrum(String::from("Kd"), Spectrum::new(0.5));
+
+    let mut builder: SceneBuilder = SceneBuilder::new(None);
+    builder
+        .look_at(Point3f {
+                     x: 0.0,
+                     y: -6.0,
+                     z: 2.0,
+                 },
+                 Point3f {
+                     x: 0.0,
+                     y: 0.0,
+                     z: 1.0,
+                 },
+                 Vector3f {
+                     x: 0.0,
+                     y: 0.0,
+                     z: 1.0,
+                 })
+        .camera("perspective", camera_params)
+        .film("image", film_params)
+        .sampler("halton", sampler_params)
+        .world_begin()
+        .add_light("point", light_params)
+        .attribute_scope(|b| {
+            b.translate(Vector3f {
+                               x: 0.0,
+                               y: 0.0,
+                               z: 1.0,
+                           })
+                .add_shape_with_material("sphere", sphere_params, "matte", red);
+        })
+        .add_shape_with_material("disk", disk_params, "matte", gray);
+    let world: World = match builder.build() {
+        Ok(world) => world,
+        Err(e) => {
+            println!("{}", e);
+            process::exit(1);
+        }
+    };
+    for warning in &world.warnings {
+        println!("{}", warning);
+    }
+    let mut sampler = world.sampler;
+    let mut integrator = world.integrator;
+    integrator.render(&world.scene, &world.camera, &mut sampler, &Options { num_threads: 0_u8 });
+}
@@ -21,6 +21,7 @@ fn main() {
         t_max: std::f32::INFINITY,
         time: 0.0,
         differential: None,
+        medium: None,
     };
     let ox = ray.o.x;
     let oy = ray.o.y;
@@ -68,6 +69,7 @@ fn main() {
         t_max: std::f32::INFINITY,
         time: 0.0,
         differential: None,
+        medium: None,
     };
     let ox = ray.o.x;
     let oy = ray.o.y;
@@ -19,6 +19,7 @@ fn main() {
         t_max: std::f32::INFINITY,
         time: 0.0,
         differential: None,
+        medium: None,
     };
 
     println!("{:?}", ray);
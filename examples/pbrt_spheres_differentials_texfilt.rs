@@ -7,9 +7,10 @@ use pbrt::core::camera::Camera;
 use pbrt::core::filter::Filter;
 use pbrt::core::geometry::{Bounds2f, Bounds2i, Normal3f, Point2f, Point2i, Point3f, Vector2f,
                            Vector3f};
-use pbrt::core::integrator::SamplerIntegrator;
+use pbrt::core::integrator::Integrator;
 use pbrt::core::light::Light;
 use pbrt::core::mipmap::ImageWrap;
+use pbrt::core::medium::MediumInterface;
 use pbrt::core::pbrt::{Float, Spectrum};
 use pbrt::core::primitive::{GeometricPrimitive, Primitive};
 use pbrt::core::transform::{AnimatedTransform, Transform};
@@ -373,13 +374,23 @@ fn main() {
         let sigma = Arc::new(ConstantTexture::new(0.0 as Float));
         let matte = Arc::new(MatteMaterial::new(kd, sigma));
         for triangle in render_options.triangles {
-            let geo_prim = Arc::new(GeometricPrimitive::new(triangle, matte.clone(), None));
+            let geo_prim = Arc::new(GeometricPrimitive::new(
+                triangle,
+                Some(matte.clone()),
+                None,
+                MediumInterface::default(),
+            ));
             render_options.primitives.push(geo_prim.clone());
         }
         if matches.opt_present("m") {
             // use only matte materials
             for sphere in render_options.spheres {
-                let geo_prim = Arc::new(GeometricPrimitive::new(sphere, matte.clone(), None));
+                let geo_prim = Arc::new(GeometricPrimitive::new(
+                    sphere,
+                    Some(matte.clone()),
+                    None,
+                    MediumInterface::default(),
+                ));
                 render_options.primitives.push(geo_prim.clone());
             }
         } else {
@@ -387,10 +398,20 @@ fn main() {
             let mut sphere_counter: u8 = 0;
             for sphere in render_options.spheres {
                 if sphere_counter == 0 {
-                    let geo_prim = Arc::new(GeometricPrimitive::new(sphere, mirror.clone(), None));
+                    let geo_prim = Arc::new(GeometricPrimitive::new(
+                        sphere,
+                        Some(mirror.clone()),
+                        None,
+                        MediumInterface::default(),
+                    ));
                     render_options.primitives.push(geo_prim.clone());
                 } else {
-                    let geo_prim = Arc::new(GeometricPrimitive::new(sphere, glass.clone(), None));
+                    let geo_prim = Arc::new(GeometricPrimitive::new(
+                        sphere,
+                        Some(glass.clone()),
+                        None,
+                        MediumInterface::default(),
+                    ));
                     render_options.primitives.push(geo_prim.clone());
                 }
                 sphere_counter += 1;
@@ -422,16 +443,31 @@ fn main() {
         let sigma = Arc::new(ConstantTexture::new(0.0 as Float));
         let matte = Arc::new(MatteMaterial::new(checker, sigma));
         for triangle in render_options.triangles {
-            let geo_prim = Arc::new(GeometricPrimitive::new(triangle, matte.clone(), None));
+            let geo_prim = Arc::new(GeometricPrimitive::new(
+                triangle,
+                Some(matte.clone()),
+                None,
+                MediumInterface::default(),
+            ));
             render_options.primitives.push(geo_prim.clone());
         }
         let mut sphere_counter: u8 = 0;
         for sphere in render_options.spheres {
             if sphere_counter == 0 {
-                let geo_prim = Arc::new(GeometricPrimitive::new(sphere, mirror.clone(), None));
+                let geo_prim = Arc::new(GeometricPrimitive::new(
+                    sphere,
+                    Some(mirror.clone()),
+                    None,
+                    MediumInterface::default(),
+                ));
                 render_options.primitives.push(geo_prim.clone());
             } else {
-                let geo_prim = Arc::new(GeometricPrimitive::new(sphere, glass.clone(), None));
+                let geo_prim = Arc::new(GeometricPrimitive::new(
+                    sphere,
+                    Some(glass.clone()),
+                    None,
+                    MediumInterface::default(),
+                ));
                 render_options.primitives.push(geo_prim.clone());
             }
             sphere_counter += 1;
@@ -466,16 +502,31 @@ fn main() {
         let sigma = Arc::new(ConstantTexture::new(0.0 as Float));
         let matte = Arc::new(MatteMaterial::new(lines_tex, sigma));
         for triangle in render_options.triangles {
-            let geo_prim = Arc::new(GeometricPrimitive::new(triangle, matte.clone(), None));
+            let geo_prim = Arc::new(GeometricPrimitive::new(
+                triangle,
+                Some(matte.clone()),
+                None,
+                MediumInterface::default(),
+            ));
             render_options.primitives.push(geo_prim.clone());
         }
         let mut sphere_counter: u8 = 0;
         for sphere in render_options.spheres {
             if sphere_counter == 0 {
-                let geo_prim = Arc::new(GeometricPrimitive::new(sphere, mirror.clone(), None));
+                let geo_prim = Arc::new(GeometricPrimitive::new(
+                    sphere,
+                    Some(mirror.clone()),
+                    None,
+                    MediumInterface::default(),
+                ));
                 render_options.primitives.push(geo_prim.clone());
             } else {
-                let geo_prim = Arc::new(GeometricPrimitive::new(sphere, glass.clone(), None));
+                let geo_prim = Arc::new(GeometricPrimitive::new(
+                    sphere,
+                    Some(glass.clone()),
+                    None,
+                    MediumInterface::default(),
+                ));
                 render_options.primitives.push(geo_prim.clone());
             }
             sphere_counter += 1;
@@ -567,11 +618,13 @@ fn main() {
         focaldistance,
         fov,
         film.clone(),
+        None,
     ));
     let mut sampler: Box<Sampler + Sync + Send> = Box::new(ZeroTwoSequenceSampler::default());
     let sample_bounds: Bounds2i = film.get_sample_bounds();
-    let mut integrator: Box<SamplerIntegrator + Send + Sync> = Box::new(
+    let mut integrator: Box<Integrator + Send + Sync> = Box::new(
         DirectLightingIntegrator::new(LightStrategy::UniformSampleAll, 10, sample_bounds),
     );
-    pbrt::render(&scene, &camera, &mut sampler, &mut integrator, 0_u8);
+    let options = pbrt::core::pbrt::Options { num_threads: 0_u8 };
+    integrator.render(&scene, &camera, &mut sampler, &options);
 }
@@ -6,9 +6,10 @@ use pbrt::core::camera::Camera;
 use pbrt::core::filter::Filter;
 use pbrt::core::geometry::{Bounds2f, Bounds2i, Normal3f, Point2f, Point2i, Point3f, Vector2f,
                            Vector3f};
-use pbrt::core::integrator::SamplerIntegrator;
+use pbrt::core::integrator::Integrator;
 use pbrt::core::light::Light;
-use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::pbrt::{Float, Options, Spectrum};
 use pbrt::core::primitive::{GeometricPrimitive, Primitive};
 use pbrt::core::transform::{AnimatedTransform, Transform};
 use pbrt::core::film::Film;
@@ -50,7 +51,11 @@ impl SceneDescriptionBuilder {
         light_to_world: &Transform,
         i: &Spectrum,
     ) -> &mut SceneDescriptionBuilder {
-        let point_light = Arc::new(PointLight::new(light_to_world, &i));
+        let point_light = Arc::new(PointLight::new(
+            light_to_world,
+            &MediumInterface::default(),
+            &i,
+        ));
         self.lights.push(point_light);
         self
     }
@@ -1952,10 +1957,20 @@ fn main() {
     let mut triangle_count: usize = 0;
     for triangle in render_options.triangles {
         if triangle_count < 72 {
-            let geo_prim = Arc::new(GeometricPrimitive::new(triangle, plastic1.clone(), None));
+            let geo_prim = Arc::new(GeometricPrimitive::new(
+                triangle,
+                Some(plastic1.clone()),
+                None,
+                MediumInterface::default(),
+            ));
             render_options.primitives.push(geo_prim.clone());
         } else {
-            let geo_prim = Arc::new(GeometricPrimitive::new(triangle, plastic2.clone(), None));
+            let geo_prim = Arc::new(GeometricPrimitive::new(
+                triangle,
+                Some(plastic2.clone()),
+                None,
+                MediumInterface::default(),
+            ));
             render_options.primitives.push(geo_prim.clone());
         }
         triangle_count += 1;
@@ -1965,7 +1980,12 @@ fn main() {
     let sigma = Arc::new(ConstantTexture::new(0.0 as Float));
     let matte = Arc::new(MatteMaterial::new(kd, sigma));
     for disk in render_options.disks {
-        let geo_prim = Arc::new(GeometricPrimitive::new(disk, matte.clone(), None));
+        let geo_prim = Arc::new(GeometricPrimitive::new(
+            disk,
+            Some(matte.clone()),
+            None,
+            MediumInterface::default(),
+        ));
         render_options.primitives.push(geo_prim.clone());
     }
     // TMP: process SceneDescription before handing primitives to BVHAccel
@@ -2035,11 +2055,12 @@ fn main() {
         focaldistance,
         fov,
         film.clone(),
+        None,
     ));
     let mut sampler: Box<Sampler + Sync + Send> = Box::new(ZeroTwoSequenceSampler::default());
     let sample_bounds: Bounds2i = film.get_sample_bounds();
-    let mut integrator: Box<SamplerIntegrator + Send + Sync> = Box::new(
+    let mut integrator: Box<Integrator + Send + Sync> = Box::new(
         DirectLightingIntegrator::new(LightStrategy::UniformSampleAll, 10, sample_bounds),
     );
-    pbrt::render(&scene, &camera, &mut sampler, &mut integrator, 0_u8);
+    integrator.render(&scene, &camera, &mut sampler, &Options { num_threads: 0_u8 });
 }
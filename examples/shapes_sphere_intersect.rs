@@ -45,6 +45,7 @@ fn main() {
         t_max: 17.7973537,
         time: 0.0,
         differential: None,
+        medium: None,
     };
     println!("translate = {:?}", translate);
     println!("r = {:?}", r);
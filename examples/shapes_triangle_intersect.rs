@@ -92,6 +92,7 @@ fn main() {
         t_max: std::f32::INFINITY,
         time: 0.0,
         differential: None,
+        medium: None,
     };
     println!("r = {:?}", r);
     if let Some((_isect, t_hit)) = <Triangle as Shape>::intersect(&triangle, &r) {
@@ -110,6 +111,7 @@ fn main() {
         t_max: std::f32::INFINITY,
         time: 0.0,
         differential: None,
+        medium: None,
     };
     println!("r = {:?}", r);
     if let Some((_isect, t_hit)) = <Triangle as Shape>::intersect(&triangle, &r) {
@@ -25,6 +25,7 @@ fn main() {
         t_max: std::f32::INFINITY,
         time: 0.0,
         differential: None,
+        medium: None,
     };
     let mut o_error: Vector3f = Vector3f::default();
     let mut d_error: Vector3f = Vector3f::default();
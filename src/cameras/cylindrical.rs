@@ -0,0 +1,226 @@
+// std
+use std;
+use std::sync::Arc;
+// pbrt
+use core::camera::{Camera, CameraSample, PointCamera};
+use core::film::Film;
+use core::geometry::{Point2f, Point3f, Ray, RayDifferential, Vector3f};
+use core::geometry::vec3_normalize;
+use core::interaction::InteractionCommon;
+use core::light::VisibilityTester;
+use core::medium::Medium;
+use core::paramset::ParamSet;
+use core::pbrt::{Float, Spectrum};
+use core::pbrt::{clamp_t, lerp, radians};
+use core::transform::AnimatedTransform;
+
+// see Arnold's cyl_camera
+
+/// Projects the scene onto a cylinder around the camera's **y** axis,
+/// with independent horizontal and vertical fields of view.
+pub struct CylindricalCamera {
+    // inherited from Camera (see camera.h)
+    pub camera_to_world: AnimatedTransform,
+    pub shutter_open: Float,
+    pub shutter_close: Float,
+    pub film: Arc<Film>,
+    pub medium: Option<Arc<Medium + Send + Sync>>,
+    // private data
+    hfov: Float,
+    vfov: Float,
+    projective: bool,
+    tan_half_vfov: Float,
+}
+
+impl CylindricalCamera {
+    pub fn new(
+        camera_to_world: AnimatedTransform,
+        shutter_open: Float,
+        shutter_close: Float,
+        film: Arc<Film>,
+        medium: Option<Arc<Medium + Send + Sync>>,
+        hfov: Float,
+        vfov: Float,
+        projective: bool,
+    ) -> Self {
+        CylindricalCamera {
+            camera_to_world: camera_to_world,
+            shutter_open: shutter_open,
+            shutter_close: shutter_close,
+            film: film,
+            medium: medium,
+            hfov: radians(hfov),
+            vfov: radians(vfov),
+            projective: projective,
+            tan_half_vfov: (radians(vfov) * 0.5 as Float).tan(),
+        }
+    }
+    pub fn create(
+        params: &ParamSet,
+        cam2world: AnimatedTransform,
+        film: Arc<Film>,
+        medium: Option<Arc<Medium + Send + Sync>>,
+        warnings: &mut Vec<String>,
+    ) -> Box<Camera + Send + Sync> {
+        let mut shutteropen: Float = params.find_one_float(String::from("shutteropen"), 0.0);
+        let mut shutterclose: Float = params.find_one_float(String::from("shutterclose"), 1.0);
+        if shutterclose < shutteropen {
+            warnings.push(format!(
+                "Shutter close time [{}] < shutter open [{}].  Swapping them.",
+                shutterclose, shutteropen
+            ));
+            std::mem::swap(&mut shutterclose, &mut shutteropen);
+        }
+        // same defaults as Arnold's cyl_camera
+        let mut hfov: Float = params.find_one_float(String::from("hfov"), 60.0);
+        let mut vfov: Float = params.find_one_float(String::from("vfov"), 90.0);
+        let projective: bool = params.find_one_bool(String::from("projective"), true);
+        if hfov <= 0.0 as Float || hfov > 360.0 as Float {
+            warnings.push(format!("\"hfov\" {} out of range, clamping to (0, 360]", hfov));
+            hfov = clamp_t(hfov, 1.0, 360.0);
+        }
+        // a projective mapping can't reach the poles
+        let max_vfov: Float = if projective { 179.0 } else { 180.0 };
+        if vfov <= 0.0 as Float || vfov > max_vfov {
+            warnings.push(format!(
+                "\"vfov\" {} out of range, clamping to (0, {}]",
+                vfov, max_vfov
+            ));
+            vfov = clamp_t(vfov, 1.0, max_vfov);
+        }
+        let camera = Box::new(CylindricalCamera::new(
+            cam2world,
+            shutteropen,
+            shutterclose,
+            film,
+            medium,
+            hfov,
+            vfov,
+            projective,
+        ));
+        camera
+    }
+    /// Direction (in camera space) for a raster position.
+    fn raster_to_direction(&self, p_film: &Point2f) -> Vector3f {
+        // the azimuth is measured from the **z** axis, the film's
+        // center looks straight ahead
+        let u: Float = p_film.x / self.film.full_resolution.x as Float;
+        let v: Float = p_film.y / self.film.full_resolution.y as Float;
+        let phi: Float = (u - 0.5 as Float) * self.hfov;
+        if self.projective {
+            // height on a unit cylinder, like a perspective camera
+            // would do vertically
+            let h: Float = self.tan_half_vfov * (1.0 as Float - 2.0 as Float * v);
+            vec3_normalize(&Vector3f {
+                x: phi.sin(),
+                y: h,
+                z: phi.cos(),
+            })
+        } else {
+            // elevation angle proportional to the film height
+            let theta: Float = (0.5 as Float - v) * self.vfov;
+            Vector3f {
+                x: theta.cos() * phi.sin(),
+                y: theta.sin(),
+                z: theta.cos() * phi.cos(),
+            }
+        }
+    }
+    /// Raster position for a (normalized) direction in camera space,
+    /// together with the density of directions per unit solid angle
+    /// (with the film sampled uniformly), or _None_ straight up or
+    /// down.
+    fn direction_to_raster(&self, dir: &Vector3f) -> Option<(Point2f, Float)> {
+        let r_xz: Float = (dir.x * dir.x + dir.z * dir.z).sqrt();
+        if r_xz == 0.0 as Float {
+            // never hit by any raster position
+            return None;
+        }
+        let phi: Float = dir.x.atan2(dir.z);
+        let u: Float = phi / self.hfov + 0.5 as Float;
+        let v: Float;
+        let pdf_dir: Float;
+        if self.projective {
+            let h: Float = dir.y / r_xz;
+            v = 0.5 as Float - h / (2.0 as Float * self.tan_half_vfov);
+            // dw = dphi dh / (1 + h^2)^(3/2)
+            pdf_dir = (1.0 as Float + h * h).powf(1.5)
+                / (self.hfov * 2.0 as Float * self.tan_half_vfov);
+        } else {
+            let theta: Float = dir.y.atan2(r_xz);
+            v = 0.5 as Float - theta / self.vfov;
+            // dw = cos(theta) dphi dtheta
+            pdf_dir = 1.0 as Float / (self.hfov * self.vfov * r_xz);
+        }
+        let p_raster: Point2f = Point2f {
+            x: u * self.film.full_resolution.x as Float,
+            y: v * self.film.full_resolution.y as Float,
+        };
+        Some((p_raster, pdf_dir))
+    }
+    fn point_camera(&self) -> PointCamera {
+        PointCamera {
+            camera_to_world: &self.camera_to_world,
+            film: &self.film,
+            medium: &self.medium,
+        }
+    }
+}
+
+impl Camera for CylindricalCamera {
+    fn generate_ray_differential(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
+        // TODO: ProfilePhase prof(Prof::GenerateCameraRay);
+        let dir: Vector3f = self.raster_to_direction(&sample.p_film);
+        // see Camera::GenerateRayDifferential() in camera.cpp
+        let rx_direction: Vector3f = self.raster_to_direction(&Point2f {
+            x: sample.p_film.x + 1.0 as Float,
+            y: sample.p_film.y,
+        });
+        let ry_direction: Vector3f = self.raster_to_direction(&Point2f {
+            x: sample.p_film.x,
+            y: sample.p_film.y + 1.0 as Float,
+        });
+        let in_ray: Ray = Ray {
+            o: Point3f::default(),
+            d: dir,
+            t_max: std::f32::INFINITY,
+            time: lerp(sample.time, self.shutter_open, self.shutter_close),
+            differential: Some(RayDifferential {
+                rx_origin: Point3f::default(),
+                ry_origin: Point3f::default(),
+                rx_direction: rx_direction,
+                ry_direction: ry_direction,
+            }),
+            medium: self.medium.clone(),
+        };
+        *ray = self.camera_to_world.transform_ray(&in_ray);
+        1.0
+    }
+    fn we(&self, ray: &Ray, p_raster2: Option<&mut Point2f>) -> Spectrum {
+        self.point_camera().we(ray, p_raster2, |dir| self.direction_to_raster(dir))
+    }
+    fn pdf_we(&self, ray: &Ray) -> (Float, Float) {
+        self.point_camera().pdf_we(ray, |dir| self.direction_to_raster(dir))
+    }
+    fn sample_wi(
+        &self,
+        iref: &InteractionCommon,
+        _u: &Point2f,
+        wi: &mut Vector3f,
+        pdf: &mut Float,
+        p_raster: &mut Point2f,
+        vis: &mut VisibilityTester,
+    ) -> Spectrum {
+        self.point_camera()
+            .sample_wi(iref, wi, pdf, p_raster, vis, |dir| self.direction_to_raster(dir))
+    }
+    fn get_film(&self) -> Arc<Film> {
+        self.film.clone()
+    }
+    fn get_shutter_open(&self) -> Float {
+        self.shutter_open
+    }
+    fn get_shutter_close(&self) -> Float {
+        self.shutter_close
+    }
+}
@@ -0,0 +1,174 @@
+// std
+use std;
+use std::f32::consts::PI;
+use std::sync::Arc;
+// pbrt
+use core::camera::{Camera, CameraSample, PointCamera};
+use core::film::Film;
+use core::geometry::{Point2f, Point3f, Ray, RayDifferential, Vector3f};
+use core::interaction::InteractionCommon;
+use core::light::VisibilityTester;
+use core::medium::Medium;
+use core::paramset::ParamSet;
+use core::pbrt::{Float, Spectrum};
+use core::pbrt::{clamp_t, lerp};
+use core::transform::AnimatedTransform;
+
+// see environment.h
+
+/// Covers the full sphere of directions around the camera position,
+/// using a latitude-longitude (equirectangular) mapping onto the film.
+pub struct EnvironmentCamera {
+    // inherited from Camera (see camera.h)
+    pub camera_to_world: AnimatedTransform,
+    pub shutter_open: Float,
+    pub shutter_close: Float,
+    pub film: Arc<Film>,
+    pub medium: Option<Arc<Medium + Send + Sync>>,
+}
+
+impl EnvironmentCamera {
+    pub fn new(
+        camera_to_world: AnimatedTransform,
+        shutter_open: Float,
+        shutter_close: Float,
+        film: Arc<Film>,
+        medium: Option<Arc<Medium + Send + Sync>>,
+    ) -> Self {
+        EnvironmentCamera {
+            camera_to_world: camera_to_world,
+            shutter_open: shutter_open,
+            shutter_close: shutter_close,
+            film: film,
+            medium: medium,
+        }
+    }
+    pub fn create(
+        params: &ParamSet,
+        cam2world: AnimatedTransform,
+        film: Arc<Film>,
+        medium: Option<Arc<Medium + Send + Sync>>,
+        warnings: &mut Vec<String>,
+    ) -> Box<Camera + Send + Sync> {
+        let mut shutteropen: Float = params.find_one_float(String::from("shutteropen"), 0.0);
+        let mut shutterclose: Float = params.find_one_float(String::from("shutterclose"), 1.0);
+        if shutterclose < shutteropen {
+            warnings.push(format!(
+                "Shutter close time [{}] < shutter open [{}].  Swapping them.",
+                shutterclose, shutteropen
+            ));
+            std::mem::swap(&mut shutterclose, &mut shutteropen);
+        }
+        // parameters of the projective cameras are accepted, but not needed
+        let _lensradius: Float = params.find_one_float(String::from("lensradius"), 0.0);
+        let _focaldistance: Float = params.find_one_float(String::from("focaldistance"), 1e30);
+        let _frame: Float = params.find_one_float(
+            String::from("frameaspectratio"),
+            (film.full_resolution.x as Float) / (film.full_resolution.y as Float),
+        );
+        let _sw: Vec<Float> = params.find_float(String::from("screenwindow"));
+        let camera = Box::new(EnvironmentCamera::new(
+            cam2world,
+            shutteropen,
+            shutterclose,
+            film,
+            medium,
+        ));
+        camera
+    }
+    /// Direction (in camera space) for a raster position.
+    fn raster_to_direction(&self, p_film: &Point2f) -> Vector3f {
+        // compute environment camera ray direction
+        let theta: Float = PI * p_film.y / self.film.full_resolution.y as Float;
+        let phi: Float = 2.0 as Float * PI * p_film.x / self.film.full_resolution.x as Float;
+        Vector3f {
+            x: theta.sin() * phi.cos(),
+            y: theta.cos(),
+            z: theta.sin() * phi.sin(),
+        }
+    }
+    /// Raster position for a (normalized) direction in camera space,
+    /// together with the density of directions per unit solid angle
+    /// (with the film sampled uniformly), or _None_ at the poles.
+    fn direction_to_raster(&self, dir: &Vector3f) -> Option<(Point2f, Float)> {
+        let theta: Float = clamp_t(dir.y, -1.0, 1.0).acos();
+        let sin_theta: Float = theta.sin();
+        if sin_theta == 0.0 as Float {
+            return None;
+        }
+        let mut phi: Float = dir.z.atan2(dir.x);
+        if phi < 0.0 as Float {
+            phi += 2.0 as Float * PI;
+        }
+        let p_raster: Point2f = Point2f {
+            x: phi / (2.0 as Float * PI) * self.film.full_resolution.x as Float,
+            y: theta / PI * self.film.full_resolution.y as Float,
+        };
+        Some((p_raster, 1.0 as Float / (2.0 as Float * PI * PI * sin_theta)))
+    }
+    fn point_camera(&self) -> PointCamera {
+        PointCamera {
+            camera_to_world: &self.camera_to_world,
+            film: &self.film,
+            medium: &self.medium,
+        }
+    }
+}
+
+impl Camera for EnvironmentCamera {
+    fn generate_ray_differential(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
+        // TODO: ProfilePhase prof(Prof::GenerateCameraRay);
+        let dir: Vector3f = self.raster_to_direction(&sample.p_film);
+        // see Camera::GenerateRayDifferential() in camera.cpp
+        let rx_direction: Vector3f = self.raster_to_direction(&Point2f {
+            x: sample.p_film.x + 1.0 as Float,
+            y: sample.p_film.y,
+        });
+        let ry_direction: Vector3f = self.raster_to_direction(&Point2f {
+            x: sample.p_film.x,
+            y: sample.p_film.y + 1.0 as Float,
+        });
+        let in_ray: Ray = Ray {
+            o: Point3f::default(),
+            d: dir,
+            t_max: std::f32::INFINITY,
+            time: lerp(sample.time, self.shutter_open, self.shutter_close),
+            differential: Some(RayDifferential {
+                rx_origin: Point3f::default(),
+                ry_origin: Point3f::default(),
+                rx_direction: rx_direction,
+                ry_direction: ry_direction,
+            }),
+            medium: self.medium.clone(),
+        };
+        *ray = self.camera_to_world.transform_ray(&in_ray);
+        1.0
+    }
+    fn we(&self, ray: &Ray, p_raster2: Option<&mut Point2f>) -> Spectrum {
+        self.point_camera().we(ray, p_raster2, |dir| self.direction_to_raster(dir))
+    }
+    fn pdf_we(&self, ray: &Ray) -> (Float, Float) {
+        self.point_camera().pdf_we(ray, |dir| self.direction_to_raster(dir))
+    }
+    fn sample_wi(
+        &self,
+        iref: &InteractionCommon,
+        _u: &Point2f,
+        wi: &mut Vector3f,
+        pdf: &mut Float,
+        p_raster: &mut Point2f,
+        vis: &mut VisibilityTester,
+    ) -> Spectrum {
+        self.point_camera()
+            .sample_wi(iref, wi, pdf, p_raster, vis, |dir| self.direction_to_raster(dir))
+    }
+    fn get_film(&self) -> Arc<Film> {
+        self.film.clone()
+    }
+    fn get_shutter_open(&self) -> Float {
+        self.shutter_open
+    }
+    fn get_shutter_close(&self) -> Float {
+        self.shutter_close
+    }
+}
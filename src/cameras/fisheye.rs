@@ -0,0 +1,360 @@
+// std
+use std;
+use std::sync::Arc;
+// pbrt
+use core::camera::{Camera, CameraSample, PointCamera};
+use core::film::Film;
+use core::geometry::{Point2f, Point3f, Ray, RayDifferential, Vector3f};
+use core::interaction::InteractionCommon;
+use core::light::VisibilityTester;
+use core::medium::Medium;
+use core::paramset::ParamSet;
+use core::pbrt::{Float, Spectrum};
+use core::pbrt::{clamp_t, lerp, radians};
+use core::transform::AnimatedTransform;
+
+/// How the angle to the optical axis maps onto the distance from the
+/// center of the image circle.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FisheyeMapping {
+    /// r = f * theta
+    Equidistant,
+    /// r = 2 * f * sin(theta / 2)
+    Equisolid,
+    /// r = 2 * f * tan(theta / 2)
+    Stereographic,
+}
+
+/// Fisheye lens looking along the **z** axis of camera space.
+pub struct FisheyeCamera {
+    // inherited from Camera (see camera.h)
+    pub camera_to_world: AnimatedTransform,
+    pub shutter_open: Float,
+    pub shutter_close: Float,
+    pub film: Arc<Film>,
+    pub medium: Option<Arc<Medium + Send + Sync>>,
+    // private data
+    mapping: FisheyeMapping,
+    theta_max: Float,
+    // radius of the image circle (in raster space)
+    radius: Float,
+    // polynomial distortion coefficients k1, k2, ...
+    distortion: Vec<Float>,
+}
+
+impl FisheyeCamera {
+    pub fn new(
+        camera_to_world: AnimatedTransform,
+        shutter_open: Float,
+        shutter_close: Float,
+        film: Arc<Film>,
+        medium: Option<Arc<Medium + Send + Sync>>,
+        mapping: FisheyeMapping,
+        fov: Float,
+        full_frame: bool,
+        distortion: Vec<Float>,
+    ) -> Self {
+        let res_x: Float = film.full_resolution.x as Float;
+        let res_y: Float = film.full_resolution.y as Float;
+        // a circular image fits into the shorter axis, a full-frame
+        // image gets the image circle through the film's corners
+        let radius: Float = if full_frame {
+            0.5 as Float * (res_x * res_x + res_y * res_y).sqrt()
+        } else {
+            0.5 as Float * res_x.min(res_y)
+        };
+        FisheyeCamera {
+            camera_to_world: camera_to_world,
+            shutter_open: shutter_open,
+            shutter_close: shutter_close,
+            film: film,
+            medium: medium,
+            mapping: mapping,
+            theta_max: radians(fov) * 0.5 as Float,
+            radius: radius,
+            distortion: distortion,
+        }
+    }
+    pub fn create(
+        params: &ParamSet,
+        cam2world: AnimatedTransform,
+        film: Arc<Film>,
+        medium: Option<Arc<Medium + Send + Sync>>,
+        warnings: &mut Vec<String>,
+    ) -> Box<Camera + Send + Sync> {
+        let mut shutteropen: Float = params.find_one_float(String::from("shutteropen"), 0.0);
+        let mut shutterclose: Float = params.find_one_float(String::from("shutterclose"), 1.0);
+        if shutterclose < shutteropen {
+            warnings.push(format!(
+                "Shutter close time [{}] < shutter open [{}].  Swapping them.",
+                shutterclose, shutteropen
+            ));
+            std::mem::swap(&mut shutterclose, &mut shutteropen);
+        }
+        let mapping_name: String =
+            params.find_one_string(String::from("mapping"), String::from("equidistant"));
+        let mapping: FisheyeMapping = if mapping_name == String::from("equidistant") {
+            FisheyeMapping::Equidistant
+        } else if mapping_name == String::from("equisolid") {
+            FisheyeMapping::Equisolid
+        } else if mapping_name == String::from("stereographic") {
+            FisheyeMapping::Stereographic
+        } else {
+            warnings.push(format!(
+                "Fisheye mapping \"{}\" unknown. Using \"equidistant\".",
+                mapping_name
+            ));
+            FisheyeMapping::Equidistant
+        };
+        let mut fov: Float = params.find_one_float(String::from("fov"), 180.0);
+        // a stereographic projection can't reach the opposite pole
+        let max_fov: Float = if mapping == FisheyeMapping::Stereographic {
+            359.0
+        } else {
+            360.0
+        };
+        if fov <= 0.0 as Float || fov > max_fov {
+            warnings.push(format!("\"fov\" {} out of range, clamping to (0, {}]", fov, max_fov));
+            fov = clamp_t(fov, 1.0, max_fov);
+        }
+        let full_frame: bool = params.find_one_bool(String::from("fullframe"), false);
+        let distortion: Vec<Float> = params.find_float(String::from("distortion"));
+        let camera = Box::new(FisheyeCamera::new(
+            cam2world,
+            shutteropen,
+            shutterclose,
+            film,
+            medium,
+            mapping,
+            fov,
+            full_frame,
+            distortion,
+        ));
+        camera
+    }
+    /// Normalized radius (one at the edge of the field of view) for
+    /// an angle to the optical axis.
+    fn theta_to_radius(&self, theta: Float) -> Float {
+        match self.mapping {
+            FisheyeMapping::Equidistant => theta / self.theta_max,
+            FisheyeMapping::Equisolid => {
+                (theta * 0.5 as Float).sin() / (self.theta_max * 0.5 as Float).sin()
+            }
+            FisheyeMapping::Stereographic => {
+                (theta * 0.5 as Float).tan() / (self.theta_max * 0.5 as Float).tan()
+            }
+        }
+    }
+    fn radius_to_theta(&self, r: Float) -> Float {
+        match self.mapping {
+            FisheyeMapping::Equidistant => r * self.theta_max,
+            FisheyeMapping::Equisolid => {
+                let s: Float = r * (self.theta_max * 0.5 as Float).sin();
+                2.0 as Float * clamp_t(s, -1.0, 1.0).asin()
+            }
+            FisheyeMapping::Stereographic => {
+                2.0 as Float * (r * (self.theta_max * 0.5 as Float).tan()).atan()
+            }
+        }
+    }
+    /// Derivative of the normalized radius with respect to the angle.
+    fn theta_to_radius_derivative(&self, theta: Float) -> Float {
+        match self.mapping {
+            FisheyeMapping::Equidistant => 1.0 as Float / self.theta_max,
+            FisheyeMapping::Equisolid => {
+                (theta * 0.5 as Float).cos()
+                    / (2.0 as Float * (self.theta_max * 0.5 as Float).sin())
+            }
+            FisheyeMapping::Stereographic => {
+                let cos_half: Float = (theta * 0.5 as Float).cos();
+                1.0 as Float
+                    / (2.0 as Float * cos_half * cos_half
+                        * (self.theta_max * 0.5 as Float).tan())
+            }
+        }
+    }
+    /// Applies r_d = r * (1 + k1 * r^2 + k2 * r^4 + ...).
+    fn distort(&self, r: Float) -> Float {
+        let r2: Float = r * r;
+        let mut rn: Float = r2;
+        let mut scale: Float = 1.0;
+        for k in &self.distortion {
+            scale += k * rn;
+            rn *= r2;
+        }
+        r * scale
+    }
+    fn distort_derivative(&self, r: Float) -> Float {
+        let r2: Float = r * r;
+        let mut rn: Float = r2;
+        let mut n: Float = 3.0;
+        let mut d: Float = 1.0;
+        for k in &self.distortion {
+            d += n * k * rn;
+            rn *= r2;
+            n += 2.0 as Float;
+        }
+        d
+    }
+    /// Inverts the distortion polynomial with a few Newton steps.
+    fn undistort(&self, r_d: Float) -> Float {
+        if self.distortion.is_empty() {
+            return r_d;
+        }
+        let mut r: Float = r_d;
+        for _i in 0..10 {
+            let d: Float = self.distort_derivative(r);
+            if d == 0.0 as Float {
+                break;
+            }
+            r -= (self.distort(r) - r_d) / d;
+        }
+        r
+    }
+    /// Direction (in camera space) for a raster position, or _None_
+    /// outside of the image circle.
+    fn raster_to_direction(&self, p_film: &Point2f) -> Option<Vector3f> {
+        // position relative to the image circle, **y** pointing up
+        let x: Float =
+            (p_film.x - 0.5 as Float * self.film.full_resolution.x as Float) / self.radius;
+        let y: Float =
+            (0.5 as Float * self.film.full_resolution.y as Float - p_film.y) / self.radius;
+        let r: Float = self.undistort((x * x + y * y).sqrt());
+        if r > 1.0 as Float || r < 0.0 as Float {
+            return None;
+        }
+        let theta: Float = self.radius_to_theta(r);
+        let phi: Float = y.atan2(x);
+        Some(Vector3f {
+            x: theta.sin() * phi.cos(),
+            y: theta.sin() * phi.sin(),
+            z: theta.cos(),
+        })
+    }
+    /// Raster position for a (normalized) direction in camera space,
+    /// together with the density of directions per unit solid angle
+    /// (with the film sampled uniformly), or _None_ outside of the
+    /// field of view.
+    fn direction_to_raster(&self, dir: &Vector3f) -> Option<(Point2f, Float)> {
+        let theta: Float = clamp_t(dir.z, -1.0, 1.0).acos();
+        if theta > self.theta_max {
+            return None;
+        }
+        let phi: Float = dir.y.atan2(dir.x);
+        let r: Float = self.theta_to_radius(theta);
+        let r_d: Float = self.distort(r);
+        let p_raster: Point2f = Point2f {
+            x: 0.5 as Float * self.film.full_resolution.x as Float
+                + self.radius * r_d * phi.cos(),
+            y: 0.5 as Float * self.film.full_resolution.y as Float
+                - self.radius * r_d * phi.sin(),
+        };
+        // dA = r_d dr_d dphi and dw = sin(theta) dtheta dphi, with
+        // r_d / sin(theta) going to dr/dtheta on the optical axis
+        let sin_theta: Float = theta.sin();
+        let r_d_over_sin_theta: Float = if theta > 1e-4 as Float {
+            r_d / sin_theta
+        } else {
+            self.theta_to_radius_derivative(0.0 as Float)
+        };
+        let film_area: Float = self.film.full_resolution.x as Float
+            * self.film.full_resolution.y as Float;
+        let pdf_dir: Float = self.radius
+            * self.radius
+            * r_d_over_sin_theta
+            * self.distort_derivative(r)
+            * self.theta_to_radius_derivative(theta)
+            / film_area;
+        Some((p_raster, pdf_dir))
+    }
+    fn point_camera(&self) -> PointCamera {
+        PointCamera {
+            camera_to_world: &self.camera_to_world,
+            film: &self.film,
+            medium: &self.medium,
+        }
+    }
+}
+
+impl Camera for FisheyeCamera {
+    fn generate_ray_differential(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
+        // TODO: ProfilePhase prof(Prof::GenerateCameraRay);
+        let time: Float = lerp(sample.time, self.shutter_open, self.shutter_close);
+        let dir: Vector3f = match self.raster_to_direction(&sample.p_film) {
+            Some(dir) => dir,
+            None => {
+                // outside of the image circle, the film stays black
+                let in_ray: Ray = Ray {
+                    o: Point3f::default(),
+                    d: Vector3f {
+                        x: 0.0,
+                        y: 0.0,
+                        z: 1.0,
+                    },
+                    t_max: std::f32::INFINITY,
+                    time: time,
+                    differential: None,
+                    medium: self.medium.clone(),
+                };
+                *ray = self.camera_to_world.transform_ray(&in_ray);
+                return 0.0;
+            }
+        };
+        // see Camera::GenerateRayDifferential() in camera.cpp
+        let rx_direction: Option<Vector3f> = self.raster_to_direction(&Point2f {
+            x: sample.p_film.x + 1.0 as Float,
+            y: sample.p_film.y,
+        });
+        let ry_direction: Option<Vector3f> = self.raster_to_direction(&Point2f {
+            x: sample.p_film.x,
+            y: sample.p_film.y + 1.0 as Float,
+        });
+        // no differentials at the rim of the image circle
+        let differential: Option<RayDifferential> = match (rx_direction, ry_direction) {
+            (Some(rx_direction), Some(ry_direction)) => Some(RayDifferential {
+                rx_origin: Point3f::default(),
+                ry_origin: Point3f::default(),
+                rx_direction: rx_direction,
+                ry_direction: ry_direction,
+            }),
+            _ => None,
+        };
+        let in_ray: Ray = Ray {
+            o: Point3f::default(),
+            d: dir,
+            t_max: std::f32::INFINITY,
+            time: time,
+            differential: differential,
+            medium: self.medium.clone(),
+        };
+        *ray = self.camera_to_world.transform_ray(&in_ray);
+        1.0
+    }
+    fn we(&self, ray: &Ray, p_raster2: Option<&mut Point2f>) -> Spectrum {
+        self.point_camera().we(ray, p_raster2, |dir| self.direction_to_raster(dir))
+    }
+    fn pdf_we(&self, ray: &Ray) -> (Float, Float) {
+        self.point_camera().pdf_we(ray, |dir| self.direction_to_raster(dir))
+    }
+    fn sample_wi(
+        &self,
+        iref: &InteractionCommon,
+        _u: &Point2f,
+        wi: &mut Vector3f,
+        pdf: &mut Float,
+        p_raster: &mut Point2f,
+        vis: &mut VisibilityTester,
+    ) -> Spectrum {
+        self.point_camera()
+            .sample_wi(iref, wi, pdf, p_raster, vis, |dir| self.direction_to_raster(dir))
+    }
+    fn get_film(&self) -> Arc<Film> {
+        self.film.clone()
+    }
+    fn get_shutter_open(&self) -> Float {
+        self.shutter_open
+    }
+    fn get_shutter_close(&self) -> Float {
+        self.shutter_close
+    }
+}
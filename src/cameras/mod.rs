@@ -2,7 +2,9 @@
 //! and defines the interface that all camera implementations must
 //! provide.
 //!
+//! - CylindricalCamera
 //! - EnvironmentCamera
+//! - FisheyeCamera
 //! - OrthographicCamera
 //! - PerspectiveCamera
 //! - RealisticCamera
@@ -16,5 +18,58 @@
 //! dimensions across the line of sight.
 //!
 //! ![Perspective Camera](https://www.janwalter.org/assets/Perspectiva-2.png)
+//!
+//! ## Cylindrical Camera
+//!
+//! The cylindrical camera projects the scene onto a cylinder around
+//! the **y** axis of camera space, like Arnold's **cyl_camera**. The
+//! film width covers the horizontal field of view (**hfov**, up to 360
+//! degrees) and the film height the vertical one (**vfov**). With
+//! **projective** set (the default) the vertical mapping behaves like
+//! a perspective camera, otherwise it is linear in the elevation
+//! angle.
+//!
+//! ## Environment Camera
+//!
+//! The environment camera traces rays in all directions around a
+//! point in the scene, giving a 360 degree panorama in a
+//! latitude-longitude (equirectangular) mapping. The **y** axis of
+//! camera space points to the poles, the full film width covers the
+//! azimuth and the film height the polar angle, so the film should be
+//! twice as wide as high.
+//!
+//! ## Fisheye Camera
+//!
+//! The fisheye camera maps the angle to the viewing direction onto
+//! the distance from the center of the image circle, using an
+//! **equidistant**, **equisolid**, or **stereographic** **mapping**.
+//! The **fov** (up to 360 degrees) covers the image circle, which
+//! fits into the film, or, with **fullframe** set, reaches the film's
+//! corners. Lens distortion can be added by polynomial coefficients
+//! (**distortion**), scaling the radius by (1 + k1 r^2 + k2 r^4 + ...).
+//!
+//! ## Orthographic Camera
+//!
+//! The orthographic camera projects the scene parallel to the viewing
+//! direction onto the film, so objects keep their size regardless of
+//! their distance. The **screenwindow** parameter defines the extent
+//! of the film in camera space units, which makes it a good fit for
+//! technical drawings like elevations or floor plans.
+//!
+//! ## Realistic Camera
+//!
+//! The realistic camera simulates a real lens system, read from a
+//! **lensfile** (four values per lens element interface: curvature
+//! radius, thickness, index of refraction, and aperture diameter, all
+//! lengths in millimeters). Rays from the film are traced through all
+//! elements, which gives effects like vignetting and the bokeh shape
+//! of the aperture for free. The film size is taken from the Film's
+//! **diagonal** (in millimeters) and the lens gets focused at the
+//! **focusdistance** (in meters).
 
+pub mod cylindrical;
+pub mod environment;
+pub mod fisheye;
+pub mod orthographic;
 pub mod perspective;
+pub mod realistic;
@@ -0,0 +1,386 @@
+// std
+use std;
+use std::f32::consts::PI;
+use std::sync::Arc;
+// pbrt
+use core::camera::{Camera, CameraSample};
+use core::film::Film;
+use core::geometry::{Bounds2f, Bounds2i, Normal3f, Point2f, Point2i, Point3f, Ray,
+                     RayDifferential, Vector3f};
+use core::geometry::{nrm_abs_dot_vec3, vec3_normalize};
+use core::interaction::InteractionCommon;
+use core::light::VisibilityTester;
+use core::medium::{Medium, MediumInterface};
+use core::paramset::ParamSet;
+use core::pbrt::{Float, Spectrum};
+use core::pbrt::lerp;
+use core::sampling::concentric_sample_disk;
+use core::transform::{AnimatedTransform, Transform};
+
+// see orthographic.h
+
+pub struct OrthographicCamera {
+    // inherited from Camera (see camera.h)
+    pub camera_to_world: AnimatedTransform,
+    pub shutter_open: Float,
+    pub shutter_close: Float,
+    pub film: Arc<Film>,
+    pub medium: Option<Arc<Medium + Send + Sync>>,
+    // inherited from ProjectiveCamera (see camera.h)
+    // camera_to_screen: Transform,
+    pub raster_to_camera: Transform,
+    // screen_to_raster: Transform,
+    // raster_to_screen: Transform,
+    pub lens_radius: Float,
+    pub focal_distance: Float,
+    // private data (see orthographic.h)
+    pub dx_camera: Vector3f,
+    pub dy_camera: Vector3f,
+    /// area of the film in camera space
+    pub a: Float,
+}
+
+impl OrthographicCamera {
+    pub fn new(
+        camera_to_world: AnimatedTransform,
+        screen_window: Bounds2f,
+        shutter_open: Float,
+        shutter_close: Float,
+        lens_radius: Float,
+        focal_distance: Float,
+        film: Arc<Film>,
+        medium: Option<Arc<Medium + Send + Sync>>,
+    ) -> Self {
+        // see orthographic.h
+        let camera_to_screen: Transform = Transform::orthographic(0.0, 1.0);
+        // see camera.h
+        // compute projective camera screen transformations
+        let scale1 = Transform::scale(
+            film.full_resolution.x as Float,
+            film.full_resolution.y as Float,
+            1.0,
+        );
+        let scale2 = Transform::scale(
+            1.0 / (screen_window.p_max.x - screen_window.p_min.x),
+            1.0 / (screen_window.p_min.y - screen_window.p_max.y),
+            1.0,
+        );
+        let translate = Transform::translate(&Vector3f {
+            x: -screen_window.p_min.x,
+            y: -screen_window.p_max.y,
+            z: 0.0,
+        });
+        let screen_to_raster = scale1 * scale2 * translate;
+        let raster_to_screen = Transform::inverse(&screen_to_raster);
+        let raster_to_camera = Transform::inverse(&camera_to_screen) * raster_to_screen;
+        // see orthographic.h
+        // compute differential changes in origin for orthographic camera rays
+        let dx_camera: Vector3f = raster_to_camera.transform_vector(&Vector3f {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        });
+        let dy_camera: Vector3f = raster_to_camera.transform_vector(&Vector3f {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        });
+        // compute film area in camera space for _OrthographicCamera_
+        let res: Point2i = film.full_resolution;
+        let p_min: Point3f = raster_to_camera.transform_point(&Point3f {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        });
+        let p_max: Point3f = raster_to_camera.transform_point(&Point3f {
+            x: res.x as Float,
+            y: res.y as Float,
+            z: 0.0,
+        });
+        let a: Float = ((p_max.x - p_min.x) * (p_max.y - p_min.y)).abs();
+
+        OrthographicCamera {
+            camera_to_world: camera_to_world,
+            shutter_open: shutter_open,
+            shutter_close: shutter_close,
+            film: film,
+            medium: medium,
+            // camera_to_screen: camera_to_screen,
+            raster_to_camera: raster_to_camera,
+            // screen_to_raster: screen_to_raster,
+            // raster_to_screen: raster_to_screen,
+            lens_radius: lens_radius,
+            focal_distance: focal_distance,
+            dx_camera: dx_camera,
+            dy_camera: dy_camera,
+            a: a,
+        }
+    }
+    pub fn create(
+        params: &ParamSet,
+        cam2world: AnimatedTransform,
+        film: Arc<Film>,
+        medium: Option<Arc<Medium + Send + Sync>>,
+        warnings: &mut Vec<String>,
+    ) -> Box<Camera + Send + Sync> {
+        let mut shutteropen: Float = params.find_one_float(String::from("shutteropen"), 0.0);
+        let mut shutterclose: Float = params.find_one_float(String::from("shutterclose"), 1.0);
+        if shutterclose < shutteropen {
+            warnings.push(format!(
+                "Shutter close time [{}] < shutter open [{}].  Swapping them.",
+                shutterclose, shutteropen
+            ));
+            std::mem::swap(&mut shutterclose, &mut shutteropen);
+        }
+        let lensradius: Float = params.find_one_float(String::from("lensradius"), 0.0);
+        let focaldistance: Float = params.find_one_float(String::from("focaldistance"), 1e6);
+        let frame: Float = params.find_one_float(
+            String::from("frameaspectratio"),
+            (film.full_resolution.x as Float) / (film.full_resolution.y as Float),
+        );
+        let mut screen: Bounds2f = Bounds2f::default();
+        if frame > 1.0 {
+            screen.p_min.x = -frame;
+            screen.p_max.x = frame;
+            screen.p_min.y = -1.0;
+            screen.p_max.y = 1.0;
+        } else {
+            screen.p_min.x = -1.0;
+            screen.p_max.x = 1.0;
+            screen.p_min.y = -1.0 / frame;
+            screen.p_max.y = 1.0 / frame;
+        }
+        let sw: Vec<Float> = params.find_float(String::from("screenwindow"));
+        if sw.len() == 4 {
+            screen.p_min.x = sw[0];
+            screen.p_max.x = sw[1];
+            screen.p_min.y = sw[2];
+            screen.p_max.y = sw[3];
+        } else if sw.len() != 0 {
+            warnings.push(String::from("\"screenwindow\" should have four values"));
+        }
+        let camera = Box::new(OrthographicCamera::new(
+            cam2world,
+            screen,
+            shutteropen,
+            shutterclose,
+            lensradius,
+            focaldistance,
+            film,
+            medium,
+        ));
+        camera
+    }
+    /// Maps a ray leaving the lens (given in camera space) back to
+    /// the point on the film it was generated for.
+    fn camera_ray_to_raster(&self, o: &Point3f, d: &Vector3f) -> Point3f {
+        let mut p_camera: Point3f = *o;
+        if self.lens_radius > 0.0 as Float {
+            // the ray passes the plane of focus above its film position
+            p_camera = *o + *d * (self.focal_distance / d.z);
+        }
+        p_camera.z = 0.0 as Float;
+        Transform::inverse(&self.raster_to_camera).transform_point(&p_camera)
+    }
+    fn is_outside_sample_bounds(&self, p_raster: &Point3f) -> bool {
+        let sample_bounds: Bounds2i = self.film.get_sample_bounds();
+        p_raster.x < sample_bounds.p_min.x as Float
+            || p_raster.x >= sample_bounds.p_max.x as Float
+            || p_raster.y < sample_bounds.p_min.y as Float
+            || p_raster.y >= sample_bounds.p_max.y as Float
+    }
+}
+
+impl Camera for OrthographicCamera {
+    fn generate_ray_differential(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
+        // TODO: ProfilePhase prof(Prof::GenerateCameraRay);
+        // compute main orthographic viewing ray
+
+        // compute raster and camera sample positions
+        let p_film: Point3f = Point3f {
+            x: sample.p_film.x,
+            y: sample.p_film.y,
+            z: 0.0,
+        };
+        let p_camera: Point3f = self.raster_to_camera.transform_point(&p_film);
+        let dir: Vector3f = Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        };
+        // *ray = RayDifferential(pCamera, Vector3f(0, 0, 1));
+        let mut in_ray: Ray = Ray {
+            o: p_camera,
+            d: dir,
+            t_max: std::f32::INFINITY,
+            time: lerp(sample.time, self.shutter_open, self.shutter_close),
+            differential: None,
+            medium: None,
+        };
+        // compute ray differentials for _OrthographicCamera_
+        let mut diff: RayDifferential = RayDifferential {
+            rx_origin: in_ray.o + self.dx_camera,
+            ry_origin: in_ray.o + self.dy_camera,
+            rx_direction: in_ray.d,
+            ry_direction: in_ray.d,
+        };
+        // modify ray for depth of field
+        if self.lens_radius > 0.0 as Float {
+            // sample point on lens
+            let p_lens: Point2f = concentric_sample_disk(&sample.p_lens) * self.lens_radius;
+            // compute point on plane of focus
+            let ft: Float = self.focal_distance / in_ray.d.z;
+            let p_focus: Point3f = in_ray.position(ft);
+            // update ray for effect of lens
+            in_ray.o = Point3f {
+                x: p_camera.x + p_lens.x,
+                y: p_camera.y + p_lens.y,
+                z: 0.0 as Float,
+            };
+            in_ray.d = vec3_normalize(&(p_focus - in_ray.o));
+            // compute _OrthographicCamera_ ray differentials accounting for lens
+            let p_focus: Point3f = p_camera + self.dx_camera + (dir * ft);
+            diff.rx_origin = in_ray.o + self.dx_camera;
+            diff.rx_direction = vec3_normalize(&(p_focus - diff.rx_origin));
+            let p_focus: Point3f = p_camera + self.dy_camera + (dir * ft);
+            diff.ry_origin = in_ray.o + self.dy_camera;
+            diff.ry_direction = vec3_normalize(&(p_focus - diff.ry_origin));
+        }
+        in_ray.differential = Some(diff);
+        in_ray.medium = self.medium.clone();
+        *ray = self.camera_to_world.transform_ray(&in_ray);
+        1.0
+    }
+    fn we(&self, ray: &Ray, p_raster2: Option<&mut Point2f>) -> Spectrum {
+        // interpolate camera matrix and check if $\w{}$ is forward-facing
+        let mut c2w: Transform = Transform::default();
+        self.camera_to_world.interpolate(ray.time, &mut c2w);
+        let w2c: Transform = Transform::inverse(&c2w);
+        let d_camera: Vector3f = vec3_normalize(&w2c.transform_vector(&ray.d));
+        let cos_theta: Float = d_camera.z;
+        if cos_theta <= 0.0 as Float {
+            return Spectrum::default();
+        }
+        // map ray $(\p{}, \w{})$ onto the raster grid
+        let p_raster: Point3f = self.camera_ray_to_raster(&w2c.transform_point(&ray.o), &d_camera);
+        // return raster position if requested
+        if let Some(p_raster2) = p_raster2 {
+            *p_raster2 = Point2f {
+                x: p_raster.x,
+                y: p_raster.y,
+            };
+        }
+        // return zero importance for out of bounds points
+        if self.is_outside_sample_bounds(&p_raster) {
+            return Spectrum::default();
+        }
+        // return importance for point on film (without a lens the
+        // direction is a delta distribution)
+        if self.lens_radius > 0.0 as Float {
+            let lens_area: Float = PI * self.lens_radius * self.lens_radius;
+            let cos_2_theta: Float = cos_theta * cos_theta;
+            Spectrum::new(
+                (self.focal_distance * self.focal_distance)
+                    / (self.a * lens_area * cos_2_theta * cos_2_theta),
+            )
+        } else {
+            Spectrum::new(1.0 as Float / self.a)
+        }
+    }
+    fn pdf_we(&self, ray: &Ray) -> (Float, Float) {
+        let mut pdf_pos: Float = 0.0;
+        let mut pdf_dir: Float = 0.0;
+        // interpolate camera matrix and fail if $\w{}$ is not forward-facing
+        let mut c2w: Transform = Transform::default();
+        self.camera_to_world.interpolate(ray.time, &mut c2w);
+        let w2c: Transform = Transform::inverse(&c2w);
+        let d_camera: Vector3f = vec3_normalize(&w2c.transform_vector(&ray.d));
+        let cos_theta: Float = d_camera.z;
+        if cos_theta <= 0.0 as Float {
+            // *pdf_pos = *pdf_dir = 0;
+            return (pdf_pos, pdf_dir);
+        }
+        // map ray $(\p{}, \w{})$ onto the raster grid
+        let p_raster: Point3f = self.camera_ray_to_raster(&w2c.transform_point(&ray.o), &d_camera);
+        // return zero probability for out of bounds points
+        if self.is_outside_sample_bounds(&p_raster) {
+            // *pdf_pos = *pdf_dir = 0;
+            return (pdf_pos, pdf_dir);
+        }
+        pdf_pos = 1.0 as Float / self.a;
+        if self.lens_radius > 0.0 as Float {
+            let lens_area: Float = PI * self.lens_radius * self.lens_radius;
+            pdf_dir = (self.focal_distance * self.focal_distance)
+                / (lens_area * cos_theta * cos_theta * cos_theta);
+        } else {
+            pdf_dir = 1.0 as Float;
+        }
+        (pdf_pos, pdf_dir)
+    }
+    fn sample_wi(
+        &self,
+        iref: &InteractionCommon,
+        u: &Point2f,
+        wi: &mut Vector3f,
+        pdf: &mut Float,
+        p_raster: &mut Point2f,
+        vis: &mut VisibilityTester,
+    ) -> Spectrum {
+        // transform reference point into camera space
+        let mut c2w: Transform = Transform::default();
+        self.camera_to_world.interpolate(iref.time, &mut c2w);
+        let p_ref: Point3f = Transform::inverse(&c2w).transform_point(&iref.p);
+        if p_ref.z <= 0.0 as Float {
+            *pdf = 0.0 as Float;
+            return Spectrum::default();
+        }
+        // sample a point on the lens and find where the ray through
+        // _iref_ leaves it (without a lens straight below _iref_)
+        let mut p_lens_camera: Point3f = Point3f {
+            x: p_ref.x,
+            y: p_ref.y,
+            z: 0.0 as Float,
+        };
+        let mut lens_area: Float = 1.0 as Float;
+        if self.lens_radius > 0.0 as Float {
+            let p_lens: Point2f = concentric_sample_disk(u) * self.lens_radius;
+            let s: Float = p_ref.z / self.focal_distance;
+            p_lens_camera.x += p_lens.x * s;
+            p_lens_camera.y += p_lens.y * s;
+            lens_area = PI * self.lens_radius * self.lens_radius * s * s;
+        }
+        // Interaction lens_intr(p_lens_world, iref.time, medium);
+        let mut lens_intr: InteractionCommon = InteractionCommon::default();
+        lens_intr.p = c2w.transform_point(&p_lens_camera);
+        lens_intr.time = iref.time;
+        lens_intr.medium_interface = MediumInterface::from_medium(self.medium.clone());
+        lens_intr.n = Normal3f::from(c2w.transform_vector(&Vector3f {
+            x: 0.0 as Float,
+            y: 0.0 as Float,
+            z: 1.0 as Float,
+        }));
+        // populate arguments and compute the importance value
+        vis.p0 = iref.clone();
+        vis.p1 = lens_intr.clone();
+        *wi = lens_intr.p - iref.p;
+        let dist: Float = wi.length();
+        *wi /= dist;
+        // compute PDF for importance arriving at _iref_
+        if self.lens_radius > 0.0 as Float {
+            *pdf = (dist * dist) / (nrm_abs_dot_vec3(&lens_intr.n, wi) * lens_area);
+        } else {
+            // the direction towards the film is fixed
+            *pdf = 1.0 as Float;
+        }
+        self.we(&lens_intr.spawn_ray(&-*wi), Some(p_raster))
+    }
+    fn get_film(&self) -> Arc<Film> {
+        self.film.clone()
+    }
+    fn get_shutter_open(&self) -> Float {
+        self.shutter_open
+    }
+    fn get_shutter_close(&self) -> Float {
+        self.shutter_close
+    }
+}
@@ -10,6 +10,7 @@ use core::geometry::{Bounds2f, Bounds2i, Normal3f, Point2f, Point2i, Point3f, Ra
 use core::geometry::{nrm_abs_dot_vec3, vec3_dot_vec3, vec3_normalize};
 use core::interaction::{InteractionCommon};
 use core::light::VisibilityTester;
+use core::medium::{Medium, MediumInterface};
 use core::paramset::ParamSet;
 use core::pbrt::{Float, Spectrum};
 use core::pbrt::lerp;
@@ -24,7 +25,7 @@ pub struct PerspectiveCamera {
     pub shutter_open: Float,
     pub shutter_close: Float,
     pub film: Arc<Film>,
-    // TODO: const Medium *medium;
+    pub medium: Option<Arc<Medium + Send + Sync>>,
     // inherited from ProjectiveCamera (see camera.h)
     // camera_to_screen: Transform,
     pub raster_to_camera: Transform,
@@ -47,7 +48,8 @@ impl PerspectiveCamera {
         lens_radius: Float,
         focal_distance: Float,
         fov: Float,
-        film: Arc<Film>, /* const Medium *medium */
+        film: Arc<Film>,
+        medium: Option<Arc<Medium + Send + Sync>>,
     ) -> Self {
         // see perspective.cpp
         let camera_to_screen: Transform = Transform::perspective(fov, 1e-2, 1000.0);
@@ -113,6 +115,7 @@ impl PerspectiveCamera {
             shutter_open: shutter_open,
             shutter_close: shutter_close,
             film: film,
+            medium: medium,
             // camera_to_screen: camera_to_screen,
             raster_to_camera: raster_to_camera,
             // screen_to_raster: screen_to_raster,
@@ -127,7 +130,8 @@ impl PerspectiveCamera {
     pub fn create(
         params: &ParamSet,
         cam2world: AnimatedTransform,
-        film: Arc<Film>, /* const Medium *medium */
+        film: Arc<Film>,
+        medium: Option<Arc<Medium + Send + Sync>>,
     ) -> Box<Camera + Send + Sync> {
         let shutteropen: Float = params.find_one_float(String::from("shutteropen"), 0.0);
         let shutterclose: Float = params.find_one_float(String::from("shutterclose"), 1.0);
@@ -166,6 +170,7 @@ impl PerspectiveCamera {
             focaldistance,
             fov,
             film,
+            medium,
         ));
         camera
     }
@@ -211,6 +216,7 @@ impl Camera for PerspectiveCamera {
             t_max: std::f32::INFINITY,
             time: lerp(sample.time, self.shutter_open, self.shutter_close),
             differential: Some(diff),
+            medium: None,
         };
         // modify ray for depth of field
         if self.lens_radius > 0.0 as Float {
@@ -254,7 +260,7 @@ impl Camera for PerspectiveCamera {
             // replace differential
             in_ray.differential = Some(diff);
         }
-        // TODO: ray->medium = medium;
+        in_ray.medium = self.medium.clone();
         *ray = self.camera_to_world.transform_ray(&in_ray);
         1.0
     }
@@ -383,6 +389,7 @@ impl Camera for PerspectiveCamera {
         let mut lens_intr: InteractionCommon = InteractionCommon::default();
         lens_intr.p = p_lens_world;
         lens_intr.time = iref.time;
+        lens_intr.medium_interface = MediumInterface::from_medium(self.medium.clone());
         lens_intr.n = Normal3f::from(self.camera_to_world.transform_vector(
             iref.time,
             &Vector3f {
@@ -392,8 +399,8 @@ impl Camera for PerspectiveCamera {
             },
         ));
         // populate arguments and compute the importance value
-        vis.p0 = *iref;
-        vis.p1 = lens_intr;
+        vis.p0 = iref.clone();
+        vis.p1 = lens_intr.clone();
         *wi = lens_intr.p - iref.p;
         let dist: Float = wi.length();
         *wi /= dist;
@@ -411,4 +418,10 @@ impl Camera for PerspectiveCamera {
     fn get_film(&self) -> Arc<Film> {
         self.film.clone()
     }
+    fn get_shutter_open(&self) -> Float {
+        self.shutter_open
+    }
+    fn get_shutter_close(&self) -> Float {
+        self.shutter_close
+    }
 }
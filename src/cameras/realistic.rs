@@ -0,0 +1,648 @@
+// std
+use std;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+// others
+use crossbeam;
+use num_cpus;
+// pbrt
+use core::camera::{Camera, CameraSample};
+use core::fileutil::read_float_file;
+use core::film::Film;
+use core::geometry::{Bounds2f, Normal3f, Point2f, Point3f, Ray, Vector3f};
+use core::geometry::{bnd2_expand, bnd2_union_pnt2, nrm_faceforward_vec3, nrm_normalize,
+                     pnt2_inside_bnd2, vec3_normalize};
+use core::interaction::InteractionCommon;
+use core::light::VisibilityTester;
+use core::lowdiscrepancy::radical_inverse;
+use core::medium::Medium;
+use core::paramset::ParamSet;
+use core::pbrt::{Float, Spectrum};
+use core::pbrt::{lerp, quadratic};
+use core::reflection::refract;
+use core::transform::AnimatedTransform;
+
+// see realistic.h
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct LensElementInterface {
+    pub curvature_radius: Float,
+    pub thickness: Float,
+    pub eta: Float,
+    pub aperture_radius: Float,
+}
+
+/// Simulates a lens system, described by a list of spherical lens
+/// element interfaces and an aperture stop, by tracing rays from the
+/// film through all elements.
+pub struct RealisticCamera {
+    // inherited from Camera (see camera.h)
+    pub camera_to_world: AnimatedTransform,
+    pub shutter_open: Float,
+    pub shutter_close: Float,
+    pub film: Arc<Film>,
+    pub medium: Option<Arc<Medium + Send + Sync>>,
+    // private data (see realistic.h)
+    pub simple_weighting: bool,
+    pub element_interfaces: Vec<LensElementInterface>,
+    pub exit_pupil_bounds: Vec<Bounds2f>,
+}
+
+impl RealisticCamera {
+    pub fn new(
+        camera_to_world: AnimatedTransform,
+        shutter_open: Float,
+        shutter_close: Float,
+        aperture_diameter: Float,
+        focus_distance: Float,
+        simple_weighting: bool,
+        lens_data: &Vec<Float>,
+        film: Arc<Film>,
+        medium: Option<Arc<Medium + Send + Sync>>,
+        warnings: &mut Vec<String>,
+    ) -> Result<Self, String> {
+        let mut element_interfaces: Vec<LensElementInterface> = Vec::new();
+        for i in (0..lens_data.len()).step_by(4) {
+            let mut diameter: Float = lens_data[i + 3];
+            if lens_data[i] == 0.0 as Float {
+                if aperture_diameter > lens_data[i + 3] {
+                    warnings.push(format!(
+                        "Specified aperture diameter {} is greater than maximum possible {}. \
+                         Clamping it.",
+                        aperture_diameter,
+                        lens_data[i + 3]
+                    ));
+                } else {
+                    diameter = aperture_diameter;
+                }
+            }
+            // lens data is given in millimeters, convert to meters
+            element_interfaces.push(LensElementInterface {
+                curvature_radius: lens_data[i] * 0.001 as Float,
+                thickness: lens_data[i + 1] * 0.001 as Float,
+                eta: lens_data[i + 2],
+                aperture_radius: diameter * 0.001 as Float / 2.0 as Float,
+            });
+        }
+        let mut camera: RealisticCamera = RealisticCamera {
+            camera_to_world: camera_to_world,
+            shutter_open: shutter_open,
+            shutter_close: shutter_close,
+            film: film,
+            medium: medium,
+            simple_weighting: simple_weighting,
+            element_interfaces: element_interfaces,
+            exit_pupil_bounds: Vec::new(),
+        };
+        // compute lens--film distance for given focus distance
+        let thickness: Float = camera.focus_thick_lens(focus_distance)?;
+        if let Some(rear) = camera.element_interfaces.last_mut() {
+            rear.thickness = thickness;
+        }
+        // compute exit pupil bounds at sampled points on the film
+        let n_samples: usize = 64;
+        let mut exit_pupil_bounds: Vec<Bounds2f> = vec![Bounds2f::default(); n_samples];
+        {
+            // the camera is created while parsing, before the render
+            // _Options_ exist, so use all available cores (like the
+            // render loops do by default)
+            let num_cores: usize = std::cmp::min(num_cpus::get(), n_samples);
+            let next_index: AtomicUsize = AtomicUsize::new(0);
+            let next_index = &next_index;
+            let camera = &camera;
+            let half_diagonal: Float = camera.film.diagonal / 2.0 as Float;
+            let exit_pupil_bounds = &mut exit_pupil_bounds;
+            crossbeam::scope(|scope| {
+                let (bounds_tx, bounds_rx) = mpsc::channel();
+                // spawn worker threads
+                for _ in 0..num_cores {
+                    let bounds_tx = bounds_tx.clone();
+                    scope.spawn(move || loop {
+                        let i: usize = next_index.fetch_add(1, Ordering::AcqRel);
+                        if i >= n_samples {
+                            break;
+                        }
+                        let r0: Float = i as Float / n_samples as Float * half_diagonal;
+                        let r1: Float = (i + 1) as Float / n_samples as Float * half_diagonal;
+                        bounds_tx
+                            .send((i, camera.bound_exit_pupil(r0, r1)))
+                            .expect(&format!("Failed to send exit pupil bounds"));
+                    });
+                }
+                drop(bounds_tx);
+                // collect the exit pupil bounds
+                for (i, bounds) in bounds_rx {
+                    exit_pupil_bounds[i] = bounds;
+                }
+            });
+        }
+        camera.exit_pupil_bounds = exit_pupil_bounds;
+        if simple_weighting {
+            warnings.push(String::from(
+                "\"simpleweighting\" option with RealisticCamera no longer necessarily matches \
+                 regular camera images. Further, pixel values will vary a bit depending on the \
+                 aperture size.",
+            ));
+        }
+        Ok(camera)
+    }
+    pub fn create(
+        params: &ParamSet,
+        cam2world: AnimatedTransform,
+        film: Arc<Film>,
+        medium: Option<Arc<Medium + Send + Sync>>,
+        lens_file: String,
+        warnings: &mut Vec<String>,
+    ) -> Result<Box<Camera + Send + Sync>, String> {
+        let mut shutteropen: Float = params.find_one_float(String::from("shutteropen"), 0.0);
+        let mut shutterclose: Float = params.find_one_float(String::from("shutterclose"), 1.0);
+        if shutterclose < shutteropen {
+            warnings.push(format!(
+                "Shutter close time [{}] < shutter open [{}].  Swapping them.",
+                shutterclose, shutteropen
+            ));
+            std::mem::swap(&mut shutterclose, &mut shutteropen);
+        }
+        // realistic camera-specific parameters (the **lensfile** is
+        // already resolved by the caller)
+        let aperture_diameter: Float =
+            params.find_one_float(String::from("aperturediameter"), 1.0);
+        let focus_distance: Float = params.find_one_float(String::from("focusdistance"), 10.0);
+        let simple_weighting: bool = params.find_one_bool(String::from("simpleweighting"), true);
+        if lens_file == String::new() {
+            return Err(String::from("No lens description file supplied!"));
+        }
+        // load element data from lens description file
+        let lens_data: Vec<Float> = match read_float_file(Path::new(&lens_file)) {
+            Ok(lens_data) => lens_data,
+            Err(err) => {
+                return Err(format!(
+                    "Error reading lens specification file {:?}: {}",
+                    lens_file, err
+                ))
+            }
+        };
+        if lens_data.is_empty() {
+            return Err(format!(
+                "No lens elements found in lens specification file {:?}.",
+                lens_file
+            ));
+        }
+        if lens_data.len() % 4 != 0 {
+            return Err(format!(
+                "Excess values in lens specification file {:?}; must be multiple-of-four \
+                 values, read {}.",
+                lens_file,
+                lens_data.len()
+            ));
+        }
+        let camera = Box::new(RealisticCamera::new(
+            cam2world,
+            shutteropen,
+            shutterclose,
+            aperture_diameter,
+            focus_distance,
+            simple_weighting,
+            &lens_data,
+            film,
+            medium,
+            warnings,
+        )?);
+        Ok(camera)
+    }
+    pub fn lens_rear_z(&self) -> Float {
+        self.element_interfaces.last().unwrap().thickness
+    }
+    pub fn lens_front_z(&self) -> Float {
+        let mut z_sum: Float = 0.0;
+        for element in &self.element_interfaces {
+            z_sum += element.thickness;
+        }
+        z_sum
+    }
+    pub fn rear_element_radius(&self) -> Float {
+        self.element_interfaces.last().unwrap().aperture_radius
+    }
+    pub fn trace_lenses_from_film(&self, r_camera: &Ray, r_out: Option<&mut Ray>) -> bool {
+        let mut element_z: Float = 0.0;
+        // transform _r_camera_ from camera to lens system space
+        let mut r_lens: Ray = camera_to_lens(r_camera);
+        for i in (0..self.element_interfaces.len()).rev() {
+            let element: &LensElementInterface = &self.element_interfaces[i];
+            // update ray from film accounting for interaction with _element_
+            element_z -= element.thickness;
+            // compute intersection of ray with lens element
+            let mut t: Float = 0.0;
+            let mut n: Normal3f = Normal3f::default();
+            let is_stop: bool = element.curvature_radius == 0.0 as Float;
+            if is_stop {
+                // The refracted ray computed in the previous lens
+                // element interface may be pointed towards film
+                // plane(+z) in some extreme situations; in such
+                // cases, 't' becomes negative.
+                if r_lens.d.z >= 0.0 as Float {
+                    return false;
+                }
+                t = (element_z - r_lens.o.z) / r_lens.d.z;
+            } else {
+                let radius: Float = element.curvature_radius;
+                let z_center: Float = element_z + element.curvature_radius;
+                if !intersect_spherical_element(radius, z_center, &r_lens, &mut t, &mut n) {
+                    return false;
+                }
+            }
+            assert!(t >= 0.0 as Float);
+            // test intersection point against element aperture
+            let p_hit: Point3f = r_lens.position(t);
+            let r2: Float = p_hit.x * p_hit.x + p_hit.y * p_hit.y;
+            if r2 > element.aperture_radius * element.aperture_radius {
+                return false;
+            }
+            r_lens.o = p_hit;
+            // update ray path for element interface interaction
+            if !is_stop {
+                let mut w: Vector3f = Vector3f::default();
+                let eta_i: Float = element.eta;
+                let eta_t: Float;
+                if i > 0 && self.element_interfaces[i - 1].eta != 0.0 as Float {
+                    eta_t = self.element_interfaces[i - 1].eta;
+                } else {
+                    eta_t = 1.0 as Float;
+                }
+                if !refract(&vec3_normalize(&-r_lens.d), &n, eta_i / eta_t, &mut w) {
+                    return false;
+                }
+                r_lens.d = w;
+            }
+        }
+        // transform _r_lens_ from lens system space back to camera space
+        if let Some(r_out) = r_out {
+            *r_out = camera_to_lens(&r_lens);
+        }
+        true
+    }
+    pub fn trace_lenses_from_scene(&self, r_camera: &Ray, r_out: Option<&mut Ray>) -> bool {
+        let mut element_z: Float = -self.lens_front_z();
+        // transform _r_camera_ from camera to lens system space
+        let mut r_lens: Ray = camera_to_lens(r_camera);
+        for i in 0..self.element_interfaces.len() {
+            let element: &LensElementInterface = &self.element_interfaces[i];
+            // compute intersection of ray with lens element
+            let mut t: Float = 0.0;
+            let mut n: Normal3f = Normal3f::default();
+            let is_stop: bool = element.curvature_radius == 0.0 as Float;
+            if is_stop {
+                t = (element_z - r_lens.o.z) / r_lens.d.z;
+            } else {
+                let radius: Float = element.curvature_radius;
+                let z_center: Float = element_z + element.curvature_radius;
+                if !intersect_spherical_element(radius, z_center, &r_lens, &mut t, &mut n) {
+                    return false;
+                }
+            }
+            assert!(t >= 0.0 as Float);
+            // test intersection point against element aperture
+            let p_hit: Point3f = r_lens.position(t);
+            let r2: Float = p_hit.x * p_hit.x + p_hit.y * p_hit.y;
+            if r2 > element.aperture_radius * element.aperture_radius {
+                return false;
+            }
+            r_lens.o = p_hit;
+            // update ray path for from-scene element interface interaction
+            if !is_stop {
+                let mut wt: Vector3f = Vector3f::default();
+                let eta_i: Float;
+                if i == 0 || self.element_interfaces[i - 1].eta == 0.0 as Float {
+                    eta_i = 1.0 as Float;
+                } else {
+                    eta_i = self.element_interfaces[i - 1].eta;
+                }
+                let eta_t: Float;
+                if self.element_interfaces[i].eta != 0.0 as Float {
+                    eta_t = self.element_interfaces[i].eta;
+                } else {
+                    eta_t = 1.0 as Float;
+                }
+                if !refract(&vec3_normalize(&-r_lens.d), &n, eta_i / eta_t, &mut wt) {
+                    return false;
+                }
+                r_lens.d = wt;
+            }
+            element_z += element.thickness;
+        }
+        // transform _r_lens_ from lens system space back to camera space
+        if let Some(r_out) = r_out {
+            *r_out = camera_to_lens(&r_lens);
+        }
+        true
+    }
+    pub fn compute_thick_lens_approximation(
+        &self,
+        pz: &mut [Float; 2],
+        fz: &mut [Float; 2],
+    ) -> Result<(), String> {
+        // find height $x$ from optical axis for parallel rays
+        let x: Float = 0.001 as Float * self.film.diagonal;
+        // compute cardinal points for film side of lens system
+        let mut r_scene: Ray = new_ray(
+            &Point3f {
+                x: x,
+                y: 0.0,
+                z: self.lens_front_z() + 1.0 as Float,
+            },
+            &Vector3f {
+                x: 0.0,
+                y: 0.0,
+                z: -1.0,
+            },
+            0.0,
+        );
+        let mut r_film: Ray = Ray::default();
+        if !self.trace_lenses_from_scene(&r_scene, Some(&mut r_film)) {
+            return Err(String::from(
+                "Unable to trace ray from scene to film for thick lens approximation. Is \
+                 aperture stop extremely small?",
+            ));
+        }
+        compute_cardinal_points(&r_scene, &r_film, &mut pz[0], &mut fz[0]);
+        // compute cardinal points for scene side of lens system
+        r_film = new_ray(
+            &Point3f {
+                x: x,
+                y: 0.0,
+                z: self.lens_rear_z() - 1.0 as Float,
+            },
+            &Vector3f {
+                x: 0.0,
+                y: 0.0,
+                z: 1.0,
+            },
+            0.0,
+        );
+        if !self.trace_lenses_from_film(&r_film, Some(&mut r_scene)) {
+            return Err(String::from(
+                "Unable to trace ray from film to scene for thick lens approximation. Is \
+                 aperture stop extremely small?",
+            ));
+        }
+        compute_cardinal_points(&r_film, &r_scene, &mut pz[1], &mut fz[1]);
+        Ok(())
+    }
+    pub fn focus_thick_lens(&self, focus_distance: Float) -> Result<Float, String> {
+        let mut pz: [Float; 2] = [0.0; 2];
+        let mut fz: [Float; 2] = [0.0; 2];
+        self.compute_thick_lens_approximation(&mut pz, &mut fz)?;
+        // compute translation of lens, _delta_, to focus at _focus_distance_
+        let f: Float = fz[0] - pz[0];
+        let z: Float = -focus_distance;
+        let c: Float = (pz[1] - z - pz[0]) * (pz[1] - z - 4.0 as Float * f - pz[0]);
+        if c <= 0.0 as Float {
+            return Err(format!(
+                "Coefficient must be positive. It looks like focusdistance {} is too short for \
+                 the given lens configuration",
+                focus_distance
+            ));
+        }
+        let delta: Float = 0.5 as Float * (pz[1] - z + pz[0] - c.sqrt());
+        Ok(self.lens_rear_z() + delta)
+    }
+    pub fn bound_exit_pupil(&self, p_film_x0: Float, p_film_x1: Float) -> Bounds2f {
+        let mut pupil_bounds: Bounds2f = Bounds2f {
+            p_min: Point2f {
+                x: std::f32::MAX,
+                y: std::f32::MAX,
+            },
+            p_max: Point2f {
+                x: std::f32::MIN,
+                y: std::f32::MIN,
+            },
+        };
+        // sample a collection of points on the rear lens to find exit pupil
+        let n_samples: u64 = 1024 * 1024;
+        let mut n_exiting_rays: u64 = 0;
+        // compute bounding box of projection of rear element on sampling plane
+        let rear_radius: Float = self.rear_element_radius();
+        let proj_rear_bounds: Bounds2f = Bounds2f {
+            p_min: Point2f {
+                x: -1.5 as Float * rear_radius,
+                y: -1.5 as Float * rear_radius,
+            },
+            p_max: Point2f {
+                x: 1.5 as Float * rear_radius,
+                y: 1.5 as Float * rear_radius,
+            },
+        };
+        for i in 0..n_samples {
+            // find location of sample points on $x$ segment and rear lens element
+            let p_film: Point3f = Point3f {
+                x: lerp(
+                    (i as Float + 0.5 as Float) / n_samples as Float,
+                    p_film_x0,
+                    p_film_x1,
+                ),
+                y: 0.0,
+                z: 0.0,
+            };
+            let u: [Float; 2] = [radical_inverse(0, i), radical_inverse(1, i)];
+            let p_rear: Point3f = Point3f {
+                x: lerp(u[0], proj_rear_bounds.p_min.x, proj_rear_bounds.p_max.x),
+                y: lerp(u[1], proj_rear_bounds.p_min.y, proj_rear_bounds.p_max.y),
+                z: self.lens_rear_z(),
+            };
+            // expand pupil bounds if ray makes it through the lens system
+            let p_rear_2: Point2f = Point2f {
+                x: p_rear.x,
+                y: p_rear.y,
+            };
+            if pnt2_inside_bnd2(&p_rear_2, &pupil_bounds)
+                || self.trace_lenses_from_film(&new_ray(&p_film, &(p_rear - p_film), 0.0), None)
+            {
+                pupil_bounds = bnd2_union_pnt2(&pupil_bounds, &p_rear_2);
+                n_exiting_rays += 1;
+            }
+        }
+        // return entire element bounds if no rays made it through the lens system
+        if n_exiting_rays == 0 {
+            return proj_rear_bounds;
+        }
+        // expand bounds to account for sample spacing
+        bnd2_expand(
+            &pupil_bounds,
+            2.0 as Float * proj_rear_bounds.diagonal().length() / (n_samples as Float).sqrt(),
+        )
+    }
+    pub fn sample_exit_pupil(
+        &self,
+        p_film: &Point2f,
+        lens_sample: &Point2f,
+        sample_bounds_area: &mut Float,
+    ) -> Point3f {
+        // find exit pupil bound for sample distance from film center
+        let r_film: Float = (p_film.x * p_film.x + p_film.y * p_film.y).sqrt();
+        let mut r_index: usize = (r_film / (self.film.diagonal / 2.0 as Float)
+            * self.exit_pupil_bounds.len() as Float) as usize;
+        r_index = std::cmp::min(self.exit_pupil_bounds.len() - 1, r_index);
+        let pupil_bounds: Bounds2f = self.exit_pupil_bounds[r_index];
+        *sample_bounds_area = pupil_bounds.area();
+        // generate sample point inside exit pupil bound
+        let p_lens: Point2f = pupil_bounds.lerp(lens_sample);
+        // return sample point rotated by angle of _p_film_ with $+x$ axis
+        let sin_theta: Float;
+        let cos_theta: Float;
+        if r_film != 0.0 as Float {
+            sin_theta = p_film.y / r_film;
+            cos_theta = p_film.x / r_film;
+        } else {
+            sin_theta = 0.0 as Float;
+            cos_theta = 1.0 as Float;
+        }
+        Point3f {
+            x: cos_theta * p_lens.x - sin_theta * p_lens.y,
+            y: sin_theta * p_lens.x + cos_theta * p_lens.y,
+            z: self.lens_rear_z(),
+        }
+    }
+}
+
+impl Camera for RealisticCamera {
+    fn generate_ray_differential(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
+        // TODO: ProfilePhase prof(Prof::GenerateCameraRay);
+        // find point on film, _p_film_, corresponding to _sample.p_film_
+        let s: Point2f = Point2f {
+            x: sample.p_film.x / self.film.full_resolution.x as Float,
+            y: sample.p_film.y / self.film.full_resolution.y as Float,
+        };
+        let p_film2: Point2f = self.film.get_physical_extent().lerp(&s);
+        let p_film: Point3f = Point3f {
+            x: -p_film2.x,
+            y: p_film2.y,
+            z: 0.0,
+        };
+        // trace ray from _p_film_ through lens system
+        let mut exit_pupil_bounds_area: Float = 0.0;
+        let p_rear: Point3f = self.sample_exit_pupil(
+            &Point2f {
+                x: p_film.x,
+                y: p_film.y,
+            },
+            &sample.p_lens,
+            &mut exit_pupil_bounds_area,
+        );
+        let r_film: Ray = new_ray(
+            &p_film,
+            &(p_rear - p_film),
+            lerp(sample.time, self.shutter_open, self.shutter_close),
+        );
+        let mut r_camera: Ray = Ray::default();
+        if !self.trace_lenses_from_film(&r_film, Some(&mut r_camera)) {
+            // TODO: ++vignettedRays;
+            return 0.0;
+        }
+        // finish initialization of _RealisticCamera_ ray
+        *ray = self.camera_to_world.transform_ray(&r_camera);
+        ray.d = vec3_normalize(&ray.d);
+        ray.medium = self.medium.clone();
+        // return weighting for _RealisticCamera_ ray
+        let cos_theta: Float = vec3_normalize(&r_film.d).z;
+        let cos_4_theta: Float = (cos_theta * cos_theta) * (cos_theta * cos_theta);
+        if self.simple_weighting {
+            cos_4_theta * exit_pupil_bounds_area / self.exit_pupil_bounds[0].area()
+        } else {
+            (self.shutter_close - self.shutter_open) * (cos_4_theta * exit_pupil_bounds_area)
+                / (self.lens_rear_z() * self.lens_rear_z())
+        }
+    }
+    fn we(&self, _ray: &Ray, _p_raster2: Option<&mut Point2f>) -> Spectrum {
+        panic!("RealisticCamera::we() is not implemented!");
+    }
+    fn pdf_we(&self, _ray: &Ray) -> (Float, Float) {
+        panic!("RealisticCamera::pdf_we() is not implemented!");
+    }
+    fn sample_wi(
+        &self,
+        _iref: &InteractionCommon,
+        _u: &Point2f,
+        _wi: &mut Vector3f,
+        _pdf: &mut Float,
+        _p_raster: &mut Point2f,
+        _vis: &mut VisibilityTester,
+    ) -> Spectrum {
+        panic!("RealisticCamera::sample_wi() is not implemented!");
+    }
+    fn get_film(&self) -> Arc<Film> {
+        self.film.clone()
+    }
+    fn get_shutter_open(&self) -> Float {
+        self.shutter_open
+    }
+    fn get_shutter_close(&self) -> Float {
+        self.shutter_close
+    }
+}
+
+fn new_ray(o: &Point3f, d: &Vector3f, time: Float) -> Ray {
+    Ray {
+        o: *o,
+        d: *d,
+        t_max: std::f32::INFINITY,
+        time: time,
+        differential: None,
+        medium: None,
+    }
+}
+
+/// Flips the **z** axis, which transforms from camera to lens system
+/// space and back.
+fn camera_to_lens(r: &Ray) -> Ray {
+    let mut r_lens: Ray = new_ray(&r.o, &r.d, r.time);
+    r_lens.o.z = -r_lens.o.z;
+    r_lens.d.z = -r_lens.d.z;
+    r_lens.t_max = r.t_max;
+    r_lens
+}
+
+fn intersect_spherical_element(
+    radius: Float,
+    z_center: Float,
+    ray: &Ray,
+    t: &mut Float,
+    n: &mut Normal3f,
+) -> bool {
+    // compute _t0_ and _t1_ for ray--element intersection
+    let o: Point3f = ray.o - Vector3f {
+        x: 0.0,
+        y: 0.0,
+        z: z_center,
+    };
+    let a: Float = ray.d.x * ray.d.x + ray.d.y * ray.d.y + ray.d.z * ray.d.z;
+    let b: Float = 2.0 as Float * (ray.d.x * o.x + ray.d.y * o.y + ray.d.z * o.z);
+    let c: Float = o.x * o.x + o.y * o.y + o.z * o.z - radius * radius;
+    let mut t0: Float = 0.0;
+    let mut t1: Float = 0.0;
+    if !quadratic(a, b, c, &mut t0, &mut t1) {
+        return false;
+    }
+    // select intersection $t$ based on ray direction and element curvature
+    let use_closer_t: bool = (ray.d.z > 0.0 as Float) ^ (radius < 0.0 as Float);
+    if use_closer_t {
+        *t = t0.min(t1);
+    } else {
+        *t = t0.max(t1);
+    }
+    if *t < 0.0 as Float {
+        return false;
+    }
+    // compute surface normal of element at ray intersection point
+    *n = Normal3f::from(Vector3f::from(o + ray.d * *t));
+    *n = nrm_faceforward_vec3(&nrm_normalize(n), &-ray.d);
+    true
+}
+
+fn compute_cardinal_points(r_in: &Ray, r_out: &Ray, pz: &mut Float, fz: &mut Float) {
+    let tf: Float = -r_out.o.x / r_out.d.x;
+    *fz = -r_out.position(tf).z;
+    let tp: Float = (r_in.o.x - r_out.o.x) / r_out.d.x;
+    *pz = -r_out.position(tp).z;
+}
@@ -1,15 +1,80 @@
 // std
+use std;
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
 use std::sync::Arc;
 // pbrt
+use accelerators::bvh::{BVHAccel, SplitMethod};
+use cameras::cylindrical::CylindricalCamera;
+use cameras::environment::EnvironmentCamera;
+use cameras::fisheye::FisheyeCamera;
+use cameras::orthographic::OrthographicCamera;
+use cameras::perspective::PerspectiveCamera;
+use cameras::realistic::RealisticCamera;
+use core::camera::Camera;
+use core::exporter::SceneExporter;
+use core::film::Film;
+use core::filter::Filter;
+use core::geometry::{Bounds2f, Bounds2i, Normal3f, Point2f, Point2i, Point3f, Vector3f};
+use core::geometry::bnd2_intersect_bnd2;
+use core::integrator::Integrator;
 use core::light::Light;
 use core::material::Material;
+use core::medium::{Medium, MediumInterface};
+use core::medium::get_medium_scattering_properties;
+use core::mipmap::ImageWrap;
 use core::paramset::{ParamSet, TextureParams};
 use core::pbrt::{Float, Spectrum};
-use core::primitive::Primitive;
-use core::texture::Texture;
-use core::transform::{Matrix4x4, Transform};
+use core::pbrt::clamp_t;
+use core::primitive::{GeometricPrimitive, Primitive, TransformedPrimitive};
+use core::sampler::Sampler;
+use core::scene::Scene;
+use core::shape::Shape;
+use core::texture::{PlanarMapping2D, Texture, TextureMapping2D, UVMapping2D};
+use core::transform::{AnimatedTransform, Matrix4x4, Transform};
+use filters::boxfilter::BoxFilter;
+use filters::gaussian::GaussianFilter;
+use filters::triangle::TriangleFilter;
+use integrators::ao::AOIntegrator;
+use integrators::bdpt::BDPTIntegrator;
+use integrators::directlighting::{DirectLightingIntegrator, LightStrategy};
+use integrators::lightpath::LightPathIntegrator;
+use integrators::mlt::MLTIntegrator;
+use integrators::path::PathIntegrator;
+use integrators::sppm::SPPMIntegrator;
+use integrators::volpath::VolPathIntegrator;
+use integrators::whitted::WhittedIntegrator;
+use lights::diffuse::DiffuseAreaLight;
+use lights::distant::DistantLight;
+use lights::infinite::InfiniteAreaLight;
+use lights::point::PointLight;
+use materials::glass::GlassMaterial;
+use materials::hair::HairMaterial;
 use materials::matte::MatteMaterial;
+use materials::metal::MetalMaterial;
+use materials::mirror::MirrorMaterial;
+use materials::mixmat::MixMaterial;
+use materials::plastic::PlasticMaterial;
+use materials::substrate::SubstrateMaterial;
+use materials::uber::UberMaterial;
+use media::grid::GridDensityMedium;
+use media::homogeneous::HomogeneousMedium;
+use samplers::halton::HaltonSampler;
+use samplers::random::RandomSampler;
+use samplers::sobol::SobolSampler;
+use samplers::zerotwosequence::ZeroTwoSequenceSampler;
+use shapes::curve::create_curve_shape;
+use shapes::cylinder::Cylinder;
+use shapes::disk::Disk;
+use shapes::objmesh::create_obj_mesh;
+use shapes::plymesh::create_ply_mesh;
+use shapes::sphere::Sphere;
+use shapes::triangle::{Triangle, TriangleMesh};
+use textures::checkerboard::Checkerboard2DTexture;
+use textures::constant::ConstantTexture;
+use textures::imagemap::ImageTexture;
 
 // see api.cpp
 
@@ -45,11 +110,12 @@ pub struct RenderOptions {
     pub camera_name: String, // "perspective";
     pub camera_params: ParamSet,
     pub camera_to_world: TransformSet,
-    // TODO: std::map<std::string, std::shared_ptr<Medium>> namedMedia;
+    pub camera_medium: String,
+    pub named_media: HashMap<String, Arc<Medium + Send + Sync>>,
     pub lights: Vec<Arc<Light + Sync + Send>>,
     pub primitives: Vec<Arc<Primitive + Sync + Send>>,
-    // TODO: std::map<std::string, std::vector<std::shared_ptr<Primitive>>> instances;
-    // TODO: std::vector<std::shared_ptr<Primitive>> *currentInstance = nullptr;
+    pub instances: HashMap<String, Vec<Arc<Primitive + Sync + Send>>>,
+    pub current_instance: Option<String>, // name of instance being defined
     pub have_scattering_media: bool, // false
 }
 
@@ -66,7 +132,7 @@ impl Default for RenderOptions {
             sampler_params: ParamSet::default(),
             accelerator_name: String::from("bvh"),
             accelerator_params: ParamSet::default(),
-            integrator_name: String::from("image"),
+            integrator_name: String::from("path"),
             integrator_params: ParamSet::default(),
             camera_name: String::from("perspective"),
             camera_params: ParamSet::default(),
@@ -86,8 +152,12 @@ impl Default for RenderOptions {
                     },
                 }; 2],
             },
+            camera_medium: String::new(),
+            named_media: HashMap::new(),
             lights: Vec::new(),
             primitives: Vec::new(),
+            instances: HashMap::new(),
+            current_instance: None,
             have_scattering_media: false,
         }
     }
@@ -102,7 +172,8 @@ impl RenderOptions {
 
 #[derive(Default)]
 pub struct GraphicsState {
-    // std::string currentInsideMedium, currentOutsideMedium;
+    pub current_inside_medium: String,
+    pub current_outside_medium: String,
     pub float_textures: HashMap<String, Arc<Texture<Float> + Send + Sync>>,
     pub spectrum_textures: HashMap<String, Arc<Texture<Spectrum> + Send + Sync>>,
     pub material_params: ParamSet,
@@ -128,6 +199,8 @@ impl GraphicsState {
         named_materials.insert(String::from("matte"), mtl);
         let current_material: String = String::from("matte");
         GraphicsState {
+            current_inside_medium: String::new(),
+            current_outside_medium: String::new(),
             float_textures: float_textures.clone(),
             spectrum_textures: spectrum_textures.clone(),
             material_params: ParamSet::default(),
@@ -140,3 +213,2171 @@ impl GraphicsState {
         }
     }
 }
+
+impl Clone for GraphicsState {
+    fn clone(&self) -> GraphicsState {
+        let mut material_params: ParamSet = ParamSet::default();
+        material_params.copy_from(&self.material_params);
+        let mut area_light_params: ParamSet = ParamSet::default();
+        area_light_params.copy_from(&self.area_light_params);
+        GraphicsState {
+            current_inside_medium: self.current_inside_medium.clone(),
+            current_outside_medium: self.current_outside_medium.clone(),
+            float_textures: self.float_textures.clone(),
+            spectrum_textures: self.spectrum_textures.clone(),
+            material_params: material_params,
+            material: self.material.clone(),
+            named_materials: self.named_materials.clone(),
+            current_material: self.current_material.clone(),
+            area_light_params: area_light_params,
+            area_light: self.area_light.clone(),
+            reverse_orientation: self.reverse_orientation,
+        }
+    }
+}
+
+/// Problems found while setting up the scene (unknown names, wrong
+/// number of parameter values, missing files, ...). The parser adds
+/// the location within the scene file.
+#[derive(Debug, Clone)]
+pub struct SceneError {
+    pub message: String,
+}
+
+impl SceneError {
+    pub fn new(message: String) -> SceneError {
+        SceneError { message: message }
+    }
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for SceneError {
+    fn description(&self) -> &str {
+        self.message.as_str()
+    }
+}
+
+pub const MAX_TRANSFORMS: usize = 2;
+pub const START_TRANSFORM_BITS: u8 = 1 << 0;
+pub const END_TRANSFORM_BITS: u8 = 1 << 1;
+pub const ALL_TRANSFORMS_BITS: u8 = (1 << MAX_TRANSFORMS) - 1;
+
+/// All the state the C++ version keeps in static variables of
+/// api.cpp. Each scene gets its own instance, so several scenes can
+/// be parsed in the same process (or in parallel).
+pub struct ApiState {
+    pub search_directory: Option<Box<PathBuf>>,
+    pub cur_transform: TransformSet,
+    pub active_transform_bits: u8,
+    pub named_coordinate_systems: HashMap<String, TransformSet>,
+    pub render_options: RenderOptions,
+    pub graphics_state: GraphicsState,
+    pub pushed_graphics_states: Vec<GraphicsState>,
+    pub pushed_transforms: Vec<TransformSet>,
+    pub pushed_active_transform_bits: Vec<u8>,
+    /// Recoverable problems, collected (and located) by the parser.
+    pub warnings: Vec<String>,
+    /// If set, the scene is also written to a .pbrt file.
+    pub exporter: Option<SceneExporter>,
+}
+
+impl Default for ApiState {
+    fn default() -> ApiState {
+        ApiState {
+            search_directory: None,
+            cur_transform: TransformSet {
+                t: [Transform::default(); MAX_TRANSFORMS],
+            },
+            active_transform_bits: ALL_TRANSFORMS_BITS,
+            named_coordinate_systems: HashMap::new(),
+            render_options: RenderOptions::default(),
+            graphics_state: GraphicsState::new(),
+            pushed_graphics_states: Vec::new(),
+            pushed_transforms: Vec::new(),
+            pushed_active_transform_bits: Vec::new(),
+            warnings: Vec::new(),
+            exporter: None,
+        }
+    }
+}
+
+impl ApiState {
+    pub fn new(search_directory: Option<PathBuf>) -> Self {
+        let mut api_state: ApiState = ApiState::default();
+        if let Some(search_directory) = search_directory {
+            api_state.search_directory = Some(Box::new(search_directory));
+        }
+        api_state
+    }
+    pub fn warning(&mut self, message: String) {
+        self.warnings.push(message);
+    }
+    /// Multiplies all active transforms (see **ActiveTransform**) by
+    /// the given transform (FOR_ACTIVE_TRANSFORMS in api.cpp).
+    fn concat_active_transforms(&mut self, transform: &Transform) {
+        for i in 0..MAX_TRANSFORMS {
+            if self.active_transform_bits & (1_u8 << i) != 0_u8 {
+                self.cur_transform.t[i] = self.cur_transform.t[i] * *transform;
+            }
+        }
+    }
+    /// Looks up the current inside and outside media
+    /// (GraphicsState::CreateMediumInterface in api.cpp).
+    fn create_medium_interface(&mut self) -> MediumInterface {
+        let inside_name: String = self.graphics_state.current_inside_medium.clone();
+        let outside_name: String = self.graphics_state.current_outside_medium.clone();
+        let inside: Option<Arc<Medium + Send + Sync>> = self.find_medium(&inside_name);
+        let outside: Option<Arc<Medium + Send + Sync>> = self.find_medium(&outside_name);
+        MediumInterface::new(inside, outside)
+    }
+    fn find_medium(&mut self, name: &String) -> Option<Arc<Medium + Send + Sync>> {
+        if *name == String::new() {
+            return None;
+        }
+        let medium: Option<Arc<Medium + Send + Sync>> =
+            self.render_options.named_media.get(name.as_str()).cloned();
+        if medium.is_none() {
+            self.warning(format!("Named medium \"{}\" undefined.", name));
+        }
+        medium
+    }
+    /// Resolves a filename relative to the directory of the scene file.
+    pub fn resolve_filename(&self, filename: String) -> String {
+        if let Some(ref search_directory) = self.search_directory {
+            // filename = AbsolutePath(ResolveFilename(filename));
+            let mut path_buf: PathBuf = PathBuf::from("/");
+            path_buf.push(search_directory.as_ref());
+            path_buf.push(filename);
+            String::from(path_buf.to_str().unwrap())
+        } else {
+            filename
+        }
+    }
+}
+
+// API Function Declarations
+
+pub fn pbrt_identity(api_state: &mut ApiState) {
+    for i in 0..MAX_TRANSFORMS {
+        if api_state.active_transform_bits & (1_u8 << i) != 0_u8 {
+            api_state.cur_transform.t[i] = Transform::default();
+        }
+    }
+}
+
+pub fn pbrt_translate(api_state: &mut ApiState, dx: Float, dy: Float, dz: Float) {
+    let translate: Transform = Transform::translate(&Vector3f {
+        x: dx,
+        y: dy,
+        z: dz,
+    });
+    api_state.concat_active_transforms(&translate);
+}
+
+/// Expects the 16 numbers in the order they appear in the scene file
+/// (column-major), see **pbrtTransform()** in api.cpp.
+pub fn pbrt_transform(api_state: &mut ApiState, tr: &[Float; 16]) {
+    let transform: Transform = Transform::new(
+        tr[0], tr[4], tr[8], tr[12], tr[1], tr[5], tr[9], tr[13], tr[2], tr[6], tr[10], tr[14],
+        tr[3], tr[7], tr[11], tr[15],
+    );
+    for i in 0..MAX_TRANSFORMS {
+        if api_state.active_transform_bits & (1_u8 << i) != 0_u8 {
+            api_state.cur_transform.t[i] = transform;
+        }
+    }
+}
+
+pub fn pbrt_concat_transform(api_state: &mut ApiState, tr: &[Float; 16]) {
+    let transform: Transform = Transform::new(
+        tr[0], tr[4], tr[8], tr[12], tr[1], tr[5], tr[9], tr[13], tr[2], tr[6], tr[10], tr[14],
+        tr[3], tr[7], tr[11], tr[15],
+    );
+    api_state.concat_active_transforms(&transform);
+}
+
+pub fn pbrt_rotate(api_state: &mut ApiState, angle: Float, dx: Float, dy: Float, dz: Float) {
+    let rotate: Transform = Transform::rotate(
+        angle,
+        &Vector3f {
+            x: dx,
+            y: dy,
+            z: dz,
+        },
+    );
+    api_state.concat_active_transforms(&rotate);
+}
+
+pub fn pbrt_scale(api_state: &mut ApiState, sx: Float, sy: Float, sz: Float) {
+    let scale: Transform = Transform::scale(sx, sy, sz);
+    api_state.concat_active_transforms(&scale);
+}
+
+pub fn pbrt_look_at(
+    api_state: &mut ApiState,
+    ex: Float,
+    ey: Float,
+    ez: Float,
+    lx: Float,
+    ly: Float,
+    lz: Float,
+    ux: Float,
+    uy: Float,
+    uz: Float,
+) {
+    let pos: Point3f = Point3f {
+        x: ex,
+        y: ey,
+        z: ez,
+    };
+    let look: Point3f = Point3f {
+        x: lx,
+        y: ly,
+        z: lz,
+    };
+    let up: Vector3f = Vector3f {
+        x: ux,
+        y: uy,
+        z: uz,
+    };
+    let look_at: Transform = Transform::look_at(&pos, &look, &up);
+    api_state.concat_active_transforms(&look_at);
+}
+
+pub fn pbrt_coordinate_system(api_state: &mut ApiState, name: String) {
+    api_state
+        .named_coordinate_systems
+        .insert(name, api_state.cur_transform);
+}
+
+pub fn pbrt_coord_sys_transform(api_state: &mut ApiState, name: String) {
+    match api_state.named_coordinate_systems.get(name.as_str()) {
+        Some(transform_set) => {
+            api_state.cur_transform.t[0] = transform_set.t[0];
+            api_state.cur_transform.t[1] = transform_set.t[1];
+        }
+        None => {
+            api_state.warning(format!("Couldn't find named coordinate system \"{}\"", name));
+        }
+    };
+}
+
+pub fn pbrt_active_transform_all(api_state: &mut ApiState) {
+    api_state.active_transform_bits = ALL_TRANSFORMS_BITS;
+}
+
+pub fn pbrt_active_transform_end_time(api_state: &mut ApiState) {
+    api_state.active_transform_bits = END_TRANSFORM_BITS;
+}
+
+pub fn pbrt_active_transform_start_time(api_state: &mut ApiState) {
+    api_state.active_transform_bits = START_TRANSFORM_BITS;
+}
+
+pub fn pbrt_transform_times(api_state: &mut ApiState, start: Float, end: Float) {
+    // TODO: VERIFY_OPTIONS("TransformTimes");
+    api_state.render_options.transform_start_time = start;
+    api_state.render_options.transform_end_time = end;
+}
+
+pub fn pbrt_pixel_filter(api_state: &mut ApiState, name: String, params: ParamSet) {
+    api_state.render_options.filter_name = name;
+    api_state.render_options.filter_params = params;
+}
+
+pub fn pbrt_film(api_state: &mut ApiState, name: String, params: ParamSet) {
+    api_state.render_options.film_name = name;
+    api_state.render_options.film_params = params;
+}
+
+pub fn pbrt_sampler(api_state: &mut ApiState, name: String, params: ParamSet) {
+    api_state.render_options.sampler_name = name;
+    api_state.render_options.sampler_params = params;
+}
+
+pub fn pbrt_accelerator(api_state: &mut ApiState, name: String, params: ParamSet) {
+    api_state.render_options.accelerator_name = name;
+    api_state.render_options.accelerator_params = params;
+}
+
+pub fn pbrt_integrator(api_state: &mut ApiState, name: String, params: ParamSet) {
+    api_state.render_options.integrator_name = name;
+    api_state.render_options.integrator_params = params;
+}
+
+pub fn pbrt_camera(api_state: &mut ApiState, name: String, params: ParamSet) {
+    api_state.render_options.camera_name = name;
+    api_state.render_options.camera_params = params;
+    api_state.render_options.camera_medium =
+        api_state.graphics_state.current_outside_medium.clone();
+    api_state.render_options.camera_to_world.t[0] =
+        Transform::inverse(&api_state.cur_transform.t[0]);
+    api_state.render_options.camera_to_world.t[1] =
+        Transform::inverse(&api_state.cur_transform.t[1]);
+    api_state.named_coordinate_systems.insert(
+        String::from("camera"),
+        api_state.render_options.camera_to_world,
+    );
+}
+
+pub fn pbrt_make_named_medium(
+    api_state: &mut ApiState,
+    name: String,
+    params: ParamSet,
+) -> Result<(), SceneError> {
+    if let Some(ref mut exporter) = api_state.exporter {
+        exporter.make_named_medium(&api_state.cur_transform, &name, &params);
+    }
+    let medium_type: String = params.find_one_string(String::from("type"), String::new());
+    if medium_type == String::new() {
+        return Err(SceneError::new(String::from(
+            "No parameter string \"type\" found in MakeNamedMedium",
+        )));
+    }
+    let medium_to_world: Transform = api_state.cur_transform.t[0];
+    if let Some(medium) = make_medium(
+        &medium_type,
+        &params,
+        &medium_to_world,
+        &mut api_state.warnings,
+    )? {
+        api_state.warnings.extend(params.report_unused());
+        api_state.render_options.named_media.insert(name, medium);
+    }
+    Ok(())
+}
+
+pub fn pbrt_medium_interface(api_state: &mut ApiState, inside_name: String, outside_name: String) {
+    api_state.graphics_state.current_inside_medium = inside_name;
+    api_state.graphics_state.current_outside_medium = outside_name;
+    api_state.render_options.have_scattering_media = true;
+}
+
+pub fn pbrt_world_begin(api_state: &mut ApiState) {
+    // TODO: VERIFY_OPTIONS("WorldBegin");
+    if let Some(ref mut exporter) = api_state.exporter {
+        exporter.world_begin(
+            &api_state.render_options,
+            api_state.search_directory.as_ref(),
+        );
+    }
+    for i in 0..MAX_TRANSFORMS {
+        api_state.cur_transform.t[i] = Transform::default();
+    }
+    api_state.active_transform_bits = ALL_TRANSFORMS_BITS;
+    api_state
+        .named_coordinate_systems
+        .insert(String::from("world"), api_state.cur_transform);
+}
+
+pub fn pbrt_attribute_begin(api_state: &mut ApiState) {
+    if let Some(ref mut exporter) = api_state.exporter {
+        exporter.attribute_begin();
+    }
+    api_state
+        .pushed_graphics_states
+        .push(api_state.graphics_state.clone());
+    api_state.pushed_transforms.push(api_state.cur_transform);
+    api_state
+        .pushed_active_transform_bits
+        .push(api_state.active_transform_bits);
+}
+
+pub fn pbrt_attribute_end(api_state: &mut ApiState) {
+    if api_state.pushed_graphics_states.is_empty() {
+        api_state.warning(String::from("Unmatched pbrtAttributeEnd() encountered. Ignoring it."));
+        return;
+    }
+    if let Some(ref mut exporter) = api_state.exporter {
+        exporter.attribute_end();
+    }
+    api_state.graphics_state = api_state.pushed_graphics_states.pop().unwrap();
+    api_state.cur_transform = api_state.pushed_transforms.pop().unwrap();
+    api_state.active_transform_bits = api_state.pushed_active_transform_bits.pop().unwrap();
+}
+
+pub fn pbrt_transform_begin(api_state: &mut ApiState) {
+    api_state.pushed_transforms.push(api_state.cur_transform);
+    api_state
+        .pushed_active_transform_bits
+        .push(api_state.active_transform_bits);
+}
+
+pub fn pbrt_transform_end(api_state: &mut ApiState) {
+    if api_state.pushed_transforms.is_empty() {
+        api_state.warning(String::from("Unmatched pbrtTransformEnd() encountered. Ignoring it."));
+        return;
+    }
+    api_state.cur_transform = api_state.pushed_transforms.pop().unwrap();
+    api_state.active_transform_bits = api_state.pushed_active_transform_bits.pop().unwrap();
+}
+
+pub fn pbrt_texture(
+    api_state: &mut ApiState,
+    name: String,
+    tex_type: String,
+    tex_name: String,
+    params: ParamSet,
+) -> Result<(), SceneError> {
+    if let Some(ref mut exporter) = api_state.exporter {
+        exporter.texture(&api_state.cur_transform, &name, &tex_type, &tex_name, &params);
+    }
+    let mut param_set: ParamSet = params;
+    param_set.name = name;
+    param_set.tex_type = tex_type;
+    param_set.tex_name = tex_name;
+    make_texture(api_state, &param_set)
+}
+
+pub fn pbrt_material(api_state: &mut ApiState, name: String, params: ParamSet) {
+    // shapes create their own material (see create_material()), create
+    // it once here to report unused parameters
+    let mut material_params: ParamSet = ParamSet::default();
+    material_params.copy_from(&params);
+    let mut mp: TextureParams = TextureParams {
+        float_textures: api_state.graphics_state.float_textures.clone(),
+        spectrum_textures: api_state.graphics_state.spectrum_textures.clone(),
+        geom_params: ParamSet::default(),
+        material_params: material_params,
+    };
+    if !is_interface_material(&name)
+        && make_material(
+            &name,
+            &mut mp,
+            &api_state.graphics_state.named_materials,
+            &mut api_state.warnings,
+        ).is_some()
+    {
+        api_state.warnings.extend(mp.report_unused());
+    }
+    api_state.graphics_state.material = name;
+    api_state.graphics_state.material_params = params;
+    api_state.graphics_state.current_material = String::new();
+}
+
+pub fn pbrt_make_named_material(
+    api_state: &mut ApiState,
+    name: String,
+    params: ParamSet,
+) -> Result<(), SceneError> {
+    if let Some(ref mut exporter) = api_state.exporter {
+        exporter.make_named_material(&name, &params);
+    }
+    let mut mp: TextureParams = TextureParams {
+        float_textures: api_state.graphics_state.float_textures.clone(),
+        spectrum_textures: api_state.graphics_state.spectrum_textures.clone(),
+        geom_params: params,
+        material_params: ParamSet::default(),
+    };
+    let mat_type: String = mp.find_string(String::from("type"), String::new());
+    if mat_type == String::new() {
+        return Err(SceneError::new(String::from(
+            "No parameter string \"type\" found in MakeNamedMaterial",
+        )));
+    }
+    let mtl: Arc<Material + Send + Sync> = match make_material(
+        &mat_type,
+        &mut mp,
+        &api_state.graphics_state.named_materials,
+        &mut api_state.warnings,
+    ) {
+        Some(material) => {
+            api_state.warnings.extend(mp.report_unused());
+            material
+        }
+        None => default_material(),
+    };
+    if api_state
+        .graphics_state
+        .named_materials
+        .contains_key(name.as_str())
+    {
+        api_state.warning(format!("Named material \"{}\" redefined", name));
+    }
+    api_state.graphics_state.named_materials.insert(name, mtl);
+    Ok(())
+}
+
+pub fn pbrt_named_material(api_state: &mut ApiState, name: String) {
+    api_state.graphics_state.current_material = name;
+}
+
+pub fn pbrt_light_source(api_state: &mut ApiState, name: String, params: ParamSet) {
+    if let Some(ref mut exporter) = api_state.exporter {
+        exporter.light_source(
+            &api_state.cur_transform,
+            &api_state.graphics_state,
+            &name,
+            &params,
+        );
+    }
+    let mut param_set: ParamSet = params;
+    param_set.name = name;
+    make_light(api_state, &param_set);
+}
+
+pub fn pbrt_area_light_source(api_state: &mut ApiState, name: String, params: ParamSet) {
+    api_state.graphics_state.area_light = name;
+    api_state.graphics_state.area_light_params = params;
+}
+
+pub fn pbrt_reverse_orientation(api_state: &mut ApiState) {
+    api_state.graphics_state.reverse_orientation =
+        !api_state.graphics_state.reverse_orientation;
+}
+
+pub fn pbrt_shape(
+    api_state: &mut ApiState,
+    name: String,
+    params: ParamSet,
+) -> Result<(), SceneError> {
+    if let Some(ref mut exporter) = api_state.exporter {
+        exporter.shape(
+            &api_state.cur_transform,
+            &api_state.graphics_state,
+            &name,
+            &params,
+        );
+    }
+    let mut param_set: ParamSet = params;
+    param_set.name = name;
+    let params: &ParamSet = &param_set;
+    let mut prims: Vec<Arc<Primitive + Send + Sync>> = Vec::new();
+    let mut area_lights: Vec<Arc<Light + Send + Sync>> = Vec::new();
+    if !api_state.cur_transform.is_animated() {
+        // initialize _prims_ and _areaLights_ for static shape
+
+        // TODO: transformCache.Lookup(curTransform[0], &ObjToWorld, &WorldToObj);
+        let obj_to_world: Transform = api_state.cur_transform.t[0];
+        let world_to_obj: Transform = Transform::inverse(&api_state.cur_transform.t[0]);
+        let (shapes, materials) = make_shapes(api_state, params, &obj_to_world, &world_to_obj)?;
+        assert_eq!(shapes.len(), materials.len());
+        let mi: MediumInterface = api_state.create_medium_interface();
+        for i in 0..shapes.len() {
+            let shape = &shapes[i];
+            let material = &materials[i];
+            let mut area_light: Option<Arc<DiffuseAreaLight>> = None;
+            if api_state.graphics_state.area_light != String::new() {
+                area_light = make_area_light(api_state, shape.clone(), &mi);
+            }
+            if let Some(area_light) = area_light {
+                area_lights.push(area_light.clone());
+                let geo_prim = Arc::new(GeometricPrimitive::new(
+                    shape.clone(),
+                    material.clone(),
+                    Some(area_light),
+                    mi.clone(),
+                ));
+                prims.push(geo_prim);
+            } else {
+                let geo_prim = Arc::new(GeometricPrimitive::new(
+                    shape.clone(),
+                    material.clone(),
+                    None,
+                    mi.clone(),
+                ));
+                prims.push(geo_prim);
+            }
+        }
+    } else {
+        // initialize _prims_ for animated shape
+
+        // create initial shape or shapes for animated shape
+        if api_state.graphics_state.area_light != String::new() {
+            api_state.warning(String::from(
+                "Ignoring currently set area light when creating animated shape",
+            ));
+        }
+        let identity: Transform = Transform::default();
+        let (shapes, materials) = make_shapes(api_state, params, &identity, &identity)?;
+        assert_eq!(shapes.len(), materials.len());
+        let mi: MediumInterface = api_state.create_medium_interface();
+        for i in 0..shapes.len() {
+            let geo_prim = Arc::new(GeometricPrimitive::new(
+                shapes[i].clone(),
+                materials[i].clone(),
+                None,
+                mi.clone(),
+            ));
+            prims.push(geo_prim);
+        }
+        // create single _TransformedPrimitive_ for _prims_
+
+        // get _animatedObjectToWorld_ transform for shape
+        let animated_object_to_world: AnimatedTransform = AnimatedTransform::new(
+            &api_state.cur_transform.t[0],
+            api_state.render_options.transform_start_time,
+            &api_state.cur_transform.t[1],
+            api_state.render_options.transform_end_time,
+        );
+        if prims.len() > 1 {
+            let bvh: Arc<Primitive + Send + Sync> =
+                Arc::new(BVHAccel::new(prims.clone(), 4, SplitMethod::SAH));
+            prims.clear();
+            prims.push(bvh);
+        }
+        if let Some(primitive) = prims.pop() {
+            let transformed_prim = Arc::new(TransformedPrimitive::new(
+                primitive,
+                animated_object_to_world,
+            ));
+            prims.push(transformed_prim);
+        }
+    }
+    // add _prims_ and _areaLights_ to scene or current instance
+    if let Some(ref current_instance) = api_state.render_options.current_instance {
+        if !area_lights.is_empty() {
+            api_state
+                .warnings
+                .push(String::from("Area lights not supported with object instancing"));
+        }
+        if let Some(instance) = api_state.render_options.instances.get_mut(current_instance) {
+            for prim in prims {
+                instance.push(prim);
+            }
+        }
+    } else {
+        for prim in prims {
+            api_state.render_options.primitives.push(prim);
+        }
+        for area_light in area_lights {
+            api_state.render_options.lights.push(area_light);
+        }
+    }
+    Ok(())
+}
+
+pub fn pbrt_object_begin(api_state: &mut ApiState, name: String) -> Result<(), SceneError> {
+    // TODO: VERIFY_WORLD("ObjectBegin");
+    pbrt_attribute_begin(api_state);
+    if api_state.render_options.current_instance.is_some() {
+        return Err(SceneError::new(String::from(
+            "ObjectBegin called inside of instance definition",
+        )));
+    }
+    if api_state.render_options.instances.contains_key(name.as_str()) {
+        api_state.warning(format!("Object \"{}\" redefined", name));
+    }
+    api_state
+        .render_options
+        .instances
+        .insert(name.clone(), Vec::new());
+    if let Some(ref mut exporter) = api_state.exporter {
+        exporter.object_begin(&name);
+    }
+    api_state.render_options.current_instance = Some(name);
+    Ok(())
+}
+
+pub fn pbrt_object_end(api_state: &mut ApiState) -> Result<(), SceneError> {
+    // TODO: VERIFY_WORLD("ObjectEnd");
+    if api_state.render_options.current_instance.is_none() {
+        return Err(SceneError::new(String::from(
+            "ObjectEnd called outside of instance definition",
+        )));
+    }
+    api_state.render_options.current_instance = None;
+    if let Some(ref mut exporter) = api_state.exporter {
+        exporter.object_end();
+    }
+    pbrt_attribute_end(api_state);
+    Ok(())
+}
+
+pub fn pbrt_object_instance(api_state: &mut ApiState, name: String) -> Result<(), SceneError> {
+    // TODO: VERIFY_WORLD("ObjectInstance");
+    // perform object instance error checking
+    if api_state.render_options.current_instance.is_some() {
+        return Err(SceneError::new(String::from(
+            "ObjectInstance can't be called inside instance definition",
+        )));
+    }
+    let ro: &mut RenderOptions = &mut api_state.render_options;
+    let mut prims: Vec<Arc<Primitive + Sync + Send>> = match ro.instances.get_mut(name.as_str()) {
+        Some(instance) => instance.drain(..).collect(),
+        None => {
+            return Err(SceneError::new(format!(
+                "Unable to find instance named \"{}\"",
+                name
+            )))
+        }
+    };
+    if let Some(ref mut exporter) = api_state.exporter {
+        exporter.object_instance(&api_state.cur_transform, &name);
+    }
+    if prims.is_empty() {
+        return Ok(());
+    }
+    if prims.len() > 1 {
+        // create aggregate for instance _Primitive_s
+        let accel: Arc<Primitive + Sync + Send> = make_accelerator(
+            &ro.accelerator_name,
+            prims,
+            &ro.accelerator_params,
+            &mut api_state.warnings,
+        );
+        prims = vec![accel];
+    }
+    // the aggregate replaces the instance's primitives
+    ro.instances.insert(name, prims.clone());
+    // create _animated_instance_to_world_ transform for instance
+    let animated_instance_to_world: AnimatedTransform = AnimatedTransform::new(
+        &api_state.cur_transform.t[0],
+        ro.transform_start_time,
+        &api_state.cur_transform.t[1],
+        ro.transform_end_time,
+    );
+    let prim: Arc<Primitive + Sync + Send> = Arc::new(TransformedPrimitive::new(
+        prims[0].clone(),
+        animated_instance_to_world,
+    ));
+    ro.primitives.push(prim);
+    Ok(())
+}
+
+/// Creates the camera, sampler, integrator and the scene, based on
+/// the accumulated render options. The C++ version renders right
+/// away, here the caller decides what to do with the result.
+pub fn pbrt_world_end(
+    api_state: &mut ApiState,
+) -> Result<
+    (
+        Scene,
+        Box<Camera + Send + Sync>,
+        Box<Sampler + Send + Sync>,
+        Box<Integrator + Send + Sync>,
+    ),
+    SceneError,
+> {
+    // TODO: VERIFY_WORLD("WorldEnd");
+    // ensure there are no pushed graphics states
+    if !api_state.pushed_graphics_states.is_empty() {
+        return Err(SceneError::new(String::from(
+            "Missing end to pbrtAttributeBegin()",
+        )));
+    }
+    if !api_state.pushed_transforms.is_empty() {
+        return Err(SceneError::new(String::from(
+            "Missing end to pbrtTransformBegin()",
+        )));
+    }
+    if let Some(mut exporter) = api_state.exporter.take() {
+        if let Err(err) = exporter.world_end() {
+            return Err(SceneError::new(format!(
+                "Couldn't export the scene: {}",
+                err
+            )));
+        }
+    }
+    let camera: Box<Camera + Send + Sync> = make_camera(api_state)?;
+    let ro: &mut RenderOptions = &mut api_state.render_options;
+    let warnings: &mut Vec<String> = &mut api_state.warnings;
+    let sampler: Box<Sampler + Send + Sync> = make_sampler(ro, &camera)?;
+    let integrator: Box<Integrator + Send + Sync> = make_integrator(ro, &camera, warnings)?;
+    report_unused_options("PixelFilter", &ro.filter_name, &ro.filter_params, warnings);
+    report_unused_options("Film", &ro.film_name, &ro.film_params, warnings);
+    report_unused_options("Camera", &ro.camera_name, &ro.camera_params, warnings);
+    report_unused_options("Sampler", &ro.sampler_name, &ro.sampler_params, warnings);
+    report_unused_options(
+        "Integrator",
+        &ro.integrator_name,
+        &ro.integrator_params,
+        warnings,
+    );
+    let scene: Scene = make_scene(ro, warnings);
+    Ok((scene, camera, sampler, integrator))
+}
+
+// API Local Functions
+
+/// Render options are only created at **WorldEnd**, so the warnings
+/// mention the statement they belong to.
+fn report_unused_options(
+    statement: &str,
+    name: &String,
+    params: &ParamSet,
+    warnings: &mut Vec<String>,
+) {
+    for message in params.report_unused() {
+        warnings.push(format!("{} \"{}\": {}", statement, name, message));
+    }
+}
+
+fn make_filter(ro: &mut RenderOptions) -> Result<Arc<Filter + Sync + Send>, SceneError> {
+    if ro.filter_name == String::from("box") {
+        Ok(BoxFilter::create(&mut ro.filter_params))
+    } else if ro.filter_name == String::from("gaussian") {
+        Ok(GaussianFilter::create(&mut ro.filter_params))
+    } else if ro.filter_name == String::from("mitchell") || ro.filter_name == String::from("sinc")
+    {
+        // TODO: CreateMitchellFilter, CreateSincFilter
+        Err(SceneError::new(format!(
+            "Filter \"{}\" not supported yet.",
+            ro.filter_name
+        )))
+    } else if ro.filter_name == String::from("triangle") {
+        Ok(TriangleFilter::create(&mut ro.filter_params))
+    } else {
+        Err(SceneError::new(format!(
+            "Filter \"{}\" unknown.",
+            ro.filter_name
+        )))
+    }
+}
+
+fn make_film(
+    ro: &mut RenderOptions,
+    filter: Arc<Filter + Sync + Send>,
+) -> Result<Arc<Film>, SceneError> {
+    if ro.film_name == String::from("image") {
+        let filename: String = ro.film_params
+            .find_one_string(String::from("filename"), String::new());
+        let xres: i32 = ro.film_params
+            .find_one_int(String::from("xresolution"), 1280);
+        let yres: i32 = ro.film_params
+            .find_one_int(String::from("yresolution"), 720);
+        // TODO: if (PbrtOptions.quickRender) xres = std::max(1, xres / 4);
+        // TODO: if (PbrtOptions.quickRender) yres = std::max(1, yres / 4);
+        let mut crop: Bounds2f = Bounds2f {
+            p_min: Point2f { x: 0.0, y: 0.0 },
+            p_max: Point2f { x: 1.0, y: 1.0 },
+        };
+        let cr: Vec<Float> = ro.film_params.find_float(String::from("cropwindow"));
+        if cr.len() == 4 {
+            crop.p_min.x = clamp_t(cr[0].min(cr[1]), 0.0, 1.0);
+            crop.p_max.x = clamp_t(cr[0].max(cr[1]), 0.0, 1.0);
+            crop.p_min.y = clamp_t(cr[2].min(cr[3]), 0.0, 1.0);
+            crop.p_max.y = clamp_t(cr[2].max(cr[3]), 0.0, 1.0);
+        } else if cr.len() != 0 {
+            return Err(SceneError::new(format!(
+                "{:?} values supplied for \"cropwindow\". Expected 4.",
+                cr.len()
+            )));
+        }
+        let scale: Float = ro.film_params.find_one_float(String::from("scale"), 1.0);
+        let diagonal: Float = ro.film_params
+            .find_one_float(String::from("diagonal"), 35.0);
+        let max_sample_luminance: Float = ro.film_params.find_one_float(
+            String::from("maxsampleluminance"),
+            std::f32::INFINITY,
+        );
+        Ok(Arc::new(Film::new(
+            Point2i { x: xres, y: yres },
+            crop,
+            filter,
+            diagonal,
+            filename,
+            scale,
+            max_sample_luminance,
+        )))
+    } else {
+        Err(SceneError::new(format!(
+            "Film \"{}\" unknown.",
+            ro.film_name
+        )))
+    }
+}
+
+fn make_camera(api_state: &mut ApiState) -> Result<Box<Camera + Send + Sync>, SceneError> {
+    // lens files are searched relative to the scene file
+    let mut lens_file: String = String::new();
+    if api_state.render_options.camera_name == String::from("realistic") {
+        lens_file = api_state
+            .render_options
+            .camera_params
+            .find_one_filename(String::from("lensfile"), String::new());
+        if lens_file != String::new() {
+            lens_file = api_state.resolve_filename(lens_file);
+        }
+    }
+    let ro: &mut RenderOptions = &mut api_state.render_options;
+    let warnings: &mut Vec<String> = &mut api_state.warnings;
+    let filter: Arc<Filter + Sync + Send> = make_filter(ro)?;
+    let film: Arc<Film> = make_film(ro, filter)?;
+    let mut medium: Option<Arc<Medium + Send + Sync>> = None;
+    if ro.camera_medium != String::new() {
+        medium = ro.named_media.get(ro.camera_medium.as_str()).cloned();
+        if medium.is_none() {
+            warnings.push(format!(
+                "Named medium \"{}\" undefined.",
+                ro.camera_medium
+            ));
+        }
+    }
+    let animated_cam_to_world: AnimatedTransform = AnimatedTransform::new(
+        &ro.camera_to_world.t[0],
+        ro.transform_start_time,
+        &ro.camera_to_world.t[1],
+        ro.transform_end_time,
+    );
+    if ro.camera_name == String::from("perspective") {
+        let camera: Box<Camera + Send + Sync> =
+            PerspectiveCamera::create(&ro.camera_params, animated_cam_to_world, film, medium);
+        Ok(camera)
+    } else if ro.camera_name == String::from("orthographic") {
+        let camera: Box<Camera + Send + Sync> = OrthographicCamera::create(
+            &ro.camera_params,
+            animated_cam_to_world,
+            film,
+            medium,
+            warnings,
+        );
+        Ok(camera)
+    } else if ro.camera_name == String::from("environment") {
+        let camera: Box<Camera + Send + Sync> = EnvironmentCamera::create(
+            &ro.camera_params,
+            animated_cam_to_world,
+            film,
+            medium,
+            warnings,
+        );
+        Ok(camera)
+    } else if ro.camera_name == String::from("cylindrical") {
+        let camera: Box<Camera + Send + Sync> = CylindricalCamera::create(
+            &ro.camera_params,
+            animated_cam_to_world,
+            film,
+            medium,
+            warnings,
+        );
+        Ok(camera)
+    } else if ro.camera_name == String::from("fisheye") {
+        let camera: Box<Camera + Send + Sync> = FisheyeCamera::create(
+            &ro.camera_params,
+            animated_cam_to_world,
+            film,
+            medium,
+            warnings,
+        );
+        Ok(camera)
+    } else if ro.camera_name == String::from("realistic") {
+        match RealisticCamera::create(
+            &ro.camera_params,
+            animated_cam_to_world,
+            film,
+            medium,
+            lens_file,
+            warnings,
+        ) {
+            Ok(camera) => Ok(camera),
+            Err(err) => Err(SceneError::new(err)),
+        }
+    } else {
+        Err(SceneError::new(format!(
+            "Camera \"{}\" unknown.",
+            ro.camera_name
+        )))
+    }
+}
+
+fn make_sampler(
+    ro: &mut RenderOptions,
+    camera: &Box<Camera + Send + Sync>,
+) -> Result<Box<Sampler + Send + Sync>, SceneError> {
+    if ro.sampler_name == String::from("lowdiscrepancy")
+        || ro.sampler_name == String::from("02sequence")
+    {
+        let nsamp: i32 = ro.sampler_params
+            .find_one_int(String::from("pixelsamples"), 16);
+        let sd: i32 = ro.sampler_params
+            .find_one_int(String::from("dimensions"), 4);
+        // TODO: if (PbrtOptions.quickRender) nsamp = 1;
+        Ok(Box::new(ZeroTwoSequenceSampler::new(nsamp as i64, sd as i64)))
+    } else if ro.sampler_name == String::from("halton") {
+        let nsamp: i32 = ro.sampler_params
+            .find_one_int(String::from("pixelsamples"), 16);
+        // TODO: if (PbrtOptions.quickRender) nsamp = 1;
+        let sample_at_center: bool = ro.sampler_params
+            .find_one_bool(String::from("samplepixelcenter"), false);
+        let sample_bounds: Bounds2i = camera.get_film().get_sample_bounds();
+        Ok(Box::new(HaltonSampler::new(
+            nsamp as i64,
+            sample_bounds,
+            sample_at_center,
+        )))
+    } else if ro.sampler_name == String::from("sobol") {
+        let nsamp: i32 = ro.sampler_params
+            .find_one_int(String::from("pixelsamples"), 16);
+        let sample_bounds: Bounds2i = camera.get_film().get_sample_bounds();
+        Ok(Box::new(SobolSampler::new(nsamp as i64, sample_bounds)))
+    } else if ro.sampler_name == String::from("random") {
+        let nsamp: i32 = ro.sampler_params
+            .find_one_int(String::from("pixelsamples"), 4);
+        Ok(Box::new(RandomSampler::new(nsamp as i64)))
+    } else if ro.sampler_name == String::from("maxmindist")
+        || ro.sampler_name == String::from("stratified")
+    {
+        // TODO: CreateMaxMinDistSampler, CreateStratifiedSampler
+        Err(SceneError::new(format!(
+            "Sampler \"{}\" not supported yet.",
+            ro.sampler_name
+        )))
+    } else {
+        Err(SceneError::new(format!(
+            "Sampler \"{}\" unknown.",
+            ro.sampler_name
+        )))
+    }
+}
+
+fn make_pixel_bounds(
+    ro: &RenderOptions,
+    camera: &Box<Camera + Send + Sync>,
+    warnings: &mut Vec<String>,
+) -> Result<Bounds2i, SceneError> {
+    let pb: Vec<i32> = ro.integrator_params.find_int(String::from("pixelbounds"));
+    let np: usize = pb.len();
+    let mut pixel_bounds: Bounds2i = camera.get_film().get_sample_bounds();
+    if np > 0 as usize {
+        if np != 4 as usize {
+            return Err(SceneError::new(format!(
+                "Expected four values for \"pixelbounds\" parameter. Got {}.",
+                np
+            )));
+        } else {
+            pixel_bounds = bnd2_intersect_bnd2(
+                &pixel_bounds,
+                &Bounds2i {
+                    p_min: Point2i { x: pb[0], y: pb[2] },
+                    p_max: Point2i { x: pb[1], y: pb[3] },
+                },
+            );
+            if pixel_bounds.p_max.x <= pixel_bounds.p_min.x
+                || pixel_bounds.p_max.y <= pixel_bounds.p_min.y
+            {
+                warnings.push(String::from("Degenerate \"pixelbounds\" specified."));
+            }
+        }
+    }
+    Ok(pixel_bounds)
+}
+
+fn make_integrator(
+    ro: &mut RenderOptions,
+    camera: &Box<Camera + Send + Sync>,
+    warnings: &mut Vec<String>,
+) -> Result<Box<Integrator + Send + Sync>, SceneError> {
+    let integrator: Box<Integrator + Send + Sync>;
+    // light paths can't be connected to a camera without importance
+    // function (see RealisticCamera::We() in realistic.h)
+    if ro.camera_name == String::from("realistic")
+        && (ro.integrator_name == String::from("bdpt")
+            || ro.integrator_name == String::from("lightpath")
+            || ro.integrator_name == String::from("mlt"))
+    {
+        return Err(SceneError::new(format!(
+            "Integrator \"{}\" doesn't support the \"realistic\" camera.",
+            ro.integrator_name
+        )));
+    }
+    if ro.integrator_name == String::from("directlighting") {
+        // CreateDirectLightingIntegrator
+        let max_depth: i32 = ro.integrator_params
+            .find_one_int(String::from("maxdepth"), 5);
+        let st: String = ro.integrator_params
+            .find_one_string(String::from("strategy"), String::from("all"));
+        let strategy: LightStrategy;
+        if st == String::from("one") {
+            strategy = LightStrategy::UniformSampleOne;
+        } else if st == String::from("all") {
+            strategy = LightStrategy::UniformSampleAll;
+        } else {
+            warnings.push(format!(
+                "Strategy \"{}\" for direct lighting unknown. Using \"all\".",
+                st
+            ));
+            strategy = LightStrategy::UniformSampleAll;
+        }
+        let pixel_bounds: Bounds2i = make_pixel_bounds(ro, camera, warnings)?;
+        integrator = Box::new(DirectLightingIntegrator::new(
+            strategy,
+            max_depth as i64,
+            pixel_bounds,
+        ));
+    } else if ro.integrator_name == String::from("path") {
+        // CreatePathIntegrator
+        let max_depth: i32 = ro.integrator_params
+            .find_one_int(String::from("maxdepth"), 5);
+        let pixel_bounds: Bounds2i = make_pixel_bounds(ro, camera, warnings)?;
+        let rr_threshold: Float = ro.integrator_params
+            .find_one_float(String::from("rrthreshold"), 1.0 as Float);
+        let light_strategy: String = ro.integrator_params.find_one_string(
+            String::from("lightsamplestrategy"),
+            String::from("spatial"),
+        );
+        integrator = Box::new(PathIntegrator::new(
+            max_depth as u32,
+            pixel_bounds,
+            rr_threshold,
+            light_strategy,
+        ));
+    } else if ro.integrator_name == String::from("volpath") {
+        // CreateVolPathIntegrator
+        let max_depth: i32 = ro.integrator_params
+            .find_one_int(String::from("maxdepth"), 5);
+        let pixel_bounds: Bounds2i = make_pixel_bounds(ro, camera, warnings)?;
+        let rr_threshold: Float = ro.integrator_params
+            .find_one_float(String::from("rrthreshold"), 1.0 as Float);
+        let light_strategy: String = ro.integrator_params.find_one_string(
+            String::from("lightsamplestrategy"),
+            String::from("spatial"),
+        );
+        integrator = Box::new(VolPathIntegrator::new(
+            max_depth as u32,
+            pixel_bounds,
+            rr_threshold,
+            light_strategy,
+        ));
+    } else if ro.integrator_name == String::from("bdpt") {
+        // CreateBDPTIntegrator
+        let mut max_depth: i32 = ro.integrator_params
+            .find_one_int(String::from("maxdepth"), 5);
+        let visualize_strategies: bool = ro.integrator_params
+            .find_one_bool(String::from("visualizestrategies"), false);
+        let visualize_weights: bool = ro.integrator_params
+            .find_one_bool(String::from("visualizeweights"), false);
+        if (visualize_strategies || visualize_weights) && max_depth > 5_i32 {
+            warnings.push(String::from(
+                "visualizestrategies/visualizeweights was enabled, limiting maxdepth to 5",
+            ));
+            max_depth = 5;
+        }
+        let pixel_bounds: Bounds2i = make_pixel_bounds(ro, camera, warnings)?;
+        let light_strategy: String = ro.integrator_params.find_one_string(
+            String::from("lightsamplestrategy"),
+            String::from("power"),
+        );
+        integrator = Box::new(BDPTIntegrator::new(
+            max_depth as u32,
+            pixel_bounds,
+            light_strategy,
+        ));
+    } else if ro.integrator_name == String::from("ambientocclusion") {
+        // CreateAOIntegrator
+        let pixel_bounds: Bounds2i = make_pixel_bounds(ro, camera, warnings)?;
+        let cos_sample: bool = ro.integrator_params
+            .find_one_bool(String::from("cossample"), true);
+        let n_samples: i32 = ro.integrator_params
+            .find_one_int(String::from("nsamples"), 64 as i32);
+        integrator = Box::new(AOIntegrator::new(
+            cos_sample,
+            n_samples,
+            pixel_bounds,
+        ));
+    } else if ro.integrator_name == String::from("whitted") {
+        // CreateWhittedIntegrator
+        let max_depth: i32 = ro.integrator_params
+            .find_one_int(String::from("maxdepth"), 5);
+        let pixel_bounds: Bounds2i = make_pixel_bounds(ro, camera, warnings)?;
+        integrator = Box::new(WhittedIntegrator::new(max_depth as i64, pixel_bounds));
+    } else if ro.integrator_name == String::from("sppm") {
+        // CreateSPPMIntegrator
+        let mut n_iterations: i32 = ro.integrator_params
+            .find_one_int(String::from("iterations"), 64);
+        n_iterations = ro.integrator_params
+            .find_one_int(String::from("numiterations"), n_iterations);
+        let max_depth: i32 = ro.integrator_params
+            .find_one_int(String::from("maxdepth"), 5);
+        let mut photons_per_iter: i32 = ro.integrator_params
+            .find_one_int(String::from("photonsperiteration"), -1);
+        if photons_per_iter <= 0 {
+            photons_per_iter = camera.get_film().cropped_pixel_bounds.area();
+        }
+        let write_freq: i32 = ro.integrator_params.find_one_int(
+            String::from("imagewritefrequency"),
+            i32::max_value(),
+        );
+        let radius: Float = ro.integrator_params
+            .find_one_float(String::from("radius"), 1.0 as Float);
+        if n_iterations <= 0 {
+            return Err(SceneError::new(format!(
+                "Integrator \"sppm\": \"numiterations\" must be positive, got {}.",
+                n_iterations
+            )));
+        }
+        if write_freq <= 0 {
+            return Err(SceneError::new(format!(
+                "Integrator \"sppm\": \"imagewritefrequency\" must be positive, got {}.",
+                write_freq
+            )));
+        }
+        integrator = Box::new(SPPMIntegrator::new(
+            n_iterations,
+            photons_per_iter,
+            max_depth as u32,
+            radius,
+            write_freq,
+        ));
+    } else if ro.integrator_name == String::from("lightpath") {
+        // CreateLightPathIntegrator
+        let max_depth: i32 = ro.integrator_params
+            .find_one_int(String::from("maxdepth"), 5);
+        integrator = Box::new(LightPathIntegrator::new(max_depth as u32));
+    } else if ro.integrator_name == String::from("mlt") {
+        // CreateMLTIntegrator
+        let max_depth: i32 = ro.integrator_params
+            .find_one_int(String::from("maxdepth"), 5);
+        let n_bootstrap: i32 = ro.integrator_params
+            .find_one_int(String::from("bootstrapsamples"), 100000);
+        let n_chains: i32 = ro.integrator_params
+            .find_one_int(String::from("chains"), 1000);
+        let mutations_per_pixel: i32 = ro.integrator_params
+            .find_one_int(String::from("mutationsperpixel"), 100);
+        let large_step_probability: Float = ro.integrator_params
+            .find_one_float(String::from("largestepprobability"), 0.3 as Float);
+        let sigma: Float = ro.integrator_params
+            .find_one_float(String::from("sigma"), 0.01 as Float);
+        if n_bootstrap <= 0 || n_chains <= 0 || mutations_per_pixel <= 0 {
+            return Err(SceneError::new(format!(
+                "Integrator \"mlt\": \"bootstrapsamples\", \"chains\" and \
+                 \"mutationsperpixel\" must be positive, got {}, {} and {}.",
+                n_bootstrap,
+                n_chains,
+                mutations_per_pixel
+            )));
+        }
+        integrator = Box::new(MLTIntegrator::new(
+            max_depth as u32,
+            n_bootstrap as u32,
+            n_chains as u32,
+            mutations_per_pixel as u32,
+            sigma,
+            large_step_probability,
+        ));
+    } else {
+        return Err(SceneError::new(format!(
+            "Integrator \"{}\" unknown.",
+            ro.integrator_name
+        )));
+    }
+    // the BDPT random walk (used by "bdpt", "mlt" and "lightpath")
+    // doesn't sample media yet
+    if ro.have_scattering_media && ro.integrator_name != String::from("volpath") {
+        warnings.push(format!(
+            "Scene has scattering media but \"{}\" integrator doesn't support volume \
+             scattering. Consider using \"volpath\".",
+            ro.integrator_name
+        ));
+    }
+    if ro.lights.is_empty() {
+        // warn if no light sources are defined
+        warnings.push(String::from(
+            "No light sources defined in scene; rendering a black image.",
+        ));
+    }
+    Ok(integrator)
+}
+
+fn make_accelerator(
+    accelerator_name: &String,
+    prims: Vec<Arc<Primitive + Sync + Send>>,
+    params: &ParamSet,
+    warnings: &mut Vec<String>,
+) -> Arc<BVHAccel> {
+    if *accelerator_name == String::from("bvh") {
+        //  CreateBVHAccelerator
+        let split_method_name: String =
+            params.find_one_string(String::from("splitmethod"), String::from("sah"));
+        let split_method;
+        if split_method_name == String::from("sah") {
+            split_method = SplitMethod::SAH;
+        } else if split_method_name == String::from("hlbvh") {
+            split_method = SplitMethod::HLBVH;
+        } else if split_method_name == String::from("middle") {
+            split_method = SplitMethod::Middle;
+        } else if split_method_name == String::from("equal") {
+            split_method = SplitMethod::EqualCounts;
+        } else {
+            warnings.push(format!(
+                "BVH split method \"{}\" unknown.  Using \"sah\".",
+                split_method_name
+            ));
+            split_method = SplitMethod::SAH;
+        }
+        let max_prims_in_node: i32 = params.find_one_int(String::from("maxnodeprims"), 4);
+        Arc::new(BVHAccel::new(
+            prims,
+            max_prims_in_node as usize,
+            split_method,
+        ))
+    } else if *accelerator_name == String::from("kdtree") {
+        // println!("TODO: CreateKdTreeAccelerator");
+        // WARNING: Use BVHAccel for now !!!
+        Arc::new(BVHAccel::new(prims, 4, SplitMethod::SAH))
+    } else {
+        warnings.push(format!(
+            "Accelerator \"{}\" unknown. Using \"bvh\".",
+            accelerator_name
+        ));
+        Arc::new(BVHAccel::new(prims, 4, SplitMethod::SAH))
+    }
+}
+
+fn make_scene(ro: &mut RenderOptions, warnings: &mut Vec<String>) -> Scene {
+    let accelerator: Arc<BVHAccel> = make_accelerator(
+        &ro.accelerator_name,
+        ro.primitives.clone(),
+        &ro.accelerator_params,
+        warnings,
+    );
+    let scene: Scene = Scene::new(accelerator, ro.lights.clone());
+    // erase primitives and lights from _RenderOptions_
+    ro.primitives.clear();
+    ro.lights.clear();
+    scene
+}
+
+/// Returns **None** for shapes which only separate two media
+/// (material "", "none" or "interface").
+fn create_material(
+    graphics_state: &GraphicsState,
+    warnings: &mut Vec<String>,
+) -> Option<Arc<Material + Send + Sync>> {
+    // CreateMaterial
+    let mut material_params = ParamSet::default();
+    material_params.copy_from(&graphics_state.material_params);
+    let mut mp: TextureParams = TextureParams {
+        float_textures: graphics_state.float_textures.clone(),
+        spectrum_textures: graphics_state.spectrum_textures.clone(),
+        geom_params: ParamSet::default(),
+        material_params: material_params,
+    };
+    if graphics_state.current_material != String::new() {
+        match graphics_state
+            .named_materials
+            .get(graphics_state.current_material.as_str())
+        {
+            Some(named_material) => {
+                return Some(named_material.clone());
+            }
+            None => {
+                warnings.push(format!(
+                    "Named material \"{}\" not defined. Using \"matte\".",
+                    graphics_state.current_material
+                ));
+            }
+        }
+    } else if is_interface_material(&graphics_state.material) {
+        return None;
+    } else if let Some(material) = make_material(
+        &graphics_state.material,
+        &mut mp,
+        &graphics_state.named_materials,
+        warnings,
+    ) {
+        return Some(material);
+    }
+    Some(default_material())
+}
+
+fn is_interface_material(name: &String) -> bool {
+    *name == String::new() || *name == String::from("none") || *name == String::from("interface")
+}
+
+/// Used whenever a material can't be created.
+fn default_material() -> Arc<Material + Send + Sync> {
+    let kd = Arc::new(ConstantTexture::new(Spectrum::new(0.5)));
+    let sigma = Arc::new(ConstantTexture::new(0.0 as Float));
+    Arc::new(MatteMaterial::new(kd, sigma))
+}
+
+/// Returns **None** (after a warning) for unknown or unsupported
+/// materials (MakeMaterial in api.cpp).
+fn make_material(
+    name: &String,
+    mp: &mut TextureParams,
+    named_materials: &HashMap<String, Arc<Material + Send + Sync>>,
+    warnings: &mut Vec<String>,
+) -> Option<Arc<Material + Send + Sync>> {
+    if *name == String::new() || *name == String::from("none") || *name == String::from("matte") {
+        return Some(MatteMaterial::create(mp));
+    } else if *name == String::from("plastic") {
+        let kd = mp.get_spectrum_texture(String::from("Kd"), Spectrum::new(0.25 as Float));
+        let ks = mp.get_spectrum_texture(String::from("Ks"), Spectrum::new(0.25 as Float));
+        let roughness = mp.get_float_texture(String::from("roughness"), 0.1 as Float);
+        // TODO: std::shared_ptr<Texture<Float>> bumpMap = mp.GetFloatTextureOrNull("bumpmap");
+        let remap_roughness: bool = mp.find_bool(String::from("remaproughness"), true);
+        let plastic = Arc::new(PlasticMaterial::new(kd, ks, roughness, remap_roughness));
+        return Some(plastic);
+    } else if *name == String::from("translucent") {
+        // TODO: CreateTranslucentMaterial
+        warnings.push(String::from(
+            "Material \"translucent\" not supported yet. Using \"matte\".",
+        ));
+    } else if *name == String::from("glass") {
+        let kr = mp.get_spectrum_texture(String::from("Kr"), Spectrum::new(1.0 as Float));
+        let kt = mp.get_spectrum_texture(String::from("Kt"), Spectrum::new(1.0 as Float));
+        // let some_eta = mp.get_float_texture_or_null(String::from("eta"));
+        // if let Some(eta) = some_eta {
+        //     println!("some eta");
+        // } else {
+        let eta = mp.get_float_texture(String::from("index"), 1.5);
+        // }
+        // std::shared_ptr<Texture<Float>> roughu =
+        //     mp.GetFloatTexture("uroughness", 0.f);
+        let roughu = mp.get_float_texture(String::from("uroughness"), 0.0 as Float);
+        // std::shared_ptr<Texture<Float>> roughv =
+        //     mp.GetFloatTexture("vroughness", 0.f);
+        let roughv = mp.get_float_texture(String::from("vroughness"), 0.0 as Float);
+        // std::shared_ptr<Texture<Float>> bumpMap =
+        //     mp.GetFloatTextureOrNull("bumpmap");
+        let remap_roughness: bool = mp.find_bool(String::from("remaproughness"), true);
+        let glass = Arc::new(GlassMaterial {
+            kr: kr,
+            kt: kt,
+            u_roughness: roughu,
+            v_roughness: roughv,
+            index: eta,
+            remap_roughness: remap_roughness,
+        });
+        return Some(glass);
+    } else if *name == String::from("mirror") {
+        let kr = mp.get_spectrum_texture(String::from("Kr"), Spectrum::new(0.9 as Float));
+        // TODO: std::shared_ptr<Texture<Float>> bumpMap = mp.GetFloatTextureOrNull("bumpmap");
+        let mirror = Arc::new(MirrorMaterial { kr: kr });
+        return Some(mirror);
+    } else if *name == String::from("hair") {
+        return Some(HairMaterial::create(mp));
+    } else if *name == String::from("mix") {
+        let m1: String = mp.find_string(String::from("namedmaterial1"), String::from(""));
+        let m2: String = mp.find_string(String::from("namedmaterial2"), String::from(""));
+        match (named_materials.get(&m1), named_materials.get(&m2)) {
+            (Some(mat1), Some(mat2)) => {
+                let scale: Arc<Texture<Spectrum> + Send + Sync> =
+                    mp.get_spectrum_texture(String::from("amount"), Spectrum::new(0.5));
+                let mix = Arc::new(MixMaterial::new(mat1.clone(), mat2.clone(), scale));
+                return Some(mix);
+            }
+            (None, _) => {
+                warnings.push(format!("Material \"{}\" unknown. Using \"matte\".", m1));
+            }
+            (_, None) => {
+                warnings.push(format!("Material \"{}\" unknown. Using \"matte\".", m2));
+            }
+        }
+    } else if *name == String::from("metal") {
+        return Some(MetalMaterial::create(mp));
+    } else if *name == String::from("substrate") {
+        return Some(SubstrateMaterial::create(mp));
+    } else if *name == String::from("uber") {
+        return Some(UberMaterial::create(mp));
+    } else if *name == String::from("subsurface")
+        || *name == String::from("kdsubsurface")
+        || *name == String::from("fourier")
+    {
+        // TODO: CreateSubsurfaceMaterial, CreateKdsubsurfaceMaterial,
+        // CreateFourierMaterial
+        warnings.push(format!(
+            "Material \"{}\" not supported yet. Using \"matte\".",
+            name
+        ));
+    } else {
+        warnings.push(format!(
+            "Material \"{}\" unknown. Using \"matte\".",
+            name
+        ));
+    }
+    None
+}
+
+/// Returns **None** (after a warning) for unknown media (MakeMedium
+/// in api.cpp).
+fn make_medium(
+    name: &String,
+    param_set: &ParamSet,
+    medium_to_world: &Transform,
+    warnings: &mut Vec<String>,
+) -> Result<Option<Arc<Medium + Send + Sync>>, SceneError> {
+    // defaults are the values for "Wholemilk"
+    let mut sig_a: Spectrum = Spectrum::rgb(0.0011, 0.0024, 0.014);
+    let mut sig_s: Spectrum = Spectrum::rgb(2.55, 3.21, 3.77);
+    let preset: String = param_set.find_one_string(String::from("preset"), String::new());
+    let found: bool = get_medium_scattering_properties(&preset, &mut sig_a, &mut sig_s);
+    if preset != String::new() && !found {
+        warnings.push(format!(
+            "Material preset \"{}\" not found.  Using defaults.",
+            preset
+        ));
+    }
+    let scale: Float = param_set.find_one_float(String::from("scale"), 1.0 as Float);
+    let g: Float = param_set.find_one_float(String::from("g"), 0.0 as Float);
+    sig_a = param_set.find_one_spectrum(String::from("sigma_a"), sig_a) * scale;
+    sig_s = param_set.find_one_spectrum(String::from("sigma_s"), sig_s) * scale;
+    if *name == String::from("homogeneous") {
+        Ok(Some(Arc::new(HomogeneousMedium::new(&sig_a, &sig_s, g))))
+    } else if *name == String::from("heterogeneous") {
+        let data: Vec<Float> = param_set.find_float(String::from("density"));
+        if data.is_empty() {
+            return Err(SceneError::new(String::from(
+                "No \"density\" values provided for heterogeneous medium?",
+            )));
+        }
+        let nx: i32 = param_set.find_one_int(String::from("nx"), 1);
+        let ny: i32 = param_set.find_one_int(String::from("ny"), 1);
+        let nz: i32 = param_set.find_one_int(String::from("nz"), 1);
+        let p0: Point3f = param_set.find_one_point3f(
+            String::from("p0"),
+            Point3f {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        );
+        let p1: Point3f = param_set.find_one_point3f(
+            String::from("p1"),
+            Point3f {
+                x: 1.0,
+                y: 1.0,
+                z: 1.0,
+            },
+        );
+        if data.len() as i32 != nx * ny * nz {
+            return Err(SceneError::new(format!(
+                "GridDensityMedium has {} density values; expected nx*ny*nz = {}",
+                data.len(),
+                nx * ny * nz
+            )));
+        }
+        let sig_t: Spectrum = sig_a + sig_s;
+        if Spectrum::new(sig_t[0]) != sig_t {
+            warnings.push(String::from(
+                "GridDensityMedium requires a spectrally uniform attenuation coefficient!",
+            ));
+        }
+        let data_to_medium: Transform = Transform::translate(&Vector3f {
+            x: p0.x,
+            y: p0.y,
+            z: p0.z,
+        }) * Transform::scale(p1.x - p0.x, p1.y - p0.y, p1.z - p0.z);
+        Ok(Some(Arc::new(GridDensityMedium::new(
+            &sig_a,
+            &sig_s,
+            g,
+            nx,
+            ny,
+            nz,
+            &(*medium_to_world * data_to_medium),
+            data,
+        ))))
+    } else {
+        warnings.push(format!("Medium \"{}\" unknown.", name));
+        Ok(None)
+    }
+}
+
+fn make_light(api_state: &mut ApiState, param_set: &ParamSet) {
+    // MakeLight (api.cpp:591)
+    let light_to_world: Transform = api_state.cur_transform.t[0];
+    let mi: MediumInterface = api_state.create_medium_interface();
+    if param_set.name == String::from("point") {
+        // CreatePointLight
+        let i: Spectrum = param_set.find_one_spectrum(String::from("I"), Spectrum::new(1.0 as Float));
+        let sc: Spectrum =
+            param_set.find_one_spectrum(String::from("scale"), Spectrum::new(1.0 as Float));
+        let p: Point3f = param_set.find_one_point3f(
+            String::from("from"),
+            Point3f {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        );
+        let l2w: Transform = Transform::translate(&Vector3f {
+            x: p.x,
+            y: p.y,
+            z: p.z,
+        }) * light_to_world;
+        let point_light = Arc::new(PointLight::new(
+            &l2w,
+            &MediumInterface::from_medium(mi.outside),
+            &(i * sc),
+        ));
+        api_state.render_options.lights.push(point_light);
+    } else if param_set.name == String::from("spot")
+        || param_set.name == String::from("goniometric")
+        || param_set.name == String::from("projection")
+    {
+        // TODO: CreateSpotLight, CreateGoniometricLight, CreateProjectionLight
+        api_state.warning(format!(
+            "Light \"{}\" not supported yet.",
+            param_set.name
+        ));
+        return;
+    } else if param_set.name == String::from("distant") {
+        // CreateDistantLight
+        let l: Spectrum = param_set.find_one_spectrum(String::from("L"), Spectrum::new(1.0 as Float));
+        let sc: Spectrum =
+            param_set.find_one_spectrum(String::from("scale"), Spectrum::new(1.0 as Float));
+        let from: Point3f = param_set.find_one_point3f(
+            String::from("from"),
+            Point3f {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        );
+        let to: Point3f = param_set.find_one_point3f(
+            String::from("to"),
+            Point3f {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        );
+        let dir: Vector3f = from - to;
+        // return std::make_shared<DistantLight>(light2world, L * sc, dir);
+        let distant_light = Arc::new(DistantLight::new(&light_to_world, &(l * sc), &dir));
+        api_state.render_options.lights.push(distant_light);
+    } else if param_set.name == String::from("infinite")
+        || param_set.name == String::from("exinfinite")
+    {
+        let l: Spectrum = param_set.find_one_spectrum(String::from("L"), Spectrum::new(1.0 as Float));
+        let sc: Spectrum =
+            param_set.find_one_spectrum(String::from("scale"), Spectrum::new(1.0 as Float));
+        let mut texmap: String = param_set.find_one_filename(String::from("mapname"), String::from(""));
+        if texmap != String::from("") {
+            // texmap = AbsolutePath(ResolveFilename(texmap));
+            texmap = api_state.resolve_filename(texmap);
+        }
+        let n_samples: i32 = param_set.find_one_int(String::from("nsamples"), 1 as i32);
+        // TODO: if (PbrtOptions.quickRender) nSamples = std::max(1, nSamples / 4);
+
+        // return std::make_shared<InfiniteAreaLight>(light2world, L * sc, nSamples, texmap);
+        let infinte_light = Arc::new(InfiniteAreaLight::new(
+            &light_to_world,
+            &(l * sc),
+            n_samples,
+            texmap,
+        ));
+        api_state.render_options.lights.push(infinte_light);
+    } else {
+        api_state.warning(format!("Light \"{}\" unknown.", param_set.name));
+        return;
+    }
+    api_state.warnings.extend(param_set.report_unused());
+}
+
+fn make_area_light(
+    api_state: &mut ApiState,
+    shape: Arc<Shape + Send + Sync>,
+    mi: &MediumInterface,
+) -> Option<Arc<DiffuseAreaLight>> {
+    let graphics_state: &GraphicsState = &api_state.graphics_state;
+    // MakeAreaLight
+    if graphics_state.area_light == String::from("area")
+        || graphics_state.area_light == String::from("diffuse")
+    {
+        // CreateDiffuseAreaLight
+        let light_to_world: Transform = api_state.cur_transform.t[0];
+        let l: Spectrum = graphics_state
+            .area_light_params
+            .find_one_spectrum(String::from("L"), Spectrum::new(1.0));
+        let sc: Spectrum = graphics_state
+            .area_light_params
+            .find_one_spectrum(String::from("scale"), Spectrum::new(1.0));
+        let n_samples: i32 = // try "nsamples" first
+            graphics_state.area_light_params.find_one_int(String::from("nsamples"), 1);
+        let n_samples: i32 = // try "samples"next
+            graphics_state.area_light_params.find_one_int(String::from("samples"), n_samples);
+        let two_sided: bool = graphics_state
+            .area_light_params
+            .find_one_bool(String::from("twosided"), false);
+        // TODO: if (PbrtOptions.quickRender) nSamples = std::max(1, nSamples / 4);
+        let l_emit: Spectrum = l * sc;
+        Some(Arc::new(DiffuseAreaLight::new(
+            &light_to_world,
+            &MediumInterface::from_medium(mi.outside.clone()),
+            &l_emit,
+            n_samples,
+            shape,
+            two_sided,
+        )))
+    } else {
+        api_state.warnings.push(format!(
+            "Area light \"{}\" unknown.",
+            graphics_state.area_light
+        ));
+        None
+    }
+}
+
+fn make_texture(api_state: &mut ApiState, param_set: &ParamSet) -> Result<(), SceneError> {
+    // pbrtTexture (api.cpp:1049)
+    let mut geom_params: ParamSet = ParamSet::default();
+    let mut material_params: ParamSet = ParamSet::default();
+    geom_params.copy_from(param_set);
+    material_params.copy_from(param_set);
+    let mut tp: TextureParams = TextureParams {
+        float_textures: api_state.graphics_state.float_textures.clone(),
+        spectrum_textures: api_state.graphics_state.spectrum_textures.clone(),
+        geom_params: geom_params,
+        material_params: material_params,
+    };
+    if param_set.tex_type == String::from("float") {
+        // TODO: MakeFloatTexture
+        api_state.warning(format!(
+            "Float texture \"{}\" not supported yet.",
+            param_set.tex_name
+        ));
+    } else if param_set.tex_type == String::from("color")
+        || param_set.tex_type == String::from("spectrum")
+    {
+        if api_state
+            .graphics_state
+            .spectrum_textures
+            .contains_key(param_set.name.as_str())
+        {
+            api_state.warning(format!("Texture \"{}\" being redefined", param_set.name));
+        }
+        // MakeSpectrumTexture(texname, curTransform[0], tp);
+        if param_set.tex_name == String::from("imagemap") {
+            // CreateImageSpectrumTexture
+            let map: Option<Box<TextureMapping2D + Send + Sync>> =
+                make_texture_mapping_2d(&mut tp, &mut api_state.warnings);
+            // initialize _ImageTexture_ parameters
+            let max_aniso: Float = tp.find_float(String::from("maxanisotropy"), 8.0);
+            let do_trilinear: bool = tp.find_bool(String::from("trilinear"), false);
+            let wrap: String = tp.find_string(String::from("wrap"), String::from("repeat"));
+            let mut wrap_mode: ImageWrap = ImageWrap::Repeat;
+            if wrap == String::from("black") {
+                wrap_mode = ImageWrap::Black;
+            } else if wrap == String::from("clamp") {
+                wrap_mode = ImageWrap::Clamp;
+            }
+            let scale: Float = tp.find_float(String::from("scale"), 1.0);
+            let mut filename: String = tp.find_filename(String::from("filename"), String::new());
+            // filename = AbsolutePath(ResolveFilename(filename));
+            filename = api_state.resolve_filename(filename);
+            // TODO: default depends on:
+            // HasExtension(filename,
+            // ".tga") ||
+            // HasExtension(filename,
+            // ".png"));
+            let gamma: bool = tp.find_bool(String::from("gamma"), true);
+
+            if let Some(mapping) = map {
+                let st = Arc::new(ImageTexture::new(
+                    mapping,
+                    filename,
+                    do_trilinear,
+                    max_aniso,
+                    wrap_mode,
+                    scale,
+                    gamma,
+                ));
+                api_state
+                    .graphics_state
+                    .spectrum_textures
+                    .insert(param_set.name.clone(), st);
+            }
+        } else if param_set.tex_name == String::from("checkerboard") {
+            // CreateCheckerboardSpectrumTexture
+            let dim: i32 = tp.find_int(String::from("dimension"), 2);
+            if dim != 2 && dim != 3 {
+                return Err(SceneError::new(format!(
+                    "{} dimensional checkerboard texture not supported",
+                    dim
+                )));
+            }
+            let tex1: Arc<Texture<Spectrum> + Send + Sync> =
+                tp.get_spectrum_texture(String::from("tex1"), Spectrum::new(1.0));
+            let tex2: Arc<Texture<Spectrum> + Send + Sync> =
+                tp.get_spectrum_texture(String::from("tex2"), Spectrum::new(0.0));
+            if dim == 2 {
+                let map: Option<Box<TextureMapping2D + Send + Sync>> =
+                    make_texture_mapping_2d(&mut tp, &mut api_state.warnings);
+                // TODO: aamode
+                if let Some(mapping) = map {
+                    let st = Arc::new(Checkerboard2DTexture::new(mapping, tex1, tex2));
+                    api_state
+                        .graphics_state
+                        .spectrum_textures
+                        .insert(param_set.name.clone(), st);
+                }
+            } else {
+                // dim == 3
+                // TODO: TextureMapping3D
+                api_state.warning(String::from(
+                    "3 dimensional checkerboard texture not supported yet",
+                ));
+            }
+        } else if param_set.tex_name == String::from("constant")
+            || param_set.tex_name == String::from("scale")
+            || param_set.tex_name == String::from("mix")
+            || param_set.tex_name == String::from("bilerp")
+            || param_set.tex_name == String::from("uv")
+            || param_set.tex_name == String::from("dots")
+            || param_set.tex_name == String::from("fbm")
+            || param_set.tex_name == String::from("wrinkled")
+            || param_set.tex_name == String::from("marble")
+            || param_set.tex_name == String::from("windy")
+        {
+            // TODO: CreateConstantSpectrumTexture, CreateScaleSpectrumTexture, ...
+            api_state.warning(format!(
+                "Spectrum texture \"{}\" not supported yet.",
+                param_set.tex_name
+            ));
+        } else {
+            api_state.warning(format!(
+                "Spectrum texture \"{}\" unknown.",
+                param_set.tex_name
+            ));
+        }
+    } else {
+        return Err(SceneError::new(format!(
+            "Texture type \"{}\" unknown.",
+            param_set.tex_type
+        )));
+    }
+    Ok(())
+}
+
+fn make_texture_mapping_2d(
+    tp: &mut TextureParams,
+    warnings: &mut Vec<String>,
+) -> Option<Box<TextureMapping2D + Send + Sync>> {
+    let mut map: Option<Box<TextureMapping2D + Send + Sync>> = None;
+    let mapping: String = tp.find_string(String::from("mapping"), String::from("uv"));
+    if mapping == String::from("uv") {
+        let su: Float = tp.find_float(String::from("uscale"), 1.0);
+        let sv: Float = tp.find_float(String::from("vscale"), 1.0);
+        let du: Float = tp.find_float(String::from("udelta"), 0.0);
+        let dv: Float = tp.find_float(String::from("vdelta"), 0.0);
+        map = Some(Box::new(UVMapping2D {
+            su: su,
+            sv: sv,
+            du: du,
+            dv: dv,
+        }));
+    } else if mapping == String::from("spherical") || mapping == String::from("cylindrical") {
+        // TODO: SphericalMapping2D, CylindricalMapping2D
+        warnings.push(format!(
+            "2D texture mapping \"{}\" not supported yet",
+            mapping
+        ));
+    } else if mapping == String::from("planar") {
+        map = Some(Box::new(PlanarMapping2D {
+            vs: tp.find_vector3f(
+                String::from("v1"),
+                Vector3f {
+                    x: 1.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+            ),
+            vt: tp.find_vector3f(
+                String::from("v2"),
+                Vector3f {
+                    x: 0.0,
+                    y: 1.0,
+                    z: 0.0,
+                },
+            ),
+            ds: tp.find_float(String::from("udelta"), 0.0),
+            dt: tp.find_float(String::from("vdelta"), 0.0),
+        }));
+    } else {
+        warnings.push(format!("2D texture mapping \"{}\" unknown", mapping));
+    }
+    map
+}
+
+fn make_shapes(
+    api_state: &mut ApiState,
+    param_set: &ParamSet,
+    obj_to_world: &Transform,
+    world_to_obj: &Transform,
+) -> Result<
+    (
+        Vec<Arc<Shape + Send + Sync>>,
+        Vec<Option<Arc<Material + Send + Sync>>>,
+    ),
+    SceneError,
+> {
+    let mut shapes: Vec<Arc<Shape + Send + Sync>> = Vec::new();
+    let mut materials: Vec<Option<Arc<Material + Send + Sync>>> = Vec::new();
+    let obj_to_world: Transform = *obj_to_world;
+    let world_to_obj: Transform = *world_to_obj;
+    // MakeShapes (api.cpp:296)
+    if param_set.name == String::from("sphere") {
+        // CreateSphereShape
+        let radius: Float = param_set.find_one_float(String::from("radius"), 1.0 as Float);
+        let z_min: Float = param_set.find_one_float(String::from("zmin"), -radius);
+        let z_max: Float = param_set.find_one_float(String::from("zmax"), radius);
+        let phi_max: Float = param_set.find_one_float(String::from("phimax"), 360.0 as Float);
+        let sphere = Arc::new(Sphere::new(
+            obj_to_world,
+            world_to_obj,
+            false,
+            false,
+            radius,
+            z_min,
+            z_max,
+            phi_max,
+        ));
+        let mtl: Option<Arc<Material + Send + Sync>> = create_material(&api_state.graphics_state, &mut api_state.warnings);
+        shapes.push(sphere.clone());
+        materials.push(mtl.clone());
+    } else if param_set.name == String::from("cylinder") {
+        let radius: Float = param_set.find_one_float(String::from("radius"), 1.0);
+        let z_min: Float = param_set.find_one_float(String::from("zmin"), -radius);
+        let z_max: Float = param_set.find_one_float(String::from("zmax"), radius);
+        let phi_max: Float = param_set.find_one_float(String::from("phimax"), 360.0 as Float);
+        let cylinder = Arc::new(Cylinder::new(
+            obj_to_world,
+            world_to_obj,
+            false,
+            radius,
+            z_min,
+            z_max,
+            phi_max,
+        ));
+        let mtl: Option<Arc<Material + Send + Sync>> = create_material(&api_state.graphics_state, &mut api_state.warnings);
+        shapes.push(cylinder.clone());
+        materials.push(mtl.clone());
+    } else if param_set.name == String::from("disk") {
+        let height: Float = param_set.find_one_float(String::from("height"), 0.0);
+        let radius: Float = param_set.find_one_float(String::from("radius"), 1.0);
+        let inner_radius: Float = param_set.find_one_float(String::from("innerradius"), 0.0);
+        let phi_max: Float = param_set.find_one_float(String::from("phimax"), 360.0);
+        let disk = Arc::new(Disk::new(
+            obj_to_world,
+            world_to_obj,
+            false,
+            false,
+            height,
+            radius,
+            inner_radius,
+            phi_max,
+        ));
+        let mtl: Option<Arc<Material + Send + Sync>> = create_material(&api_state.graphics_state, &mut api_state.warnings);
+        shapes.push(disk.clone());
+        materials.push(mtl.clone());
+    } else if param_set.name == String::from("cone")
+        || param_set.name == String::from("paraboloid")
+        || param_set.name == String::from("hyperboloid")
+    {
+        // TODO: CreateConeShape, CreateParaboloidShape, CreateHyperboloidShape
+        api_state.warning(format!(
+            "Shape \"{}\" not supported yet.",
+            param_set.name
+        ));
+    } else if param_set.name == String::from("curve") {
+        let mtl: Option<Arc<Material + Send + Sync>> = create_material(&api_state.graphics_state, &mut api_state.warnings);
+        let curve_shapes: Vec<Arc<Shape + Send + Sync>> = create_curve_shape(
+            &obj_to_world,
+            &world_to_obj,
+            false, // reverse_orientation
+            param_set,
+        );
+        for shape in curve_shapes {
+            shapes.push(shape.clone());
+            materials.push(mtl.clone());
+        }
+    } else if param_set.name == String::from("trianglemesh") {
+        let mut vi = param_set.find_int(String::from("indices"));
+        if vi.len() % 3 != 0 {
+            let excess: usize = vi.len() % 3;
+            api_state.warning(format!(
+                "Number of vertex indices {} not a multiple of 3. Discarding {} excess.",
+                vi.len(),
+                excess
+            ));
+            let n_indices: usize = vi.len() - excess;
+            vi.truncate(n_indices);
+        }
+        let p = param_set.find_point3f(String::from("P"));
+        // try "uv" with Point2f
+        let mut uvs = param_set.find_point2f(String::from("uv"));
+        if uvs.is_empty() {
+            // try "st" with Point2f
+            uvs = param_set.find_point2f(String::from("st"));
+        }
+        if uvs.is_empty() {
+            // try "uv" with float
+            let mut fuv = param_set.find_float(String::from("uv"));
+            if fuv.is_empty() {
+                // try "st" with float
+                fuv = param_set.find_float(String::from("st"));
+            }
+            if !fuv.is_empty() {
+                // found some float UVs
+                for i in 0..(fuv.len() / 2) {
+                    uvs.push(Point2f {
+                        x: fuv[2 * i],
+                        y: fuv[2 * i + 1],
+                    });
+                }
+            }
+        }
+        if !uvs.is_empty() {
+            // TODO: if (nuvi < npi) {...} else if (nuvi > npi) ...
+            if uvs.len() != p.len() {
+                return Err(SceneError::new(format!(
+                    "Number of \"uv\"s for triangle mesh ({}) must match \"P\"s ({})",
+                    uvs.len(),
+                    p.len()
+                )));
+            }
+        }
+        if vi.is_empty() {
+            return Err(SceneError::new(String::from(
+                "Vertex indices \"indices\" not provided with triangle mesh shape",
+            )));
+        }
+        if p.is_empty() {
+            return Err(SceneError::new(String::from(
+                "Vertex positions \"P\" not provided with triangle mesh shape",
+            )));
+        }
+        let s = param_set.find_vector3f(String::from("S"));
+        let mut s_ws: Vec<Vector3f> = Vec::new();
+        if !s.is_empty() {
+            if s.len() != p.len() {
+                return Err(SceneError::new(String::from(
+                    "Number of \"S\"s for triangle mesh must match \"P\"s",
+                )));
+            }
+            // transform tangents to world space
+            let n_tangents: usize = s.len();
+            for i in 0..n_tangents {
+                s_ws.push(obj_to_world.transform_vector(&s[i]));
+            }
+        }
+        let n = param_set.find_normal3f(String::from("N"));
+        let mut n_ws: Vec<Normal3f> = Vec::new();
+        if !n.is_empty() {
+            if n.len() != p.len() {
+                return Err(SceneError::new(String::from(
+                    "Number of \"N\"s for triangle mesh must match \"P\"s",
+                )));
+            }
+            // transform normals to world space
+            let n_normals: usize = n.len();
+            for i in 0..n_normals {
+                n_ws.push(obj_to_world.transform_normal(&n[i]));
+            }
+        }
+        for i in 0..vi.len() {
+            if vi[i] < 0 || vi[i] as usize >= p.len() {
+                return Err(SceneError::new(format!(
+                    "trianglemesh has out of-bounds vertex index {} ({} \"P\" values were given)",
+                    vi[i],
+                    p.len()
+                )));
+            }
+        }
+        let fi = param_set.find_int(String::from("faceIndices"));
+        let mut face_indices: Vec<usize> = Vec::new();
+        if !fi.is_empty() {
+            if fi.len() != vi.len() / 3 {
+                api_state.warning(format!(
+                    "Number of face indices, {}, doesn't match number of faces, {}. Ignoring them.",
+                    fi.len(),
+                    vi.len() / 3
+                ));
+            } else {
+                if let Some(i) = fi.iter().find(|i| **i < 0) {
+                    return Err(SceneError::new(format!(
+                        "trianglemesh has negative face index {}",
+                        i
+                    )));
+                }
+                face_indices = fi.iter().map(|i| *i as usize).collect();
+            }
+        }
+        // TODO: alpha
+        // CreateTriangleMesh
+        // transform mesh vertices to world space
+        let mut p_ws: Vec<Point3f> = Vec::new();
+        let n_vertices: usize = p.len();
+        for i in 0..n_vertices {
+            p_ws.push(obj_to_world.transform_point(&p[i]));
+        }
+        // vertex indices are expected as usize, not i32
+        let mut vertex_indices: Vec<usize> = Vec::new();
+        for i in 0..vi.len() {
+            vertex_indices.push(vi[i] as usize);
+        }
+        let mut mesh: TriangleMesh = TriangleMesh::new(
+            obj_to_world,
+            world_to_obj,
+            api_state.graphics_state.reverse_orientation,
+            false,        // transform_swaps_handedness
+            vi.len() / 3, // n_triangles
+            vertex_indices,
+            n_vertices,
+            p_ws, // in world space
+            s_ws, // in world space
+            n_ws, // in world space
+            uvs,
+        );
+        mesh.face_indices = face_indices;
+        let mesh = Arc::new(mesh);
+        let mtl: Option<Arc<Material + Send + Sync>> = create_material(&api_state.graphics_state, &mut api_state.warnings);
+        for id in 0..mesh.n_triangles {
+            let triangle = Arc::new(Triangle::new(
+                mesh.object_to_world,
+                mesh.world_to_object,
+                mesh.reverse_orientation,
+                mesh.clone(),
+                id,
+            ));
+            shapes.push(triangle.clone());
+            materials.push(mtl.clone());
+        }
+    } else if param_set.name == String::from("plymesh") {
+        let mtl: Option<Arc<Material + Send + Sync>> = create_material(&api_state.graphics_state, &mut api_state.warnings);
+        let filename: String = param_set.find_one_string(String::from("filename"), String::new());
+        let filename: String = api_state.resolve_filename(filename);
+        let ply_shapes: Vec<Arc<Shape + Send + Sync>> = create_ply_mesh(
+            &obj_to_world,
+            &world_to_obj,
+            api_state.graphics_state.reverse_orientation,
+            param_set,
+            api_state.graphics_state.float_textures.clone(),
+            filename,
+        ).map_err(SceneError::new)?;
+        for shape in ply_shapes {
+            shapes.push(shape.clone());
+            materials.push(mtl.clone());
+        }
+    } else if param_set.name == String::from("objmesh") {
+        let mtl: Option<Arc<Material + Send + Sync>> = create_material(&api_state.graphics_state, &mut api_state.warnings);
+        let filename: String = param_set.find_one_string(String::from("filename"), String::new());
+        let filename: String = api_state.resolve_filename(filename);
+        let (obj_shapes, obj_materials) = create_obj_mesh(
+            &obj_to_world,
+            &world_to_obj,
+            api_state.graphics_state.reverse_orientation,
+            param_set,
+            filename,
+            &mut api_state.warnings,
+        ).map_err(SceneError::new)?;
+        // faces without MTL material use the current material
+        for (shape, obj_material) in obj_shapes.into_iter().zip(obj_materials.into_iter()) {
+            shapes.push(shape);
+            materials.push(obj_material.or_else(|| mtl.clone()));
+        }
+    } else if param_set.name == String::from("heightfield")
+        || param_set.name == String::from("loopsubdiv")
+        || param_set.name == String::from("nurbs")
+    {
+        // TODO: CreateHeightfield, CreateLoopSubdiv, CreateNURBS
+        api_state.warning(format!(
+            "Shape \"{}\" not supported yet.",
+            param_set.name
+        ));
+    } else {
+        api_state.warning(format!("Shape \"{}\" unknown.", param_set.name));
+    }
+    if !shapes.is_empty() {
+        api_state.warnings.extend(param_set.report_unused());
+    }
+    Ok((shapes, materials))
+}
@@ -0,0 +1,854 @@
+//! Scenes exported for **Arnold** (e.g. from Blender) are stored in
+//! **.ass** files. Their nodes are translated into calls of the API
+//! (see **api.rs**), so such a scene ends up with the same camera,
+//! film, sampler, materials, lights and triangle meshes as the
+//! equivalent **.pbrt** file. Only the most common nodes are
+//! supported:
+//!
+//! - **options**
+//! - **persp_camera** and **cyl_camera**
+//! - **gaussian_filter**, **box_filter** and **triangle_filter**
+//! - **polymesh**
+//! - **standard** shaders
+//! - **skydome_light**, **distant_light** and **point_light**
+//!
+//! All other nodes are ignored (with a warning).
+
+// std
+use std::collections::HashMap;
+use std::env;
+use std::io;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+// pbrt
+use core::api::{pbrt_attribute_begin, pbrt_attribute_end, pbrt_camera, pbrt_concat_transform,
+                pbrt_film, pbrt_integrator, pbrt_light_source, pbrt_look_at,
+                pbrt_make_named_material, pbrt_named_material, pbrt_pixel_filter,
+                pbrt_sampler, pbrt_scale, pbrt_shape, pbrt_translate, pbrt_world_begin,
+                pbrt_world_end};
+use core::api::{ApiState, SceneError};
+use core::fileutil;
+use core::geometry::Point3f;
+use core::paramset::ParamSet;
+use core::parser::{Location, ParseError, ParseErrorKind, ParseResult, ParseWarning, World};
+use core::pbrt::{Float, Spectrum};
+use core::pbrt::{degrees, radians};
+
+/// A node like **polymesh { ... }** with the values of its
+/// parameters (array headers like **28 1 POINT** are removed).
+struct AssNode {
+    node_type: String,
+    line: usize,
+    params: HashMap<String, Vec<String>>,
+}
+
+impl AssNode {
+    fn name(&self) -> String {
+        match self.params.get("name") {
+            Some(values) if !values.is_empty() => values[0].clone(),
+            _ => String::new(),
+        }
+    }
+    /// Type and name of the node (e.g. **polymesh "MEroom"**).
+    fn summary(&self) -> String {
+        format!("{} \"{}\"", self.node_type, self.name())
+    }
+    fn floats(&self, param: &str) -> Result<Option<Vec<Float>>, SceneError> {
+        match self.params.get(param) {
+            Some(values) => {
+                let mut floats: Vec<Float> = Vec::with_capacity(values.len());
+                for value in values {
+                    match Float::from_str(value) {
+                        Ok(float) => floats.push(float),
+                        Err(_) => {
+                            return Err(SceneError::new(format!("Parameter \"{}\": number \
+                                                                expected, found \"{}\"",
+                                                               param,
+                                                               value)));
+                        }
+                    }
+                }
+                Ok(Some(floats))
+            }
+            None => Ok(None),
+        }
+    }
+    fn ints(&self, param: &str) -> Result<Option<Vec<i32>>, SceneError> {
+        match self.params.get(param) {
+            Some(values) => {
+                let mut ints: Vec<i32> = Vec::with_capacity(values.len());
+                for value in values {
+                    match i32::from_str(value) {
+                        Ok(int) => ints.push(int),
+                        Err(_) => {
+                            return Err(SceneError::new(format!("Parameter \"{}\": integer \
+                                                                expected, found \"{}\"",
+                                                               param,
+                                                               value)));
+                        }
+                    }
+                }
+                Ok(Some(ints))
+            }
+            None => Ok(None),
+        }
+    }
+    fn float(&self, param: &str, d: Float) -> Result<Float, SceneError> {
+        match self.floats(param)? {
+            Some(ref floats) if !floats.is_empty() => Ok(floats[0]),
+            _ => Ok(d),
+        }
+    }
+    fn int(&self, param: &str, d: i32) -> Result<i32, SceneError> {
+        match self.ints(param)? {
+            Some(ref ints) if !ints.is_empty() => Ok(ints[0]),
+            _ => Ok(d),
+        }
+    }
+    /// Arnold writes booleans as **on**/**off** (or **true**/**false**).
+    fn boolean(&self, param: &str, d: bool) -> Result<bool, SceneError> {
+        match self.string(param) {
+            Some(value) => {
+                match value.as_str() {
+                    "on" | "true" | "1" => Ok(true),
+                    "off" | "false" | "0" => Ok(false),
+                    _ => {
+                        Err(SceneError::new(format!("Parameter \"{}\": boolean expected, \
+                                                     found \"{}\"",
+                                                    param,
+                                                    value)))
+                    }
+                }
+            }
+            None => Ok(d),
+        }
+    }
+    /// Reads **RGB** (or **RGBA**) colors and **POINT**s.
+    fn triple(&self, param: &str, d: [Float; 3]) -> Result<[Float; 3], SceneError> {
+        match self.floats(param)? {
+            Some(ref floats) if floats.len() >= 3 => Ok([floats[0], floats[1], floats[2]]),
+            Some(_) => {
+                Err(SceneError::new(format!("Parameter \"{}\" needs 3 values", param)))
+            }
+            None => Ok(d),
+        }
+    }
+    fn string(&self, param: &str) -> Option<String> {
+        match self.params.get(param) {
+            Some(values) if !values.is_empty() => Some(values[0].clone()),
+            _ => None,
+        }
+    }
+    /// The node's transformation (Arnold stores it row by row with the
+    /// translation in the last row, which is the order
+    /// **pbrt_concat_transform()** expects).
+    fn matrix(&self) -> Result<Option<[Float; 16]>, SceneError> {
+        match self.floats("matrix")? {
+            Some(floats) => {
+                if floats.len() < 16 {
+                    return Err(SceneError::new(String::from("Parameter \"matrix\" needs 16 \
+                                                             values")));
+                }
+                // only the first motion key is used
+                let mut m: [Float; 16] = [0.0 as Float; 16];
+                m.copy_from_slice(&floats[0..16]);
+                Ok(Some(m))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Splits the lines of an **.ass** file into tokens (removing
+/// comments and the quotes around strings).
+struct AssReader<R: BufRead> {
+    reader: R,
+    line: usize,
+}
+
+impl<R: BufRead> AssReader<R> {
+    /// Returns the tokens of the next line which isn't empty.
+    fn next_line(&mut self) -> io::Result<Option<Vec<String>>> {
+        let mut text: String = String::new();
+        loop {
+            text.clear();
+            if self.reader.read_line(&mut text)? == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+            let mut tokens: Vec<String> = Vec::new();
+            let mut token: String = String::new();
+            let mut in_quotes: bool = false;
+            for c in text.chars() {
+                if in_quotes {
+                    if c == '"' {
+                        tokens.push(token.clone());
+                        token.clear();
+                        in_quotes = false;
+                    } else {
+                        token.push(c);
+                    }
+                } else if c == '"' {
+                    in_quotes = true;
+                } else if c == '#' {
+                    break;
+                } else if c.is_whitespace() {
+                    if !token.is_empty() {
+                        tokens.push(token.clone());
+                        token.clear();
+                    }
+                } else {
+                    token.push(c);
+                }
+            }
+            if !token.is_empty() {
+                tokens.push(token);
+            }
+            if !tokens.is_empty() {
+                return Ok(Some(tokens));
+            }
+        }
+    }
+    /// Reads the values of an array parameter, which follow on the next
+    /// lines.
+    fn read_values(&mut self, values: &mut Vec<String>, n: usize) -> io::Result<()> {
+        while values.len() < n {
+            match self.next_line()? {
+                Some(tokens) => values.extend(tokens),
+                None => break,
+            }
+        }
+        Ok(())
+    }
+    fn next_node(&mut self, file: &Path) -> Result<Option<AssNode>, ParseError> {
+        let mut tokens: Vec<String> = match self.next_line()? {
+            Some(tokens) => tokens,
+            None => return Ok(None),
+        };
+        let line: usize = self.line;
+        let node_type: String = tokens.remove(0);
+        if tokens.is_empty() {
+            tokens = self.next_line()?.unwrap_or(Vec::new());
+        }
+        if tokens.len() != 1 || tokens[0] != "{" {
+            return Err(syntax_error(file, self.line, format!("{{ expected after {}", node_type)));
+        }
+        let mut node: AssNode = AssNode {
+            node_type: node_type,
+            line: line,
+            params: HashMap::new(),
+        };
+        loop {
+            let mut tokens: Vec<String> = match self.next_line()? {
+                Some(tokens) => tokens,
+                None => {
+                    return Err(syntax_error(file,
+                                            self.line,
+                                            format!("}} expected to close {}", node.summary())))
+                }
+            };
+            if tokens[0] == "}" {
+                return Ok(Some(node));
+            }
+            let param: String = tokens.remove(0);
+            let mut values: Vec<String> = tokens;
+            let array_size: Option<usize> = if values.len() >= 3 {
+                match (usize::from_str(&values[0]),
+                       usize::from_str(&values[1]),
+                       array_components(&values[2])) {
+                    (Ok(n_elements), Ok(n_keys), Some(n_components)) => {
+                        Some(n_elements * n_keys * n_components)
+                    }
+                    _ => None,
+                }
+            } else {
+                None
+            };
+            if let Some(n) = array_size {
+                values.drain(0..3);
+                self.read_values(&mut values, n)?;
+            } else if param == "matrix" && values.is_empty() {
+                self.read_values(&mut values, 16)?;
+            }
+            node.params.insert(param, values);
+        }
+    }
+}
+
+/// Number of values per array element, **None** if **type_name**
+/// isn't an array type.
+fn array_components(type_name: &str) -> Option<usize> {
+    match type_name {
+        "BYTE" | "INT" | "UINT" | "BOOL" | "FLOAT" | "STRING" | "NODE" | "POINTER" => Some(1),
+        "POINT2" | "VECTOR2" => Some(2),
+        "RGB" | "POINT" | "VECTOR" => Some(3),
+        "RGBA" => Some(4),
+        "MATRIX" => Some(16),
+        _ => None,
+    }
+}
+
+fn syntax_error(file: &Path, line: usize, message: String) -> ParseError {
+    let mut err: ParseError = ParseError::new(ParseErrorKind::Syntax(message));
+    err.location = Some(Location {
+                            file: Some(file.to_path_buf()),
+                            line: line,
+                            column: 1,
+                            statement: String::new(),
+                        });
+    err
+}
+
+fn node_location(file: &Path, node: &AssNode) -> Location {
+    Location {
+        file: Some(file.to_path_buf()),
+        line: node.line,
+        column: 1,
+        statement: node.summary(),
+    }
+}
+
+/// Reads an **.ass** file (which may be gzip-compressed) and creates
+/// the scene it describes.
+pub fn parse_ass_file(filename: &Path) -> ParseResult {
+    let abs_path: PathBuf = if filename.is_relative() {
+        env::current_dir()?.join(filename)
+    } else {
+        filename.to_path_buf()
+    };
+    let mut reader: AssReader<Box<BufRead>> = AssReader {
+        reader: fileutil::open_file(&abs_path)?,
+        line: 0,
+    };
+    // shaders can be used before they are defined, therefore all
+    // nodes are read first
+    let mut nodes: Vec<AssNode> = Vec::new();
+    while let Some(node) = reader.next_node(&abs_path)? {
+        nodes.push(node);
+    }
+    let mut api_state: ApiState = ApiState::new(abs_path.parent().map(|p| p.to_path_buf()));
+    let mut warnings: Vec<ParseWarning> = Vec::new();
+    let result: Result<World, ParseError> =
+        create_world(&abs_path, &nodes, &mut api_state, &mut warnings);
+    match result {
+        Ok(mut world) => {
+            world.warnings = warnings;
+            Ok(world)
+        }
+        Err(mut err) => {
+            err.warnings = warnings;
+            Err(err)
+        }
+    }
+}
+
+fn create_world(file: &Path,
+                nodes: &Vec<AssNode>,
+                api_state: &mut ApiState,
+                warnings: &mut Vec<ParseWarning>)
+                -> Result<World, ParseError> {
+    let mut named_nodes: HashMap<String, &AssNode> = HashMap::new();
+    for node in nodes {
+        named_nodes.insert(node.name(), node);
+    }
+    let default_options: AssNode = AssNode {
+        node_type: String::from("options"),
+        line: 0,
+        params: HashMap::new(),
+    };
+    let options: &AssNode = nodes.iter()
+        .find(|node| node.node_type == "options")
+        .unwrap_or(&default_options);
+    // render options
+    let result: Result<(), SceneError> = make_options(options, nodes, &named_nodes, api_state);
+    handle_node(file, options, result, api_state, warnings)?;
+    pbrt_world_begin(api_state);
+    // materials first, they are referenced by name
+    for node in nodes {
+        if node.node_type == "standard" {
+            let result: Result<(), SceneError> = make_standard(node, api_state);
+            handle_node(file, node, result, api_state, warnings)?;
+        }
+    }
+    for node in nodes {
+        let result: Result<(), SceneError> = match node.node_type.as_str() {
+            "options" | "standard" | "persp_camera" | "cyl_camera" | "gaussian_filter" |
+            "box_filter" | "triangle_filter" => Ok(()),
+            "point_light" => make_point_light(node, api_state),
+            "distant_light" => make_distant_light(node, api_state),
+            "skydome_light" => make_skydome_light(node, &named_nodes, api_state),
+            "polymesh" => make_polymesh(node, api_state),
+            node_type => {
+                // drivers are only used for the filename of the film
+                if !node_type.starts_with("driver_") {
+                    api_state.warning(format!("Node \"{}\" not supported yet, ignoring it",
+                                              node_type));
+                }
+                Ok(())
+            }
+        };
+        handle_node(file, node, result, api_state, warnings)?;
+    }
+    let result = pbrt_world_end(api_state);
+    let (scene, camera, sampler, integrator) =
+        handle_node(file, options, result, api_state, warnings)?;
+    Ok(World {
+           scene: scene,
+           camera: camera,
+           sampler: sampler,
+           integrator: integrator,
+           warnings: Vec::new(),
+       })
+}
+
+/// Locates errors and warnings at the node causing them.
+fn handle_node<T>(file: &Path,
+                  node: &AssNode,
+                  result: Result<T, SceneError>,
+                  api_state: &mut ApiState,
+                  warnings: &mut Vec<ParseWarning>)
+                  -> Result<T, ParseError> {
+    let location: Location = node_location(file, node);
+    for message in api_state.warnings.drain(..) {
+        warnings.push(ParseWarning {
+                          message: message,
+                          location: location.clone(),
+                      });
+    }
+    result.map_err(|err| {
+                       let mut err: ParseError = ParseError::from(err);
+                       err.location = Some(location);
+                       err
+                   })
+}
+
+/// Film, pixel filter, camera, sampler and integrator (the render
+/// options of pbrt).
+fn make_options(options: &AssNode,
+                nodes: &Vec<AssNode>,
+                named_nodes: &HashMap<String, &AssNode>,
+                api_state: &mut ApiState)
+                -> Result<(), SceneError> {
+    let xres: i32 = options.int("xres", 320)?;
+    let yres: i32 = options.int("yres", 240)?;
+    // e.g. "RGBA RGB filter driver"
+    let output: Vec<String> = match options.string("outputs") {
+        Some(output) => output.split_whitespace().map(|s| String::from(s)).collect(),
+        None => Vec::new(),
+    };
+    let mut film_params: ParamSet = ParamSet::default();
+    film_params.add_int(String::from("xresolution"), xres);
+    film_params.add_int(String::from("yresolution"), yres);
+    if output.len() >= 4 {
+        if let Some(filter) = named_nodes.get(&output[2]) {
+            make_filter(filter, api_state)?;
+        }
+        if let Some(driver) = named_nodes.get(&output[3]) {
+            if let Some(filename) = driver.string("filename") {
+                film_params.add_string(String::from("filename"), filename);
+            }
+        }
+    }
+    pbrt_film(api_state, String::from("image"), film_params);
+    // camera
+    let camera: Option<&AssNode> = match options.string("camera") {
+        Some(name) => named_nodes.get(&name).map(|node| *node),
+        None => {
+            nodes.iter()
+                .find(|node| node.node_type == "persp_camera" || node.node_type == "cyl_camera")
+        }
+    };
+    match camera {
+        Some(camera) => make_camera(camera, xres, yres, api_state)?,
+        None => api_state.warning(String::from("No camera found, using the default camera")),
+    }
+    // sampler
+    let aa_samples: i32 = options.int("AA_samples", 1)?;
+    let mut sampler_params: ParamSet = ParamSet::default();
+    sampler_params.add_int(String::from("pixelsamples"), aa_samples * aa_samples);
+    pbrt_sampler(api_state, String::from("halton"), sampler_params);
+    // integrator
+    let mut integrator_params: ParamSet = ParamSet::default();
+    integrator_params.add_int(String::from("maxdepth"), options.int("GI_total_depth", 10)?);
+    pbrt_integrator(api_state, String::from("path"), integrator_params);
+    Ok(())
+}
+
+fn make_filter(filter: &AssNode, api_state: &mut ApiState) -> Result<(), SceneError> {
+    let name: &str = match filter.node_type.as_str() {
+        "gaussian_filter" => "gaussian",
+        "box_filter" => "box",
+        "triangle_filter" => "triangle",
+        _ => {
+            api_state.warning(format!("Filter \"{}\" not supported yet, using \"box\"",
+                                      filter.node_type));
+            return Ok(());
+        }
+    };
+    let mut params: ParamSet = ParamSet::default();
+    if let Some(width) = filter.floats("width")? {
+        if !width.is_empty() {
+            // Arnold's width is the diameter of the filter
+            params.add_float(String::from("xwidth"), width[0] * 0.5);
+            params.add_float(String::from("ywidth"), width[0] * 0.5);
+        }
+    }
+    pbrt_pixel_filter(api_state, String::from(name), params);
+    Ok(())
+}
+
+fn make_camera(camera: &AssNode,
+               xres: i32,
+               yres: i32,
+               api_state: &mut ApiState)
+               -> Result<(), SceneError> {
+    let (pos, look, up): ([Float; 3], [Float; 3], [Float; 3]) = match camera.matrix()? {
+        Some(m) => {
+            // Arnold cameras look along -z (with y up)
+            ([m[12], m[13], m[14]],
+             [m[12] - m[8], m[13] - m[9], m[14] - m[10]],
+             [m[4], m[5], m[6]])
+        }
+        None => {
+            (camera.triple("position", [0.0, 0.0, 0.0])?,
+             camera.triple("look_at", [0.0, 0.0, -1.0])?,
+             camera.triple("up", [0.0, 1.0, 0.0])?)
+        }
+    };
+    // Arnold is right-handed, pbrt is left-handed
+    pbrt_scale(api_state, -1.0, 1.0, 1.0);
+    pbrt_look_at(api_state,
+                 pos[0],
+                 pos[1],
+                 pos[2],
+                 look[0],
+                 look[1],
+                 look[2],
+                 up[0],
+                 up[1],
+                 up[2]);
+    let mut params: ParamSet = ParamSet::default();
+    if camera.node_type == "persp_camera" {
+        // Arnold's field of view is horizontal, pbrt's is the one of
+        // the shorter image axis
+        let mut fov: Float = camera.float("fov", 54.43)?;
+        if xres > yres {
+            fov = degrees(2.0 as Float *
+                          ((radians(fov) * 0.5 as Float).tan() * yres as Float / xres as Float)
+                              .atan());
+        }
+        params.add_float(String::from("fov"), fov);
+        pbrt_camera(api_state, String::from("perspective"), params);
+    } else if camera.node_type == "cyl_camera" {
+        params.add_float(String::from("hfov"), camera.float("horizontal_fov", 60.0)?);
+        params.add_float(String::from("vfov"), camera.float("vertical_fov", 90.0)?);
+        params.add_bool(String::from("projective"), camera.boolean("projective", true)?);
+        pbrt_camera(api_state, String::from("cylindrical"), params);
+    } else {
+        api_state.warning(format!("Camera \"{}\" not supported yet, using \"perspective\"",
+                                  camera.node_type));
+        pbrt_camera(api_state, String::from("perspective"), params);
+    }
+    Ok(())
+}
+
+/// Maps the **standard** shader onto **matte** (only diffuse
+/// reflection) or **uber**.
+fn make_standard(shader: &AssNode, api_state: &mut ApiState) -> Result<(), SceneError> {
+    let kd: Float = shader.float("Kd", 0.7)?;
+    let kd_color: [Float; 3] = shader.triple("Kd_color", [1.0, 1.0, 1.0])?;
+    let ks: Float = shader.float("Ks", 0.0)?;
+    let ks_color: [Float; 3] = shader.triple("Ks_color", [1.0, 1.0, 1.0])?;
+    let kr: Float = shader.float("Kr", 0.0)?;
+    let kr_color: [Float; 3] = shader.triple("Kr_color", [1.0, 1.0, 1.0])?;
+    let kt: Float = shader.float("Kt", 0.0)?;
+    let kt_color: [Float; 3] = shader.triple("Kt_color", [1.0, 1.0, 1.0])?;
+    let roughness: Float = shader.float("specular_roughness", 0.466905)?;
+    let ior: Float = shader.float("IOR", 1.0)?;
+    if shader.float("emission", 0.0)? > 0.0 as Float {
+        // TODO: area lights for emissive shaders
+        api_state.warning(String::from("Emission not supported yet, ignoring it"));
+    }
+    let scaled = |k: Float, color: [Float; 3]| {
+        Spectrum { c: [k * color[0], k * color[1], k * color[2]] }
+    };
+    let mut params: ParamSet = ParamSet::default();
+    params.add_rgb_spectrum(String::from("Kd"), scaled(kd, kd_color));
+    if ks == 0.0 as Float && kr == 0.0 as Float && kt == 0.0 as Float {
+        params.add_string(String::from("type"), String::from("matte"));
+    } else {
+        params.add_string(String::from("type"), String::from("uber"));
+        params.add_rgb_spectrum(String::from("Ks"), scaled(ks, ks_color));
+        params.add_rgb_spectrum(String::from("Kr"), scaled(kr, kr_color));
+        params.add_rgb_spectrum(String::from("Kt"), scaled(kt, kt_color));
+        // Arnold's roughness is (roughly) the square root of alpha
+        params.add_float(String::from("roughness"), roughness * roughness);
+        params.add_bool(String::from("remaproughness"), false);
+        params.add_float(String::from("eta"), ior);
+    }
+    pbrt_make_named_material(api_state, shader.name(), params)
+}
+
+/// Light color scaled by **intensity** and **exposure**.
+fn light_color(light: &AssNode) -> Result<Spectrum, SceneError> {
+    let color: [Float; 3] = light.triple("color", [1.0, 1.0, 1.0])?;
+    let scale: Float = light.float("intensity", 1.0)? *
+                       (2.0 as Float).powf(light.float("exposure", 0.0)?);
+    Ok(Spectrum { c: [color[0] * scale, color[1] * scale, color[2] * scale] })
+}
+
+fn make_point_light(light: &AssNode, api_state: &mut ApiState) -> Result<(), SceneError> {
+    let mut params: ParamSet = ParamSet::default();
+    params.add_rgb_spectrum(String::from("I"), light_color(light)?);
+    let position: [Float; 3] = light.triple("position", [0.0, 0.0, 0.0])?;
+    pbrt_attribute_begin(api_state);
+    if let Some(m) = light.matrix()? {
+        pbrt_concat_transform(api_state, &m);
+    }
+    // the position is given in the light's object space
+    pbrt_translate(api_state, position[0], position[1], position[2]);
+    pbrt_light_source(api_state, String::from("point"), params);
+    pbrt_attribute_end(api_state);
+    Ok(())
+}
+
+fn make_distant_light(light: &AssNode, api_state: &mut ApiState) -> Result<(), SceneError> {
+    let mut params: ParamSet = ParamSet::default();
+    params.add_rgb_spectrum(String::from("L"), light_color(light)?);
+    // the light shines along -z
+    let to: [Float; 3] = light.triple("direction", [0.0, 0.0, -1.0])?;
+    params.add_point3f(String::from("from"), Point3f::default());
+    params.add_point3f(String::from("to"),
+                       Point3f {
+                           x: to[0],
+                           y: to[1],
+                           z: to[2],
+                       });
+    pbrt_attribute_begin(api_state);
+    if let Some(m) = light.matrix()? {
+        pbrt_concat_transform(api_state, &m);
+    }
+    pbrt_light_source(api_state, String::from("distant"), params);
+    pbrt_attribute_end(api_state);
+    Ok(())
+}
+
+/// The color of the skydome can be a constant, an **image** node
+/// (used as environment map) or another node (ignored).
+fn make_skydome_light(light: &AssNode,
+                      named_nodes: &HashMap<String, &AssNode>,
+                      api_state: &mut ApiState)
+                      -> Result<(), SceneError> {
+    let mut params: ParamSet = ParamSet::default();
+    let color_node: Option<&AssNode> = match light.string("color") {
+        Some(name) => named_nodes.get(&name).map(|node| *node),
+        None => None,
+    };
+    if let Some(color_node) = color_node {
+        let scale: Float = light.float("intensity", 1.0)? *
+                           (2.0 as Float).powf(light.float("exposure", 0.0)?);
+        params.add_rgb_spectrum(String::from("L"), Spectrum::new(scale));
+        match color_node.string("filename") {
+            Some(ref filename) if color_node.node_type == "image" => {
+                params.add_string(String::from("mapname"), filename.clone());
+            }
+            _ => {
+                api_state.warning(format!("Skydome color from \"{}\" not supported yet, using \
+                                           white",
+                                          color_node.node_type));
+            }
+        }
+    } else {
+        params.add_rgb_spectrum(String::from("L"), light_color(light)?);
+    }
+    let samples: i32 = light.int("samples", 1)?;
+    params.add_int(String::from("nsamples"), samples * samples);
+    pbrt_attribute_begin(api_state);
+    if let Some(m) = light.matrix()? {
+        pbrt_concat_transform(api_state, &m);
+    }
+    pbrt_light_source(api_state, String::from("infinite"), params);
+    pbrt_attribute_end(api_state);
+    Ok(())
+}
+
+/// Polygons are triangulated (as fans) and split into one
+/// **trianglemesh** per shader. Vertices with different normal or uv
+/// indices are duplicated, because pbrt uses one index per vertex.
+fn make_polymesh(mesh: &AssNode, api_state: &mut ApiState) -> Result<(), SceneError> {
+    let vlist: Vec<Float> = mesh.floats("vlist")?.unwrap_or(Vec::new());
+    let vidxs: Vec<i32> = mesh.ints("vidxs")?.unwrap_or(Vec::new());
+    let nsides: Vec<i32> = match mesh.ints("nsides")? {
+        Some(nsides) => nsides,
+        None => vec![3; vidxs.len() / 3],
+    };
+    let nlist: Option<Vec<Float>> = mesh.floats("nlist")?;
+    let nidxs: Option<Vec<i32>> = mesh.ints("nidxs")?;
+    let uvlist: Option<Vec<Float>> = mesh.floats("uvlist")?;
+    let uvidxs: Option<Vec<i32>> = mesh.ints("uvidxs")?;
+    let shidxs: Option<Vec<i32>> = mesh.ints("shidxs")?;
+    let shaders: Vec<String> = mesh.params.get("shader").cloned().unwrap_or(Vec::new());
+    if let Some(sides) = nsides.iter().find(|sides| **sides < 0) {
+        return Err(SceneError::new(format!("Negative number of sides {} in \"nsides\"", sides)));
+    }
+    let n_corners: usize = nsides.iter().map(|n| *n as usize).sum();
+    if n_corners != vidxs.len() {
+        return Err(SceneError::new(format!("\"vidxs\" has {} indices, \"nsides\" needs {}",
+                                           vidxs.len(),
+                                           n_corners)));
+    }
+    // normals and uvs are indexed per polygon corner as well
+    for &(param, idxs) in &[("nidxs", &nidxs), ("uvidxs", &uvidxs)] {
+        if let Some(ref idxs) = *idxs {
+            if n_corners != idxs.len() {
+                return Err(SceneError::new(format!("\"{}\" has {} indices, \"nsides\" needs {}",
+                                                   param,
+                                                   idxs.len(),
+                                                   n_corners)));
+            }
+        }
+    }
+    // per shader: map of (vertex, normal, uv) indices to mesh vertices
+    let n_shaders: usize = shaders.len().max(1);
+    let mut corners: Vec<HashMap<(i32, i32, i32), i32>> = vec![HashMap::new(); n_shaders];
+    let mut keys: Vec<Vec<(i32, i32, i32)>> = vec![Vec::new(); n_shaders];
+    let mut indices: Vec<Vec<i32>> = vec![Vec::new(); n_shaders];
+    let mut offset: usize = 0;
+    for (face, sides) in nsides.iter().enumerate() {
+        let shader: usize = match shidxs {
+            Some(ref shidxs) if face < shidxs.len() => shidxs[face] as usize,
+            _ => 0,
+        };
+        if shader >= n_shaders {
+            return Err(SceneError::new(format!("Shader index {} out of range", shader)));
+        }
+        let mut face_indices: Vec<i32> = Vec::with_capacity(*sides as usize);
+        for i in offset..offset + *sides as usize {
+            let key: (i32, i32, i32) = (vidxs[i],
+                                        match nidxs {
+                                            Some(ref nidxs) => nidxs[i],
+                                            None => vidxs[i],
+                                        },
+                                        match uvidxs {
+                                            Some(ref uvidxs) => uvidxs[i],
+                                            None => vidxs[i],
+                                        });
+            let next: i32 = keys[shader].len() as i32;
+            let index: i32 = *corners[shader].entry(key).or_insert(next);
+            if index == next {
+                keys[shader].push(key);
+            }
+            face_indices.push(index);
+        }
+        for i in 1..face_indices.len().saturating_sub(1) {
+            indices[shader].push(face_indices[0]);
+            indices[shader].push(face_indices[i]);
+            indices[shader].push(face_indices[i + 1]);
+        }
+        offset += *sides as usize;
+    }
+    pbrt_attribute_begin(api_state);
+    if let Some(m) = mesh.matrix()? {
+        pbrt_concat_transform(api_state, &m);
+    }
+    for shader in 0..n_shaders {
+        if indices[shader].is_empty() {
+            continue;
+        }
+        let mut p: Vec<Float> = Vec::with_capacity(keys[shader].len() * 3);
+        let mut n: Vec<Float> = Vec::new();
+        let mut uv: Vec<Float> = Vec::new();
+        for &(vi, ni, uvi) in &keys[shader] {
+            p.extend_from_slice(lookup(&vlist, vi, 3, "vlist")?);
+            if let Some(ref nlist) = nlist {
+                n.extend_from_slice(lookup(nlist, ni, 3, "nlist")?);
+            }
+            if let Some(ref uvlist) = uvlist {
+                uv.extend_from_slice(lookup(uvlist, uvi, 2, "uvlist")?);
+            }
+        }
+        let mut params: ParamSet = ParamSet::default();
+        params.add_ints(String::from("indices"), indices[shader].clone());
+        params.add_point3fs(String::from("P"), p);
+        if !n.is_empty() {
+            params.add_normal3fs(String::from("N"), n);
+        }
+        if !uv.is_empty() {
+            params.add_point2fs(String::from("uv"), uv);
+        }
+        if shader < shaders.len() {
+            pbrt_named_material(api_state, shaders[shader].clone());
+        }
+        pbrt_shape(api_state, String::from("trianglemesh"), params)?;
+    }
+    pbrt_attribute_end(api_state);
+    Ok(())
+}
+
+/// The **n** values of element **index** of an array parameter.
+fn lookup<'a>(list: &'a Vec<Float>,
+              index: i32,
+              n: usize,
+              param: &str)
+              -> Result<&'a [Float], SceneError> {
+    if index < 0 || index as usize * n + n > list.len() {
+        return Err(SceneError::new(format!("Index {} out of range for \"{}\"", index, param)));
+    }
+    let start: usize = index as usize * n;
+    Ok(&list[start..start + n])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::process;
+    use super::*;
+    use core::geometry::{Point2f, Vector3f};
+    use core::interaction::InteractionCommon;
+    use core::light::VisibilityTester;
+
+    fn parse_ass(test: &str, content: &str) -> World {
+        let dir: PathBuf = env::temp_dir()
+            .join(format!("pbrt_assparser_{}_{}", test, process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path: PathBuf = dir.join("scene.ass");
+        fs::File::create(&path).unwrap().write_all(content.as_bytes()).unwrap();
+        let world: World = parse_ass_file(&path).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        world
+    }
+
+    fn light_position(world: &World, index: usize) -> Point3f {
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0;
+        let mut vis: VisibilityTester = VisibilityTester::default();
+        world.scene.lights[index].sample_li(&InteractionCommon::default(),
+                                            &Point2f::default(),
+                                            &mut wi,
+                                            &mut pdf,
+                                            &mut vis);
+        vis.p1.p
+    }
+
+    #[test]
+    fn point_light_position() {
+        let world: World = parse_ass("point_light",
+                                     "options\n{\n xres 8\n yres 8\n}\n\
+                                      polymesh\n{\n name ground\n nsides 1 1 UINT 3\n \
+                                      vidxs 3 1 UINT 0 1 2\n \
+                                      vlist 3 1 POINT 0 0 0 1 0 0 0 1 0\n}\n\
+                                      point_light\n{\n name plain\n position 1 2 3\n}\n\
+                                      point_light\n{\n name moved\n position 1 0 0\n \
+                                      matrix\n 0 1 0 0\n -1 0 0 0\n 0 0 1 0\n 0 0 5 1\n}\n");
+        let p: Point3f = light_position(&world, 0);
+        assert_eq!((p.x, p.y, p.z), (1.0, 2.0, 3.0));
+        // the position is transformed by the light's matrix
+        let p: Point3f = light_position(&world, 1);
+        assert_eq!((p.x, p.y, p.z), (0.0, 1.0, 5.0));
+        assert!(!world.warnings.iter().any(|warning| warning.message.contains("not used")));
+    }
+}
@@ -0,0 +1,528 @@
+//! Scenes can be created from Rust code without writing a **.pbrt**
+//! file first. The methods of **SceneBuilder** mirror the statements
+//! of a scene file (and the API functions in **api.rs**) and can be
+//! chained:
+//!
+//! ```rust
+//! extern crate pbrt;
+//!
+//! use pbrt::core::builder::SceneBuilder;
+//! use pbrt::core::geometry::{Point3f, Vector3f};
+//! use pbrt::core::paramset::ParamSet;
+//! use pbrt::core::parser::World;
+//! use pbrt::core::pbrt::Spectrum;
+//!
+//! fn main() {
+//!     let mut film_params: ParamSet = ParamSet::default();
+//!     film_params.add_int(String::from("xresolution"), 32);
+//!     film_params.add_int(String::from("yresolution"), 32);
+//!     let mut light_params: ParamSet = ParamSet::default();
+//!     light_params.add_rgb_spectrum(String::from("I"), Spectrum::new(10.0));
+//!     let mut sphere_params: ParamSet = ParamSet::default();
+//!     sphere_params.add_float(String::from("radius"), 0.5);
+//!     let mut matte_params: ParamSet = ParamSet::default();
+//!     matte_params.add_rgb_spectrum(String::from("Kd"), Spectrum::rgb(0.8, 0.2, 0.2));
+//!
+//!     let mut builder: SceneBuilder = SceneBuilder::new(None);
+//!     builder
+//!         .look_at(Point3f { x: 0.0, y: 0.0, z: 5.0 },
+//!                  Point3f { x: 0.0, y: 0.0, z: 0.0 },
+//!                  Vector3f { x: 0.0, y: 1.0, z: 0.0 })
+//!         .camera("perspective", ParamSet::default())
+//!         .film("image", film_params)
+//!         .world_begin()
+//!         .add_light("point", light_params)
+//!         .attribute_scope(|b| {
+//!             b.translate(Vector3f { x: 0.0, y: 0.0, z: 1.0 })
+//!                 .add_shape_with_material("sphere", sphere_params, "matte", matte_params);
+//!         });
+//!     let world: World = builder.build().unwrap();
+//!     assert_eq!(world.scene.lights.len(), 1);
+//!
+//!     // a shape before WorldBegin
+//!     let mut builder: SceneBuilder = SceneBuilder::new(None);
+//!     builder.add_shape("sphere", ParamSet::default()).world_begin();
+//!     assert!(builder.build().is_err());
+//! }
+//! ```
+//!
+//! The first error stops the construction, it's returned by
+//! **build()** together with the statement it belongs to.
+
+// std
+use std::path::PathBuf;
+// pbrt
+use core::api::{pbrt_accelerator, pbrt_active_transform_all, pbrt_active_transform_end_time,
+                pbrt_active_transform_start_time, pbrt_area_light_source, pbrt_attribute_begin,
+                pbrt_attribute_end, pbrt_camera, pbrt_concat_transform, pbrt_coord_sys_transform,
+                pbrt_coordinate_system, pbrt_film, pbrt_identity, pbrt_integrator,
+                pbrt_light_source, pbrt_look_at, pbrt_make_named_material, pbrt_make_named_medium,
+                pbrt_material, pbrt_medium_interface, pbrt_named_material, pbrt_object_begin,
+                pbrt_object_end, pbrt_object_instance, pbrt_pixel_filter, pbrt_reverse_orientation,
+                pbrt_rotate, pbrt_sampler, pbrt_scale, pbrt_shape, pbrt_texture, pbrt_transform,
+                pbrt_transform_begin, pbrt_transform_end, pbrt_transform_times, pbrt_translate,
+                pbrt_world_begin, pbrt_world_end};
+use core::api::{ApiState, SceneError};
+use core::exporter::SceneExporter;
+use core::geometry::{Point3f, Vector3f};
+use core::paramset::ParamSet;
+use core::parser::{Location, ParseWarning, World};
+use core::pbrt::Float;
+
+/// Which statements are allowed (see VERIFY_OPTIONS and VERIFY_WORLD
+/// in api.cpp).
+#[derive(PartialEq)]
+enum Block {
+    Options,
+    World,
+    Any,
+}
+
+pub struct SceneBuilder {
+    api_state: ApiState,
+    world_begun: bool,
+    warnings: Vec<ParseWarning>,
+    error: Option<SceneError>,
+}
+
+impl SceneBuilder {
+    /// Relative filenames (e.g. of PLY meshes or image textures) are
+    /// resolved against **search_directory**.
+    pub fn new(search_directory: Option<PathBuf>) -> SceneBuilder {
+        SceneBuilder {
+            api_state: ApiState::new(search_directory),
+            world_begun: false,
+            warnings: Vec::new(),
+            error: None,
+        }
+    }
+    /// Writes the scene into a **.pbrt** file while it's built (has
+    /// to be called before **world_begin()**).
+    pub fn export(&mut self, exporter: SceneExporter) -> &mut SceneBuilder {
+        if self.verify("Export", Block::Options) {
+            self.api_state.exporter = Some(exporter);
+        }
+        self
+    }
+    // transformations
+    pub fn identity(&mut self) -> &mut SceneBuilder {
+        if self.verify("Identity", Block::Any) {
+            pbrt_identity(&mut self.api_state);
+            self.finish(String::from("Identity"), Ok(()));
+        }
+        self
+    }
+    pub fn translate(&mut self, delta: Vector3f) -> &mut SceneBuilder {
+        if self.verify("Translate", Block::Any) {
+            pbrt_translate(&mut self.api_state, delta.x, delta.y, delta.z);
+            self.finish(String::from("Translate"), Ok(()));
+        }
+        self
+    }
+    /// The **angle** is given in degrees.
+    pub fn rotate(&mut self, angle: Float, axis: Vector3f) -> &mut SceneBuilder {
+        if self.verify("Rotate", Block::Any) {
+            pbrt_rotate(&mut self.api_state, angle, axis.x, axis.y, axis.z);
+            self.finish(String::from("Rotate"), Ok(()));
+        }
+        self
+    }
+    pub fn scale(&mut self, sx: Float, sy: Float, sz: Float) -> &mut SceneBuilder {
+        if self.verify("Scale", Block::Any) {
+            pbrt_scale(&mut self.api_state, sx, sy, sz);
+            self.finish(String::from("Scale"), Ok(()));
+        }
+        self
+    }
+    pub fn look_at(&mut self, eye: Point3f, look: Point3f, up: Vector3f) -> &mut SceneBuilder {
+        if self.verify("LookAt", Block::Any) {
+            pbrt_look_at(&mut self.api_state,
+                         eye.x,
+                         eye.y,
+                         eye.z,
+                         look.x,
+                         look.y,
+                         look.z,
+                         up.x,
+                         up.y,
+                         up.z);
+            self.finish(String::from("LookAt"), Ok(()));
+        }
+        self
+    }
+    /// The matrix is given column by column, like in a scene file.
+    pub fn transform(&mut self, tr: &[Float; 16]) -> &mut SceneBuilder {
+        if self.verify("Transform", Block::Any) {
+            pbrt_transform(&mut self.api_state, tr);
+            self.finish(String::from("Transform"), Ok(()));
+        }
+        self
+    }
+    pub fn concat_transform(&mut self, tr: &[Float; 16]) -> &mut SceneBuilder {
+        if self.verify("ConcatTransform", Block::Any) {
+            pbrt_concat_transform(&mut self.api_state, tr);
+            self.finish(String::from("ConcatTransform"), Ok(()));
+        }
+        self
+    }
+    pub fn coordinate_system(&mut self, name: &str) -> &mut SceneBuilder {
+        let statement: String = summary("CoordinateSystem", name);
+        if self.verify(&statement, Block::Any) {
+            pbrt_coordinate_system(&mut self.api_state, String::from(name));
+            self.finish(statement, Ok(()));
+        }
+        self
+    }
+    pub fn coord_sys_transform(&mut self, name: &str) -> &mut SceneBuilder {
+        let statement: String = summary("CoordSysTransform", name);
+        if self.verify(&statement, Block::Any) {
+            pbrt_coord_sys_transform(&mut self.api_state, String::from(name));
+            self.finish(statement, Ok(()));
+        }
+        self
+    }
+    pub fn active_transform_all(&mut self) -> &mut SceneBuilder {
+        if self.verify("ActiveTransform All", Block::Any) {
+            pbrt_active_transform_all(&mut self.api_state);
+            self.finish(String::from("ActiveTransform All"), Ok(()));
+        }
+        self
+    }
+    pub fn active_transform_start_time(&mut self) -> &mut SceneBuilder {
+        if self.verify("ActiveTransform StartTime", Block::Any) {
+            pbrt_active_transform_start_time(&mut self.api_state);
+            self.finish(String::from("ActiveTransform StartTime"), Ok(()));
+        }
+        self
+    }
+    pub fn active_transform_end_time(&mut self) -> &mut SceneBuilder {
+        if self.verify("ActiveTransform EndTime", Block::Any) {
+            pbrt_active_transform_end_time(&mut self.api_state);
+            self.finish(String::from("ActiveTransform EndTime"), Ok(()));
+        }
+        self
+    }
+    pub fn transform_times(&mut self, start: Float, end: Float) -> &mut SceneBuilder {
+        if self.verify("TransformTimes", Block::Options) {
+            pbrt_transform_times(&mut self.api_state, start, end);
+            self.finish(String::from("TransformTimes"), Ok(()));
+        }
+        self
+    }
+    // render options
+    pub fn pixel_filter(&mut self, name: &str, params: ParamSet) -> &mut SceneBuilder {
+        let statement: String = summary("PixelFilter", name);
+        if self.verify(&statement, Block::Options) {
+            pbrt_pixel_filter(&mut self.api_state, String::from(name), params);
+            self.finish(statement, Ok(()));
+        }
+        self
+    }
+    pub fn film(&mut self, name: &str, params: ParamSet) -> &mut SceneBuilder {
+        let statement: String = summary("Film", name);
+        if self.verify(&statement, Block::Options) {
+            pbrt_film(&mut self.api_state, String::from(name), params);
+            self.finish(statement, Ok(()));
+        }
+        self
+    }
+    pub fn sampler(&mut self, name: &str, params: ParamSet) -> &mut SceneBuilder {
+        let statement: String = summary("Sampler", name);
+        if self.verify(&statement, Block::Options) {
+            pbrt_sampler(&mut self.api_state, String::from(name), params);
+            self.finish(statement, Ok(()));
+        }
+        self
+    }
+    pub fn accelerator(&mut self, name: &str, params: ParamSet) -> &mut SceneBuilder {
+        let statement: String = summary("Accelerator", name);
+        if self.verify(&statement, Block::Options) {
+            pbrt_accelerator(&mut self.api_state, String::from(name), params);
+            self.finish(statement, Ok(()));
+        }
+        self
+    }
+    pub fn integrator(&mut self, name: &str, params: ParamSet) -> &mut SceneBuilder {
+        let statement: String = summary("Integrator", name);
+        if self.verify(&statement, Block::Options) {
+            pbrt_integrator(&mut self.api_state, String::from(name), params);
+            self.finish(statement, Ok(()));
+        }
+        self
+    }
+    /// Uses the current transformation as world to camera transform.
+    pub fn camera(&mut self, name: &str, params: ParamSet) -> &mut SceneBuilder {
+        let statement: String = summary("Camera", name);
+        if self.verify(&statement, Block::Options) {
+            pbrt_camera(&mut self.api_state, String::from(name), params);
+            self.finish(statement, Ok(()));
+        }
+        self
+    }
+    pub fn world_begin(&mut self) -> &mut SceneBuilder {
+        if self.verify("WorldBegin", Block::Options) {
+            pbrt_world_begin(&mut self.api_state);
+            self.world_begun = true;
+            self.finish(String::from("WorldBegin"), Ok(()));
+        }
+        self
+    }
+    // scene description
+    pub fn attribute_begin(&mut self) -> &mut SceneBuilder {
+        if self.verify("AttributeBegin", Block::World) {
+            pbrt_attribute_begin(&mut self.api_state);
+            self.finish(String::from("AttributeBegin"), Ok(()));
+        }
+        self
+    }
+    pub fn attribute_end(&mut self) -> &mut SceneBuilder {
+        if self.verify("AttributeEnd", Block::World) {
+            pbrt_attribute_end(&mut self.api_state);
+            self.finish(String::from("AttributeEnd"), Ok(()));
+        }
+        self
+    }
+    /// Calls **f** between **attribute_begin()** and
+    /// **attribute_end()**.
+    pub fn attribute_scope<F>(&mut self, f: F) -> &mut SceneBuilder
+        where F: FnOnce(&mut SceneBuilder)
+    {
+        self.attribute_begin();
+        f(self);
+        self.attribute_end()
+    }
+    pub fn transform_begin(&mut self) -> &mut SceneBuilder {
+        if self.verify("TransformBegin", Block::World) {
+            pbrt_transform_begin(&mut self.api_state);
+            self.finish(String::from("TransformBegin"), Ok(()));
+        }
+        self
+    }
+    pub fn transform_end(&mut self) -> &mut SceneBuilder {
+        if self.verify("TransformEnd", Block::World) {
+            pbrt_transform_end(&mut self.api_state);
+            self.finish(String::from("TransformEnd"), Ok(()));
+        }
+        self
+    }
+    /// Calls **f** between **transform_begin()** and
+    /// **transform_end()**.
+    pub fn transform_scope<F>(&mut self, f: F) -> &mut SceneBuilder
+        where F: FnOnce(&mut SceneBuilder)
+    {
+        self.transform_begin();
+        f(self);
+        self.transform_end()
+    }
+    pub fn texture(&mut self,
+                   name: &str,
+                   tex_type: &str,
+                   tex_name: &str,
+                   params: ParamSet)
+                   -> &mut SceneBuilder {
+        let statement: String = format!("Texture \"{}\" \"{}\" \"{}\"", name, tex_type, tex_name);
+        if self.verify(&statement, Block::World) {
+            let result: Result<(), SceneError> = pbrt_texture(&mut self.api_state,
+                                                              String::from(name),
+                                                              String::from(tex_type),
+                                                              String::from(tex_name),
+                                                              params);
+            self.finish(statement, result);
+        }
+        self
+    }
+    pub fn material(&mut self, name: &str, params: ParamSet) -> &mut SceneBuilder {
+        let statement: String = summary("Material", name);
+        if self.verify(&statement, Block::World) {
+            pbrt_material(&mut self.api_state, String::from(name), params);
+            self.finish(statement, Ok(()));
+        }
+        self
+    }
+    pub fn make_named_material(&mut self, name: &str, params: ParamSet) -> &mut SceneBuilder {
+        let statement: String = summary("MakeNamedMaterial", name);
+        if self.verify(&statement, Block::World) {
+            let result: Result<(), SceneError> =
+                pbrt_make_named_material(&mut self.api_state, String::from(name), params);
+            self.finish(statement, result);
+        }
+        self
+    }
+    pub fn named_material(&mut self, name: &str) -> &mut SceneBuilder {
+        let statement: String = summary("NamedMaterial", name);
+        if self.verify(&statement, Block::World) {
+            pbrt_named_material(&mut self.api_state, String::from(name));
+            self.finish(statement, Ok(()));
+        }
+        self
+    }
+    /// Defines a medium, which can be referred to by name in
+    /// **medium_interface()**.
+    pub fn make_named_medium(&mut self, name: &str, params: ParamSet) -> &mut SceneBuilder {
+        let statement: String = summary("MakeNamedMedium", name);
+        if self.verify(&statement, Block::Any) {
+            let result: Result<(), SceneError> =
+                pbrt_make_named_medium(&mut self.api_state, String::from(name), params);
+            self.finish(statement, result);
+        }
+        self
+    }
+    /// The media inside and outside of shapes added afterwards (an
+    /// empty name stands for vacuum). Lights and the camera are in
+    /// the outside medium.
+    pub fn medium_interface(&mut self, inside: &str, outside: &str) -> &mut SceneBuilder {
+        let statement: String = format!("MediumInterface \"{}\" \"{}\"", inside, outside);
+        if self.verify(&statement, Block::Any) {
+            pbrt_medium_interface(&mut self.api_state,
+                                  String::from(inside),
+                                  String::from(outside));
+            self.finish(statement, Ok(()));
+        }
+        self
+    }
+    /// Adds a light source (**LightSource** in a scene file).
+    pub fn add_light(&mut self, name: &str, params: ParamSet) -> &mut SceneBuilder {
+        let statement: String = summary("LightSource", name);
+        if self.verify(&statement, Block::World) {
+            pbrt_light_source(&mut self.api_state, String::from(name), params);
+            self.finish(statement, Ok(()));
+        }
+        self
+    }
+    /// Shapes added afterwards (within the current attribute block)
+    /// emit light.
+    pub fn area_light_source(&mut self, name: &str, params: ParamSet) -> &mut SceneBuilder {
+        let statement: String = summary("AreaLightSource", name);
+        if self.verify(&statement, Block::World) {
+            pbrt_area_light_source(&mut self.api_state, String::from(name), params);
+            self.finish(statement, Ok(()));
+        }
+        self
+    }
+    pub fn reverse_orientation(&mut self) -> &mut SceneBuilder {
+        if self.verify("ReverseOrientation", Block::World) {
+            pbrt_reverse_orientation(&mut self.api_state);
+            self.finish(String::from("ReverseOrientation"), Ok(()));
+        }
+        self
+    }
+    /// Adds a shape (**Shape** in a scene file) using the current
+    /// material.
+    pub fn add_shape(&mut self, name: &str, params: ParamSet) -> &mut SceneBuilder {
+        let statement: String = summary("Shape", name);
+        if self.verify(&statement, Block::World) {
+            let result: Result<(), SceneError> =
+                pbrt_shape(&mut self.api_state, String::from(name), params);
+            self.finish(statement, result);
+        }
+        self
+    }
+    /// Adds a shape with its own material, without changing the
+    /// current material.
+    pub fn add_shape_with_material(&mut self,
+                                   name: &str,
+                                   params: ParamSet,
+                                   material: &str,
+                                   material_params: ParamSet)
+                                   -> &mut SceneBuilder {
+        self.attribute_scope(|b| {
+            b.material(material, material_params).add_shape(name, params);
+        })
+    }
+    pub fn object_begin(&mut self, name: &str) -> &mut SceneBuilder {
+        let statement: String = summary("ObjectBegin", name);
+        if self.verify(&statement, Block::World) {
+            let result: Result<(), SceneError> =
+                pbrt_object_begin(&mut self.api_state, String::from(name));
+            self.finish(statement, result);
+        }
+        self
+    }
+    pub fn object_end(&mut self) -> &mut SceneBuilder {
+        if self.verify("ObjectEnd", Block::World) {
+            let result: Result<(), SceneError> = pbrt_object_end(&mut self.api_state);
+            self.finish(String::from("ObjectEnd"), result);
+        }
+        self
+    }
+    pub fn object_instance(&mut self, name: &str) -> &mut SceneBuilder {
+        let statement: String = summary("ObjectInstance", name);
+        if self.verify(&statement, Block::World) {
+            let result: Result<(), SceneError> =
+                pbrt_object_instance(&mut self.api_state, String::from(name));
+            self.finish(statement, result);
+        }
+        self
+    }
+    /// Creates the scene, camera, sampler and integrator (like
+    /// **WorldEnd** in a scene file).
+    pub fn build(mut self) -> Result<World, SceneError> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        if !self.world_begun {
+            return Err(SceneError::new(String::from("WorldBegin missing, the scene is empty")));
+        }
+        let result = pbrt_world_end(&mut self.api_state);
+        self.finish(String::from("WorldEnd"), Ok(()));
+        match result {
+            Ok((scene, camera, sampler, integrator)) => {
+                Ok(World {
+                       scene: scene,
+                       camera: camera,
+                       sampler: sampler,
+                       integrator: integrator,
+                       warnings: self.warnings,
+                   })
+            }
+            Err(err) => Err(SceneError::new(format!("WorldEnd: {}", err.message))),
+        }
+    }
+    /// Recoverable problems found so far.
+    pub fn warnings(&self) -> &Vec<ParseWarning> {
+        &self.warnings
+    }
+    /// Returns **false** (and ignores the statement) after an error,
+    /// or if the statement isn't allowed at this point.
+    fn verify(&mut self, statement: &str, block: Block) -> bool {
+        if self.error.is_some() {
+            return false;
+        }
+        if block == Block::Options && self.world_begun {
+            self.error = Some(SceneError::new(format!("Options cannot be set inside world \
+                                                       block; {} not allowed",
+                                                      statement)));
+            return false;
+        }
+        if block == Block::World && !self.world_begun {
+            self.error = Some(SceneError::new(format!("Scene description must be inside \
+                                                       world block; {} not allowed",
+                                                      statement)));
+            return false;
+        }
+        true
+    }
+    /// Collects the warnings of the last API call and keeps the first
+    /// error.
+    fn finish(&mut self, statement: String, result: Result<(), SceneError>) {
+        for message in self.api_state.warnings.drain(..) {
+            self.warnings.push(ParseWarning {
+                                   message: message,
+                                   location: Location {
+                                       file: None,
+                                       line: 0,
+                                       column: 0,
+                                       statement: statement.clone(),
+                                   },
+                               });
+        }
+        if let Err(err) = result {
+            self.error = Some(SceneError::new(format!("{}: {}", statement, err.message)));
+        }
+    }
+}
+
+/// E.g. **Shape "sphere"**.
+fn summary(keyword: &str, name: &str) -> String {
+    format!("{} \"{}\"", keyword, name)
+}
@@ -6,10 +6,13 @@
 use std::sync::Arc;
 // pbrt
 use core::film::Film;
-use core::geometry::{Point2f, Ray, Vector3f};
+use core::geometry::{Bounds2i, Point2f, Point3f, Ray, Vector3f};
+use core::geometry::vec3_normalize;
 use core::interaction::InteractionCommon;
 use core::light::VisibilityTester;
+use core::medium::{Medium, MediumInterface};
 use core::pbrt::{Float, Spectrum};
+use core::transform::{AnimatedTransform, Transform};
 
 // see camera.h
 
@@ -27,6 +30,8 @@ pub trait Camera {
         vis: &mut VisibilityTester,
     ) -> Spectrum;
     fn get_film(&self) -> Arc<Film>;
+    fn get_shutter_open(&self) -> Float;
+    fn get_shutter_close(&self) -> Float;
 }
 
 #[derive(Debug, Default, Copy, Clone)]
@@ -35,3 +40,99 @@ pub struct CameraSample {
     pub p_lens: Point2f,
     pub time: Float,
 }
+
+/// Importance of cameras which project through a single point
+/// without a lens (like the environment camera). Each camera only
+/// provides a mapping from a (normalized) camera space direction to
+/// its raster position and the density of directions per unit solid
+/// angle (with the film sampled uniformly), or _None_ for directions
+/// the camera can't see.
+pub struct PointCamera<'a> {
+    pub camera_to_world: &'a AnimatedTransform,
+    pub film: &'a Film,
+    pub medium: &'a Option<Arc<Medium + Send + Sync>>,
+}
+
+impl<'a> PointCamera<'a> {
+    /// Maps a ray leaving the camera onto the raster grid, the
+    /// density is zero for directions outside the sample bounds.
+    fn raster_and_pdf<F>(&self, ray: &Ray, direction_to_raster: F) -> Option<(Point2f, Float)>
+    where
+        F: Fn(&Vector3f) -> Option<(Point2f, Float)>,
+    {
+        let mut c2w: Transform = Transform::default();
+        self.camera_to_world.interpolate(ray.time, &mut c2w);
+        let dir: Vector3f = vec3_normalize(&Transform::inverse(&c2w).transform_vector(&ray.d));
+        if let Some((p_raster, pdf_dir)) = direction_to_raster(&dir) {
+            let sample_bounds: Bounds2i = self.film.get_sample_bounds();
+            if p_raster.x < sample_bounds.p_min.x as Float
+                || p_raster.x >= sample_bounds.p_max.x as Float
+                || p_raster.y < sample_bounds.p_min.y as Float
+                || p_raster.y >= sample_bounds.p_max.y as Float
+            {
+                Some((p_raster, 0.0 as Float))
+            } else {
+                Some((p_raster, pdf_dir))
+            }
+        } else {
+            None
+        }
+    }
+    pub fn we<F>(
+        &self,
+        ray: &Ray,
+        p_raster2: Option<&mut Point2f>,
+        direction_to_raster: F,
+    ) -> Spectrum
+    where
+        F: Fn(&Vector3f) -> Option<(Point2f, Float)>,
+    {
+        if let Some((p_raster, pdf_dir)) = self.raster_and_pdf(ray, direction_to_raster) {
+            // return raster position if requested
+            if let Some(p_raster2) = p_raster2 {
+                *p_raster2 = p_raster;
+            }
+            // the camera is a point, so importance and directional
+            // density are the same
+            Spectrum::new(pdf_dir)
+        } else {
+            Spectrum::default()
+        }
+    }
+    pub fn pdf_we<F>(&self, ray: &Ray, direction_to_raster: F) -> (Float, Float)
+    where
+        F: Fn(&Vector3f) -> Option<(Point2f, Float)>,
+    {
+        match self.raster_and_pdf(ray, direction_to_raster) {
+            Some((_p_raster, pdf_dir)) if pdf_dir != 0.0 as Float => (1.0 as Float, pdf_dir),
+            _ => (0.0 as Float, 0.0 as Float),
+        }
+    }
+    pub fn sample_wi<F>(
+        &self,
+        iref: &InteractionCommon,
+        wi: &mut Vector3f,
+        pdf: &mut Float,
+        p_raster: &mut Point2f,
+        vis: &mut VisibilityTester,
+        direction_to_raster: F,
+    ) -> Spectrum
+    where
+        F: Fn(&Vector3f) -> Option<(Point2f, Float)>,
+    {
+        // the camera position is a point, no normal is needed
+        let mut cam_intr: InteractionCommon = InteractionCommon::default();
+        cam_intr.p = self.camera_to_world.transform_point(iref.time, &Point3f::default());
+        cam_intr.time = iref.time;
+        cam_intr.medium_interface = MediumInterface::from_medium(self.medium.clone());
+        // populate arguments and compute the importance value
+        vis.p0 = iref.clone();
+        vis.p1 = cam_intr.clone();
+        *wi = cam_intr.p - iref.p;
+        let dist: Float = wi.length();
+        *wi /= dist;
+        // convert the delta position to a solid angle density at _iref_
+        *pdf = dist * dist;
+        self.we(&cam_intr.spawn_ray(&-*wi), Some(p_raster), direction_to_raster)
+    }
+}
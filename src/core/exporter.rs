@@ -0,0 +1,496 @@
+//! Writes a scene back into a **.pbrt** file (pbrt-v3 syntax) while
+//! it's created via the API (see **api.rs**), no matter whether the
+//! API calls come from the parser or from a program. The render
+//! options are written at **WorldBegin**, each texture, material,
+//! medium, light and shape together with the transformation and
+//! graphics state it depends on. Triangle meshes are stored in (binary) PLY
+//! files next to the exported scene.
+//!
+//! Textures and named materials are written at the point they are
+//! defined. The attribute blocks of the scene are kept, so they are
+//! scoped the same way when the exported scene is read again.
+
+// std
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+// pbrt
+use core::api::{GraphicsState, RenderOptions, TransformSet};
+use core::geometry::{Normal3f, Point2f, Point3f};
+use core::paramset::{ParamSet, ParamSetItem};
+use core::pbrt::Float;
+use core::transform::Transform;
+
+/// String parameters holding filenames, which are written as
+/// absolute paths (the exported scene might be stored elsewhere).
+const FILENAME_PARAMS: [&'static str; 3] = ["filename", "mapname", "lensfile"];
+
+pub struct SceneExporter {
+    out: BufWriter<File>,
+    /// Where the PLY files for triangle meshes are written.
+    directory: PathBuf,
+    /// Prefix for the names of the PLY files.
+    stem: String,
+    n_meshes: usize,
+    /// Relative filenames of the original scene are resolved against
+    /// this directory.
+    search_directory: Option<PathBuf>,
+    /// The first error, reported by **world_end()**.
+    error: Option<io::Error>,
+}
+
+impl SceneExporter {
+    pub fn create(filename: &Path) -> io::Result<SceneExporter> {
+        let file: File = File::create(filename)?;
+        let stem: String = match filename.file_stem() {
+            Some(stem) => stem.to_string_lossy().into_owned(),
+            None => String::from("scene"),
+        };
+        let mut exporter: SceneExporter = SceneExporter {
+            out: BufWriter::new(file),
+            directory: filename.parent().map(|p| p.to_path_buf()).unwrap_or(PathBuf::new()),
+            stem: stem,
+            n_meshes: 0,
+            search_directory: None,
+            error: None,
+        };
+        let result = writeln!(exporter.out, "# exported by rs_pbrt");
+        exporter.record(result);
+        Ok(exporter)
+    }
+    /// Camera, film, pixel filter, sampler, accelerator and integrator.
+    pub fn world_begin(&mut self,
+                       ro: &RenderOptions,
+                       search_directory: Option<&Box<PathBuf>>) {
+        self.search_directory = search_directory.map(|d| d.as_ref().clone());
+        let result = self.write_render_options(ro);
+        self.record(result);
+    }
+    pub fn texture(&mut self,
+                   ctm: &TransformSet,
+                   name: &String,
+                   tex_type: &String,
+                   tex_name: &String,
+                   params: &ParamSet) {
+        let result = self.write_texture(ctm, name, tex_type, tex_name, params);
+        self.record(result);
+    }
+    pub fn make_named_material(&mut self, name: &String, params: &ParamSet) {
+        let result = self.write_statement("", "MakeNamedMaterial", name, params, &[]);
+        self.record(result);
+    }
+    pub fn make_named_medium(&mut self, ctm: &TransformSet, name: &String, params: &ParamSet) {
+        let result = self.write_named_medium(ctm, name, params);
+        self.record(result);
+    }
+    pub fn light_source(&mut self,
+                        ctm: &TransformSet,
+                        graphics_state: &GraphicsState,
+                        name: &String,
+                        params: &ParamSet) {
+        let result = self.write_light_source(ctm, graphics_state, name, params);
+        self.record(result);
+    }
+    pub fn shape(&mut self,
+                 ctm: &TransformSet,
+                 graphics_state: &GraphicsState,
+                 name: &String,
+                 params: &ParamSet) {
+        let result = self.write_shape(ctm, graphics_state, name, params);
+        self.record(result);
+    }
+    /// Named materials and textures belong to the graphics state,
+    /// the attribute blocks defining it are written as they occur.
+    pub fn attribute_begin(&mut self) {
+        let result = writeln!(self.out, "AttributeBegin");
+        self.record(result);
+    }
+    pub fn attribute_end(&mut self) {
+        let result = writeln!(self.out, "AttributeEnd");
+        self.record(result);
+    }
+    pub fn object_begin(&mut self, name: &String) {
+        let result = writeln!(self.out, "ObjectBegin {}", quoted(name));
+        self.record(result);
+    }
+    pub fn object_end(&mut self) {
+        let result = writeln!(self.out, "ObjectEnd");
+        self.record(result);
+    }
+    pub fn object_instance(&mut self, ctm: &TransformSet, name: &String) {
+        let result = self.write_object_instance(ctm, name);
+        self.record(result);
+    }
+    /// Finishes the file, returns the first error which occurred
+    /// while writing.
+    pub fn world_end(&mut self) -> io::Result<()> {
+        let result = writeln!(self.out, "WorldEnd");
+        self.record(result);
+        let result = self.out.flush();
+        self.record(result);
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+    fn record(&mut self, result: io::Result<()>) {
+        if let Err(err) = result {
+            if self.error.is_none() {
+                self.error = Some(err);
+            }
+        }
+    }
+    fn write_render_options(&mut self, ro: &RenderOptions) -> io::Result<()> {
+        if ro.transform_start_time != 0.0 as Float || ro.transform_end_time != 1.0 as Float {
+            writeln!(self.out,
+                     "TransformTimes {} {}",
+                     ro.transform_start_time,
+                     ro.transform_end_time)?;
+        }
+        // the camera was defined with the world to camera transform
+        let world_to_camera: TransformSet = TransformSet {
+            t: [Transform::inverse(&ro.camera_to_world.t[0]),
+                Transform::inverse(&ro.camera_to_world.t[1])],
+        };
+        self.write_transform("", &world_to_camera)?;
+        if ro.camera_medium != String::new() {
+            writeln!(self.out,
+                     "MediumInterface {} {}",
+                     quoted(&ro.camera_medium),
+                     quoted(&ro.camera_medium))?;
+            self.write_statement("", "Camera", &ro.camera_name, &ro.camera_params, &[])?;
+            // shapes and lights write their own media
+            writeln!(self.out, "MediumInterface \"\" \"\"")?;
+        } else {
+            self.write_statement("", "Camera", &ro.camera_name, &ro.camera_params, &[])?;
+        }
+        self.write_statement("", "Film", &ro.film_name, &ro.film_params, &[])?;
+        self.write_statement("", "PixelFilter", &ro.filter_name, &ro.filter_params, &[])?;
+        self.write_statement("", "Sampler", &ro.sampler_name, &ro.sampler_params, &[])?;
+        self.write_statement("",
+                             "Accelerator",
+                             &ro.accelerator_name,
+                             &ro.accelerator_params,
+                             &[])?;
+        self.write_statement("",
+                             "Integrator",
+                             &ro.integrator_name,
+                             &ro.integrator_params,
+                             &[])?;
+        writeln!(self.out, "WorldBegin")
+    }
+    fn write_texture(&mut self,
+                     ctm: &TransformSet,
+                     name: &String,
+                     tex_type: &String,
+                     tex_name: &String,
+                     params: &ParamSet)
+                     -> io::Result<()> {
+        // textures belong to the graphics state, which AttributeEnd
+        // would restore
+        writeln!(self.out, "TransformBegin")?;
+        self.write_transform("  ", ctm)?;
+        writeln!(self.out,
+                 "  Texture {} {} {}",
+                 quoted(name),
+                 quoted(tex_type),
+                 quoted(tex_name))?;
+        self.write_params("    ", params, &[])?;
+        writeln!(self.out, "TransformEnd")
+    }
+    /// Media can be defined before **WorldBegin**, where
+    /// **TransformBegin** isn't allowed. All other statements write
+    /// their own transformation, so it's fine to leave it set.
+    fn write_named_medium(&mut self,
+                          ctm: &TransformSet,
+                          name: &String,
+                          params: &ParamSet)
+                          -> io::Result<()> {
+        self.write_transform("", ctm)?;
+        self.write_statement("", "MakeNamedMedium", name, params, &[])
+    }
+    fn write_light_source(&mut self,
+                          ctm: &TransformSet,
+                          graphics_state: &GraphicsState,
+                          name: &String,
+                          params: &ParamSet)
+                          -> io::Result<()> {
+        writeln!(self.out, "AttributeBegin")?;
+        self.write_transform("  ", ctm)?;
+        self.write_medium_interface("  ", graphics_state)?;
+        self.write_statement("  ", "LightSource", name, params, &[])?;
+        writeln!(self.out, "AttributeEnd")
+    }
+    fn write_object_instance(&mut self, ctm: &TransformSet, name: &String) -> io::Result<()> {
+        writeln!(self.out, "AttributeBegin")?;
+        self.write_transform("  ", ctm)?;
+        writeln!(self.out, "  ObjectInstance {}", quoted(name))?;
+        writeln!(self.out, "AttributeEnd")
+    }
+    fn write_shape(&mut self,
+                   ctm: &TransformSet,
+                   graphics_state: &GraphicsState,
+                   name: &String,
+                   params: &ParamSet)
+                   -> io::Result<()> {
+        writeln!(self.out, "AttributeBegin")?;
+        self.write_transform("  ", ctm)?;
+        if graphics_state.reverse_orientation {
+            writeln!(self.out, "  ReverseOrientation")?;
+        }
+        self.write_medium_interface("  ", graphics_state)?;
+        if graphics_state.current_material != String::new() {
+            writeln!(self.out,
+                     "  NamedMaterial {}",
+                     quoted(&graphics_state.current_material))?;
+        } else {
+            self.write_statement("  ",
+                                 "Material",
+                                 &graphics_state.material,
+                                 &graphics_state.material_params,
+                                 &[])?;
+        }
+        if graphics_state.area_light != String::new() {
+            self.write_statement("  ",
+                                 "AreaLightSource",
+                                 &graphics_state.area_light,
+                                 &graphics_state.area_light_params,
+                                 &[])?;
+        }
+        let has_tangents: bool = params.vector3fs.iter().any(|item| item.name == "S");
+        if *name == String::from("trianglemesh") && !has_tangents {
+            // PLY files can't store tangents, therefore meshes with
+            // "S" stay inline
+            let filename: String = self.write_ply_mesh(params)?;
+            writeln!(self.out, "  Shape \"plymesh\"")?;
+            writeln!(self.out, "    \"string filename\" [ {} ]", quoted(&filename))?;
+            self.write_params("    ",
+                              params,
+                              &["indices", "P", "N", "uv", "st", "faceIndices"])?;
+        } else {
+            self.write_statement("  ", "Shape", name, params, &[])?;
+        }
+        writeln!(self.out, "AttributeEnd")
+    }
+    fn write_medium_interface(&mut self,
+                              indent: &str,
+                              graphics_state: &GraphicsState)
+                              -> io::Result<()> {
+        if graphics_state.current_inside_medium != String::new() ||
+           graphics_state.current_outside_medium != String::new() {
+            writeln!(self.out,
+                     "{}MediumInterface {} {}",
+                     indent,
+                     quoted(&graphics_state.current_inside_medium),
+                     quoted(&graphics_state.current_outside_medium))?;
+        }
+        Ok(())
+    }
+    fn write_transform(&mut self, indent: &str, ctm: &TransformSet) -> io::Result<()> {
+        if ctm.is_animated() {
+            writeln!(self.out, "{}ActiveTransform StartTime", indent)?;
+            self.write_matrix(indent, &ctm.t[0])?;
+            writeln!(self.out, "{}ActiveTransform EndTime", indent)?;
+            self.write_matrix(indent, &ctm.t[1])?;
+            writeln!(self.out, "{}ActiveTransform All", indent)
+        } else {
+            self.write_matrix(indent, &ctm.t[0])
+        }
+    }
+    /// The **Transform** statement expects the matrix column by column
+    /// (see **pbrt_transform()**).
+    fn write_matrix(&mut self, indent: &str, transform: &Transform) -> io::Result<()> {
+        let m: &[[Float; 4]; 4] = &transform.m.m;
+        let mut values: Vec<String> = Vec::with_capacity(16);
+        for j in 0..4 {
+            for i in 0..4 {
+                values.push(format!("{}", m[i][j]));
+            }
+        }
+        writeln!(self.out, "{}Transform [ {} ]", indent, values.join(" "))
+    }
+    /// E.g. **Shape "sphere"** followed by its parameters (one per line),
+    /// leaving out the ones in **skip**.
+    fn write_statement(&mut self,
+                       indent: &str,
+                       keyword: &str,
+                       name: &String,
+                       params: &ParamSet,
+                       skip: &[&str])
+                       -> io::Result<()> {
+        writeln!(self.out, "{}{} {}", indent, keyword, quoted(name))?;
+        let param_indent: String = format!("{}  ", indent);
+        self.write_params(&param_indent, params, skip)
+    }
+    fn write_params(&mut self, indent: &str, params: &ParamSet, skip: &[&str]) -> io::Result<()> {
+        let mut lines: Vec<String> = Vec::new();
+        add_param_lines(&mut lines, "bool", &params.bools, skip, |b| vec![format!("{}", b)]);
+        add_param_lines(&mut lines, "integer", &params.ints, skip, |i| vec![format!("{}", i)]);
+        add_param_lines(&mut lines, "float", &params.floats, skip, |f| vec![format!("{}", f)]);
+        add_param_lines(&mut lines,
+                        "point2",
+                        &params.point2fs,
+                        skip,
+                        |p| vec![format!("{}", p.x), format!("{}", p.y)]);
+        add_param_lines(&mut lines,
+                        "vector2",
+                        &params.vector2fs,
+                        skip,
+                        |v| vec![format!("{}", v.x), format!("{}", v.y)]);
+        add_param_lines(&mut lines,
+                        "point",
+                        &params.point3fs,
+                        skip,
+                        |p| vec![format!("{}", p.x), format!("{}", p.y), format!("{}", p.z)]);
+        add_param_lines(&mut lines,
+                        "vector",
+                        &params.vector3fs,
+                        skip,
+                        |v| vec![format!("{}", v.x), format!("{}", v.y), format!("{}", v.z)]);
+        add_param_lines(&mut lines,
+                        "normal",
+                        &params.normals,
+                        skip,
+                        |n| vec![format!("{}", n.x), format!("{}", n.y), format!("{}", n.z)]);
+        add_param_lines(&mut lines,
+                        "rgb",
+                        &params.spectra,
+                        skip,
+                        |s| s.c.iter().map(|c| format!("{}", c)).collect());
+        for item in &params.strings {
+            if skip.contains(&item.name.as_str()) {
+                continue;
+            }
+            let values: Vec<String> = if FILENAME_PARAMS.contains(&item.name.as_str()) {
+                item.values.iter().map(|v| quoted(&self.absolute_filename(v))).collect()
+            } else {
+                item.values.iter().map(|v| quoted(v)).collect()
+            };
+            lines.push(format!("\"string {}\" [ {} ]", item.name, values.join(" ")));
+        }
+        add_param_lines(&mut lines, "texture", &params.textures, skip, |t| vec![quoted(t)]);
+        for line in lines {
+            writeln!(self.out, "{}{}", indent, line)?;
+        }
+        Ok(())
+    }
+    fn absolute_filename(&self, filename: &String) -> String {
+        match self.search_directory {
+            Some(ref directory) if Path::new(filename).is_relative() => {
+                directory.join(filename).to_string_lossy().into_owned()
+            }
+            _ => filename.clone(),
+        }
+    }
+    /// Writes the vertices (with normals and uvs, if available),
+    /// triangles and face indices of a **trianglemesh** into a binary
+    /// PLY file and returns its name (relative to the exported scene).
+    fn write_ply_mesh(&mut self, params: &ParamSet) -> io::Result<String> {
+        let indices: Vec<i32> = find_items(&params.ints, "indices");
+        let p: Vec<Point3f> = find_items(&params.point3fs, "P");
+        let n: Vec<Normal3f> = find_items(&params.normals, "N");
+        let mut uvs: Vec<Point2f> = find_items(&params.point2fs, "uv");
+        if uvs.is_empty() {
+            uvs = find_items(&params.point2fs, "st");
+        }
+        if uvs.is_empty() {
+            let mut fuv: Vec<Float> = find_items(&params.floats, "uv");
+            if fuv.is_empty() {
+                fuv = find_items(&params.floats, "st");
+            }
+            for i in 0..(fuv.len() / 2) {
+                uvs.push(Point2f {
+                             x: fuv[2 * i],
+                             y: fuv[2 * i + 1],
+                         });
+            }
+        }
+        let face_indices: Vec<i32> = find_items(&params.ints, "faceIndices");
+        let has_normals: bool = !n.is_empty() && n.len() == p.len();
+        let has_uvs: bool = !uvs.is_empty() && uvs.len() == p.len();
+        let has_face_indices: bool = !face_indices.is_empty() &&
+                                     face_indices.len() == indices.len() / 3;
+        self.n_meshes += 1;
+        let filename: String = format!("{}_mesh_{:05}.ply", self.stem, self.n_meshes);
+        let file: File = File::create(self.directory.join(&filename))?;
+        let mut ply: BufWriter<File> = BufWriter::new(file);
+        writeln!(ply, "ply")?;
+        writeln!(ply, "format binary_little_endian 1.0")?;
+        writeln!(ply, "element vertex {}", p.len())?;
+        writeln!(ply, "property float x\nproperty float y\nproperty float z")?;
+        if has_normals {
+            writeln!(ply, "property float nx\nproperty float ny\nproperty float nz")?;
+        }
+        if has_uvs {
+            writeln!(ply, "property float u\nproperty float v")?;
+        }
+        writeln!(ply, "element face {}", indices.len() / 3)?;
+        writeln!(ply, "property list uchar int vertex_indices")?;
+        if has_face_indices {
+            writeln!(ply, "property int face_indices")?;
+        }
+        writeln!(ply, "end_header")?;
+        for i in 0..p.len() {
+            write_f32(&mut ply, p[i].x)?;
+            write_f32(&mut ply, p[i].y)?;
+            write_f32(&mut ply, p[i].z)?;
+            if has_normals {
+                write_f32(&mut ply, n[i].x)?;
+                write_f32(&mut ply, n[i].y)?;
+                write_f32(&mut ply, n[i].z)?;
+            }
+            if has_uvs {
+                write_f32(&mut ply, uvs[i].x)?;
+                write_f32(&mut ply, uvs[i].y)?;
+            }
+        }
+        for face in 0..indices.len() / 3 {
+            ply.write_all(&[3_u8])?;
+            for i in 0..3 {
+                write_u32(&mut ply, indices[face * 3 + i] as u32)?;
+            }
+            if has_face_indices {
+                write_u32(&mut ply, face_indices[face] as u32)?;
+            }
+        }
+        ply.flush()?;
+        Ok(filename)
+    }
+}
+
+fn quoted(s: &String) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn find_items<T: Clone>(items: &Vec<ParamSetItem<T>>, name: &str) -> Vec<T> {
+    match items.iter().find(|item| item.name == name) {
+        Some(item) => item.values.clone(),
+        None => Vec::new(),
+    }
+}
+
+/// One line per parameter, e.g. **"float radius" [ 0.25 ]**.
+fn add_param_lines<T, F>(lines: &mut Vec<String>,
+                         type_name: &str,
+                         items: &Vec<ParamSetItem<T>>,
+                         skip: &[&str],
+                         format_value: F)
+    where F: Fn(&T) -> Vec<String>
+{
+    for item in items {
+        if skip.contains(&item.name.as_str()) {
+            continue;
+        }
+        let values: Vec<String> = item.values.iter().flat_map(|v| format_value(v)).collect();
+        lines.push(format!("\"{} {}\" [ {} ]", type_name, item.name, values.join(" ")));
+    }
+}
+
+fn write_u32<W: Write>(out: &mut W, value: u32) -> io::Result<()> {
+    out.write_all(&[value as u8, (value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8])
+}
+
+fn write_f32<W: Write>(out: &mut W, value: Float) -> io::Result<()> {
+    write_u32(out, (value as f32).to_bits())
+}
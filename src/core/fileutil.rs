@@ -0,0 +1,123 @@
+//! Helpers to read files referenced by a scene (see fileutil.h).
+
+// std
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+// others
+use flate2::bufread::MultiGzDecoder;
+// pbrt
+use core::pbrt::Float;
+
+/// Opens a file for reading, decompressing it on the fly if it's
+/// gzip-compressed (e.g. **bathroom.pbrt.gz**). Compressed files are
+/// recognized by their content, not by the file extension.
+pub fn open_file(path: &Path) -> io::Result<Box<BufRead>> {
+    let mut reader: BufReader<File> = BufReader::new(File::open(path)?);
+    let is_gzip: bool = {
+        let buf: &[u8] = reader.fill_buf()?;
+        buf.len() >= 2 && buf[0] == 0x1f && buf[1] == 0x8b
+    };
+    if is_gzip {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Reads a (possibly gzip-compressed) text file.
+pub fn read_to_string(path: &Path) -> io::Result<String> {
+    let mut reader: Box<BufRead> = open_file(path)?;
+    let mut str_buf: String = String::default();
+    reader.read_to_string(&mut str_buf)?;
+    Ok(str_buf)
+}
+
+/// Reads all floating point values of a text file (see floatfile.cpp),
+/// ignoring everything after a **#** up to the end of a line.
+pub fn read_float_file(path: &Path) -> io::Result<Vec<Float>> {
+    let text: String = read_to_string(path)?;
+    let mut values: Vec<Float> = Vec::new();
+    for line in text.lines() {
+        let data: &str = match line.find('#') {
+            Some(pos) => &line[..pos],
+            None => line,
+        };
+        for token in data.split_whitespace() {
+            match token.parse::<Float>() {
+                Ok(value) => values.push(value),
+                Err(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("unexpected text \"{}\" found", token),
+                    ))
+                }
+            }
+        }
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::f32;
+    use std::fs;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::process;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use super::*;
+    use core::geometry::{Point3f, Ray, Vector3f};
+    use core::parser::{parse_file, World};
+
+    fn write_gzip(path: &Path, content: &[u8]) {
+        let mut encoder: GzEncoder<File> =
+            GzEncoder::new(File::create(path).unwrap(), Compression::default());
+        encoder.write_all(content).unwrap();
+        encoder.finish().unwrap();
+    }
+
+    #[test]
+    fn gzip_compressed_scene_include_and_ply() {
+        let dir: PathBuf = env::temp_dir().join(format!("pbrt_fileutil_gzip_{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        write_gzip(
+            &dir.join("scene.pbrt.gz"),
+            b"WorldBegin\nInclude \"geometry.pbrt.gz\"\nWorldEnd\n",
+        );
+        write_gzip(
+            &dir.join("geometry.pbrt.gz"),
+            b"Shape \"plymesh\" \"string filename\" \"quad.ply.gz\"\n",
+        );
+        write_gzip(
+            &dir.join("quad.ply.gz"),
+            b"ply\nformat ascii 1.0\n\
+              element vertex 4\nproperty float x\nproperty float y\nproperty float z\n\
+              element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+              -1 -1 0\n1 -1 0\n1 1 0\n-1 1 0\n4 0 1 2 3\n",
+        );
+        let text: String = read_to_string(&dir.join("geometry.pbrt.gz")).unwrap();
+        assert!(text.starts_with("Shape \"plymesh\""));
+        let world: World = parse_file(&dir.join("scene.pbrt.gz")).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        let mut ray: Ray = Ray {
+            o: Point3f { x: 0.5, y: -0.5, z: 5.0 },
+            d: Vector3f { x: 0.0, y: 0.0, z: -1.0 },
+            t_max: f32::INFINITY,
+            time: 0.0,
+            differential: None,
+            medium: None,
+        };
+        // both triangles of the quad
+        assert!(world.scene.intersect_p(&mut ray));
+        ray.o.x = -0.5;
+        ray.o.y = 0.5;
+        assert!(world.scene.intersect_p(&mut ray));
+        ray.o.x = 1.5;
+        assert!(!world.scene.intersect_p(&mut ray));
+    }
+}
@@ -247,6 +247,23 @@ impl Film {
             },
         }
     }
+    /// The extent of the film in scene space (meters), centered at
+    /// the origin and scaled to match the **diagonal**.
+    pub fn get_physical_extent(&self) -> Bounds2f {
+        let aspect: Float = self.full_resolution.y as Float / self.full_resolution.x as Float;
+        let x: Float = (self.diagonal * self.diagonal / (1.0 as Float + aspect * aspect)).sqrt();
+        let y: Float = aspect * x;
+        Bounds2f {
+            p_min: Point2f {
+                x: -x / 2.0 as Float,
+                y: -y / 2.0 as Float,
+            },
+            p_max: Point2f {
+                x: x / 2.0 as Float,
+                y: y / 2.0 as Float,
+            },
+        }
+    }
     pub fn get_film_tile(&self, sample_bounds: &Bounds2i) -> FilmTile {
         // bound image pixels that samples in _sample_bounds_ contribute to
         let half_pixel: Vector2f = Vector2f { x: 0.5, y: 0.5 };
@@ -360,6 +377,22 @@ impl Film {
         splat_xyz[1].add(xyz[1]);
         splat_xyz[2].add(xyz[2]);
     }
+    /// Replaces the image with the given pixel values (one per pixel
+    /// of the cropped image, in scanline order).
+    pub fn set_image(&self, img: &[Spectrum]) {
+        let n_pixels: usize = self.cropped_pixel_bounds.area() as usize;
+        let mut pixels_write: RwLockWriteGuard<Vec<Pixel>> = self.pixels.write().unwrap();
+        for i in 0..n_pixels {
+            let p: &mut Pixel = &mut pixels_write[i];
+            img[i].to_xyz(&mut p.xyz);
+            p.filter_weight_sum = 1.0 as Float;
+            p.splat_xyz = [
+                AtomicFloat::default(),
+                AtomicFloat::default(),
+                AtomicFloat::default(),
+            ];
+        }
+    }
     #[cfg(not(feature = "openexr"))]
     pub fn write_image(&self, splat_scale: Float) {
         println!("Converting image to RGB and computing final weighted pixel values");
@@ -116,6 +116,7 @@
 //!         t_max: std::f32::INFINITY,
 //!         time: 0.0,
 //!         differential: None,
+//!         medium: None,
 //!     };
 //!
 //!     println!("{:?}", ray);
@@ -183,10 +184,13 @@
 // std
 use std;
 use std::f32::consts::PI;
+use std::fmt;
 use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub};
+use std::sync::Arc;
 // others
 use num;
 // pbrt
+use core::medium::Medium;
 use core::pbrt::Float;
 use core::pbrt::{clamp_t, gamma, lerp, next_float_down, next_float_up};
 
@@ -1323,6 +1327,15 @@ impl<T> Bounds2<T> {
     }
 }
 
+impl Bounds2<Float> {
+    pub fn lerp(&self, t: &Point2f) -> Point2f {
+        Point2f {
+            x: lerp(t.x, self.p_min.x as Float, self.p_max.x as Float),
+            y: lerp(t.y, self.p_min.y as Float, self.p_max.y as Float),
+        }
+    }
+}
+
 pub struct Bounds2Iterator<'a> {
     p: Point2i,
     bounds: &'a Bounds2i,
@@ -1381,6 +1394,38 @@ where
     }
 }
 
+/// Given a bounding box and a point, the **bnd2_union_pnt2()**
+/// function returns a new bounding box that encompasses that point
+/// as well as the original box.
+pub fn bnd2_union_pnt2<T>(b: &Bounds2<T>, p: &Point2<T>) -> Bounds2<T>
+where
+    T: num::Float,
+{
+    Bounds2::<T> {
+        p_min: Point2::<T> {
+            x: b.p_min.x.min(p.x),
+            y: b.p_min.y.min(p.y),
+        },
+        p_max: Point2::<T> {
+            x: b.p_max.x.max(p.x),
+            y: b.p_max.y.max(p.y),
+        },
+    }
+}
+
+/// Is a 2D point inside a 2D bound (including the boundary)?
+pub fn pnt2_inside_bnd2(p: &Point2f, b: &Bounds2f) -> bool {
+    p.x >= b.p_min.x && p.x <= b.p_max.x && p.y >= b.p_min.y && p.y <= b.p_max.y
+}
+
+/// Pads the bounding box by a constant factor in both dimensions.
+pub fn bnd2_expand(b: &Bounds2f, delta: Float) -> Bounds2f {
+    Bounds2f {
+        p_min: b.p_min - Vector2f { x: delta, y: delta },
+        p_max: b.p_max + Vector2f { x: delta, y: delta },
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct Bounds3<T> {
     pub p_min: Point3<T>,
@@ -1556,6 +1601,36 @@ impl Bounds3<Float> {
         }
         (t_min < ray.t_max) && (t_max > 0.0)
     }
+    /// Returns the parametric range **[hitt0, hitt1]** of the ray
+    /// within the box (clipped to **[0, ray.t_max]**).
+    pub fn intersect_b(&self, ray: &Ray, hitt0: &mut Float, hitt1: &mut Float) -> bool {
+        let mut t0: Float = 0.0;
+        let mut t1: Float = ray.t_max;
+        for i in 0..3 {
+            // update interval for _i_th bounding box slab
+            let inv_ray_dir: Float = 1.0 as Float / ray.d[i];
+            let mut t_near: Float = (self.p_min[i] - ray.o[i]) * inv_ray_dir;
+            let mut t_far: Float = (self.p_max[i] - ray.o[i]) * inv_ray_dir;
+            // update parametric interval from slab intersection $t$ values
+            if t_near > t_far {
+                std::mem::swap(&mut t_near, &mut t_far);
+            }
+            // update _t_far_ to ensure robust ray--bounds intersection
+            t_far *= 1.0 + 2.0 * gamma(3_i32);
+            if t_near > t0 {
+                t0 = t_near;
+            }
+            if t_far < t1 {
+                t1 = t_far;
+            }
+            if t0 > t1 {
+                return false;
+            }
+        }
+        *hitt0 = t0;
+        *hitt1 = t1;
+        true
+    }
 }
 
 impl<T> Index<u8> for Bounds3<T> {
@@ -1646,7 +1721,7 @@ pub fn bnd3_expand(b: &Bounds3f, delta: Float) -> Bounds3f {
     )
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Default, Clone)]
 pub struct Ray {
     /// origin
     pub o: Point3f,
@@ -1658,6 +1733,21 @@ pub struct Ray {
     pub time: Float,
     /// in C++: 'class RayDifferential : public Ray'
     pub differential: Option<RayDifferential>,
+    /// the medium containing the ray's origin
+    pub medium: Option<Arc<Medium + Send + Sync>>,
+}
+
+impl fmt::Debug for Ray {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Ray")
+            .field("o", &self.o)
+            .field("d", &self.d)
+            .field("t_max", &self.t_max)
+            .field("time", &self.time)
+            .field("differential", &self.differential)
+            .field("medium", &self.medium.is_some())
+            .finish()
+    }
 }
 
 impl Ray {
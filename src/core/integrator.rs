@@ -1,22 +1,47 @@
 // std
 use std;
 use std::sync::Arc;
+use std::sync::mpsc;
+// others
+use crossbeam;
+use num_cpus;
+use pbr;
 // pbrt
-use core::geometry::{Bounds2i, Point2f, Ray, Vector3f};
-use core::geometry::vec3_abs_dot_nrm;
+use core::camera::{Camera, CameraSample};
+use core::geometry::{Bounds2i, Normal3f, Point2f, Point2i, Ray, RayDifferential, Vector2i,
+                     Vector3f};
+use core::geometry::{pnt2_inside_exclusive, vec3_abs_dot_nrm, vec3_dot_nrm};
 use core::interaction::{Interaction, InteractionCommon, SurfaceInteraction};
 use core::light::{Light, VisibilityTester};
 use core::light::is_delta_light;
-use core::pbrt::{Float, Spectrum};
+use core::pbrt::{Float, Options, Spectrum};
 use core::primitive::Primitive;
 use core::reflection::BxdfType;
 use core::sampler::Sampler;
 use core::sampling::Distribution1D;
 use core::sampling::power_heuristic;
 use core::scene::Scene;
+use BlockQueue;
 
 // see integrator.h
 
+/// All integrators render an image of a scene for a given camera. The
+/// _SamplerIntegrator_ implementations get their render loop via a
+/// blanket implementation, the others (e.g. BDPT, MLT, or SPPM)
+/// provide their own.
+pub trait Integrator {
+    /// **Main function** to **render** a scene multi-threaded (using
+    /// all available cores, unless **options** limit the number of
+    /// threads).
+    fn render(
+        &mut self,
+        scene: &Scene,
+        camera: &Box<Camera + Send + Sync>,
+        sampler: &mut Box<Sampler + Send + Sync>,
+        options: &Options,
+    );
+}
+
 pub trait SamplerIntegrator {
     // TODO: use Sampler trait
     fn preprocess(&mut self, scene: &Scene, sampler: &mut Box<Sampler + Send + Sync>);
@@ -32,13 +57,308 @@ pub trait SamplerIntegrator {
         depth: i32,
     ) -> Spectrum;
     fn get_pixel_bounds(&self) -> Bounds2i;
+    /// Traces a ray for perfectly specular reflection at a surface
+    /// point and returns the (weighted) incident radiance.
+    fn specular_reflect(
+        &self,
+        ray: &Ray,
+        isect: &SurfaceInteraction,
+        scene: &Scene,
+        sampler: &mut Box<Sampler + Send + Sync>,
+        // arena: &mut Arena,
+        depth: i32,
+    ) -> Spectrum {
+        // compute specular reflection direction _wi_ and BSDF value
+        let wo: Vector3f = isect.wo;
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0 as Float;
+        let ns: Normal3f = isect.shading.n;
+        let mut sampled_type: u8 = 0_u8;
+        let bsdf_flags: u8 = BxdfType::BsdfReflection as u8 | BxdfType::BsdfSpecular as u8;
+        let f: Spectrum;
+        if let Some(ref bsdf) = isect.bsdf {
+            f = bsdf.sample_f(
+                &wo,
+                &mut wi,
+                &sampler.get_2d(),
+                &mut pdf,
+                bsdf_flags,
+                &mut sampled_type,
+            );
+            if pdf > 0.0 as Float && !f.is_black() && vec3_abs_dot_nrm(&wi, &ns) != 0.0 as Float {
+                // compute ray differential _rd_ for specular reflection
+                let mut rd: Ray = isect.spawn_ray(&wi);
+                if let Some(d) = ray.differential.iter().next() {
+                    let dndx: Normal3f =
+                        isect.shading.dndu * isect.dudx + isect.shading.dndv * isect.dvdx;
+                    let dndy: Normal3f =
+                        isect.shading.dndu * isect.dudy + isect.shading.dndv * isect.dvdy;
+                    let dwodx: Vector3f = -d.rx_direction - wo;
+                    let dwody: Vector3f = -d.ry_direction - wo;
+                    let ddndx: Float = vec3_dot_nrm(&dwodx, &ns) + vec3_dot_nrm(&wo, &dndx);
+                    let ddndy: Float = vec3_dot_nrm(&dwody, &ns) + vec3_dot_nrm(&wo, &dndy);
+                    // compute differential reflected directions
+                    let diff: RayDifferential = RayDifferential {
+                        rx_origin: isect.p + isect.dpdx,
+                        ry_origin: isect.p + isect.dpdy,
+                        rx_direction: wi - dwodx
+                            + Vector3f::from(dndx * vec3_dot_nrm(&wo, &ns) + ns * ddndx)
+                                * 2.0 as Float,
+                        ry_direction: wi - dwody
+                            + Vector3f::from(dndy * vec3_dot_nrm(&wo, &ns) + ns * ddndy)
+                                * 2.0 as Float,
+                    };
+                    rd.differential = Some(diff);
+                }
+                return f
+                    * self.li(&mut rd, scene, sampler, depth + 1)
+                    * Spectrum::new(vec3_abs_dot_nrm(&wi, &ns) / pdf);
+            } else {
+                Spectrum::new(0.0)
+            }
+        } else {
+            Spectrum::new(0.0)
+        }
+    }
+    /// Like **specular_reflect()**, but for perfectly specular
+    /// transmission.
+    fn specular_transmit(
+        &self,
+        ray: &Ray,
+        isect: &SurfaceInteraction,
+        scene: &Scene,
+        sampler: &mut Box<Sampler + Send + Sync>,
+        // arena: &mut Arena,
+        depth: i32,
+    ) -> Spectrum {
+        let wo: Vector3f = isect.wo;
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0 as Float;
+        // let p: Point3f = isect.p;
+        let ns: Normal3f = isect.shading.n;
+        let mut sampled_type: u8 = 0_u8;
+        let bsdf_flags: u8 = BxdfType::BsdfTransmission as u8 | BxdfType::BsdfSpecular as u8;
+        let f: Spectrum;
+        if let Some(ref bsdf) = isect.bsdf {
+            f = bsdf.sample_f(
+                &wo,
+                &mut wi,
+                &sampler.get_2d(),
+                &mut pdf,
+                bsdf_flags,
+                &mut sampled_type,
+            );
+            if pdf > 0.0 as Float && !f.is_black() && vec3_abs_dot_nrm(&wi, &ns) != 0.0 as Float {
+                // compute ray differential _rd_ for specular transmission
+                let mut rd: Ray = isect.spawn_ray(&wi);
+                if let Some(d) = ray.differential.iter().next() {
+                    let mut eta: Float = bsdf.eta;
+                    let w: Vector3f = -wo;
+                    if vec3_dot_nrm(&wo, &ns) < 0.0 as Float {
+                        eta = 1.0 / eta;
+                    }
+                    let dndx: Normal3f =
+                        isect.shading.dndu * isect.dudx + isect.shading.dndv * isect.dvdx;
+                    let dndy: Normal3f =
+                        isect.shading.dndu * isect.dudy + isect.shading.dndv * isect.dvdy;
+                    let dwodx: Vector3f = -d.rx_direction - wo;
+                    let dwody: Vector3f = -d.ry_direction - wo;
+                    let ddndx: Float = vec3_dot_nrm(&dwodx, &ns) + vec3_dot_nrm(&wo, &dndx);
+                    let ddndy: Float = vec3_dot_nrm(&dwody, &ns) + vec3_dot_nrm(&wo, &dndy);
+                    let mu: Float = eta * vec3_dot_nrm(&w, &ns) - vec3_dot_nrm(&wi, &ns);
+                    let dmudx: Float = (eta
+                        - (eta * eta * vec3_dot_nrm(&w, &ns)) / vec3_dot_nrm(&wi, &ns))
+                        * ddndx;
+                    let dmudy: Float = (eta
+                        - (eta * eta * vec3_dot_nrm(&w, &ns)) / vec3_dot_nrm(&wi, &ns))
+                        * ddndy;
+                    let diff: RayDifferential = RayDifferential {
+                        rx_origin: isect.p + isect.dpdx,
+                        ry_origin: isect.p + isect.dpdy,
+                        rx_direction: wi + dwodx * eta - Vector3f::from(dndx * mu + ns * dmudx),
+                        ry_direction: wi + dwody * eta - Vector3f::from(dndy * mu + ns * dmudy),
+                    };
+                    rd.differential = Some(diff);
+                }
+                return f
+                    * self.li(&mut rd, scene, sampler, depth + 1)
+                    * Spectrum::new(vec3_abs_dot_nrm(&wi, &ns) / pdf);
+            } else {
+                Spectrum::new(0.0)
+            }
+        } else {
+            Spectrum::new(0.0)
+        }
+    }
+}
+
+// SamplerIntegrator::Render (integrator.cpp)
+impl<T> Integrator for T
+where
+    T: SamplerIntegrator + Send + Sync,
+{
+    fn render(
+        &mut self,
+        scene: &Scene,
+        camera: &Box<Camera + Send + Sync>,
+        sampler: &mut Box<Sampler + Send + Sync>,
+        options: &Options,
+    ) {
+        let film = camera.get_film();
+        let sample_bounds: Bounds2i = film.get_sample_bounds();
+        println!("sample_bounds = {:?}", sample_bounds);
+        self.preprocess(scene, sampler);
+        // use camera below
+        let sample_extent: Vector2i = sample_bounds.diagonal();
+        println!("sample_extent = {:?}", sample_extent);
+        let tile_size: i32 = 16;
+        let x: i32 = (sample_extent.x + tile_size - 1) / tile_size;
+        let y: i32 = (sample_extent.y + tile_size - 1) / tile_size;
+        let n_tiles: Point2i = Point2i { x: x, y: y };
+        println!("n_tiles = {:?}", n_tiles);
+        // TODO: ProgressReporter reporter(nTiles.x * nTiles.y, "Rendering");
+        let num_cores: usize;
+        if options.num_threads == 0_u8 {
+            num_cores = num_cpus::get();
+        } else {
+            num_cores = options.num_threads as usize;
+        }
+        println!("Rendering with {:?} thread(s) ...", num_cores);
+        {
+            let block_queue = BlockQueue::new(
+                (
+                    (n_tiles.x * tile_size) as u32,
+                    (n_tiles.y * tile_size) as u32,
+                ),
+                (tile_size as u32, tile_size as u32),
+                (0, 0),
+            );
+            println!("block_queue.len() = {}", block_queue.len());
+            let integrator = &*self;
+            let bq = &block_queue;
+            let sampler = sampler;
+            let camera = &camera;
+            let film = &film;
+            let pixel_bounds = integrator.get_pixel_bounds().clone();
+            crossbeam::scope(|scope| {
+                let (pixel_tx, pixel_rx) = mpsc::channel();
+                // spawn worker threads
+                for _ in 0..num_cores {
+                    let pixel_tx = pixel_tx.clone();
+                    let mut tile_sampler: Box<Sampler + Send + Sync> = sampler.box_clone();
+                    scope.spawn(move || {
+                        while let Some((x, y)) = bq.next() {
+                            let tile: Point2i = Point2i {
+                                x: x as i32,
+                                y: y as i32,
+                            };
+                            let seed: i32 = tile.y * n_tiles.x + tile.x;
+                            tile_sampler.reseed(seed as u64);
+                            let x0: i32 = sample_bounds.p_min.x + tile.x * tile_size;
+                            let x1: i32 = std::cmp::min(x0 + tile_size, sample_bounds.p_max.x);
+                            let y0: i32 = sample_bounds.p_min.y + tile.y * tile_size;
+                            let y1: i32 = std::cmp::min(y0 + tile_size, sample_bounds.p_max.y);
+                            let tile_bounds: Bounds2i =
+                                Bounds2i::new(Point2i { x: x0, y: y0 }, Point2i { x: x1, y: y1 });
+                            // println!("Starting image tile {:?}", tile_bounds);
+                            let mut film_tile = film.get_film_tile(&tile_bounds);
+                            for pixel in &tile_bounds {
+                                tile_sampler.start_pixel(&pixel);
+                                if !pnt2_inside_exclusive(&pixel, &pixel_bounds) {
+                                    continue;
+                                }
+                                let mut done: bool = false;
+                                while !done {
+                                    // let's use the copy_arena crate instead of pbrt's MemoryArena
+                                    // let mut arena: Arena = Arena::with_capacity(262144); // 256kB
+
+                                    // initialize _CameraSample_ for current sample
+                                    let camera_sample: CameraSample =
+                                        tile_sampler.get_camera_sample(&pixel);
+                                    // generate camera ray for current sample
+                                    let mut ray: Ray = Ray::default();
+                                    let ray_weight: Float =
+                                        camera.generate_ray_differential(&camera_sample, &mut ray);
+                                    ray.scale_differentials(
+                                        1.0 as Float
+                                            / (tile_sampler.get_samples_per_pixel() as Float)
+                                                .sqrt(),
+                                    );
+                                    // TODO: ++nCameraRays;
+                                    // evaluate radiance along camera ray
+                                    let mut l: Spectrum = Spectrum::new(0.0 as Float);
+                                    let y: Float = l.y();
+                                    if ray_weight > 0.0 {
+                                        l = integrator.li(
+                                            &mut ray,
+                                            scene,
+                                            &mut tile_sampler, // &mut arena,
+                                            0_i32,
+                                        );
+                                    }
+                                    if l.has_nans() {
+                                        println!(
+                                            "Not-a-number radiance value returned for pixel \
+                                             ({:?}, {:?}), sample {:?}. Setting to black.",
+                                            pixel.x,
+                                            pixel.y,
+                                            tile_sampler.get_current_sample_number()
+                                        );
+                                        l = Spectrum::new(0.0);
+                                    } else if y < -10.0e-5 as Float {
+                                        println!(
+                                            "Negative luminance value, {:?}, returned for pixel \
+                                             ({:?}, {:?}), sample {:?}. Setting to black.",
+                                            y,
+                                            pixel.x,
+                                            pixel.y,
+                                            tile_sampler.get_current_sample_number()
+                                        );
+                                        l = Spectrum::new(0.0);
+                                    } else if y.is_infinite() {
+                                        println!(
+                                            "Infinite luminance value returned for pixel ({:?}, \
+                                             {:?}), sample {:?}. Setting to black.",
+                                            pixel.x,
+                                            pixel.y,
+                                            tile_sampler.get_current_sample_number()
+                                        );
+                                        l = Spectrum::new(0.0);
+                                    }
+                                    // println!("Camera sample: {:?} -> ray: {:?} -> L = {:?}",
+                                    //          camera_sample, ray, l);
+                                    // add camera ray's contribution to image
+                                    film_tile.add_sample(&camera_sample.p_film, &mut l, ray_weight);
+                                    done = !tile_sampler.start_next_sample();
+                                } // arena is dropped here !
+                            }
+                            // send the tile through the channel to main thread
+                            pixel_tx
+                                .send(film_tile)
+                                .expect(&format!("Failed to send tile"));
+                        }
+                    });
+                }
+                // spawn thread to collect pixels and render image to file
+                scope.spawn(move || {
+                    for _ in pbr::PbIter::new(0..bq.len()) {
+                        let film_tile = pixel_rx.recv().unwrap();
+                        // merge image tile into _Film_
+                        film.merge_film_tile(&film_tile);
+                    }
+                });
+            });
+        }
+        println!("Rendering finished");
+        film.write_image(1.0 as Float);
+    }
 }
 
 // see integrator.cpp
 
 /// Most basic direct lighting strategy.
 pub fn uniform_sample_all_lights(
-    it: &SurfaceInteraction,
+    it: &Interaction,
     scene: &Scene,
     sampler: &mut Box<Sampler + Send + Sync>,
     n_light_samples: &Vec<i32>,
@@ -90,7 +410,7 @@ pub fn uniform_sample_all_lights(
 /// Estimate direct lighting for only one randomly chosen light and
 /// multiply the result by the number of lights to compensate.
 pub fn uniform_sample_one_light(
-    it: &SurfaceInteraction,
+    it: &Interaction,
     scene: &Scene,
     sampler: &mut Box<Sampler + Send + Sync>,
     handle_media: bool,
@@ -137,12 +457,12 @@ pub fn uniform_sample_one_light(
 
 /// Computes a direct lighting estimate for a single light source sample.
 pub fn estimate_direct(
-    it: &SurfaceInteraction,
+    it: &Interaction,
     u_scattering: &Point2f,
     light: Arc<Light + Send + Sync>,
     u_light: &Point2f,
     scene: &Scene,
-    _sampler: &mut Box<Sampler + Send + Sync>,
+    sampler: &mut Box<Sampler + Send + Sync>,
     // TODO: arena
     handle_media: bool,
     specular: bool,
@@ -158,13 +478,7 @@ pub fn estimate_direct(
     let mut light_pdf: Float = 0.0 as Float;
     let mut scattering_pdf: Float = 0.0 as Float;
     let mut visibility: VisibilityTester = VisibilityTester::default();
-    let it_common: InteractionCommon = InteractionCommon {
-        p: it.get_p(),
-        time: it.get_time(),
-        p_error: it.get_p_error(),
-        wo: it.get_wo(),
-        n: it.get_n(),
-    };
+    let it_common: InteractionCommon = it.get_common();
     let mut li: Spectrum = light.sample_li(
         &it_common,
         u_light,
@@ -179,21 +493,25 @@ pub fn estimate_direct(
         let mut f: Spectrum = Spectrum::new(0.0);
         if it.is_surface_interaction() {
             // evaluate BSDF for light sampling strategy
-            if let Some(ref bsdf) = it.bsdf {
+            if let (Some(bsdf), Some(shading_n)) = (it.get_bsdf(), it.get_shading_n()) {
                 f = bsdf.f(&it.get_wo(), &wi, bsdf_flags)
-                    * Spectrum::new(vec3_abs_dot_nrm(&wi, &it.shading.n));
+                    * Spectrum::new(vec3_abs_dot_nrm(&wi, &shading_n));
                 scattering_pdf = bsdf.pdf(&it.get_wo(), &wi, bsdf_flags);
                 // TODO: println!("  surf f*dot :{:?}, scatteringPdf: {:?}", f, scattering_pdf);
             }
         } else {
             // evaluate phase function for light sampling strategy
-            // TODO
-            println!("TODO: evaluate phase function for light sampling strategy");
+            if let Some(phase) = it.get_phase() {
+                let p: Float = phase.p(&it.get_wo(), &wi);
+                f = Spectrum::new(p);
+                scattering_pdf = p;
+                // TODO: VLOG(2) << "  medium p: " << p;
+            }
         }
         if !f.is_black() {
             // compute effect of visibility for light source sample
             if handle_media {
-                // TODO: li *= tr(scene, sampler);
+                li *= visibility.tr(scene, sampler);
                 // TODO: VLOG(2) << "  after Tr, Li: " << Li;
             } else {
                 if !visibility.unoccluded(scene) {
@@ -221,7 +539,7 @@ pub fn estimate_direct(
         if it.is_surface_interaction() {
             // sample scattered direction for surface interactions
             let mut sampled_type: u8 = 0_u8;
-            if let Some(ref bsdf) = it.bsdf {
+            if let (Some(bsdf), Some(shading_n)) = (it.get_bsdf(), it.get_shading_n()) {
                 f = bsdf.sample_f(
                     &it.get_wo(),
                     &mut wi,
@@ -230,14 +548,16 @@ pub fn estimate_direct(
                     bsdf_flags,
                     &mut sampled_type,
                 );
-                f *= Spectrum::new(vec3_abs_dot_nrm(&wi, &it.shading.n));
+                f *= Spectrum::new(vec3_abs_dot_nrm(&wi, &shading_n));
                 sampled_specular = (sampled_type & BxdfType::BsdfSpecular as u8) != 0_u8;
-            } else {
-                println!("TODO: if let Some(ref bsdf) = it.bsdf failed");
             }
         } else {
-            // TODO
-            println!("TODO: estimate_direct 1");
+            // sample scattered direction for medium interactions
+            if let Some(phase) = it.get_phase() {
+                let p: Float = phase.sample_p(&it.get_wo(), &mut wi, u_scattering);
+                f = Spectrum::new(p);
+                scattering_pdf = p;
+            }
         }
         // TODO: println!("  BSDF / phase sampling f: {:?}, scatteringPdf: {:?}",
         //          f, scattering_pdf);
@@ -253,22 +573,25 @@ pub fn estimate_direct(
             }
             // find intersection and compute transmittance
             let mut ray: Ray = it.spawn_ray(&wi);
-            let tr: Spectrum = Spectrum::new(1.0 as Float);
+            let mut tr: Spectrum = Spectrum::new(1.0 as Float);
+            let light_isect_opt: Option<SurfaceInteraction> = if handle_media {
+                let (light_isect_opt, ray_tr) = scene.intersect_tr(&mut ray, sampler);
+                tr = ray_tr;
+                light_isect_opt
+            } else {
+                scene.intersect(&mut ray)
+            };
             let mut found_surface_interaction: bool = false;
             // add light contribution from material sampling
             let mut li: Spectrum = Spectrum::default();
-            if handle_media {
-                // TODO: scene.IntersectTr(ray, sampler, &lightIsect, &Tr)
-            } else {
-                if let Some(light_isect) = scene.intersect(&mut ray) {
-                    found_surface_interaction = true;
-                    if let Some(primitive) = light_isect.primitive {
-                        if let Some(area_light) = primitive.get_area_light() {
-                            let pa = &*area_light as *const _ as *const usize;
-                            let pl = &*light as *const _ as *const usize;
-                            if pa == pl {
-                                li = light_isect.le(&-wi);
-                            }
+            if let Some(light_isect) = light_isect_opt {
+                found_surface_interaction = true;
+                if let Some(primitive) = light_isect.primitive {
+                    if let Some(area_light) = primitive.get_area_light() {
+                        let pa = &*area_light as *const _ as *const usize;
+                        let pl = &*light as *const _ as *const usize;
+                        if pa == pl {
+                            li = light_isect.le(&-wi);
                         }
                     }
                 }
@@ -17,6 +17,7 @@ use core::geometry::{nrm_faceforward_nrm, nrm_normalize, pnt3_offset_ray_origin,
 use core::pbrt::SHADOW_EPSILON;
 use core::pbrt::{Float, Spectrum};
 use core::material::TransportMode;
+use core::medium::{Medium, MediumInterface, PhaseFunction};
 use core::primitive::{GeometricPrimitive, Primitive};
 use core::reflection::Bsdf;
 use core::shape::Shape;
@@ -33,9 +34,32 @@ pub trait Interaction {
     fn get_p_error(&self) -> Vector3f;
     fn get_wo(&self) -> Vector3f;
     fn get_n(&self) -> Normal3f;
+    /// The medium on the side of the surface **w** points to.
+    fn get_medium(&self, w: &Vector3f) -> Option<Arc<Medium + Send + Sync>>;
+    fn get_common(&self) -> InteractionCommon;
+    /// The BSDF at a surface point (if any).
+    fn get_bsdf(&self) -> Option<Arc<Bsdf>>;
+    /// The shading normal at a surface point (if any).
+    fn get_shading_n(&self) -> Option<Normal3f>;
+    /// The phase function at a point in a medium (if any).
+    fn get_phase(&self) -> Option<Arc<PhaseFunction + Send + Sync>>;
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+/// Returns the outside medium if **w** points to the side of the
+/// normal, the inside medium otherwise.
+pub fn medium_towards(
+    medium_interface: &MediumInterface,
+    n: &Normal3f,
+    w: &Vector3f,
+) -> Option<Arc<Medium + Send + Sync>> {
+    if vec3_dot_vec3(w, &Vector3f::from(*n)) > 0.0 as Float {
+        medium_interface.outside.clone()
+    } else {
+        medium_interface.inside.clone()
+    }
+}
+
+#[derive(Debug, Default, Clone)]
 pub struct InteractionCommon {
     // Interaction Public Data
     pub p: Point3f,
@@ -43,9 +67,13 @@ pub struct InteractionCommon {
     pub p_error: Vector3f,
     pub wo: Vector3f,
     pub n: Normal3f,
+    pub medium_interface: MediumInterface,
 }
 
 impl InteractionCommon {
+    pub fn get_medium(&self, w: &Vector3f) -> Option<Arc<Medium + Send + Sync>> {
+        medium_towards(&self.medium_interface, &self.n, w)
+    }
     pub fn spawn_ray(&self, d: &Vector3f) -> Ray {
         let o: Point3f = pnt3_offset_ray_origin(&self.p, &self.p_error, &self.n, d);
         Ray {
@@ -53,7 +81,8 @@ impl InteractionCommon {
             d: *d,
             t_max: std::f32::INFINITY,
             time: self.time,
-            differential: None, // TODO: GetMedium(d)
+            differential: None,
+            medium: self.get_medium(d),
         }
     }
     pub fn spawn_ray_to(&self, it: &InteractionCommon) -> Ray {
@@ -67,10 +96,105 @@ impl InteractionCommon {
             t_max: 1.0 - SHADOW_EPSILON,
             time: self.time,
             differential: None,
+            medium: self.get_medium(&d),
         }
     }
 }
 
+/// Represents an interaction point in a scattering medium.
+#[derive(Clone)]
+pub struct MediumInteraction {
+    // Interaction Public Data
+    pub p: Point3f,
+    pub time: Float,
+    pub p_error: Vector3f,
+    pub wo: Vector3f,
+    pub n: Normal3f,
+    pub medium_interface: MediumInterface,
+    // MediumInteraction Public Data
+    pub phase: Option<Arc<PhaseFunction + Send + Sync>>,
+}
+
+impl MediumInteraction {
+    pub fn new(
+        p: &Point3f,
+        wo: &Vector3f,
+        time: Float,
+        medium: Option<Arc<Medium + Send + Sync>>,
+        phase: Option<Arc<PhaseFunction + Send + Sync>>,
+    ) -> Self {
+        MediumInteraction {
+            p: *p,
+            time: time,
+            p_error: Vector3f::default(),
+            wo: *wo,
+            n: Normal3f::default(),
+            medium_interface: MediumInterface::from_medium(medium),
+            phase: phase,
+        }
+    }
+    pub fn is_valid(&self) -> bool {
+        self.phase.is_some()
+    }
+}
+
+impl Interaction for MediumInteraction {
+    fn is_surface_interaction(&self) -> bool {
+        false
+    }
+    fn is_medium_interaction(&self) -> bool {
+        true
+    }
+    fn spawn_ray(&self, d: &Vector3f) -> Ray {
+        Ray {
+            o: self.p,
+            d: *d,
+            t_max: std::f32::INFINITY,
+            time: self.time,
+            differential: None,
+            medium: self.get_medium(d),
+        }
+    }
+    fn get_p(&self) -> Point3f {
+        self.p.clone()
+    }
+    fn get_time(&self) -> Float {
+        self.time
+    }
+    fn get_p_error(&self) -> Vector3f {
+        self.p_error.clone()
+    }
+    fn get_wo(&self) -> Vector3f {
+        self.wo.clone()
+    }
+    fn get_n(&self) -> Normal3f {
+        self.n.clone()
+    }
+    fn get_medium(&self, _w: &Vector3f) -> Option<Arc<Medium + Send + Sync>> {
+        // same medium in all directions
+        self.medium_interface.inside.clone()
+    }
+    fn get_common(&self) -> InteractionCommon {
+        InteractionCommon {
+            p: self.p,
+            time: self.time,
+            p_error: self.p_error,
+            wo: self.wo,
+            n: self.n,
+            medium_interface: self.medium_interface.clone(),
+        }
+    }
+    fn get_bsdf(&self) -> Option<Arc<Bsdf>> {
+        None
+    }
+    fn get_shading_n(&self) -> Option<Normal3f> {
+        None
+    }
+    fn get_phase(&self) -> Option<Arc<PhaseFunction + Send + Sync>> {
+        self.phase.clone()
+    }
+}
+
 #[derive(Debug, Default, Copy, Clone)]
 pub struct Shading {
     pub n: Normal3f,
@@ -88,7 +212,7 @@ pub struct SurfaceInteraction<'p, 's> {
     pub p_error: Vector3f,
     pub wo: Vector3f,
     pub n: Normal3f,
-    // TODO: MediumInterface mediumInterface;
+    pub medium_interface: MediumInterface,
     // SurfaceInteraction Public Data
     pub uv: Point2f,
     pub dpdu: Vector3f,
@@ -141,6 +265,7 @@ impl<'p, 's> SurfaceInteraction<'p, 's> {
             p_error: *p_error,
             wo: vec3_normalize(wo),
             n: n,
+            medium_interface: MediumInterface::default(),
             uv: *uv,
             dpdu: *dpdu,
             dpdv: *dpdv,
@@ -285,13 +410,7 @@ impl<'p, 's> SurfaceInteraction<'p, 's> {
         if let Some(primitive) = self.primitive {
             if let Some(area_light) = primitive.get_area_light() {
                 // create InteractionCommon from self
-                let interaction: InteractionCommon = InteractionCommon {
-                    p: self.p,
-                    time: self.time,
-                    p_error: self.p_error,
-                    wo: self.wo,
-                    n: self.n,
-                };
+                let interaction: InteractionCommon = self.get_common();
                 return area_light.l(&interaction, w);
             }
         }
@@ -314,6 +433,7 @@ impl<'p, 's> Interaction for SurfaceInteraction<'p, 's> {
             t_max: std::f32::INFINITY,
             time: self.time,
             differential: None,
+            medium: self.get_medium(d),
         }
     }
     fn get_p(&self) -> Point3f {
@@ -331,4 +451,26 @@ impl<'p, 's> Interaction for SurfaceInteraction<'p, 's> {
     fn get_n(&self) -> Normal3f {
         self.n.clone()
     }
+    fn get_medium(&self, w: &Vector3f) -> Option<Arc<Medium + Send + Sync>> {
+        medium_towards(&self.medium_interface, &self.n, w)
+    }
+    fn get_common(&self) -> InteractionCommon {
+        InteractionCommon {
+            p: self.p,
+            time: self.time,
+            p_error: self.p_error,
+            wo: self.wo,
+            n: self.n,
+            medium_interface: self.medium_interface.clone(),
+        }
+    }
+    fn get_bsdf(&self) -> Option<Arc<Bsdf>> {
+        self.bsdf.clone()
+    }
+    fn get_shading_n(&self) -> Option<Normal3f> {
+        Some(self.shading.n)
+    }
+    fn get_phase(&self) -> Option<Arc<PhaseFunction + Send + Sync>> {
+        None
+    }
 }
@@ -67,7 +67,7 @@ pub fn is_delta_light(flags: u8) -> bool {
 
 /// A closure - an object that encapsulates a small amount of data and
 /// some computation that is yet to be done.
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct VisibilityTester {
     pub p0: InteractionCommon, // TODO: private
     pub p1: InteractionCommon, // TODO: private
@@ -77,28 +77,28 @@ impl VisibilityTester {
     pub fn unoccluded(&self, scene: &Scene) -> bool {
         !scene.intersect_p(&mut self.p0.spawn_ray_to(&self.p1))
     }
-    pub fn tr(&self, scene: &Scene, _sampler: &mut Box<Sampler + Send + Sync>) -> Spectrum {
+    /// Like **unoccluded()**, but accounts for the transmittance of
+    /// participating media (and ignores surfaces without material,
+    /// which only separate two media).
+    pub fn tr(&self, scene: &Scene, sampler: &mut Box<Sampler + Send + Sync>) -> Spectrum {
         let mut ray: Ray = self.p0.spawn_ray_to(&self.p1);
-        let tr: Spectrum = Spectrum::new(1.0 as Float);
+        let mut tr: Spectrum = Spectrum::new(1.0 as Float);
         loop {
-            if let Some(isect) = scene.intersect(&mut ray) {
+            let isect_opt = scene.intersect(&mut ray);
+            // update transmittance for current ray segment
+            if let Some(ref medium) = ray.medium {
+                tr *= medium.tr(&ray, sampler);
+            }
+            // generate next ray segment or return final transmittance
+            if let Some(isect) = isect_opt {
                 // handle opaque surface along ray's path
                 if let Some(primitive) = isect.primitive {
                     if let Some(_material) = primitive.get_material() {
                         return Spectrum::default();
-                    } else {
-                        // update transmittance for current ray segment
-                        // TODO: if (ray.medium) Tr *= ray.medium->Tr(ray, sampler);
-                        let it: InteractionCommon = InteractionCommon {
-                            p: isect.p,
-                            time: isect.time,
-                            p_error: isect.p_error,
-                            wo: isect.wo,
-                            n: isect.n,
-                        };
-                        ray = it.spawn_ray_to(&self.p1);
                     }
                 }
+                let it: InteractionCommon = isect.get_common();
+                ray = it.spawn_ray_to(&self.p1);
             } else {
                 break;
             }
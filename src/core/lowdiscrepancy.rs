@@ -453,9 +453,13 @@ pub fn radical_inverse(base_index: u16, a: u64) -> Float {
         6 => {
             return radical_inverse_specialized(17_u16, a);
         }
-        // WORK
         _ => {
-            panic!("TODO: radical_inverse({:?}, {:?})", base_index, a);
+            // all remaining bases are taken from the prime table
+            assert!(base_index < PRIME_TABLE_SIZE,
+                    "radical_inverse({:?}, {:?})",
+                    base_index,
+                    a);
+            return radical_inverse_specialized(PRIMES[base_index as usize] as u16, a);
         }
     };
 }
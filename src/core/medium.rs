@@ -0,0 +1,422 @@
+//! Participating media (like smoke, fog or milk) absorb, emit and
+//! scatter light within a volume. A **Medium** describes the
+//! scattering properties of such a region of space, and a
+//! **MediumInterface** the media on both sides of a surface (or the
+//! medium a light source or camera is in).
+
+// std
+use std::f32::consts::PI;
+use std::fmt;
+use std::sync::Arc;
+// pbrt
+use core::geometry::{Point2f, Ray, Vector3f};
+use core::geometry::{spherical_direction_vec3, vec3_coordinate_system, vec3_dot_vec3};
+use core::interaction::MediumInteraction;
+use core::pbrt::{Float, Spectrum};
+use core::pbrt::INV_4_PI;
+use core::sampler::Sampler;
+
+// see medium.h
+
+/// Measured scattering properties (in mm^-1) of some materials,
+/// selected by the **preset** parameter of media.
+pub struct MeasuredSS {
+    pub name: &'static str,
+    pub sigma_prime_s: [Float; 3],
+    pub sigma_a: [Float; 3],
+}
+
+// see medium.cpp
+
+pub const SUBSURFACE_PARAMETER_TABLE: [MeasuredSS; 47] = [
+    // From "A Practical Model for Subsurface Light Transport"
+    // Jensen, Marschner, Levoy, Hanrahan
+    // Proc SIGGRAPH 2001
+    MeasuredSS {
+        name: "Apple",
+        sigma_prime_s: [2.29, 2.39, 1.97],
+        sigma_a: [0.0030, 0.0034, 0.046],
+    },
+    MeasuredSS {
+        name: "Chicken1",
+        sigma_prime_s: [0.15, 0.21, 0.38],
+        sigma_a: [0.015, 0.077, 0.19],
+    },
+    MeasuredSS {
+        name: "Chicken2",
+        sigma_prime_s: [0.19, 0.25, 0.32],
+        sigma_a: [0.018, 0.088, 0.20],
+    },
+    MeasuredSS {
+        name: "Cream",
+        sigma_prime_s: [7.38, 5.47, 3.15],
+        sigma_a: [0.0002, 0.0028, 0.0163],
+    },
+    MeasuredSS {
+        name: "Ketchup",
+        sigma_prime_s: [0.18, 0.07, 0.03],
+        sigma_a: [0.061, 0.97, 1.45],
+    },
+    MeasuredSS {
+        name: "Marble",
+        sigma_prime_s: [2.19, 2.62, 3.00],
+        sigma_a: [0.0021, 0.0041, 0.0071],
+    },
+    MeasuredSS {
+        name: "Potato",
+        sigma_prime_s: [0.68, 0.70, 0.55],
+        sigma_a: [0.0024, 0.0090, 0.12],
+    },
+    MeasuredSS {
+        name: "Skimmilk",
+        sigma_prime_s: [0.70, 1.22, 1.90],
+        sigma_a: [0.0014, 0.0025, 0.0142],
+    },
+    MeasuredSS {
+        name: "Skin1",
+        sigma_prime_s: [0.74, 0.88, 1.01],
+        sigma_a: [0.032, 0.17, 0.48],
+    },
+    MeasuredSS {
+        name: "Skin2",
+        sigma_prime_s: [1.09, 1.59, 1.79],
+        sigma_a: [0.013, 0.070, 0.145],
+    },
+    MeasuredSS {
+        name: "Spectralon",
+        sigma_prime_s: [11.6, 20.4, 14.9],
+        sigma_a: [0.00, 0.00, 0.00],
+    },
+    MeasuredSS {
+        name: "Wholemilk",
+        sigma_prime_s: [2.55, 3.21, 3.77],
+        sigma_a: [0.0011, 0.0024, 0.014],
+    },
+    // From "Acquiring Scattering Properties of Participating Media by
+    // Dilution",
+    // Narasimhan, Gupta, Donner, Ramamoorthi, Nayar, Jensen
+    // Proc SIGGRAPH 2006
+    MeasuredSS {
+        name: "Lowfat Milk",
+        sigma_prime_s: [0.89187, 1.5136, 2.532],
+        sigma_a: [0.002875, 0.00575, 0.0115],
+    },
+    MeasuredSS {
+        name: "Reduced Milk",
+        sigma_prime_s: [2.4858, 3.1669, 4.5214],
+        sigma_a: [0.0025556, 0.0051111, 0.012778],
+    },
+    MeasuredSS {
+        name: "Regular Milk",
+        sigma_prime_s: [4.5513, 5.8294, 7.136],
+        sigma_a: [0.0015333, 0.0046, 0.019933],
+    },
+    MeasuredSS {
+        name: "Espresso",
+        sigma_prime_s: [0.72378, 0.84557, 1.0247],
+        sigma_a: [4.7984, 6.5751, 8.8493],
+    },
+    MeasuredSS {
+        name: "Mint Mocha Coffee",
+        sigma_prime_s: [0.31602, 0.38538, 0.48131],
+        sigma_a: [3.772, 5.8228, 7.82],
+    },
+    MeasuredSS {
+        name: "Lowfat Soy Milk",
+        sigma_prime_s: [0.30576, 0.34233, 0.61664],
+        sigma_a: [0.0014375, 0.0071875, 0.035937],
+    },
+    MeasuredSS {
+        name: "Regular Soy Milk",
+        sigma_prime_s: [0.59223, 0.73866, 1.4693],
+        sigma_a: [0.0019167, 0.0095833, 0.065167],
+    },
+    MeasuredSS {
+        name: "Lowfat Chocolate Milk",
+        sigma_prime_s: [0.64925, 0.83916, 1.1057],
+        sigma_a: [0.0115, 0.0368, 0.1564],
+    },
+    MeasuredSS {
+        name: "Regular Chocolate Milk",
+        sigma_prime_s: [1.4585, 2.1289, 2.9527],
+        sigma_a: [0.010063, 0.043125, 0.14375],
+    },
+    MeasuredSS {
+        name: "Coke",
+        sigma_prime_s: [8.9053e-05, 8.372e-05, 0.0],
+        sigma_a: [0.10014, 0.16503, 0.2468],
+    },
+    MeasuredSS {
+        name: "Pepsi",
+        sigma_prime_s: [6.1697e-05, 4.2564e-05, 0.0],
+        sigma_a: [0.091641, 0.14158, 0.20729],
+    },
+    MeasuredSS {
+        name: "Sprite",
+        sigma_prime_s: [6.0306e-06, 6.4139e-06, 6.5504e-06],
+        sigma_a: [0.001886, 0.0018308, 0.0020025],
+    },
+    MeasuredSS {
+        name: "Gatorade",
+        sigma_prime_s: [0.0024574, 0.003007, 0.0037325],
+        sigma_a: [0.024794, 0.019289, 0.008878],
+    },
+    MeasuredSS {
+        name: "Chardonnay",
+        sigma_prime_s: [1.7982e-05, 1.3758e-05, 1.2023e-05],
+        sigma_a: [0.010782, 0.011855, 0.023997],
+    },
+    MeasuredSS {
+        name: "White Zinfandel",
+        sigma_prime_s: [1.7501e-05, 1.9069e-05, 1.288e-05],
+        sigma_a: [0.012072, 0.016184, 0.019843],
+    },
+    MeasuredSS {
+        name: "Merlot",
+        sigma_prime_s: [2.1129e-05, 0.0, 0.0],
+        sigma_a: [0.11632, 0.25191, 0.29434],
+    },
+    MeasuredSS {
+        name: "Budweiser Beer",
+        sigma_prime_s: [2.4356e-05, 2.4079e-05, 1.0564e-05],
+        sigma_a: [0.011492, 0.024911, 0.057786],
+    },
+    MeasuredSS {
+        name: "Coors Light Beer",
+        sigma_prime_s: [5.0922e-05, 4.301e-05, 0.0],
+        sigma_a: [0.006164, 0.013984, 0.034983],
+    },
+    MeasuredSS {
+        name: "Clorox",
+        sigma_prime_s: [0.0024035, 0.0031373, 0.003991],
+        sigma_a: [0.0033542, 0.014892, 0.026297],
+    },
+    MeasuredSS {
+        name: "Apple Juice",
+        sigma_prime_s: [0.00013612, 0.00015836, 0.000227],
+        sigma_a: [0.012957, 0.023741, 0.052184],
+    },
+    MeasuredSS {
+        name: "Cranberry Juice",
+        sigma_prime_s: [0.00010402, 0.00011646, 7.8139e-05],
+        sigma_a: [0.039437, 0.094223, 0.12426],
+    },
+    MeasuredSS {
+        name: "Grape Juice",
+        sigma_prime_s: [5.382e-05, 0.0, 0.0],
+        sigma_a: [0.10404, 0.23958, 0.29325],
+    },
+    MeasuredSS {
+        name: "Ruby Grapefruit Juice",
+        sigma_prime_s: [0.011002, 0.010927, 0.011036],
+        sigma_a: [0.085867, 0.18314, 0.25262],
+    },
+    MeasuredSS {
+        name: "White Grapefruit Juice",
+        sigma_prime_s: [0.22826, 0.23998, 0.32748],
+        sigma_a: [0.0138, 0.018831, 0.056781],
+    },
+    MeasuredSS {
+        name: "Shampoo",
+        sigma_prime_s: [0.0007176, 0.0008303, 0.0009016],
+        sigma_a: [0.014107, 0.045693, 0.061717],
+    },
+    MeasuredSS {
+        name: "Strawberry Shampoo",
+        sigma_prime_s: [0.00015671, 0.00015947, 1.518e-05],
+        sigma_a: [0.01449, 0.05796, 0.075823],
+    },
+    MeasuredSS {
+        name: "Head & Shoulders Shampoo",
+        sigma_prime_s: [0.023805, 0.028804, 0.034306],
+        sigma_a: [0.084621, 0.15688, 0.20365],
+    },
+    MeasuredSS {
+        name: "Lemon Tea Powder",
+        sigma_prime_s: [0.040224, 0.045264, 0.051081],
+        sigma_a: [2.4288, 4.5757, 7.2127],
+    },
+    MeasuredSS {
+        name: "Orange Powder",
+        sigma_prime_s: [0.00015617, 0.00017482, 0.0001762],
+        sigma_a: [0.001449, 0.003441, 0.007863],
+    },
+    MeasuredSS {
+        name: "Pink Lemonade Powder",
+        sigma_prime_s: [0.00012103, 0.00013073, 0.00012528],
+        sigma_a: [0.001165, 0.002366, 0.003195],
+    },
+    MeasuredSS {
+        name: "Cappuccino Powder",
+        sigma_prime_s: [1.8436, 2.5851, 2.1662],
+        sigma_a: [35.844, 49.547, 61.084],
+    },
+    MeasuredSS {
+        name: "Salt Powder",
+        sigma_prime_s: [0.027333, 0.032451, 0.031979],
+        sigma_a: [0.28415, 0.3257, 0.34148],
+    },
+    MeasuredSS {
+        name: "Sugar Powder",
+        sigma_prime_s: [0.00022272, 0.00025513, 0.000271],
+        sigma_a: [0.012638, 0.031051, 0.050124],
+    },
+    MeasuredSS {
+        name: "Suisse Mocha Powder",
+        sigma_prime_s: [2.7979, 3.5452, 4.3365],
+        sigma_a: [17.502, 27.004, 35.433],
+    },
+    MeasuredSS {
+        name: "Pacific Ocean Surface Water",
+        sigma_prime_s: [0.0001764, 0.00032095, 0.00019617],
+        sigma_a: [0.031845, 0.031324, 0.030147],
+    },
+];
+
+/// Looks up the scattering properties of a preset (see
+/// **SUBSURFACE_PARAMETER_TABLE**).
+pub fn get_medium_scattering_properties(name: &String,
+                                        sigma_a: &mut Spectrum,
+                                        sigma_prime_s: &mut Spectrum)
+                                        -> bool {
+    for mss in SUBSURFACE_PARAMETER_TABLE.iter() {
+        if name.as_str() == mss.name {
+            *sigma_a = Spectrum::from_rgb(&mss.sigma_a);
+            *sigma_prime_s = Spectrum::from_rgb(&mss.sigma_prime_s);
+            return true;
+        }
+    }
+    false
+}
+
+/// The Henyey-Greenstein phase function (**g** in (-1, 1) controls
+/// the distribution of scattered light).
+pub fn phase_hg(cos_theta: Float, g: Float) -> Float {
+    let denom: Float = 1.0 as Float + g * g + 2.0 as Float * g * cos_theta;
+    INV_4_PI * (1.0 as Float - g * g) / (denom * denom.sqrt())
+}
+
+/// Describes the angular distribution of light scattered at a point
+/// within a participating medium.
+pub trait PhaseFunction {
+    /// Returns the value of the phase function for the pair of
+    /// directions.
+    fn p(&self, wo: &Vector3f, wi: &Vector3f) -> Float;
+    /// Samples an incident direction **wi** given the outgoing
+    /// direction **wo** and returns the value of the phase function
+    /// (which is also the PDF).
+    fn sample_p(&self, wo: &Vector3f, wi: &mut Vector3f, u: &Point2f) -> Float;
+}
+
+pub trait Medium {
+    /// Returns the beam transmittance along the given ray (from its
+    /// origin to **ray.t_max**).
+    fn tr(&self, ray: &Ray, sampler: &mut Box<Sampler + Send + Sync>) -> Spectrum;
+    /// Samples a scattering event along the ray. Returns the
+    /// throughput weight and, if the ray was scattered before
+    /// reaching **ray.t_max**, the **MediumInteraction**.
+    fn sample(&self,
+              ray: &Ray,
+              sampler: &mut Box<Sampler + Send + Sync>)
+              -> (Spectrum, Option<MediumInteraction>);
+}
+
+/// The media on the inside and outside of a surface. **None** stands
+/// for vacuum.
+#[derive(Default, Clone)]
+pub struct MediumInterface {
+    pub inside: Option<Arc<Medium + Send + Sync>>,
+    pub outside: Option<Arc<Medium + Send + Sync>>,
+}
+
+impl MediumInterface {
+    pub fn new(inside: Option<Arc<Medium + Send + Sync>>,
+               outside: Option<Arc<Medium + Send + Sync>>)
+               -> Self {
+        MediumInterface {
+            inside: inside,
+            outside: outside,
+        }
+    }
+    /// The same medium on both sides (e.g. for lights and cameras).
+    pub fn from_medium(medium: Option<Arc<Medium + Send + Sync>>) -> Self {
+        MediumInterface {
+            inside: medium.clone(),
+            outside: medium,
+        }
+    }
+    /// Does the medium change while crossing the surface?
+    pub fn is_medium_transition(&self) -> bool {
+        !same_medium(&self.inside, &self.outside)
+    }
+    /// Is there any medium other than vacuum?
+    pub fn has_media(&self) -> bool {
+        self.inside.is_some() || self.outside.is_some()
+    }
+}
+
+impl fmt::Debug for MediumInterface {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MediumInterface")
+            .field("inside", &self.inside.is_some())
+            .field("outside", &self.outside.is_some())
+            .finish()
+    }
+}
+
+/// Media are compared by identity (like pointers in the C++ version).
+pub fn same_medium(m1: &Option<Arc<Medium + Send + Sync>>,
+                   m2: &Option<Arc<Medium + Send + Sync>>)
+                   -> bool {
+    match (m1, m2) {
+        (&Some(ref m1), &Some(ref m2)) => {
+            let p1 = &**m1 as *const _ as *const usize;
+            let p2 = &**m2 as *const _ as *const usize;
+            p1 == p2
+        }
+        (&None, &None) => true,
+        _ => false,
+    }
+}
+
+/// Widely used phase function, which can represent backward
+/// (**g** < 0), isotropic (**g** = 0) and forward (**g** > 0)
+/// scattering.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct HenyeyGreenstein {
+    pub g: Float,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(g: Float) -> Self {
+        HenyeyGreenstein { g: g }
+    }
+}
+
+impl PhaseFunction for HenyeyGreenstein {
+    fn p(&self, wo: &Vector3f, wi: &Vector3f) -> Float {
+        // TODO: ProfilePhase _(Prof::PhaseFuncEvaluation);
+        phase_hg(vec3_dot_vec3(wo, wi), self.g)
+    }
+    fn sample_p(&self, wo: &Vector3f, wi: &mut Vector3f, u: &Point2f) -> Float {
+        // TODO: ProfilePhase _(Prof::PhaseFuncSampling);
+        // compute $\cos \theta$ for Henyey--Greenstein sample
+        let cos_theta: Float;
+        if self.g.abs() < 1e-3 as Float {
+            cos_theta = 1.0 as Float - 2.0 as Float * u[0];
+        } else {
+            let sqr_term: Float = (1.0 as Float - self.g * self.g) /
+                                  (1.0 as Float + self.g - 2.0 as Float * self.g * u[0]);
+            cos_theta = -(1.0 as Float + self.g * self.g - sqr_term * sqr_term) /
+                        (2.0 as Float * self.g);
+        }
+        // compute direction _wi_ for Henyey--Greenstein sample
+        let sin_theta: Float = (0.0 as Float).max(1.0 as Float - cos_theta * cos_theta).sqrt();
+        let phi: Float = 2.0 as Float * PI * u[1];
+        let mut v1: Vector3f = Vector3f::default();
+        let mut v2: Vector3f = Vector3f::default();
+        vec3_coordinate_system(wo, &mut v1, &mut v2);
+        *wi = spherical_direction_vec3(sin_theta, cos_theta, phi, &v1, &v2, wo);
+        phase_hg(cos_theta, self.g)
+    }
+}
@@ -1,8 +1,12 @@
 //! All the code for the PBRT core.
 
 pub mod api;
+pub mod assparser;
+pub mod builder;
 pub mod camera;
 pub mod efloat;
+pub mod exporter;
+pub mod fileutil;
 pub mod film;
 pub mod filter;
 pub mod geometry;
@@ -12,11 +16,13 @@ pub mod lightdistrib;
 pub mod light;
 pub mod lowdiscrepancy;
 pub mod material;
+pub mod medium;
 pub mod memory;
 pub mod microfacet;
 pub mod mipmap;
 pub mod parallel;
 pub mod paramset;
+pub mod parser;
 pub mod pbrt;
 pub mod primitive;
 pub mod quaternion;
@@ -1,4 +1,5 @@
 // std
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::sync::Arc;
 // pbrt
@@ -15,7 +16,7 @@ pub struct ParamSetItem<T> {
     pub name: String,
     pub values: Vec<T>,
     pub n_values: usize,
-    pub looked_up: bool, // false
+    pub looked_up: Cell<bool>, // false
 }
 
 #[derive(Default)]
@@ -35,6 +36,8 @@ pub struct ParamSet {
     pub spectra: Vec<ParamSetItem<Spectrum>>,
     pub strings: Vec<ParamSetItem<String>>,
     pub textures: Vec<ParamSetItem<String>>,
+    /// Names and types of all lookups (see **report_unused()**).
+    requested: RefCell<Vec<(String, &'static str)>>,
 }
 
 impl ParamSet {
@@ -54,6 +57,7 @@ impl ParamSet {
         self.spectra.clear();
         self.strings.clear();
         self.textures.clear();
+        self.requested.borrow_mut().clear();
     }
     pub fn add_float(&mut self, name: String, value: Float) {
         self.floats
@@ -61,7 +65,7 @@ impl ParamSet {
                       name: name,
                       values: vec![value],
                       n_values: 1_usize,
-                      looked_up: false,
+                      looked_up: Cell::new(false),
                   });
     }
     pub fn add_floats(&mut self, name: String, values: Vec<Float>) {
@@ -71,7 +75,7 @@ impl ParamSet {
                       name: name,
                       values: values,
                       n_values: n_values,
-                      looked_up: false,
+                      looked_up: Cell::new(false),
                   });
     }
     pub fn add_int(&mut self, name: String, value: i32) {
@@ -80,7 +84,7 @@ impl ParamSet {
                       name: name,
                       values: vec![value],
                       n_values: 1_usize,
-                      looked_up: false,
+                      looked_up: Cell::new(false),
                   });
     }
     pub fn add_ints(&mut self, name: String, values: Vec<i32>) {
@@ -90,7 +94,7 @@ impl ParamSet {
                       name: name,
                       values: values,
                       n_values: n_values,
-                      looked_up: false,
+                      looked_up: Cell::new(false),
                   });
     }
     pub fn add_bool(&mut self, name: String, value: bool) {
@@ -99,7 +103,43 @@ impl ParamSet {
                       name: name,
                       values: vec![value],
                       n_values: 1_usize,
-                      looked_up: false,
+                      looked_up: Cell::new(false),
+                  });
+    }
+    pub fn add_point2fs(&mut self, name: String, values: Vec<Float>) {
+        let n_values: usize = values.len();
+        let mut p_values: Vec<Point2f> = Vec::new();
+        let n_items: usize = values.len() / 2_usize;
+        assert!(n_values % 2 == 0, "point2 parameters need 2 coordinates");
+        for i in 0..n_items {
+            let x: Float = values[i * 2 + 0];
+            let y: Float = values[i * 2 + 1];
+            p_values.push(Point2f { x: x, y: y });
+        }
+        self.point2fs
+            .push(ParamSetItem::<Point2f> {
+                      name: name,
+                      values: p_values,
+                      n_values: n_items,
+                      looked_up: Cell::new(false),
+                  });
+    }
+    pub fn add_vector2fs(&mut self, name: String, values: Vec<Float>) {
+        let n_values: usize = values.len();
+        let mut p_values: Vec<Vector2f> = Vec::new();
+        let n_items: usize = values.len() / 2_usize;
+        assert!(n_values % 2 == 0, "vector2 parameters need 2 coordinates");
+        for i in 0..n_items {
+            let x: Float = values[i * 2 + 0];
+            let y: Float = values[i * 2 + 1];
+            p_values.push(Vector2f { x: x, y: y });
+        }
+        self.vector2fs
+            .push(ParamSetItem::<Vector2f> {
+                      name: name,
+                      values: p_values,
+                      n_values: n_items,
+                      looked_up: Cell::new(false),
                   });
     }
     pub fn add_point3f(&mut self, name: String, value: Point3f) {
@@ -108,7 +148,7 @@ impl ParamSet {
                       name: name,
                       values: vec![value],
                       n_values: 1_usize,
-                      looked_up: false,
+                      looked_up: Cell::new(false),
                   });
     }
     pub fn add_point3fs(&mut self, name: String, values: Vec<Float>) {
@@ -127,7 +167,7 @@ impl ParamSet {
                       name: name,
                       values: p_values,
                       n_values: n_points,
-                      looked_up: false,
+                      looked_up: Cell::new(false),
                   });
     }
     pub fn add_string(&mut self, name: String, value: String) {
@@ -136,7 +176,7 @@ impl ParamSet {
                       name: name,
                       values: vec![value],
                       n_values: 1_usize,
-                      looked_up: false,
+                      looked_up: Cell::new(false),
                   });
     }
     pub fn add_texture(&mut self, name: String, value: String) {
@@ -145,7 +185,7 @@ impl ParamSet {
                       name: name,
                       values: vec![value],
                       n_values: 1_usize,
-                      looked_up: false,
+                      looked_up: Cell::new(false),
                   });
     }
     pub fn add_vector3f(&mut self, name: String, value: Vector3f) {
@@ -154,7 +194,26 @@ impl ParamSet {
                       name: name,
                       values: vec![value],
                       n_values: 1_usize,
-                      looked_up: false,
+                      looked_up: Cell::new(false),
+                  });
+    }
+    pub fn add_vector3fs(&mut self, name: String, values: Vec<Float>) {
+        let n_values: usize = values.len();
+        let mut p_values: Vec<Vector3f> = Vec::new();
+        let n_items: usize = values.len() / 3_usize;
+        assert!(n_values % 3 == 0, "vector parameters need 3 coordinates");
+        for i in 0..n_items {
+            let x: Float = values[i * 3 + 0];
+            let y: Float = values[i * 3 + 1];
+            let z: Float = values[i * 3 + 2];
+            p_values.push(Vector3f { x: x, y: y, z: z });
+        }
+        self.vector3fs
+            .push(ParamSetItem::<Vector3f> {
+                      name: name,
+                      values: p_values,
+                      n_values: n_items,
+                      looked_up: Cell::new(false),
                   });
     }
     pub fn add_normal3f(&mut self, name: String, value: Normal3f) {
@@ -163,7 +222,7 @@ impl ParamSet {
                       name: name,
                       values: vec![value],
                       n_values: 1_usize,
-                      looked_up: false,
+                      looked_up: Cell::new(false),
                   });
     }
     pub fn add_normal3fs(&mut self, name: String, values: Vec<Float>) {
@@ -182,7 +241,7 @@ impl ParamSet {
                       name: name,
                       values: p_values,
                       n_values: n_normals,
-                      looked_up: false,
+                      looked_up: Cell::new(false),
                   });
     }
     pub fn add_rgb_spectrum(&mut self, name: String, value: Spectrum) {
@@ -191,7 +250,7 @@ impl ParamSet {
                       name: name,
                       values: vec![value],
                       n_values: 1_usize,
-                      looked_up: false,
+                      looked_up: Cell::new(false),
                   });
     }
     pub fn add_blackbody_spectrum(&mut self, name: String, values: Vec<Float>) {
@@ -210,11 +269,12 @@ impl ParamSet {
                       name: name,
                       values: s,
                       n_values: n_values,
-                      looked_up: false,
+                      looked_up: Cell::new(false),
                   });
     }
     pub fn copy_from(&mut self, param_set: &ParamSet) {
         self.key_word = param_set.key_word.clone();
+        self.requested.borrow_mut().clear();
         // self.name = param_set.name.clone();
         self.bools.clear();
         for b in &param_set.bools {
@@ -227,7 +287,7 @@ impl ParamSet {
                           name: b.name.clone(),
                           values: values,
                           n_values: b.n_values,
-                          looked_up: false,
+                          looked_up: Cell::new(false),
                       });
         }
         self.ints.clear();
@@ -241,7 +301,7 @@ impl ParamSet {
                           name: i.name.clone(),
                           values: values,
                           n_values: i.n_values,
-                          looked_up: false,
+                          looked_up: Cell::new(false),
                       });
         }
         self.floats.clear();
@@ -255,7 +315,7 @@ impl ParamSet {
                           name: f.name.clone(),
                           values: values,
                           n_values: f.n_values,
-                          looked_up: false,
+                          looked_up: Cell::new(false),
                       });
         }
         self.point2fs.clear();
@@ -271,7 +331,7 @@ impl ParamSet {
                           name: p.name.clone(),
                           values: values,
                           n_values: p.n_values,
-                          looked_up: false,
+                          looked_up: Cell::new(false),
                       });
         }
         self.vector3fs.clear();
@@ -287,7 +347,7 @@ impl ParamSet {
                           name: s.name.clone(),
                           values: values,
                           n_values: s.n_values,
-                          looked_up: false,
+                          looked_up: Cell::new(false),
                       });
         }
         self.strings.clear();
@@ -301,7 +361,7 @@ impl ParamSet {
                           name: s.name.clone(),
                           values: values,
                           n_values: s.n_values,
-                          looked_up: false,
+                          looked_up: Cell::new(false),
                       });
         }
         self.textures.clear();
@@ -315,68 +375,75 @@ impl ParamSet {
                           name: s.name.clone(),
                           values: values,
                           n_values: s.n_values,
-                          looked_up: false,
+                          looked_up: Cell::new(false),
                       });
         }
     }
     pub fn find_one_float(&self, name: String, d: Float) -> Float {
+        self.request(&name, "float");
         for v in &self.floats {
             if v.name == name && v.n_values == 1 {
-                // v.looked_up = true;
+                v.looked_up.set(true);
                 return v.values[0];
             }
         }
         d
     }
     pub fn find_one_int(&self, name: String, d: i32) -> i32 {
+        self.request(&name, "integer");
         for v in &self.ints {
             if v.name == name && v.n_values == 1 {
-                // v.looked_up = true;
+                v.looked_up.set(true);
                 return v.values[0];
             }
         }
         d
     }
     pub fn find_one_bool(&self, name: String, d: bool) -> bool {
+        self.request(&name, "bool");
         for v in &self.bools {
             if v.name == name && v.n_values == 1 {
-                // v.looked_up = true;
+                v.looked_up.set(true);
                 return v.values[0];
             }
         }
         d
     }
     pub fn find_one_point3f(&self, name: String, d: Point3f) -> Point3f {
+        self.request(&name, "point3");
         for v in &self.point3fs {
             if v.name == name && v.n_values == 1 {
-                // v.looked_up = true;
+                v.looked_up.set(true);
                 return v.values[0];
             }
         }
         d
     }
     pub fn find_one_vector3f(&self, name: String, d: Vector3f) -> Vector3f {
+        self.request(&name, "vector3");
         for v in &self.vector3fs {
             if v.name == name && v.n_values == 1 {
-                // v.looked_up = true;
+                v.looked_up.set(true);
                 return v.values[0];
             }
         }
         d
     }
     pub fn find_one_spectrum(&self, name: String, d: Spectrum) -> Spectrum {
+        self.request(&name, "spectrum");
         for v in &self.spectra {
             if v.name == name && v.n_values == 1 {
-                // v.looked_up = true;
+                v.looked_up.set(true);
                 return v.values[0];
             }
         }
         d
     }
     pub fn find_one_string(&self, name: String, d: String) -> String {
+        self.request(&name, "string");
         for v in &self.strings {
             if v.name == name && v.n_values == 1 {
-                // v.looked_up = true;
+                v.looked_up.set(true);
                 return v.values[0].clone();
             }
         }
@@ -391,15 +458,17 @@ impl ParamSet {
         filename
     }
     pub fn find_texture(&self, name: String) -> String {
+        self.request(&name, "texture");
         let d: String = String::new();
         lookup_one(&self.textures, name, d)
     }
     pub fn find_int(&self, name: String) -> Vec<i32> {
+        self.request(&name, "integer");
         let mut values: Vec<i32> = Vec::new();
         for v in &self.ints {
             if v.name == name {
                 let n_values = v.n_values;
-                // v.looked_up = true;
+                v.looked_up.set(true);
                 for i in 0..n_values {
                     values.push(v.values[i]);
                 }
@@ -408,11 +477,12 @@ impl ParamSet {
         values
     }
     pub fn find_float(&self, name: String) -> Vec<Float> {
+        self.request(&name, "float");
         let mut values: Vec<Float> = Vec::new();
         for v in &self.floats {
             if v.name == name {
                 let n_values = v.n_values;
-                // v.looked_up = true;
+                v.looked_up.set(true);
                 for i in 0..n_values {
                     values.push(v.values[i]);
                 }
@@ -421,11 +491,12 @@ impl ParamSet {
         values
     }
     pub fn find_point2f(&self, name: String) -> Vec<Point2f> {
+        self.request(&name, "point2");
         let mut values: Vec<Point2f> = Vec::new();
         for v in &self.point2fs {
             if v.name == name {
                 let n_values = v.n_values;
-                // v.looked_up = true;
+                v.looked_up.set(true);
                 for i in 0..n_values {
                     values.push(v.values[i]);
                 }
@@ -434,11 +505,12 @@ impl ParamSet {
         values
     }
     pub fn find_vector2f(&self, name: String) -> Vec<Vector2f> {
+        self.request(&name, "vector2");
         let mut values: Vec<Vector2f> = Vec::new();
         for v in &self.vector2fs {
             if v.name == name {
                 let n_values = v.n_values;
-                // v.looked_up = true;
+                v.looked_up.set(true);
                 for i in 0..n_values {
                     values.push(v.values[i]);
                 }
@@ -447,11 +519,12 @@ impl ParamSet {
         values
     }
     pub fn find_point3f(&self, name: String) -> Vec<Point3f> {
+        self.request(&name, "point3");
         let mut values: Vec<Point3f> = Vec::new();
         for v in &self.point3fs {
             if v.name == name {
                 let n_values = v.n_values;
-                // v.looked_up = true;
+                v.looked_up.set(true);
                 for i in 0..n_values {
                     values.push(v.values[i]);
                 }
@@ -460,11 +533,12 @@ impl ParamSet {
         values
     }
     pub fn find_vector3f(&self, name: String) -> Vec<Vector3f> {
+        self.request(&name, "vector3");
         let mut values: Vec<Vector3f> = Vec::new();
         for v in &self.vector3fs {
             if v.name == name {
                 let n_values = v.n_values;
-                // v.looked_up = true;
+                v.looked_up.set(true);
                 for i in 0..n_values {
                     values.push(v.values[i]);
                 }
@@ -473,11 +547,12 @@ impl ParamSet {
         values
     }
     pub fn find_normal3f(&self, name: String) -> Vec<Normal3f> {
+        self.request(&name, "normal");
         let mut values: Vec<Normal3f> = Vec::new();
         for v in &self.normals {
             if v.name == name {
                 let n_values = v.n_values;
-                // v.looked_up = true;
+                v.looked_up.set(true);
                 for i in 0..n_values {
                     values.push(v.values[i]);
                 }
@@ -486,11 +561,12 @@ impl ParamSet {
         values
     }
     pub fn find_spectrum(&self, name: String) -> Vec<Spectrum> {
+        self.request(&name, "spectrum");
         let mut values: Vec<Spectrum> = Vec::new();
         for v in &self.spectra {
             if v.name == name {
                 let n_values = v.n_values;
-                // v.looked_up = true;
+                v.looked_up.set(true);
                 for i in 0..n_values {
                     values.push(v.values[i]);
                 }
@@ -498,6 +574,68 @@ impl ParamSet {
         }
         values
     }
+    /// Returns a warning for each parameter which was never looked up
+    /// (ReportUnused in paramset.cpp), e.g. because of a typo in its
+    /// name or because it was given with the wrong type.
+    pub fn report_unused(&self) -> Vec<String> {
+        let mut messages: Vec<String> = Vec::new();
+        self.report_unused_items(&self.bools, "bool", &mut messages);
+        self.report_unused_items(&self.ints, "integer", &mut messages);
+        self.report_unused_items(&self.floats, "float", &mut messages);
+        self.report_unused_items(&self.point2fs, "point2", &mut messages);
+        self.report_unused_items(&self.vector2fs, "vector2", &mut messages);
+        self.report_unused_items(&self.point3fs, "point3", &mut messages);
+        self.report_unused_items(&self.vector3fs, "vector3", &mut messages);
+        self.report_unused_items(&self.normals, "normal", &mut messages);
+        self.report_unused_items(&self.spectra, "spectrum", &mut messages);
+        self.report_unused_items(&self.strings, "string", &mut messages);
+        self.report_unused_items(&self.textures, "texture", &mut messages);
+        messages
+    }
+    fn report_unused_items<T>(&self,
+                              items: &Vec<ParamSetItem<T>>,
+                              type_name: &str,
+                              messages: &mut Vec<String>) {
+        let requested = self.requested.borrow();
+        for item in items {
+            if item.looked_up.get() {
+                continue;
+            }
+            let mut same_type: bool = false;
+            let mut other_types: Vec<&str> = Vec::new();
+            for &(ref name, requested_type) in requested.iter() {
+                if *name == item.name {
+                    if requested_type == type_name {
+                        same_type = true;
+                    } else {
+                        other_types.push(requested_type);
+                    }
+                }
+            }
+            if same_type {
+                // e.g. several values where a single one is expected
+                messages.push(format!("Parameter \"{}\" has an unexpected number of values ({})",
+                                      item.name,
+                                      item.n_values));
+            } else if !other_types.is_empty() {
+                messages.push(format!("Parameter \"{}\" given as \"{}\", expected \"{}\"",
+                                      item.name,
+                                      type_name,
+                                      other_types.join("\" or \"")));
+            } else {
+                messages.push(format!("Parameter \"{}\" not used", item.name));
+            }
+        }
+    }
+    /// Remembers the type a parameter was looked up with.
+    fn request(&self, name: &String, type_name: &'static str) {
+        let mut requested = self.requested.borrow_mut();
+        if !requested
+                .iter()
+                .any(|&(ref n, t)| n == name && t == type_name) {
+            requested.push((name.clone(), type_name));
+        }
+    }
 }
 
 #[derive(Default)]
@@ -653,6 +791,11 @@ impl TextureParams {
             .find_one_vector3f(name.clone(),
                                self.material_params.find_one_vector3f(name.clone(), d))
     }
+    pub fn report_unused(&self) -> Vec<String> {
+        let mut messages: Vec<String> = self.geom_params.report_unused();
+        messages.extend(self.material_params.report_unused());
+        messages
+    }
 }
 
 /// Replaces a macro on the C++ side.
@@ -661,7 +804,7 @@ pub fn lookup_one<T>(vec: &Vec<ParamSetItem<T>>, name: String, d: T) -> T
 {
     for v in vec {
         if v.name == name && v.n_values == 1_usize {
-            // v.looked_up = true;
+            v.looked_up.set(true);
             return v.values[0].clone();
         }
     }
@@ -0,0 +1,1557 @@
+//! The scene description files of **pbrt** are read statement by
+//! statement. A small tokenizer splits the input (while reading it)
+//! into keywords, quoted strings, numbers and brackets, and each
+//! statement is forwarded to the matching API function (see
+//! **api.rs**) as soon as it's complete. This way shapes are created
+//! while the file is read, and only the current statement has to be
+//! held in memory, even for scene files of several gigabytes.
+
+// std
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+// pbrt
+use core::api::{pbrt_accelerator, pbrt_active_transform_all, pbrt_active_transform_end_time,
+                pbrt_active_transform_start_time, pbrt_area_light_source, pbrt_attribute_begin,
+                pbrt_attribute_end, pbrt_camera, pbrt_concat_transform, pbrt_coord_sys_transform,
+                pbrt_coordinate_system, pbrt_film, pbrt_identity, pbrt_integrator,
+                pbrt_light_source, pbrt_look_at, pbrt_make_named_material, pbrt_make_named_medium,
+                pbrt_material, pbrt_medium_interface, pbrt_named_material, pbrt_object_begin,
+                pbrt_object_end, pbrt_object_instance, pbrt_pixel_filter, pbrt_reverse_orientation,
+                pbrt_rotate, pbrt_sampler, pbrt_scale, pbrt_shape, pbrt_texture, pbrt_transform,
+                pbrt_transform_begin, pbrt_transform_end, pbrt_transform_times, pbrt_translate,
+                pbrt_world_begin, pbrt_world_end};
+use core::api::{ApiState, SceneError};
+use core::camera::Camera;
+use core::exporter::SceneExporter;
+use core::fileutil;
+use core::integrator::Integrator;
+use core::geometry::{Normal3f, Point3f, Vector3f};
+use core::paramset::ParamSet;
+use core::pbrt::{Float, Spectrum};
+use core::sampler::Sampler;
+use core::scene::Scene;
+
+
+/// Everything needed to start rendering once **WorldEnd** was parsed.
+pub struct World {
+    pub scene: Scene,
+    pub camera: Box<Camera + Send + Sync>,
+    pub sampler: Box<Sampler + Send + Sync>,
+    pub integrator: Box<Integrator + Send + Sync>,
+    /// Recoverable problems found while parsing.
+    pub warnings: Vec<ParseWarning>,
+}
+
+pub type ParseResult = Result<World, ParseError>;
+
+/// Where a statement (or the offending token within it) starts within
+/// a scene file (lines and columns start at 1, they are 0 for scenes
+/// created by **SceneBuilder**).
+#[derive(Debug, Clone)]
+pub struct Location {
+    /// **None** for scenes parsed from a string.
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+    /// Keyword and name of the offending statement (e.g. **Shape
+    /// "sphere"**), empty if it's unknown.
+    pub statement: String,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.file {
+            Some(ref file) => write!(f, "{}:{}:{}", file.display(), self.line, self.column),
+            None if self.line == 0 => write!(f, "<builder>"),
+            None => write!(f, "<string>:{}:{}", self.line, self.column),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    Io(io::Error),
+    Syntax(String),
+    /// The API refused a statement (unknown names, wrong number of
+    /// parameter values, missing files, ...).
+    Scene(SceneError),
+    /// The chain of files which include each other.
+    IncludeCycle(Vec<PathBuf>),
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseErrorKind::Io(ref err) => write!(f, "I/O error: {}", err),
+            ParseErrorKind::Syntax(ref msg) => write!(f, "syntax error: {}", msg),
+            ParseErrorKind::Scene(ref err) => write!(f, "error: {}", err),
+            ParseErrorKind::IncludeCycle(ref chain) => {
+                let files: Vec<String> = chain.iter().map(|p| format!("{:?}", p)).collect();
+                write!(f, "include cycle: {}", files.join(" -> "))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// **None** if the error isn't related to a statement (e.g. the
+    /// scene file itself can't be read).
+    pub location: Option<Location>,
+    /// The **Include** statements leading to **location** (innermost
+    /// first).
+    pub included_from: Vec<Location>,
+    /// Warnings collected before the error occurred.
+    pub warnings: Vec<ParseWarning>,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            kind: kind,
+            location: None,
+            included_from: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+    fn syntax(message: String, location: Location) -> ParseError {
+        let mut err: ParseError = ParseError::new(ParseErrorKind::Syntax(message));
+        err.location = Some(location);
+        err
+    }
+    /// Errors which don't know where they happened (e.g. those of the
+    /// API) are located at the statement causing them.
+    fn locate(&mut self, location: Location) {
+        if self.location.is_none() {
+            self.location = Some(location);
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref location) = self.location {
+            write!(f, "{}: {}", location, self.kind)?;
+            if !location.statement.is_empty() {
+                write!(f, "\n    {}", location.statement)?;
+            }
+        } else {
+            write!(f, "{}", self.kind)?;
+        }
+        for location in &self.included_from {
+            write!(f, "\n  included from {}", location)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {
+    fn description(&self) -> &str {
+        match self.kind {
+            ParseErrorKind::Io(ref err) => err.description(),
+            ParseErrorKind::Syntax(ref msg) => msg.as_str(),
+            ParseErrorKind::Scene(ref err) => err.description(),
+            ParseErrorKind::IncludeCycle(_) => "include cycle",
+        }
+    }
+    fn cause(&self) -> Option<&Error> {
+        match self.kind {
+            ParseErrorKind::Io(ref err) => Some(err),
+            ParseErrorKind::Scene(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(err: io::Error) -> ParseError {
+        ParseError::new(ParseErrorKind::Io(err))
+    }
+}
+
+impl From<SceneError> for ParseError {
+    fn from(err: SceneError) -> ParseError {
+        ParseError::new(ParseErrorKind::Scene(err))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseWarning {
+    pub message: String,
+    pub location: Location,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: warning: {}", self.location, self.message)?;
+        if !self.location.statement.is_empty() {
+            write!(f, "\n    {}", self.location.statement)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+enum TokenKind {
+    /// Unquoted words, e.g. **Shape** or **WorldBegin**.
+    Keyword(String),
+    /// Quoted strings (without the quotes).
+    Str(String),
+    Number(String),
+    LBrack,
+    RBrack,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TokenKind::Keyword(ref text) => write!(f, "{}", text),
+            TokenKind::Str(ref text) => write!(f, "\"{}\"", text),
+            TokenKind::Number(ref text) => write!(f, "{}", text),
+            TokenKind::LBrack => write!(f, "["),
+            TokenKind::RBrack => write!(f, "]"),
+        }
+    }
+}
+
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+/// Splits the input into tokens while reading it (see pbrt-v3's
+/// Tokenizer in parser.cpp).
+struct Tokenizer<R: BufRead> {
+    reader: R,
+    line: usize,
+    column: usize,
+    /// The next token, if it was peeked at.
+    peeked: Option<Token>,
+}
+
+impl<R: BufRead> Tokenizer<R> {
+    fn new(reader: R) -> Tokenizer<R> {
+        Tokenizer {
+            reader: reader,
+            line: 1,
+            column: 1,
+            peeked: None,
+        }
+    }
+    fn peek_byte(&mut self) -> io::Result<Option<u8>> {
+        let buf: &[u8] = self.reader.fill_buf()?;
+        Ok(buf.first().cloned())
+    }
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        let byte: Option<u8> = self.peek_byte()?;
+        if let Some(b) = byte {
+            self.reader.consume(1);
+            if b == b'\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        Ok(byte)
+    }
+    fn next_token(&mut self, ctx: &ParseContext) -> Result<Option<Token>, ParseError> {
+        if self.peeked.is_some() {
+            return Ok(self.peeked.take());
+        }
+        loop {
+            let line: usize = self.line;
+            let column: usize = self.column;
+            let byte: u8 = match self.next_byte()? {
+                Some(b) => b,
+                None => return Ok(None),
+            };
+            let kind: TokenKind = match byte {
+                b' ' | b'\t' | b'\r' | b'\n' => continue,
+                b'#' => {
+                    // comments reach until the end of the line
+                    while let Some(b) = self.next_byte()? {
+                        if b == b'\n' {
+                            break;
+                        }
+                    }
+                    continue;
+                }
+                b'[' => TokenKind::LBrack,
+                b']' => TokenKind::RBrack,
+                b'"' => {
+                    let mut bytes: Vec<u8> = Vec::new();
+                    loop {
+                        match self.next_byte()? {
+                            Some(b'"') => break,
+                            Some(b'\\') => {
+                                match self.next_byte()? {
+                                    Some(b'n') => bytes.push(b'\n'),
+                                    Some(b't') => bytes.push(b'\t'),
+                                    Some(b) => bytes.push(b),
+                                    None => {}
+                                }
+                            }
+                            Some(b'\n') | None => {
+                                return Err(ctx.syntax_error(String::from("unterminated string"),
+                                                            line,
+                                                            column));
+                            }
+                            Some(b) => bytes.push(b),
+                        }
+                    }
+                    match String::from_utf8(bytes) {
+                        Ok(text) => TokenKind::Str(text),
+                        Err(_) => {
+                            return Err(ctx.syntax_error(String::from("invalid UTF-8 in string"),
+                                                        line,
+                                                        column));
+                        }
+                    }
+                }
+                _ => {
+                    // everything else reaches until the next delimiter
+                    if !byte.is_ascii() {
+                        return Err(ctx.syntax_error(String::from("non-ASCII character outside \
+                                                                  of a string"),
+                                                    line,
+                                                    column));
+                    }
+                    let mut text: String = String::new();
+                    text.push(byte as char);
+                    loop {
+                        match self.peek_byte()? {
+                            Some(b' ') | Some(b'\t') | Some(b'\r') | Some(b'\n') | Some(b'"') |
+                            Some(b'[') | Some(b']') | Some(b'#') | None => break,
+                            Some(b) if !b.is_ascii() => {
+                                return Err(ctx.syntax_error(String::from("non-ASCII character \
+                                                                          outside of a string"),
+                                                            self.line,
+                                                            self.column));
+                            }
+                            Some(b) => {
+                                self.next_byte()?;
+                                text.push(b as char);
+                            }
+                        }
+                    }
+                    match byte {
+                        b'0'...b'9' | b'-' | b'+' | b'.' => TokenKind::Number(text),
+                        _ => TokenKind::Keyword(text),
+                    }
+                }
+            };
+            return Ok(Some(Token {
+                               kind: kind,
+                               line: line,
+                               column: column,
+                           }));
+        }
+    }
+    /// Like **next_token()**, but an error at the end of the input.
+    fn expect_token(&mut self, ctx: &ParseContext) -> Result<Token, ParseError> {
+        match self.next_token(ctx)? {
+            Some(token) => Ok(token),
+            None => {
+                Err(ctx.syntax_error(String::from("unexpected end of file"),
+                                     self.line,
+                                     self.column))
+            }
+        }
+    }
+    fn peek_token(&mut self, ctx: &ParseContext) -> Result<Option<&Token>, ParseError> {
+        if self.peeked.is_none() {
+            self.peeked = self.next_token(ctx)?;
+        }
+        Ok(self.peeked.as_ref())
+    }
+    /// Parameter lists (and a few statements) continue as long as
+    /// quoted strings follow.
+    fn next_is_string(&mut self, ctx: &ParseContext) -> Result<bool, ParseError> {
+        match self.peek_token(ctx)? {
+            Some(&Token { kind: TokenKind::Str(_), .. }) => Ok(true),
+            _ => Ok(false),
+        }
+    }
+    fn next_is_lbrack(&mut self, ctx: &ParseContext) -> Result<bool, ParseError> {
+        match self.peek_token(ctx)? {
+            Some(&Token { kind: TokenKind::LBrack, .. }) => Ok(true),
+            _ => Ok(false),
+        }
+    }
+}
+
+/// The statement currently being parsed, to locate errors and
+/// warnings.
+struct Statement {
+    keyword: String,
+    /// The first quoted string, e.g. the type of a **Shape**.
+    name: Option<String>,
+    line: usize,
+    column: usize,
+}
+
+impl Statement {
+    fn summary(&self) -> String {
+        match self.name {
+            Some(ref name) => format!("{} \"{}\"", self.keyword, name),
+            None => self.keyword.clone(),
+        }
+    }
+}
+
+/// State which is only needed while parsing (not by the API).
+struct ParseContext {
+    /// The files currently being parsed (the last one is the current
+    /// file), used to resolve and detect cycles of **Include**s.
+    include_stack: Vec<PathBuf>,
+    warnings: Vec<ParseWarning>,
+    /// Summary of the current statement (see **Statement**).
+    statement: String,
+}
+
+impl ParseContext {
+    fn new(include_stack: Vec<PathBuf>) -> ParseContext {
+        ParseContext {
+            include_stack: include_stack,
+            warnings: Vec::new(),
+            statement: String::new(),
+        }
+    }
+    fn location(&self, line: usize, column: usize) -> Location {
+        Location {
+            file: self.include_stack.last().cloned(),
+            line: line,
+            column: column,
+            statement: self.statement.clone(),
+        }
+    }
+    fn syntax_error(&self, message: String, line: usize, column: usize) -> ParseError {
+        ParseError::syntax(message, self.location(line, column))
+    }
+    /// Moves the warnings of the last statement from the API state
+    /// into the context.
+    fn collect_warnings(&mut self, api_state: &mut ApiState, statement: &Statement) {
+        if api_state.warnings.is_empty() {
+            return;
+        }
+        let location: Location = self.location(statement.line, statement.column);
+        for message in api_state.warnings.drain(..) {
+            self.warnings.push(ParseWarning {
+                                   message: message,
+                                   location: location.clone(),
+                               });
+        }
+    }
+}
+
+/// Reads and parses a scene file, which (like included files) may be
+/// gzip-compressed. Relative filenames within the scene (textures,
+/// PLY meshes, ...) are resolved against the directory of the scene
+/// file.
+pub fn parse_file(filename: &Path) -> ParseResult {
+    parse_file_with(filename, None)
+}
+
+/// Parses a scene file like **parse_file()** and writes the scene
+/// (as the API sees it) into a new **.pbrt** file at the same time.
+pub fn parse_file_and_export(filename: &Path, exporter: SceneExporter) -> ParseResult {
+    parse_file_with(filename, Some(exporter))
+}
+
+fn parse_file_with(filename: &Path, exporter: Option<SceneExporter>) -> ParseResult {
+    let reader: Box<BufRead> = fileutil::open_file(filename)
+        .map_err(|e| io::Error::new(e.kind(), format!("{:?}: {}", filename, e)))?;
+    let abs_path: PathBuf = if filename.is_relative() {
+        env::current_dir()?.join(filename)
+    } else {
+        filename.to_path_buf()
+    };
+    let mut api_state: ApiState = ApiState::new(abs_path.parent().map(|p| p.to_path_buf()));
+    api_state.exporter = exporter;
+    let mut ctx: ParseContext = ParseContext::new(vec![abs_path.canonicalize()?]);
+    parse_world(&mut ctx, &mut api_state, &mut Tokenizer::new(reader))
+}
+
+/// Parses a scene description held in memory. Included files are
+/// searched relative to **search_directory** (or the current working
+/// directory).
+pub fn parse_string(str_buf: &str, search_directory: Option<&Path>) -> ParseResult {
+    let mut api_state: ApiState = ApiState::new(search_directory.map(|p| p.to_path_buf()));
+    let mut ctx: ParseContext = ParseContext::new(Vec::new());
+    parse_world(&mut ctx,
+                &mut api_state,
+                &mut Tokenizer::new(str_buf.as_bytes()))
+}
+
+fn parse_world<R: BufRead>(ctx: &mut ParseContext,
+                           api_state: &mut ApiState,
+                           tokenizer: &mut Tokenizer<R>)
+                           -> ParseResult {
+    let result: Result<Option<World>, ParseError> = parse_statements(ctx, api_state, tokenizer);
+    let warnings: Vec<ParseWarning> = ctx.warnings.drain(..).collect();
+    match result {
+        Ok(Some(mut world)) => {
+            world.warnings = warnings;
+            Ok(world)
+        }
+        Ok(None) => {
+            let mut err: ParseError =
+                ParseError::new(ParseErrorKind::Syntax(String::from("WorldEnd expected")));
+            err.warnings = warnings;
+            Err(err)
+        }
+        Err(mut err) => {
+            err.warnings = warnings;
+            Err(err)
+        }
+    }
+}
+
+/// Reads statements until the end of the input. Returns the created
+/// world if **WorldEnd** was found (possibly within an included
+/// file).
+fn parse_statements<R: BufRead>(ctx: &mut ParseContext,
+                                api_state: &mut ApiState,
+                                tokenizer: &mut Tokenizer<R>)
+                                -> Result<Option<World>, ParseError> {
+    loop {
+        ctx.statement = String::new();
+        let token: Token = match tokenizer.next_token(ctx) {
+            Ok(Some(token)) => token,
+            Ok(None) => return Ok(None),
+            Err(mut err) => {
+                err.locate(ctx.location(tokenizer.line, tokenizer.column));
+                return Err(err);
+            }
+        };
+        let mut statement: Statement = match token.kind {
+            TokenKind::Keyword(keyword) => {
+                Statement {
+                    keyword: keyword,
+                    name: None,
+                    line: token.line,
+                    column: token.column,
+                }
+            }
+            kind => {
+                return Err(ctx.syntax_error(format!("statement expected, found {}", kind),
+                                            token.line,
+                                            token.column));
+            }
+        };
+        ctx.statement = statement.summary();
+        match pbrt_statement(ctx, api_state, tokenizer, &mut statement) {
+            Ok(Some(world)) => {
+                ctx.collect_warnings(api_state, &statement);
+                return Ok(Some(world));
+            }
+            Ok(None) => ctx.collect_warnings(api_state, &statement),
+            Err(mut err) => {
+                ctx.statement = statement.summary();
+                err.locate(ctx.location(statement.line, statement.column));
+                return Err(err);
+            }
+        }
+    }
+}
+
+/// Included files are searched relative to the including file.
+fn pbrt_include(ctx: &mut ParseContext,
+                api_state: &mut ApiState,
+                filename: String,
+                statement: &Statement)
+                -> Result<Option<World>, ParseError> {
+    let directory: PathBuf = match ctx.include_stack.last() {
+        Some(including_file) => including_file.parent().unwrap().to_path_buf(),
+        None => {
+            if let Some(ref search_directory) = api_state.search_directory {
+                search_directory.as_ref().clone()
+            } else {
+                env::current_dir()?
+            }
+        }
+    };
+    let path: PathBuf = directory.join(filename);
+    let canonical_path: PathBuf = path.canonicalize()
+        .map_err(|e| io::Error::new(e.kind(), format!("{:?}: {}", path, e)))?;
+    if ctx.include_stack.contains(&canonical_path) {
+        let mut chain: Vec<PathBuf> = ctx.include_stack.clone();
+        chain.push(canonical_path);
+        return Err(ParseError::new(ParseErrorKind::IncludeCycle(chain)));
+    }
+    let reader: Box<BufRead> = fileutil::open_file(&canonical_path)?;
+    ctx.include_stack.push(canonical_path);
+    let result = parse_statements(ctx, api_state, &mut Tokenizer::new(reader));
+    ctx.include_stack.pop();
+    result.map_err(|mut err| {
+        // errors within the included file are already located
+        ctx.statement = statement.summary();
+        let location: Location = ctx.location(statement.line, statement.column);
+        err.included_from.push(location);
+        err
+    })
+}
+
+fn pbrt_statement<R: BufRead>(ctx: &mut ParseContext,
+                              api_state: &mut ApiState,
+                              tokenizer: &mut Tokenizer<R>,
+                              statement: &mut Statement)
+                              -> Result<Option<World>, ParseError> {
+    match statement.keyword.as_str() {
+        "Accelerator" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            let params: ParamSet = read_parameters(ctx, api_state, tokenizer)?;
+            pbrt_accelerator(api_state, name, params);
+        }
+        "ActiveTransform" => {
+            let token: Token = tokenizer.expect_token(ctx)?;
+            match token.kind {
+                TokenKind::Keyword(ref time) if time == "All" => {
+                    pbrt_active_transform_all(api_state)
+                }
+                TokenKind::Keyword(ref time) if time == "StartTime" => {
+                    pbrt_active_transform_start_time(api_state)
+                }
+                TokenKind::Keyword(ref time) if time == "EndTime" => {
+                    pbrt_active_transform_end_time(api_state)
+                }
+                ref kind => {
+                    return Err(ctx.syntax_error(format!("All, StartTime or EndTime expected, \
+                                                         found {}",
+                                                        kind),
+                                                token.line,
+                                                token.column));
+                }
+            }
+        }
+        "AreaLightSource" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            let params: ParamSet = read_parameters(ctx, api_state, tokenizer)?;
+            pbrt_area_light_source(api_state, name, params);
+        }
+        "AttributeBegin" => pbrt_attribute_begin(api_state),
+        "AttributeEnd" => pbrt_attribute_end(api_state),
+        "Camera" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            let params: ParamSet = read_parameters(ctx, api_state, tokenizer)?;
+            pbrt_camera(api_state, name, params);
+        }
+        "ConcatTransform" => {
+            let numbers: Vec<Float> = read_numbers(ctx, tokenizer, 16)?;
+            let mut tr: [Float; 16] = [0.0 as Float; 16];
+            tr.copy_from_slice(&numbers[0..16]);
+            pbrt_concat_transform(api_state, &tr);
+        }
+        "CoordinateSystem" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            pbrt_coordinate_system(api_state, name);
+        }
+        "CoordSysTransform" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            pbrt_coord_sys_transform(api_state, name);
+        }
+        "Film" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            let params: ParamSet = read_parameters(ctx, api_state, tokenizer)?;
+            pbrt_film(api_state, name, params);
+        }
+        "Identity" => pbrt_identity(api_state),
+        "Include" => {
+            let filename: String = read_name(ctx, tokenizer, statement)?;
+            return pbrt_include(ctx, api_state, filename, statement);
+        }
+        "Integrator" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            let params: ParamSet = read_parameters(ctx, api_state, tokenizer)?;
+            pbrt_integrator(api_state, name, params);
+        }
+        "LightSource" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            let params: ParamSet = read_parameters(ctx, api_state, tokenizer)?;
+            pbrt_light_source(api_state, name, params);
+        }
+        "LookAt" => {
+            let v: Vec<Float> = read_numbers(ctx, tokenizer, 9)?;
+            pbrt_look_at(api_state, v[0], v[1], v[2], v[3], v[4], v[5], v[6], v[7], v[8]);
+        }
+        "MakeNamedMaterial" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            let params: ParamSet = read_parameters(ctx, api_state, tokenizer)?;
+            pbrt_make_named_material(api_state, name, params)?;
+        }
+        "MakeNamedMedium" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            let params: ParamSet = read_parameters(ctx, api_state, tokenizer)?;
+            pbrt_make_named_medium(api_state, name, params)?;
+        }
+        "Material" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            let params: ParamSet = read_parameters(ctx, api_state, tokenizer)?;
+            pbrt_material(api_state, name, params);
+        }
+        "MediumInterface" => {
+            let inside_name: String = read_name(ctx, tokenizer, statement)?;
+            // the exterior medium is optional
+            let mut outside_name: String = inside_name.clone();
+            if tokenizer.next_is_string(ctx)? {
+                outside_name = read_string(ctx, tokenizer)?;
+            }
+            pbrt_medium_interface(api_state, inside_name, outside_name);
+        }
+        "NamedMaterial" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            pbrt_named_material(api_state, name);
+        }
+        "ObjectBegin" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            pbrt_object_begin(api_state, name)?;
+        }
+        "ObjectEnd" => pbrt_object_end(api_state)?,
+        "ObjectInstance" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            pbrt_object_instance(api_state, name)?;
+        }
+        "PixelFilter" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            let params: ParamSet = read_parameters(ctx, api_state, tokenizer)?;
+            pbrt_pixel_filter(api_state, name, params);
+        }
+        "ReverseOrientation" => pbrt_reverse_orientation(api_state),
+        "Rotate" => {
+            let v: Vec<Float> = read_numbers(ctx, tokenizer, 4)?;
+            pbrt_rotate(api_state, v[0], v[1], v[2], v[3]);
+        }
+        "Sampler" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            let params: ParamSet = read_parameters(ctx, api_state, tokenizer)?;
+            pbrt_sampler(api_state, name, params);
+        }
+        "Scale" => {
+            let v: Vec<Float> = read_numbers(ctx, tokenizer, 3)?;
+            pbrt_scale(api_state, v[0], v[1], v[2]);
+        }
+        "Shape" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            let params: ParamSet = read_parameters(ctx, api_state, tokenizer)?;
+            pbrt_shape(api_state, name, params)?;
+        }
+        "Texture" => {
+            let name: String = read_name(ctx, tokenizer, statement)?;
+            let tex_type: String = read_string(ctx, tokenizer)?;
+            let tex_name: String = read_string(ctx, tokenizer)?;
+            let params: ParamSet = read_parameters(ctx, api_state, tokenizer)?;
+            pbrt_texture(api_state, name, tex_type, tex_name, params)?;
+        }
+        "Transform" => {
+            let numbers: Vec<Float> = read_numbers(ctx, tokenizer, 16)?;
+            let mut tr: [Float; 16] = [0.0 as Float; 16];
+            tr.copy_from_slice(&numbers[0..16]);
+            pbrt_transform(api_state, &tr);
+        }
+        "TransformBegin" => pbrt_transform_begin(api_state),
+        "TransformEnd" => pbrt_transform_end(api_state),
+        "TransformTimes" => {
+            let v: Vec<Float> = read_numbers(ctx, tokenizer, 2)?;
+            pbrt_transform_times(api_state, v[0], v[1]);
+        }
+        "Translate" => {
+            let v: Vec<Float> = read_numbers(ctx, tokenizer, 3)?;
+            pbrt_translate(api_state, v[0], v[1], v[2]);
+        }
+        "WorldBegin" => pbrt_world_begin(api_state),
+        "WorldEnd" => {
+            let (scene, camera, sampler, integrator) = pbrt_world_end(api_state)?;
+            return Ok(Some(World {
+                               scene: scene,
+                               camera: camera,
+                               sampler: sampler,
+                               integrator: integrator,
+                               warnings: Vec::new(),
+                           }));
+        }
+        _ => {
+            return Err(ctx.syntax_error(format!("unknown directive {}", statement.keyword),
+                                        statement.line,
+                                        statement.column));
+        }
+    }
+    Ok(None)
+}
+
+fn read_string<R: BufRead>(ctx: &ParseContext,
+                           tokenizer: &mut Tokenizer<R>)
+                           -> Result<String, ParseError> {
+    let token: Token = tokenizer.expect_token(ctx)?;
+    match token.kind {
+        TokenKind::Str(text) => Ok(text),
+        kind => {
+            Err(ctx.syntax_error(format!("quoted string expected, found {}", kind),
+                                 token.line,
+                                 token.column))
+        }
+    }
+}
+
+/// Reads the first quoted string of a statement, which names it in
+/// errors and warnings.
+fn read_name<R: BufRead>(ctx: &mut ParseContext,
+                         tokenizer: &mut Tokenizer<R>,
+                         statement: &mut Statement)
+                         -> Result<String, ParseError> {
+    let name: String = read_string(ctx, tokenizer)?;
+    statement.name = Some(name.clone());
+    ctx.statement = statement.summary();
+    Ok(name)
+}
+
+fn parse_number(ctx: &ParseContext, token: &Token) -> Result<Float, ParseError> {
+    let number: Option<Float> = match token.kind {
+        TokenKind::Number(ref text) => Float::from_str(text).ok(),
+        _ => None,
+    };
+    match number {
+        Some(number) => Ok(number),
+        None => {
+            Err(ctx.syntax_error(format!("number expected, found {}", token.kind),
+                                 token.line,
+                                 token.column))
+        }
+    }
+}
+
+/// Reads the numbers of a transformation statement, optionally
+/// enclosed in brackets.
+fn read_numbers<R: BufRead>(ctx: &ParseContext,
+                            tokenizer: &mut Tokenizer<R>,
+                            n: usize)
+                            -> Result<Vec<Float>, ParseError> {
+    let brackets: bool = tokenizer.next_is_lbrack(ctx)?;
+    if brackets {
+        tokenizer.next_token(ctx)?;
+    }
+    let mut numbers: Vec<Float> = Vec::with_capacity(n);
+    for _ in 0..n {
+        let token: Token = tokenizer.expect_token(ctx)?;
+        numbers.push(parse_number(ctx, &token)?);
+    }
+    if brackets {
+        let token: Token = tokenizer.expect_token(ctx)?;
+        match token.kind {
+            TokenKind::RBrack => {}
+            kind => {
+                return Err(ctx.syntax_error(format!("] expected, found {}", kind),
+                                            token.line,
+                                            token.column));
+            }
+        }
+    }
+    Ok(numbers)
+}
+
+/// Reads a single value or a list of values in brackets, converting
+/// each token as soon as it's read.
+fn read_values<R, T, F>(ctx: &ParseContext,
+                        tokenizer: &mut Tokenizer<R>,
+                        convert: F)
+                        -> Result<Vec<T>, ParseError>
+    where R: BufRead,
+          F: Fn(&ParseContext, &Token) -> Result<T, ParseError>
+{
+    let mut values: Vec<T> = Vec::new();
+    if tokenizer.next_is_lbrack(ctx)? {
+        tokenizer.next_token(ctx)?;
+        loop {
+            let token: Token = tokenizer.expect_token(ctx)?;
+            if let TokenKind::RBrack = token.kind {
+                break;
+            }
+            values.push(convert(ctx, &token)?);
+        }
+    } else {
+        let token: Token = tokenizer.expect_token(ctx)?;
+        values.push(convert(ctx, &token)?);
+    }
+    Ok(values)
+}
+
+fn parse_integer(ctx: &ParseContext, token: &Token) -> Result<i32, ParseError> {
+    let integer: Option<i32> = match token.kind {
+        TokenKind::Number(ref text) => i32::from_str(text).ok(),
+        _ => None,
+    };
+    match integer {
+        Some(integer) => Ok(integer),
+        None => {
+            Err(ctx.syntax_error(format!("integer expected, found {}", token.kind),
+                                 token.line,
+                                 token.column))
+        }
+    }
+}
+
+fn parse_quoted(ctx: &ParseContext, token: &Token) -> Result<String, ParseError> {
+    match token.kind {
+        TokenKind::Str(ref text) => Ok(text.clone()),
+        ref kind => {
+            Err(ctx.syntax_error(format!("quoted string expected, found {}", kind),
+                                 token.line,
+                                 token.column))
+        }
+    }
+}
+
+/// Booleans can be given with or without quotes.
+fn parse_bool(ctx: &ParseContext, token: &Token) -> Result<String, ParseError> {
+    match token.kind {
+        TokenKind::Str(ref text) |
+        TokenKind::Keyword(ref text) => Ok(text.clone()),
+        ref kind => {
+            Err(ctx.syntax_error(format!("true or false expected, found {}", kind),
+                                 token.line,
+                                 token.column))
+        }
+    }
+}
+
+fn parse_any(_ctx: &ParseContext, _token: &Token) -> Result<(), ParseError> {
+    Ok(())
+}
+
+/// Reads the parameter list of a statement, e.g. **"float radius"
+/// 0.25** or **"point P" [ 0 0 0 1 0 0 ... ]**.
+fn read_parameters<R: BufRead>(ctx: &ParseContext,
+                               api_state: &mut ApiState,
+                               tokenizer: &mut Tokenizer<R>)
+                               -> Result<ParamSet, ParseError> {
+    let mut param_set: ParamSet = ParamSet::default();
+    while tokenizer.next_is_string(ctx)? {
+        let token: Token = tokenizer.expect_token(ctx)?;
+        let decl: String = parse_quoted(ctx, &token)?;
+        let words: Vec<&str> = decl.split_whitespace().collect();
+        if words.len() != 2 {
+            return Err(ctx.syntax_error(format!("parameter \"type name\" expected, found \"{}\"",
+                                                decl),
+                                        token.line,
+                                        token.column));
+        }
+        let param_type: &str = words[0];
+        let name: String = String::from(words[1]);
+        // wrong numbers of values are reported where the parameter starts
+        let error = |message: String| ctx.syntax_error(message, token.line, token.column);
+        match param_type {
+            "bool" => {
+                let values: Vec<String> = read_values(ctx, tokenizer, parse_bool)?;
+                let b: bool = if values.len() == 1 && values[0] == "true" {
+                    true
+                } else if values.len() == 1 && values[0] == "false" {
+                    false
+                } else {
+                    api_state.warning(format!("Parameter {:?} not well defined, defaulting to \
+                                               false",
+                                              name));
+                    false
+                };
+                param_set.add_bool(name, b);
+            }
+            "integer" => {
+                let integers: Vec<i32> = read_values(ctx, tokenizer, parse_integer)?;
+                if integers.len() == 1 {
+                    param_set.add_int(name, integers[0]);
+                } else {
+                    param_set.add_ints(name, integers);
+                }
+            }
+            "float" => {
+                let floats: Vec<Float> = read_values(ctx, tokenizer, parse_number)?;
+                if floats.len() == 1 {
+                    param_set.add_float(name, floats[0]);
+                } else {
+                    param_set.add_floats(name, floats);
+                }
+            }
+            "point2" | "vector2" => {
+                let floats: Vec<Float> = read_values(ctx, tokenizer, parse_number)?;
+                if floats.len() % 2 != 0 {
+                    return Err(error(format!("{} parameters need 2 coordinates", param_type)));
+                }
+                if param_type == "point2" {
+                    param_set.add_point2fs(name, floats);
+                } else {
+                    param_set.add_vector2fs(name, floats);
+                }
+            }
+            "point" | "point3" | "vector" | "vector3" | "normal" | "normal3" => {
+                let floats: Vec<Float> = read_values(ctx, tokenizer, parse_number)?;
+                if floats.len() % 3 != 0 {
+                    return Err(error(format!("{} parameters need 3 coordinates", param_type)));
+                }
+                if param_type.starts_with("point") {
+                    if floats.len() == 3 {
+                        param_set.add_point3f(name,
+                                              Point3f {
+                                                  x: floats[0],
+                                                  y: floats[1],
+                                                  z: floats[2],
+                                              });
+                    } else {
+                        param_set.add_point3fs(name, floats);
+                    }
+                } else if param_type.starts_with("vector") {
+                    if floats.len() == 3 {
+                        param_set.add_vector3f(name,
+                                               Vector3f {
+                                                   x: floats[0],
+                                                   y: floats[1],
+                                                   z: floats[2],
+                                               });
+                    } else {
+                        param_set.add_vector3fs(name, floats);
+                    }
+                } else {
+                    if floats.len() == 3 {
+                        param_set.add_normal3f(name,
+                                               Normal3f {
+                                                   x: floats[0],
+                                                   y: floats[1],
+                                                   z: floats[2],
+                                               });
+                    } else {
+                        param_set.add_normal3fs(name, floats);
+                    }
+                }
+            }
+            "rgb" | "color" => {
+                let floats: Vec<Float> = read_values(ctx, tokenizer, parse_number)?;
+                if floats.len() != 3 {
+                    return Err(error(format!("{} parameters need 3 values", param_type)));
+                }
+                param_set.add_rgb_spectrum(name, Spectrum { c: [floats[0], floats[1], floats[2]] });
+            }
+            "blackbody" => {
+                let floats: Vec<Float> = read_values(ctx, tokenizer, parse_number)?;
+                if floats.len() % 2 != 0 {
+                    return Err(error(String::from("blackbody parameters need temperature and \
+                                                   scale values")));
+                }
+                param_set.add_blackbody_spectrum(name, floats);
+            }
+            "string" | "texture" => {
+                let strings: Vec<String> = read_values(ctx, tokenizer, parse_quoted)?;
+                let value: String = match strings.into_iter().next() {
+                    Some(value) => value,
+                    None => return Err(error(format!("{} parameters need a value", param_type))),
+                };
+                if param_type == "string" {
+                    param_set.add_string(name, value);
+                } else {
+                    param_set.add_texture(name, value);
+                }
+            }
+            "spectrum" => {
+                // TODO: "spectrum" parameters
+                read_values(ctx, tokenizer, parse_any)?;
+                api_state.warning(format!("Parameter \"{}\" not supported yet, ignoring it",
+                                          decl));
+            }
+            _ => {
+                read_values(ctx, tokenizer, parse_any)?;
+                api_state.warning(format!("Unknown parameter type \"{}\", ignoring \"{}\"",
+                                          param_type,
+                                          name));
+            }
+        }
+    }
+    Ok(param_set)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::f32;
+    use std::process;
+    use super::*;
+    use core::exporter::SceneExporter;
+    use core::geometry::Ray;
+    use core::geometry::Point2f;
+    use core::interaction::{InteractionCommon, SurfaceInteraction};
+    use core::light::VisibilityTester;
+    use core::material::TransportMode;
+    use core::reflection::{Bsdf, BxdfType};
+    use std::sync::Arc;
+
+    /// Writes the files (relative name and content) of a scene into a
+    /// fresh temporary directory.
+    fn scene_dir(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir: PathBuf = env::temp_dir().join(format!("pbrt_parser_{}_{}", test, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for &(name, content) in files {
+            let path: PathBuf = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::File::create(&path).unwrap().write_all(content.as_bytes()).unwrap();
+        }
+        dir.canonicalize().unwrap()
+    }
+
+    fn world_with(statement: &str) -> String {
+        format!("WorldBegin\n{}\nWorldEnd\n", statement)
+    }
+
+    /// Kind (in debug format), line and column of all tokens.
+    fn tokens(text: &[u8]) -> Result<Vec<(String, usize, usize)>, ParseError> {
+        let ctx: ParseContext = ParseContext::new(Vec::new());
+        let mut tokenizer: Tokenizer<&[u8]> = Tokenizer::new(text);
+        let mut tokens: Vec<(String, usize, usize)> = Vec::new();
+        while let Some(token) = tokenizer.next_token(&ctx)? {
+            tokens.push((format!("{:?}", token.kind), token.line, token.column));
+        }
+        Ok(tokens)
+    }
+
+    /// Message, line and column of a syntax error.
+    fn syntax_error(result: Result<(), ParseError>) -> (String, usize, usize) {
+        match result {
+            Err(ParseError { kind: ParseErrorKind::Syntax(message),
+                             location: Some(location),
+                             .. }) => (message, location.line, location.column),
+            Err(err) => panic!("syntax error expected, found {}", err),
+            Ok(_) => panic!("syntax error not reported"),
+        }
+    }
+
+    #[test]
+    fn tokenize_comments() {
+        let text: &[u8] = b"# header\n\
+                            Shape \"sphere\" # \"ignored\" [\n\
+                            # another\n\
+                            \"float radius\"#\"ignored\"\n\
+                            [1]WorldEnd#";
+        assert_eq!(tokens(text).unwrap(),
+                   vec![(String::from("Keyword(\"Shape\")"), 2, 1),
+                        (String::from("Str(\"sphere\")"), 2, 7),
+                        (String::from("Str(\"float radius\")"), 4, 1),
+                        (String::from("LBrack"), 5, 1),
+                        (String::from("Number(\"1\")"), 5, 2),
+                        (String::from("RBrack"), 5, 3),
+                        (String::from("Keyword(\"WorldEnd\")"), 5, 4)]);
+    }
+
+    #[test]
+    fn tokenize_escaped_strings() {
+        let text: &[u8] = br##""a\"b" "c\\d" "e\nf\tg" "# [x]""##;
+        let kinds: Vec<String> = tokens(text).unwrap().into_iter().map(|t| t.0).collect();
+        assert_eq!(kinds,
+                   vec![String::from(r#"Str("a\"b")"#),
+                        String::from(r#"Str("c\\d")"#),
+                        String::from(r#"Str("e\nf\tg")"#),
+                        String::from(r##"Str("# [x]")"##)]);
+    }
+
+    #[test]
+    fn unterminated_string() {
+        let result = tokens(b"Shape \"sphere\n\"float radius\" 1").map(|_| ());
+        assert_eq!(syntax_error(result), (String::from("unterminated string"), 1, 7));
+        let result = tokens(b"Shape\n  \"sphere").map(|_| ());
+        assert_eq!(syntax_error(result), (String::from("unterminated string"), 2, 3));
+    }
+
+    #[test]
+    fn non_ascii_keyword() {
+        let result = tokens(b"Shape \"sph\xc3\xa4re\"\nW\xc3\xb6rldEnd").map(|_| ());
+        let (message, line, column) = syntax_error(result);
+        assert!(message.contains("non-ASCII"));
+        assert_eq!((line, column), (2, 2));
+        let result = tokens(b"\xc3\xa4").map(|_| ());
+        assert_eq!(syntax_error(result).1, 1);
+        // strings may contain UTF-8
+        assert_eq!(tokens(b"\"sph\xc3\xa4re\"").unwrap()[0].0,
+                   String::from("Str(\"sph\u{e4}re\")"));
+    }
+
+    #[test]
+    fn bracketless_single_values() {
+        let scene: String = world_with("Shape \"sphere\" \"float radius\" 2\n\
+                                        Translate 5 0 0\n\
+                                        Shape \"sphere\" \"float radius\" [2]");
+        let world: World = parse_string(&scene, None).unwrap();
+        for x in &[0.0, 5.0] {
+            let isect: SurfaceInteraction = world.scene.intersect(&mut ray_down(*x, 0.0)).unwrap();
+            assert_eq!(isect.p.z, 2.0);
+        }
+    }
+
+    #[test]
+    fn unterminated_array() {
+        let scene: &str = "WorldBegin\nShape \"sphere\" \"float radius\" [2\n";
+        let result = parse_string(scene, None).map(|_| ());
+        assert_eq!(syntax_error(result), (String::from("unexpected end of file"), 3, 1));
+        let scene: &str = "WorldBegin\nShape \"sphere\" \"float radius\" [2\nWorldEnd\n";
+        let (message, line, column) = syntax_error(parse_string(scene, None).map(|_| ()));
+        assert!(message.contains("WorldEnd"));
+        assert_eq!((line, column), (3, 1));
+    }
+
+    #[test]
+    fn include_relative_to_including_file() {
+        let main: String = world_with("Include \"geometry/sphere.pbrt\"");
+        let dir: PathBuf = scene_dir("relative",
+                                     &[("main.pbrt", main.as_str()),
+                                       ("geometry/sphere.pbrt", "Include \"shape.pbrt\"\n"),
+                                       ("geometry/shape.pbrt", "Shape \"sphere\"\n")]);
+        let world: World = parse_file(&dir.join("main.pbrt")).unwrap();
+        assert_eq!(world.scene.world_bound().p_max.x, 1.0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn include_from_string_uses_search_directory() {
+        let dir: PathBuf = scene_dir("string", &[("shape.pbrt", "Shape \"sphere\"\n")]);
+        let world: World = parse_string(&world_with("Include \"shape.pbrt\""), Some(&dir))
+            .unwrap();
+        assert_eq!(world.scene.world_bound().p_max.x, 1.0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn include_cycle() {
+        let main: String = world_with("Include \"a.pbrt\"");
+        let dir: PathBuf = scene_dir("cycle",
+                                     &[("main.pbrt", main.as_str()),
+                                       ("a.pbrt", "Include \"b.pbrt\"\n"),
+                                       ("b.pbrt", "Shape \"sphere\"\nInclude \"a.pbrt\"\n")]);
+        let err: ParseError = match parse_file(&dir.join("main.pbrt")) {
+            Ok(_) => panic!("include cycle not detected"),
+            Err(err) => err,
+        };
+        match err.kind {
+            ParseErrorKind::IncludeCycle(ref chain) => {
+                assert_eq!(*chain,
+                           vec![dir.join("main.pbrt"),
+                                dir.join("a.pbrt"),
+                                dir.join("b.pbrt"),
+                                dir.join("a.pbrt")]);
+            }
+            ref kind => panic!("include cycle expected, found {}", kind),
+        }
+        // the error is located at the innermost Include ...
+        let location: &Location = err.location.as_ref().unwrap();
+        assert_eq!(location.file, Some(dir.join("b.pbrt")));
+        assert_eq!(location.line, 2);
+        // ... and lists the Includes leading there
+        let included_from: Vec<(Option<PathBuf>, usize)> = err.included_from
+            .iter()
+            .map(|location| (location.file.clone(), location.line))
+            .collect();
+        assert_eq!(included_from,
+                   vec![(Some(dir.join("a.pbrt")), 1), (Some(dir.join("main.pbrt")), 2)]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn error_in_included_file() {
+        let main: String = world_with("Include \"shape.pbrt\"");
+        let dir: PathBuf = scene_dir("error",
+                                     &[("main.pbrt", main.as_str()),
+                                       ("shape.pbrt", "Shape \"sphere\"\nShpe \"sphere\"\n")]);
+        let err: ParseError = match parse_file(&dir.join("main.pbrt")) {
+            Ok(_) => panic!("unknown directive not reported"),
+            Err(err) => err,
+        };
+        let location: &Location = err.location.as_ref().unwrap();
+        assert_eq!(location.file, Some(dir.join("shape.pbrt")));
+        assert_eq!(location.line, 2);
+        assert_eq!(err.included_from.len(), 1);
+        assert_eq!(err.included_from[0].file, Some(dir.join("main.pbrt")));
+        assert!(format!("{}", err).contains("included from"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_scene_file() {
+        let dir: PathBuf = scene_dir("missing_scene", &[("main.pbrt", "")]);
+        match parse_file(&dir.join("none.pbrt")) {
+            Err(err) => assert!(format!("{}", err).contains("none.pbrt")),
+            Ok(_) => panic!("missing scene file not reported"),
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_include() {
+        let dir: PathBuf = scene_dir("missing", &[("main.pbrt", "Include \"none.pbrt\"\n")]);
+        match parse_file(&dir.join("main.pbrt")) {
+            Err(ParseError { kind: ParseErrorKind::Io(_), .. }) => {}
+            Err(err) => panic!("I/O error expected, found {}", err),
+            Ok(_) => panic!("missing include not reported"),
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// Ray straight down from above the scene.
+    fn ray_down(x: Float, y: Float) -> Ray {
+        Ray {
+            o: Point3f { x: x, y: y, z: 5.0 },
+            d: Vector3f {
+                x: 0.0,
+                y: 0.0,
+                z: -1.0,
+            },
+            t_max: f32::INFINITY,
+            time: 0.0,
+            differential: None,
+            medium: None,
+        }
+    }
+
+    /// Geometric normal where a ray straight down (next to the
+    /// sphere) hits the scene.
+    fn normal_below(world: &World) -> Normal3f {
+        world.scene.intersect(&mut ray_down(0.75, 0.75)).unwrap().n
+    }
+
+    /// Diffuse reflectance (red channel) where a ray straight down
+    /// hits the scene.
+    fn reflectance_below(world: &World, x: Float, y: Float) -> Float {
+        let ray: Ray = ray_down(x, y);
+        let mut isect: SurfaceInteraction = world.scene.intersect(&mut ray.clone()).unwrap();
+        isect.compute_scattering_functions(&ray, true, TransportMode::Radiance);
+        let bsdf: Arc<Bsdf> = isect.bsdf.clone().unwrap();
+        bsdf.f(&isect.wo, &isect.wo, BxdfType::BsdfAll as u8).c[0] * f32::consts::PI
+    }
+
+    #[test]
+    fn export_round_trip() {
+        let main: String = world_with("AttributeBegin\n\
+                                       ReverseOrientation\n\
+                                       AreaLightSource \"diffuse\"\n\
+                                       Shape \"trianglemesh\" \"integer indices\" [0 1 2 0 2 3]\n\
+                                       \"point P\" [-1 -1 0 1 -1 0 1 1 0 -1 1 0]\n\
+                                       AttributeEnd\n\
+                                       Shape \"sphere\" \"float radius\" 0.5\n\
+                                       AttributeBegin\n\
+                                       Translate 3 0 0\n\
+                                       Texture \"c\" \"spectrum\" \"checkerboard\"\n\
+                                       \"rgb tex1\" [0.5 0.5 0.5] \"rgb tex2\" [0.5 0.5 0.5]\n\
+                                       MakeNamedMaterial \"m\" \"string type\" \"matte\"\n\
+                                       \"texture Kd\" \"c\"\n\
+                                       AttributeBegin\n\
+                                       Texture \"c\" \"spectrum\" \"checkerboard\"\n\
+                                       \"rgb tex1\" [0.1 0.1 0.1] \"rgb tex2\" [0.1 0.1 0.1]\n\
+                                       MakeNamedMaterial \"m\" \"string type\" \"matte\"\n\
+                                       \"rgb Kd\" [0.9 0.9 0.9]\n\
+                                       NamedMaterial \"m\"\n\
+                                       Translate 0 3 0\n\
+                                       Shape \"sphere\" \"float radius\" 0.5\n\
+                                       AttributeEnd\n\
+                                       NamedMaterial \"m\"\n\
+                                       Shape \"sphere\" \"float radius\" 0.5\n\
+                                       Material \"matte\" \"texture Kd\" \"c\"\n\
+                                       Translate 0 -3 0\n\
+                                       Shape \"sphere\" \"float radius\" 0.5\n\
+                                       AttributeEnd");
+        let dir: PathBuf = scene_dir("export", &[("main.pbrt", main.as_str())]);
+        let exporter: SceneExporter = SceneExporter::create(&dir.join("export.pbrt")).unwrap();
+        let world: World = parse_file_and_export(&dir.join("main.pbrt"), exporter).unwrap();
+        // triangle meshes come back as PLY meshes
+        let exported: World = parse_file(&dir.join("export.pbrt")).unwrap();
+        assert_eq!(exported.scene.lights.len(), world.scene.lights.len());
+        assert_eq!(exported.scene.world_bound().p_min.x, world.scene.world_bound().p_min.x);
+        assert_eq!(exported.scene.world_bound().p_max.y, world.scene.world_bound().p_max.y);
+        // reversed meshes keep their orientation
+        let n: Normal3f = normal_below(&world);
+        assert!(n.z != 0.0);
+        assert_eq!(normal_below(&exported).z, n.z);
+        // redefined materials and textures are scoped by attribute
+        // blocks
+        let expected: [(Float, Float); 3] = [(3.0, 0.9), (0.0, 0.5), (-3.0, 0.5)];
+        for &(y, kd) in expected.iter() {
+            assert!((reflectance_below(&world, 3.0, y) - kd).abs() < 1e-5);
+            assert!((reflectance_below(&exported, 3.0, y) - kd).abs() < 1e-5);
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn instance_keeps_medium_interface() {
+        let scene: String = world_with("MakeNamedMedium \"fog\" \"string type\" \"homogeneous\"\n\
+                                        AttributeBegin\n\
+                                        MediumInterface \"fog\" \"\"\n\
+                                        ObjectBegin \"ball\"\n\
+                                        Shape \"sphere\"\n\
+                                        ObjectEnd\n\
+                                        AttributeEnd\n\
+                                        Translate 3 0 0\n\
+                                        ObjectInstance \"ball\"");
+        let world: World = parse_string(&scene, None).unwrap();
+        let isect: SurfaceInteraction = world.scene.intersect(&mut ray_down(3.0, 0.0)).unwrap();
+        assert_eq!(isect.p.z, 1.0);
+        assert!(isect.medium_interface.inside.is_some());
+        assert!(isect.medium_interface.outside.is_none());
+    }
+
+    /// Scene error (and its line) of a scene which must fail.
+    fn scene_error(scene: &str) -> (String, usize) {
+        match parse_string(scene, None) {
+            Err(ParseError { kind: ParseErrorKind::Scene(ref err),
+                             location: Some(ref location),
+                             .. }) => (format!("{}", err), location.line),
+            Err(err) => panic!("scene error expected, found {}", err),
+            Ok(_) => panic!("scene error not reported"),
+        }
+    }
+
+    #[test]
+    fn nested_object_begin() {
+        let scene: String = world_with("ObjectBegin \"outer\"\n\
+                                        ObjectBegin \"inner\"\n\
+                                        Shape \"sphere\"\n\
+                                        ObjectEnd\n\
+                                        ObjectEnd");
+        let (message, line) = scene_error(&scene);
+        assert!(message.contains("ObjectBegin called inside of instance definition"));
+        assert_eq!(line, 3);
+    }
+
+    #[test]
+    fn unknown_object_instance() {
+        let scene: String = world_with("ObjectBegin \"ball\"\n\
+                                        Shape \"sphere\"\n\
+                                        ObjectEnd\n\
+                                        ObjectInstance \"bal\"");
+        let (message, line) = scene_error(&scene);
+        assert!(message.contains("Unable to find instance named \"bal\""));
+        assert_eq!(line, 5);
+    }
+
+    #[test]
+    fn instance_transform() {
+        let scene: String = world_with("ObjectBegin \"ball\"\n\
+                                        Translate 1 0 0\n\
+                                        Shape \"sphere\" \"float radius\" 0.5\n\
+                                        ObjectEnd\n\
+                                        Translate 3 0 0\n\
+                                        Rotate 90 0 0 1\n\
+                                        Scale 2 2 2\n\
+                                        ObjectInstance \"ball\"");
+        let world: World = parse_string(&scene, None).unwrap();
+        // the sphere's center (1, 0, 0) is scaled, rotated and translated
+        // to (3, 2, 0), its radius is scaled to 1
+        let isect: SurfaceInteraction = world.scene.intersect(&mut ray_down(3.0, 2.0)).unwrap();
+        assert!((isect.p.z - 1.0).abs() < 1e-4);
+        assert!(isect.n.z.abs() > 0.999);
+        assert!(world.scene.intersect(&mut ray_down(3.0, 2.9)).is_some());
+        assert!(world.scene.intersect(&mut ray_down(1.0, 0.0)).is_none());
+        assert!(world.scene.intersect(&mut ray_down(5.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn trianglemesh_excess_indices() {
+        let scene: String = world_with("Shape \"trianglemesh\" \
+                                        \"integer indices\" [0 1 2 0 2 3 1]\n\
+                                        \"point P\" [-1 -1 0 1 -1 0 1 1 0 -1 1 0]");
+        let world: World = parse_string(&scene, None).unwrap();
+        assert!(world.warnings
+                    .iter()
+                    .any(|warning| warning.message.contains("Discarding 1 excess")));
+        assert!(world.scene.intersect(&mut ray_down(0.5, -0.5)).is_some());
+        assert!(world.scene.intersect(&mut ray_down(-0.5, 0.5)).is_some());
+    }
+
+    #[test]
+    fn trianglemesh_negative_face_index() {
+        let scene: String = world_with("Shape \"trianglemesh\" \"integer indices\" [0 1 2 0 2 3]\n\
+                                        \"point P\" [-1 -1 0 1 -1 0 1 1 0 -1 1 0]\n\
+                                        \"integer faceIndices\" [0 -1]");
+        let (message, line) = scene_error(&scene);
+        assert!(message.contains("negative face index -1"));
+        assert_eq!(line, 2);
+    }
+
+    #[test]
+    fn media_warning_for_bdpt() {
+        let scene: String = format!("Integrator \"bdpt\"\n{}",
+                                    world_with("MakeNamedMedium \"fog\" \"string type\" \
+                                                \"homogeneous\"\n\
+                                                MediumInterface \"\" \"fog\"\n\
+                                                Shape \"sphere\""));
+        let world: World = parse_string(&scene, None).unwrap();
+        assert!(world.warnings
+                    .iter()
+                    .any(|warning| warning.message.contains("doesn't support volume scattering")));
+    }
+
+    #[test]
+    fn unused_parameter_warnings() {
+        let scene: &str = "WorldBegin\n\
+                           LightSource \"point\"\n\
+                           Shape \"sphere\" \"float radius\" [1 2]\n\
+                           Shape \"sphere\" \"integer radius\" 1\n\
+                           Shape \"sphere\" \"float radus\" 1\n\
+                           WorldEnd\n";
+        let world: World = parse_string(scene, None).unwrap();
+        let warnings: Vec<(&str, usize, &str)> = world.warnings
+            .iter()
+            .map(|warning| {
+                     (warning.message.as_str(),
+                      warning.location.line,
+                      warning.location.statement.as_str())
+                 })
+            .collect();
+        assert_eq!(warnings,
+                   vec![("Parameter \"radius\" has an unexpected number of values (2)",
+                         3,
+                         "Shape \"sphere\""),
+                        ("Parameter \"radius\" given as \"integer\", expected \"float\"",
+                         4,
+                         "Shape \"sphere\""),
+                        ("Parameter \"radus\" not used", 5, "Shape \"sphere\"")]);
+    }
+
+    /// Position of the first light, as seen from the origin.
+    fn light_position(world: &World) -> Point3f {
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0;
+        let mut vis: VisibilityTester = VisibilityTester::default();
+        world.scene.lights[0].sample_li(&InteractionCommon::default(),
+                                        &Point2f::default(),
+                                        &mut wi,
+                                        &mut pdf,
+                                        &mut vis);
+        vis.p1.p
+    }
+
+    #[test]
+    fn point_light_from_and_scale() {
+        let scene: String = world_with("AttributeBegin\n\
+                                        Translate 0 0 1\n\
+                                        LightSource \"point\" \"point from\" [1 2 3]\n\
+                                        \"rgb I\" [1 1 1] \"rgb scale\" [2 2 2]\n\
+                                        AttributeEnd\n\
+                                        Shape \"sphere\"");
+        let world: World = parse_string(&scene, None).unwrap();
+        let p: Point3f = light_position(&world);
+        assert_eq!((p.x, p.y, p.z), (1.0, 2.0, 4.0));
+        assert_eq!(world.scene.lights[0].power().c[0], 8.0 * f32::consts::PI);
+        assert!(!world.warnings.iter().any(|warning| warning.message.contains("not used")));
+    }
+
+    #[test]
+    fn degenerate_pixel_bounds() {
+        let scene: String = format!("Integrator \"path\" \"integer pixelbounds\" [10 5 0 4]\n{}",
+                                    world_with("Shape \"sphere\""));
+        let world: World = parse_string(&scene, None).unwrap();
+        assert!(world.warnings
+                    .iter()
+                    .any(|warning| warning.message == "Degenerate \"pixelbounds\" specified."));
+    }
+}
@@ -13,6 +13,14 @@ pub type Spectrum = RGBSpectrum;
 
 pub type Float = f32;
 
+/// Options which control the rendering process, but are not part of
+/// the scene description.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Options {
+    /// number of threads to render with (0 means all available cores)
+    pub num_threads: u8,
+}
+
 pub const MACHINE_EPSILON: Float = std::f32::EPSILON * 0.5;
 pub const SHADOW_EPSILON: Float = 0.0001;
 pub const INV_PI: Float = 0.31830988618379067154;
@@ -255,3 +263,35 @@ pub fn quadratic(a: Float, b: Float, c: Float, t0: &mut Float, t1: &mut Float) -
         true
     }
 }
+
+/// Approximation of the inverse error function (used to sample the
+/// standard normal distribution).
+pub fn erf_inv(x: Float) -> Float {
+    let clamped_x: Float = clamp_t(x, -0.99999, 0.99999);
+    let mut w: Float = -((1.0 as Float - clamped_x) * (1.0 as Float + clamped_x)).ln();
+    let mut p: Float;
+    if w < 5.0 as Float {
+        w = w - 2.5 as Float;
+        p = 2.81022636e-08;
+        p = 3.43273939e-07 + p * w;
+        p = -3.5233877e-06 + p * w;
+        p = -4.39150654e-06 + p * w;
+        p = 0.00021858087 + p * w;
+        p = -0.00125372503 + p * w;
+        p = -0.00417768164 + p * w;
+        p = 0.246640727 + p * w;
+        p = 1.50140941 + p * w;
+    } else {
+        w = w.sqrt() - 3.0 as Float;
+        p = -0.000200214257;
+        p = 0.000100950558 + p * w;
+        p = 0.00134934322 + p * w;
+        p = -0.00367342844 + p * w;
+        p = 0.00573950773 + p * w;
+        p = -0.0076224613 + p * w;
+        p = 0.00943887047 + p * w;
+        p = 1.00167406 + p * w;
+        p = 2.83297682 + p * w;
+    }
+    p * clamped_x
+}
@@ -6,6 +6,7 @@ use core::geometry::nrm_dot_nrm;
 use core::interaction::SurfaceInteraction;
 use core::light::AreaLight;
 use core::material::{Material, TransportMode};
+use core::medium::MediumInterface;
 use core::pbrt::Float;
 use core::shape::Shape;
 use core::transform::{AnimatedTransform, Transform};
@@ -43,29 +44,24 @@ pub trait Primitive {
 
 pub struct GeometricPrimitive {
     pub shape: Arc<Shape + Send + Sync>,
+    /// **None** for shapes which only separate two media.
     pub material: Option<Arc<Material + Send + Sync>>,
     pub area_light: Option<Arc<AreaLight + Send + Sync>>,
-    // TODO: MediumInterface mediumInterface;
+    pub medium_interface: MediumInterface,
 }
 
 impl GeometricPrimitive {
     pub fn new(
         shape: Arc<Shape + Send + Sync>,
-        material: Arc<Material + Send + Sync>,
+        material: Option<Arc<Material + Send + Sync>>,
         area_light: Option<Arc<AreaLight + Send + Sync>>,
+        medium_interface: MediumInterface,
     ) -> Self {
-        if let Some(area_light) = area_light {
-            GeometricPrimitive {
-                shape: shape,
-                material: Some(material),
-                area_light: Some(area_light),
-            }
-        } else {
-            GeometricPrimitive {
-                shape: shape,
-                material: Some(material),
-                area_light: None,
-            }
+        GeometricPrimitive {
+            shape: shape,
+            material: material,
+            area_light: area_light,
+            medium_interface: medium_interface,
         }
     }
 }
@@ -78,6 +74,13 @@ impl Primitive for GeometricPrimitive {
         self.shape.intersect(ray).map(|(mut isect, t_hit)| {
             isect.primitive = Some(self.clone());
             ray.t_max = t_hit;
+            // initialize _SurfaceInteraction::mediumInterface_ after
+            // _Shape_ intersection
+            if self.medium_interface.is_medium_transition() {
+                isect.medium_interface = self.medium_interface.clone();
+            } else {
+                isect.medium_interface = MediumInterface::from_medium(ray.medium.clone());
+            }
             isect
         })
     }
@@ -131,7 +134,9 @@ impl Primitive for TransformedPrimitive {
         if let Some(isect) = self.primitive.intersect(&mut ray) {
             r.t_max = ray.t_max;
             // transform instance's intersection data to world space
-            if !interpolated_prim_to_world.is_identity() {
+            if interpolated_prim_to_world.is_identity() {
+                Some(isect)
+            } else {
                 let new_isect = interpolated_prim_to_world.transform_surface_interaction(&isect);
                 assert!(nrm_dot_nrm(&new_isect.n, &new_isect.shading.n) >= 0.0 as Float);
                 let mut is: SurfaceInteraction = SurfaceInteraction::new(
@@ -150,9 +155,9 @@ impl Primitive for TransformedPrimitive {
                 if let Some(primitive) = isect.primitive {
                     is.primitive = Some(primitive);
                 }
-                return Some(is);
+                is.medium_interface = new_isect.medium_interface;
+                Some(is)
             }
-            None
         } else {
             None
         }
@@ -2,6 +2,8 @@
 //! samplers but also provides some common functionality for use by
 //! **Sampler** implementations.
 
+// std
+use std::any::Any;
 // pbrt
 use core::camera::CameraSample;
 use core::geometry::{Point2f, Point2i};
@@ -9,7 +11,7 @@ use core::pbrt::Float;
 
 // see sampler.h
 
-pub trait Sampler: SamplerClone {
+pub trait Sampler: SamplerClone + SamplerAsAny {
     fn start_pixel(&mut self, p: &Point2i);
     fn get_1d(&mut self) -> Float;
     fn get_2d(&mut self) -> Point2f;
@@ -27,6 +29,9 @@ pub trait Sampler: SamplerClone {
     fn round_count(&self, count: i32) -> i32;
     fn get_2d_array(&mut self, n: i32) -> Vec<Point2f>;
     fn start_next_sample(&mut self) -> bool;
+    /// Like **start_next_sample()**, but jumps directly to the given
+    /// sample of the current pixel.
+    fn set_sample_number(&mut self, sample_num: i64) -> bool;
     fn reseed(&mut self, seed: u64);
     fn get_current_pixel(&self) -> Point2i;
     fn get_current_sample_number(&self) -> i64;
@@ -51,6 +56,20 @@ impl<T> SamplerClone for T
     }
 }
 
+/// Gives access to the concrete sampler behind a boxed **Sampler**
+/// (e.g. the **MLTSampler** needs to be told about mutations).
+pub trait SamplerAsAny {
+    fn as_any_mut(&mut self) -> &mut Any;
+}
+
+impl<T> SamplerAsAny for T
+    where T: 'static + Sampler
+{
+    fn as_any_mut(&mut self) -> &mut Any {
+        self
+    }
+}
+
 impl Clone for Box<Sampler + Send + Sync> {
     fn clone(&self) -> Box<Sampler + Send + Sync> {
         self.box_clone()
@@ -8,9 +8,11 @@ use std::sync::Arc;
 // pbrt
 use accelerators::bvh::BVHAccel;
 use core::geometry::{Bounds3f, Ray, Vector3f};
-use core::interaction::SurfaceInteraction;
+use core::interaction::{Interaction, SurfaceInteraction};
 use core::light::{Light, LightFlags};
+use core::pbrt::{Float, Spectrum};
 use core::primitive::Primitive;
+use core::sampler::Sampler;
 
 // see scene.h
 
@@ -73,4 +75,31 @@ impl Scene {
                    });
         self.aggregate.intersect_p(ray)
     }
+    /// Finds the first surface with a material along the ray
+    /// (skipping medium boundaries) and returns it together with the
+    /// beam transmittance up to that point.
+    pub fn intersect_tr(&self,
+                        ray: &mut Ray,
+                        sampler: &mut Box<Sampler + Send + Sync>)
+                        -> (Option<SurfaceInteraction>, Spectrum) {
+        let mut tr: Spectrum = Spectrum::new(1.0 as Float);
+        loop {
+            let isect_opt = self.intersect(ray);
+            // accumulate beam transmittance for ray segment
+            if let Some(ref medium) = ray.medium {
+                tr *= medium.tr(ray, sampler);
+            }
+            // initialize next ray segment or terminate transmittance computation
+            if let Some(isect) = isect_opt {
+                if let Some(primitive) = isect.primitive {
+                    if let Some(_material) = primitive.get_material() {
+                        return (Some(isect), tr);
+                    }
+                }
+                *ray = isect.spawn_ray(&ray.d);
+            } else {
+                return (None, tr);
+            }
+        }
+    }
 }
@@ -583,6 +583,14 @@ impl Transform {
             }
         }
     }
+    pub fn orthographic(z_near: Float, z_far: Float) -> Transform {
+        Transform::scale(1.0, 1.0, 1.0 / (z_far - z_near))
+            * Transform::translate(&Vector3f {
+                x: 0.0,
+                y: 0.0,
+                z: -z_near,
+            })
+    }
     pub fn perspective(fov: Float, n: Float, f: Float) -> Transform {
         // perform projective divide for perspective projection
         let persp = Matrix4x4::new(
@@ -685,6 +693,7 @@ impl Transform {
                 t_max: t_max,
                 time: r.time,
                 differential: Some(diff),
+                medium: r.medium.clone(),
             }
         } else {
             Ray {
@@ -693,6 +702,7 @@ impl Transform {
                 t_max: t_max,
                 time: r.time,
                 differential: None,
+                medium: r.medium.clone(),
             }
         }
     }
@@ -902,6 +912,7 @@ impl Transform {
             t_max: r.t_max,
             time: r.time,
             differential: None,
+            medium: r.medium.clone(),
         }
     }
     pub fn transform_surface_interaction(&self, si: &SurfaceInteraction) -> SurfaceInteraction {
@@ -929,6 +940,7 @@ impl Transform {
         ret.dvdy = si.dvdy;
         ret.dpdx = self.transform_vector(&si.dpdx);
         ret.dpdy = self.transform_vector(&si.dpdy);
+        ret.medium_interface = si.medium_interface.clone();
         ret.bsdf = si.bsdf.clone();
         ret.primitive = None; // TODO? si.primitive;
         ret.shading.n = nrm_faceforward_nrm(&ret.shading.n, &ret.n);
@@ -57,15 +57,16 @@ impl SamplerIntegrator for AOIntegrator {
             t_max: r.t_max,
             time: r.time,
             differential: r.differential,
+            medium: r.medium.clone(),
         };
         if let Some(mut isect) = scene.intersect(&mut ray) {
             let mode: TransportMode = TransportMode::Radiance;
             isect.compute_scattering_functions(&mut ray, true, mode);
-            // if (!isect.bsdf) {
-            //     VLOG(2) << "Skipping intersection due to null bsdf";
-            //     ray = isect.SpawnRay(ray.d);
-            //     goto retry;
-            // }
+            if isect.bsdf.is_none() {
+                // skipping intersection due to null bsdf
+                let mut new_ray: Ray = isect.spawn_ray(&ray.d);
+                return self.li(&mut new_ray, scene, sampler, _depth);
+            }
             // compute coordinate frame based on true geometry, not
             // shading geometry.
             let n: Normal3f = nrm_faceforward_vec3(&isect.n, &-ray.d);
@@ -2,20 +2,32 @@
 use std;
 use std::f32::consts::PI;
 use std::sync::Arc;
+use std::sync::mpsc;
+// others
+use crossbeam;
+use num_cpus;
+use pbr;
 // pbrt
 use core::camera::{Camera, CameraSample};
-use core::geometry::{Bounds2i, Bounds3f, Normal3f, Point2f, Point3f, Ray, Vector3f};
-use core::geometry::{nrm_abs_dot_vec3, pnt3_offset_ray_origin, vec3_abs_dot_nrm, vec3_normalize};
+use core::geometry::{Bounds2i, Bounds3f, Normal3f, Point2f, Point2i, Point3f, Ray, Vector2i,
+                     Vector3f};
+use core::geometry::{nrm_abs_dot_vec3, pnt2_inside_exclusive, pnt3_offset_ray_origin,
+                     vec3_abs_dot_nrm, vec3_normalize};
+use core::integrator::Integrator;
 use core::light::{Light, LightFlags, VisibilityTester};
 use core::light::is_delta_light;
+use core::lightdistrib::create_light_sample_distribution;
 use core::material::TransportMode;
+use core::medium::{Medium, MediumInterface, PhaseFunction};
 use core::primitive::Primitive;
 use core::interaction::{Interaction, InteractionCommon, SurfaceInteraction};
-use core::pbrt::{Float, Spectrum};
-use core::reflection::BxdfType;
+use core::interaction::medium_towards;
+use core::pbrt::{Float, Options, Spectrum};
+use core::reflection::{Bsdf, BxdfType};
 use core::sampler::Sampler;
 use core::sampling::Distribution1D;
 use core::scene::Scene;
+use BlockQueue;
 
 // see bdpt.h
 
@@ -27,6 +39,7 @@ pub struct EndpointInteraction<'a> {
     pub p_error: Vector3f,
     pub wo: Vector3f,
     pub n: Normal3f,
+    pub medium_interface: MediumInterface,
     // EndpointInteraction Public Data
     pub camera: Option<&'a Box<Camera + Send + Sync>>,
     pub light: Option<&'a Arc<Light + Send + Sync>>,
@@ -50,11 +63,13 @@ impl<'a> EndpointInteraction<'a> {
     }
     pub fn new_camera(camera: &'a Box<Camera + Send + Sync>, ray: &Ray) -> Self {
         let mut ei: EndpointInteraction = EndpointInteraction::new(&ray.o, ray.time);
+        ei.medium_interface = MediumInterface::from_medium(ray.medium.clone());
         ei.camera = Some(camera);
         ei
     }
     pub fn new_light(light: &'a Arc<Light + Send + Sync>, ray: &Ray, nl: &Normal3f) -> Self {
         let mut ei: EndpointInteraction = EndpointInteraction::new(&ray.o, ray.time);
+        ei.medium_interface = MediumInterface::from_medium(ray.medium.clone());
         ei.light = Some(light);
         ei.n = *nl;
         ei
@@ -69,11 +84,13 @@ impl<'a> EndpointInteraction<'a> {
         ei.p_error = it.p_error;
         ei.wo = it.wo;
         ei.n = it.n;
+        ei.medium_interface = it.medium_interface.clone();
         ei.light = Some(light);
         ei
     }
     pub fn new_ray(ray: &Ray) -> Self {
         let mut ei: EndpointInteraction = EndpointInteraction::new(&ray.o, ray.time);
+        ei.medium_interface = MediumInterface::from_medium(ray.medium.clone());
         ei.n = Normal3f::from(-ray.d);
         ei
     }
@@ -94,6 +111,7 @@ impl<'a> Interaction for EndpointInteraction<'a> {
             t_max: std::f32::INFINITY,
             time: self.time,
             differential: None,
+            medium: self.get_medium(d),
         }
     }
     fn get_p(&self) -> Point3f {
@@ -111,6 +129,28 @@ impl<'a> Interaction for EndpointInteraction<'a> {
     fn get_n(&self) -> Normal3f {
         self.n.clone()
     }
+    fn get_medium(&self, w: &Vector3f) -> Option<Arc<Medium + Send + Sync>> {
+        medium_towards(&self.medium_interface, &self.n, w)
+    }
+    fn get_common(&self) -> InteractionCommon {
+        InteractionCommon {
+            p: self.p,
+            time: self.time,
+            p_error: self.p_error,
+            wo: self.wo,
+            n: self.n,
+            medium_interface: self.medium_interface.clone(),
+        }
+    }
+    fn get_bsdf(&self) -> Option<Arc<Bsdf>> {
+        None
+    }
+    fn get_shading_n(&self) -> Option<Normal3f> {
+        None
+    }
+    fn get_phase(&self) -> Option<Arc<PhaseFunction + Send + Sync>> {
+        None
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -413,6 +453,7 @@ impl<'a, 'p, 's> Vertex<'a, 'p, 's> {
                     t_max: Float::default(),
                     time: Float::default(),
                     differential: None,
+                    medium: None,
                 };
                 le += light.le(&mut ray);
             }
@@ -528,6 +569,7 @@ impl<'a, 'p, 's> Vertex<'a, 'p, 's> {
                                 t_max: std::f32::INFINITY,
                                 time: self.time(),
                                 differential: None,
+                                medium: None,
                             },
                             &self.ng(),
                             &mut pdf_pos,
@@ -551,6 +593,7 @@ impl<'a, 'p, 's> Vertex<'a, 'p, 's> {
                                     t_max: std::f32::INFINITY,
                                     time: self.time(),
                                     differential: None,
+                                    medium: None,
                                 },
                                 &self.ng(),
                                 &mut pdf_pos,
@@ -610,6 +653,7 @@ impl<'a, 'p, 's> Vertex<'a, 'p, 's> {
                                         t_max: std::f32::INFINITY,
                                         time: self.time(),
                                         differential: None,
+                                        medium: None,
                                     },
                                     &self.ng(),
                                     &mut pdf_pos,
@@ -643,6 +687,7 @@ impl<'a, 'p, 's> Vertex<'a, 'p, 's> {
                                             t_max: std::f32::INFINITY,
                                             time: self.time(),
                                             differential: None,
+                                            medium: None,
                                         },
                                         &self.ng(),
                                         &mut pdf_pos,
@@ -693,6 +738,248 @@ impl BDPTIntegrator {
     }
 }
 
+impl Integrator for BDPTIntegrator {
+    fn render(
+        &mut self,
+        scene: &Scene,
+        camera: &Box<Camera + Send + Sync>,
+        sampler: &mut Box<Sampler + Send + Sync>,
+        options: &Options,
+    ) {
+        // TODO
+        // Compute a reverse mapping from light pointers to offsets into
+        // the scene lights vector (and, equivalently, offsets into
+        // lightDistr). Added after book text was finalized; this is
+        // critical to reasonable performance with 100s+ of light sources.
+        // let mut light_to_index = HashMap::new();
+        // for li in 0..scene.lights.len() {
+        //     let ref light = scene.lights[li];
+        //     light_to_index.insert(light, li);
+        // }
+        // partition the image into tiles
+        let film = camera.get_film();
+        let sample_bounds: Bounds2i = film.get_sample_bounds();
+        println!("sample_bounds = {:?}", sample_bounds);
+        let sample_extent: Vector2i = sample_bounds.diagonal();
+        println!("sample_extent = {:?}", sample_extent);
+        let tile_size: i32 = 16;
+        let n_x_tiles: i32 = (sample_extent.x + tile_size - 1) / tile_size;
+        let n_y_tiles: i32 = (sample_extent.y + tile_size - 1) / tile_size;
+        // TODO: ProgressReporter reporter(nXTiles * nYTiles, "Rendering");
+        // TODO: Allocate buffers for debug visualization
+        // ...
+        // render and write the output image to disk
+        if scene.lights.len() > 0 {
+            let samples_per_pixel: i64 = sampler.get_samples_per_pixel();
+            println!("samples_per_pixel = {:?}", samples_per_pixel);
+            let num_cores: usize;
+            if options.num_threads == 0_u8 {
+                num_cores = num_cpus::get();
+            } else {
+                num_cores = options.num_threads as usize;
+            }
+            println!("Rendering with {:?} thread(s) ...", num_cores);
+            {
+                let block_queue = BlockQueue::new(
+                    (
+                        (n_x_tiles * tile_size) as u32,
+                        (n_y_tiles * tile_size) as u32,
+                    ),
+                    (tile_size as u32, tile_size as u32),
+                    (0, 0),
+                );
+                println!("block_queue.len() = {}", block_queue.len());
+                let integrator = &*self;
+                let bq = &block_queue;
+                let sampler = sampler;
+                let camera = &camera;
+                let film = &film;
+                // let pixel_bounds = integrator.get_pixel_bounds().clone();
+                crossbeam::scope(|scope| {
+                    let (pixel_tx, pixel_rx) = mpsc::channel();
+                    // spawn worker threads
+                    for _ in 0..num_cores {
+                        let pixel_tx = pixel_tx.clone();
+                        let mut tile_sampler: Box<Sampler + Send + Sync> = sampler.box_clone();
+                        scope.spawn(move || {
+                            while let Some((x, y)) = bq.next() {
+                                let tile: Point2i = Point2i {
+                                    x: x as i32,
+                                    y: y as i32,
+                                };
+                                let seed: i32 = tile.y * n_x_tiles + tile.x;
+                                tile_sampler.reseed(seed as u64);
+                                let x0: i32 = sample_bounds.p_min.x + tile.x * tile_size;
+                                let x1: i32 = std::cmp::min(x0 + tile_size, sample_bounds.p_max.x);
+                                let y0: i32 = sample_bounds.p_min.y + tile.y * tile_size;
+                                let y1: i32 = std::cmp::min(y0 + tile_size, sample_bounds.p_max.y);
+                                let tile_bounds: Bounds2i = Bounds2i::new(
+                                    Point2i { x: x0, y: y0 },
+                                    Point2i { x: x1, y: y1 },
+                                );
+                                // println!("Starting image tile {:?}", tile_bounds);
+                                let mut film_tile = film.get_film_tile(&tile_bounds);
+                                for p_pixel in &tile_bounds {
+                                    tile_sampler.start_pixel(&p_pixel);
+                                    if !pnt2_inside_exclusive(&p_pixel, &integrator.pixel_bounds) {
+                                        continue;
+                                    }
+                                    let mut done: bool = false;
+                                    while !done {
+                                        // Get a distribution for sampling
+                                        // the light at the start of the
+                                        // light subpath. Because the
+                                        // light path follows multiple
+                                        // bounces, basing the sampling
+                                        // distribution on any of the
+                                        // vertices of the camera path is
+                                        // unlikely to be a good
+                                        // strategy. We use the
+                                        // PowerLightDistribution by
+                                        // default here, which doesn't use
+                                        // the point passed to it. Now
+                                        // trace the light subpath
+                                        if let Some(light_distribution) =
+                                            create_light_sample_distribution(
+                                                integrator.get_light_sample_strategy(),
+                                                scene,
+                                            )
+                                        {
+                                            // generate a single sample using BDPT
+                                            let p_film: Point2f = Point2f {
+                                                x: p_pixel.x as Float,
+                                                y: p_pixel.y as Float,
+                                            } + tile_sampler.get_2d();
+                                            // trace the camera subpath
+                                            let mut camera_vertices: Vec<Vertex> =
+                                                Vec::with_capacity(
+                                                    (integrator.max_depth + 2) as usize,
+                                                );
+                                            let mut n_camera;
+                                            let mut p;
+                                            let mut time;
+                                            {
+                                                let (n_camera_new, p_new, time_new) =
+                                                    generate_camera_subpath(
+                                                        scene,
+                                                        &mut tile_sampler,
+                                                        integrator.max_depth + 2,
+                                                        camera,
+                                                        &p_film,
+                                                        &mut camera_vertices,
+                                                    );
+                                                n_camera = n_camera_new;
+                                                p = p_new;
+                                                time = time_new;
+                                            }
+                                            let light_distr: Arc<Distribution1D> =
+                                                light_distribution.lookup(&p);
+                                            let mut light_vertices: Vec<Vertex> =
+                                                Vec::with_capacity(
+                                                    (integrator.max_depth + 1) as usize,
+                                                );
+                                            let mut n_light;
+                                            {
+                                                n_light = generate_light_subpath(
+                                                    scene,
+                                                    &mut tile_sampler,
+                                                    integrator.max_depth + 1,
+                                                    time,
+                                                    &light_distr,
+                                                    // light_to_index,
+                                                    &mut light_vertices,
+                                                );
+                                            }
+                                            // Execute all BDPT connection strategies
+                                            let mut l: Spectrum = Spectrum::new(0.0 as Float);
+                                            // println!("n_camera = {:?}", n_camera);
+                                            // println!("n_light = {:?}", n_light);
+                                            for t in 1..n_camera + 1 {
+                                                for s in 0..n_light + 1 {
+                                                    // int depth = t + s - 2;
+                                                    let depth: isize = (t + s) as isize - 2;
+                                                    if (s == 1 && t == 1)
+                                                        || depth < 0
+                                                        || depth > integrator.max_depth as isize
+                                                    {
+                                                        continue;
+                                                    }
+                                                    // execute the $(s, t)$ connection strategy and update _L_
+                                                    let mut p_film_new: Point2f = Point2f {
+                                                        x: p_film.x,
+                                                        y: p_film.y,
+                                                    };
+                                                    let mut mis_weight: Option<Float> =
+                                                        Some(0.0 as Float);
+                                                    let lpath: Spectrum = connect_bdpt(
+                                                        scene,
+                                                        &light_vertices,
+                                                        &camera_vertices,
+                                                        s,
+                                                        t,
+                                                        &light_distr,
+                                                        camera,
+                                                        &mut tile_sampler,
+                                                        &mut p_film_new,
+                                                        mis_weight.as_mut(),
+                                                    );
+                                                    // if let Some(mis_weight_flt) = mis_weight {
+                                                    //     println!("Connect bdpt s: {:?}, t: {:?}, lpath: {:?}, mis_weight: {:?}",
+                                                    //              s, t, lpath, mis_weight_flt);
+                                                    // }
+                                                    // if (visualizeStrategies || visualizeWeights) {
+                                                    //     Spectrum value;
+                                                    //     if (visualizeStrategies)
+                                                    //         value =
+                                                    //             mis_weight == 0 ? 0 : lpath / mis_weight;
+                                                    //     if (visualizeWeights) value = lpath;
+                                                    //     weightFilms[BufferIndex(s, t)]->AddSplat(
+                                                    //         pFilmNew, value);
+                                                    // }
+                                                    if t != 1 {
+                                                        l += lpath;
+                                                    } else {
+                                                        if !lpath.is_black() {
+                                                            film.add_splat(&p_film_new, &lpath);
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                            // println!(
+                                            //     "Add film sample pFilm: {:?}, L: {:?}, (y: {:?})",
+                                            //     p_film,
+                                            //     l,
+                                            //     l.y()
+                                            // );
+                                            film_tile.add_sample(&p_film, &mut l, 1.0 as Float);
+                                            done = !tile_sampler.start_next_sample();
+                                        }
+                                    }
+                                }
+                                // send the tile through the channel to main thread
+                                pixel_tx
+                                    .send(film_tile)
+                                    .expect(&format!("Failed to send tile"));
+                            }
+                        });
+                    }
+                    // spawn thread to collect pixels and render image to file
+                    scope.spawn(move || {
+                        for _ in pbr::PbIter::new(0..bq.len()) {
+                            let film_tile = pixel_rx.recv().unwrap();
+                            // merge image tile into _Film_
+                            film.merge_film_tile(&film_tile);
+                        }
+                    });
+                });
+            }
+            println!("Rendering finished");
+            film.write_image(1.0 as Float / samples_per_pixel as Float);
+            // TODO: Write buffers for debug visualization
+        }
+    }
+}
+
 // BDPT Utility Functions
 
 pub fn correct_shading_normal(
@@ -873,11 +1160,11 @@ pub fn random_walk<'a>(
             // compute scattering functions for _mode_ and skip over medium
             // boundaries
             isect.compute_scattering_functions(ray /*, arena, */, true, mode.clone());
-
-            // if (!isect.bsdf) {
-            //     ray = isect.SpawnRay(ray.d);
-            //     continue;
-            // }
+            if isect.bsdf.is_none() {
+                let new_ray = isect.spawn_ray(&ray.d);
+                *ray = new_ray;
+                continue;
+            }
 
             // initialize _vertex_ with surface intersection information
             let mut vertex: Vertex = Vertex::create_surface_interaction(
@@ -940,12 +1227,6 @@ pub fn random_walk<'a>(
                 path[(bounces - 1) as usize].pdf_rev = new_pdf_rev;
                 // store new vertex
                 path.push(vertex);
-            } else {
-                let new_ray = isect.spawn_ray(&ray.d);
-                *ray = new_ray;
-                // in C++ code ++bounces is called after continue !!!
-                bounces -= 1;
-                continue;
             }
         } else {
             // capture escaped rays when tracing from the camera
@@ -1096,6 +1377,7 @@ pub fn mis_weight<'a>(
                 p_error: lv_ei.p_error.clone(),
                 wo: lv_ei.wo.clone(),
                 n: lv_ei.n.clone(),
+                medium_interface: lv_ei.medium_interface.clone(),
                 camera: camera,
                 light: light,
             };
@@ -1142,6 +1424,7 @@ pub fn mis_weight<'a>(
                 p_error: lv_ei.p_error.clone(),
                 wo: lv_ei.wo.clone(),
                 n: lv_ei.n.clone(),
+                medium_interface: lv_ei.medium_interface.clone(),
                 camera: camera,
                 light: light,
             };
@@ -1192,6 +1475,7 @@ pub fn mis_weight<'a>(
                 p_error: cv_ei.p_error.clone(),
                 wo: cv_ei.wo.clone(),
                 n: cv_ei.n.clone(),
+                medium_interface: cv_ei.medium_interface.clone(),
                 camera: camera,
                 light: light,
             };
@@ -1241,6 +1525,7 @@ pub fn mis_weight<'a>(
                 p_error: lv_ei.p_error.clone(),
                 wo: lv_ei.wo.clone(),
                 n: lv_ei.n.clone(),
+                medium_interface: lv_ei.medium_interface.clone(),
                 camera: camera,
                 light: light,
             };
@@ -1308,6 +1593,7 @@ pub fn mis_weight<'a>(
                     p_error: cv_ei.p_error.clone(),
                     wo: cv_ei.wo.clone(),
                     n: cv_ei.n.clone(),
+                    medium_interface: cv_ei.medium_interface.clone(),
                     camera: camera,
                     light: light,
                 };
@@ -1376,6 +1662,7 @@ pub fn mis_weight<'a>(
                     p_error: lv_ei.p_error.clone(),
                     wo: lv_ei.wo.clone(),
                     n: lv_ei.n.clone(),
+                    medium_interface: lv_ei.medium_interface.clone(),
                     camera: camera,
                     light: light,
                 };
@@ -1537,52 +1824,11 @@ pub fn connect_bdpt<'a>(
             (s - 1),
             light_vertices.len()
         );
-        if light_vertices[s - 1].is_connectible() {
-            let mut iref: InteractionCommon = InteractionCommon::default();
-            // qs.GetInteraction()
-            match light_vertices[s - 1].vertex_type {
-                VertexType::Medium => {}
-                VertexType::Surface => {
-                    if let Some(ref si) = light_vertices[s - 1].si {
-                        iref.p = si.p;
-                        iref.time = si.time;
-                        iref.p_error = si.p_error;
-                        iref.wo = si.wo;
-                        iref.n = si.n;
-                    } else {
-                    }
-                }
-                _ => {}
-            }
-            let mut wi: Vector3f = Vector3f::default();
-            let mut pdf: Float = 0.0 as Float;
-            let mut vis: VisibilityTester = VisibilityTester::default();
-            let wi_color: Spectrum = camera.sample_wi(
-                &iref,
-                &sampler.get_2d(),
-                &mut wi,
-                &mut pdf,
-                p_raster,
-                &mut vis,
-            );
-            if pdf > 0.0 as Float && !wi_color.is_black() {
-                // initialize dynamically sampled vertex and _L_ for $t=1$ case
-                sampled =
-                    Vertex::create_camera_from_interaction(camera, &vis.p1, &(wi_color / pdf));
-                l = light_vertices[s - 1].beta
-                    * light_vertices[s - 1].f(&sampled, TransportMode::Importance)
-                    * sampled.beta;
-                // println!("l = {:?}", l);
-                if light_vertices[s - 1].is_on_surface() {
-                    l *= Spectrum::new(vec3_abs_dot_nrm(&wi, &light_vertices[s - 1].ns()));
-                }
-                assert!(!l.has_nans());
-                // only check visibility after we know that the path
-                // would make a non-zero contribution.
-                if !l.is_black() {
-                    l *= vis.tr(scene, sampler);
-                }
-            }
+        if let Some((l_camera, sampled_camera)) =
+            connect_to_camera(scene, &light_vertices[s - 1], camera, sampler, p_raster)
+        {
+            l = l_camera;
+            sampled = sampled_camera;
         }
     } else if s == 1 {
         // sample a point on a light and connect it to the camera subpath
@@ -1718,6 +1964,67 @@ pub fn connect_bdpt<'a>(
     l
 }
 
+/// The $t=1$ strategy without MIS weight: samples a point on the
+/// camera lens, connects it to the light subpath vertex **qs** and
+/// returns the unweighted contribution together with the sampled
+/// camera vertex. The raster position gets written to **p_raster**.
+pub fn connect_to_camera<'a>(
+    scene: &Scene,
+    qs: &Vertex<'a, 'a, 'a>,
+    camera: &'a Box<Camera + Send + Sync>,
+    sampler: &mut Box<Sampler + Send + Sync>,
+    p_raster: &mut Point2f,
+) -> Option<(Spectrum, Vertex<'a, 'a, 'a>)> {
+    if !qs.is_connectible() {
+        return None;
+    }
+    let mut iref: InteractionCommon = InteractionCommon::default();
+    // qs.GetInteraction()
+    match qs.vertex_type {
+        VertexType::Medium => {}
+        VertexType::Surface => {
+            if let Some(ref si) = qs.si {
+                iref.p = si.p;
+                iref.time = si.time;
+                iref.p_error = si.p_error;
+                iref.wo = si.wo;
+                iref.n = si.n;
+            } else {
+            }
+        }
+        _ => {}
+    }
+    let mut wi: Vector3f = Vector3f::default();
+    let mut pdf: Float = 0.0 as Float;
+    let mut vis: VisibilityTester = VisibilityTester::default();
+    let wi_color: Spectrum = camera.sample_wi(
+        &iref,
+        &sampler.get_2d(),
+        &mut wi,
+        &mut pdf,
+        p_raster,
+        &mut vis,
+    );
+    if pdf > 0.0 as Float && !wi_color.is_black() {
+        // initialize dynamically sampled vertex and _L_ for $t=1$ case
+        let sampled: Vertex =
+            Vertex::create_camera_from_interaction(camera, &vis.p1, &(wi_color / pdf));
+        let mut l: Spectrum = qs.beta * qs.f(&sampled, TransportMode::Importance) * sampled.beta;
+        if qs.is_on_surface() {
+            l *= Spectrum::new(vec3_abs_dot_nrm(&wi, &qs.ns()));
+        }
+        assert!(!l.has_nans());
+        // only check visibility after we know that the path
+        // would make a non-zero contribution.
+        if !l.is_black() {
+            l *= vis.tr(scene, sampler);
+        }
+        Some((l, sampled))
+    } else {
+        None
+    }
+}
+
 pub fn infinite_light_density<'a>(
     scene: &'a Scene,
     light_distr: &Arc<Distribution1D>,
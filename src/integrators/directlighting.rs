@@ -1,12 +1,10 @@
 // pbrt
-use core::geometry::{Bounds2i, Normal3f, Ray, RayDifferential, Vector3f};
-use core::geometry::{vec3_abs_dot_nrm, vec3_dot_nrm};
+use core::geometry::{Bounds2i, Ray, Vector3f};
 use core::integrator::SamplerIntegrator;
 use core::integrator::{uniform_sample_all_lights, uniform_sample_one_light};
-use core::interaction::{Interaction, SurfaceInteraction};
+use core::interaction::Interaction;
 use core::material::TransportMode;
 use core::pbrt::{Float, Spectrum};
-use core::reflection::BxdfType;
 use core::sampler::Sampler;
 use core::scene::Scene;
 
@@ -37,125 +35,6 @@ impl DirectLightingIntegrator {
             n_light_samples: Vec::new(),
         }
     }
-    pub fn specular_reflect(&self,
-                            ray: &Ray,
-                            isect: &SurfaceInteraction,
-                            scene: &Scene,
-                            sampler: &mut Box<Sampler + Send + Sync>,
-                            // arena: &mut Arena,
-                            depth: i32)
-                            -> Spectrum {
-        // compute specular reflection direction _wi_ and BSDF value
-        let wo: Vector3f = isect.wo;
-        let mut wi: Vector3f = Vector3f::default();
-        let mut pdf: Float = 0.0 as Float;
-        let ns: Normal3f = isect.shading.n;
-        let mut sampled_type: u8 = 0_u8;
-        let bsdf_flags: u8 = BxdfType::BsdfReflection as u8 | BxdfType::BsdfSpecular as u8;
-        let f: Spectrum;
-        if let Some(ref bsdf) = isect.bsdf {
-            f = bsdf.sample_f(&wo,
-                              &mut wi,
-                              &sampler.get_2d(),
-                              &mut pdf,
-                              bsdf_flags,
-                              &mut sampled_type);
-            if pdf > 0.0 as Float && !f.is_black() && vec3_abs_dot_nrm(&wi, &ns) != 0.0 as Float {
-                // compute ray differential _rd_ for specular reflection
-                let mut rd: Ray = isect.spawn_ray(&wi);
-                if let Some(d) = ray.differential.iter().next() {
-                    let dndx: Normal3f = isect.shading.dndu * isect.dudx +
-                                         isect.shading.dndv * isect.dvdx;
-                    let dndy: Normal3f = isect.shading.dndu * isect.dudy +
-                                         isect.shading.dndv * isect.dvdy;
-                    let dwodx: Vector3f = -d.rx_direction - wo;
-                    let dwody: Vector3f = -d.ry_direction - wo;
-                    let ddndx: Float = vec3_dot_nrm(&dwodx, &ns) + vec3_dot_nrm(&wo, &dndx);
-                    let ddndy: Float = vec3_dot_nrm(&dwody, &ns) + vec3_dot_nrm(&wo, &dndy);
-                    // compute differential reflected directions
-                    let diff: RayDifferential = RayDifferential {
-                        rx_origin: isect.p + isect.dpdx,
-                        ry_origin: isect.p + isect.dpdy,
-                        rx_direction: wi - dwodx +
-                                      Vector3f::from(dndx * vec3_dot_nrm(&wo, &ns) + ns * ddndx) *
-                                      2.0 as Float,
-                        ry_direction: wi - dwody +
-                                      Vector3f::from(dndy * vec3_dot_nrm(&wo, &ns) + ns * ddndy) *
-                                      2.0 as Float,
-                    };
-                    rd.differential = Some(diff);
-                }
-                return f * self.li(&mut rd, scene, sampler, depth + 1) *
-                       Spectrum::new(vec3_abs_dot_nrm(&wi, &ns) / pdf);
-            } else {
-                Spectrum::new(0.0)
-            }
-        } else {
-            Spectrum::new(0.0)
-        }
-    }
-    pub fn specular_transmit(&self,
-                             ray: &Ray,
-                             isect: &SurfaceInteraction,
-                             scene: &Scene,
-                             sampler: &mut Box<Sampler + Send + Sync>,
-                             // arena: &mut Arena,
-                             depth: i32)
-                             -> Spectrum {
-        let wo: Vector3f = isect.wo;
-        let mut wi: Vector3f = Vector3f::default();
-        let mut pdf: Float = 0.0 as Float;
-        // let p: Point3f = isect.p;
-        let ns: Normal3f = isect.shading.n;
-        let mut sampled_type: u8 = 0_u8;
-        let bsdf_flags: u8 = BxdfType::BsdfTransmission as u8 | BxdfType::BsdfSpecular as u8;
-        let f: Spectrum;
-        if let Some(ref bsdf) = isect.bsdf {
-            f = bsdf.sample_f(&wo,
-                              &mut wi,
-                              &sampler.get_2d(),
-                              &mut pdf,
-                              bsdf_flags,
-                              &mut sampled_type);
-            if pdf > 0.0 as Float && !f.is_black() && vec3_abs_dot_nrm(&wi, &ns) != 0.0 as Float {
-                // compute ray differential _rd_ for specular transmission
-                let mut rd: Ray = isect.spawn_ray(&wi);
-                if let Some(d) = ray.differential.iter().next() {
-                    let mut eta: Float = bsdf.eta;
-                    let w: Vector3f = -wo;
-                    if vec3_dot_nrm(&wo, &ns) < 0.0 as Float {
-                        eta = 1.0 / eta;
-                    }
-                    let dndx: Normal3f = isect.shading.dndu * isect.dudx +
-                                         isect.shading.dndv * isect.dvdx;
-                    let dndy: Normal3f = isect.shading.dndu * isect.dudy +
-                                         isect.shading.dndv * isect.dvdy;
-                    let dwodx: Vector3f = -d.rx_direction - wo;
-                    let dwody: Vector3f = -d.ry_direction - wo;
-                    let ddndx: Float = vec3_dot_nrm(&dwodx, &ns) + vec3_dot_nrm(&wo, &dndx);
-                    let ddndy: Float = vec3_dot_nrm(&dwody, &ns) + vec3_dot_nrm(&wo, &dndy);
-                    let mu: Float = eta * vec3_dot_nrm(&w, &ns) - vec3_dot_nrm(&wi, &ns);
-                    let dmudx: Float =
-                        (eta - (eta * eta * vec3_dot_nrm(&w, &ns)) / vec3_dot_nrm(&wi, &ns)) * ddndx;
-                    let dmudy: Float =
-                        (eta - (eta * eta * vec3_dot_nrm(&w, &ns)) / vec3_dot_nrm(&wi, &ns)) * ddndy;
-                    let diff: RayDifferential = RayDifferential {
-                        rx_origin: isect.p + isect.dpdx,
-                        ry_origin: isect.p + isect.dpdy,
-                        rx_direction: wi + dwodx * eta - Vector3f::from(dndx * mu + ns * dmudx),
-                        ry_direction: wi + dwody * eta - Vector3f::from(dndy * mu + ns * dmudy),
-                    };
-                    rd.differential = Some(diff);
-                }
-                return f * self.li(&mut rd, scene, sampler, depth + 1) *
-                       Spectrum::new(vec3_abs_dot_nrm(&wi, &ns) / pdf);
-            } else {
-                Spectrum::new(0.0)
-            }
-        } else {
-            Spectrum::new(0.0)
-        }
-    }
 }
 
 impl SamplerIntegrator for DirectLightingIntegrator {
@@ -190,8 +69,10 @@ impl SamplerIntegrator for DirectLightingIntegrator {
             // compute scattering functions for surface interaction
             let mode: TransportMode = TransportMode::Radiance;
             isect.compute_scattering_functions(ray /* arena, */, false, mode);
-            // if (!isect.bsdf)
-            //     return Li(isect.SpawnRay(ray.d), scene, sampler, arena, depth);
+            if isect.bsdf.is_none() {
+                let mut new_ray: Ray = isect.spawn_ray(&ray.d);
+                return self.li(&mut new_ray, scene, sampler, depth);
+            }
             let wo: Vector3f = isect.wo;
             l += isect.le(&wo);
             if scene.lights.len() > 0 {
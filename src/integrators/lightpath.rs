@@ -0,0 +1,154 @@
+// std
+use std;
+use std::sync::Arc;
+use std::sync::mpsc;
+// others
+use crossbeam;
+use num_cpus;
+use pbr;
+// pbrt
+use core::camera::Camera;
+use core::geometry::{Bounds2i, Point2f, Point2i, Vector2i};
+use core::integrator::Integrator;
+use core::integrator::compute_light_power_distribution;
+use core::pbrt::{Float, Options};
+use core::pbrt::lerp;
+use core::sampler::Sampler;
+use core::sampling::Distribution1D;
+use core::scene::Scene;
+use integrators::bdpt::Vertex;
+use integrators::bdpt::{connect_to_camera, generate_light_subpath};
+use BlockQueue;
+
+// see lightpath.h
+
+/// Light Tracing (paths are traced from the light sources only and
+/// connected to the camera)
+pub struct LightPathIntegrator {
+    pub max_depth: u32,
+}
+
+impl LightPathIntegrator {
+    pub fn new(max_depth: u32) -> Self {
+        LightPathIntegrator {
+            max_depth: max_depth,
+        }
+    }
+}
+
+impl Integrator for LightPathIntegrator {
+    fn render(
+        &mut self,
+        scene: &Scene,
+        camera: &Box<Camera + Send + Sync>,
+        sampler: &mut Box<Sampler + Send + Sync>,
+        options: &Options,
+    ) {
+        // partition the image into tiles
+        let film = camera.get_film();
+        let sample_bounds: Bounds2i = film.get_sample_bounds();
+        let sample_extent: Vector2i = sample_bounds.diagonal();
+        let tile_size: i32 = 16;
+        let n_x_tiles: i32 = (sample_extent.x + tile_size - 1) / tile_size;
+        let n_y_tiles: i32 = (sample_extent.y + tile_size - 1) / tile_size;
+        let samples_per_pixel: i64 = sampler.get_samples_per_pixel();
+        // compute _light_distr_ for sampling lights proportional to power
+        let light_distr: Option<Arc<Distribution1D>> = compute_light_power_distribution(scene);
+        if let Some(ref light_distr) = light_distr {
+            let num_cores: usize;
+            if options.num_threads == 0_u8 {
+                num_cores = num_cpus::get();
+            } else {
+                num_cores = options.num_threads as usize;
+            }
+            println!("Rendering with {:?} thread(s) ...", num_cores);
+            let block_queue = BlockQueue::new(
+                (
+                    (n_x_tiles * tile_size) as u32,
+                    (n_y_tiles * tile_size) as u32,
+                ),
+                (tile_size as u32, tile_size as u32),
+                (0, 0),
+            );
+            let max_depth: u32 = self.max_depth;
+            let bq = &block_queue;
+            let sampler = &sampler;
+            let film = &film;
+            crossbeam::scope(|scope| {
+                let (tile_tx, tile_rx) = mpsc::channel();
+                // spawn worker threads
+                for _ in 0..num_cores {
+                    let tile_tx = tile_tx.clone();
+                    let mut tile_sampler: Box<Sampler + Send + Sync> = sampler.box_clone();
+                    scope.spawn(move || {
+                        while let Some((x, y)) = bq.next() {
+                            let tile: Point2i = Point2i {
+                                x: x as i32,
+                                y: y as i32,
+                            };
+                            let seed: i32 = tile.y * n_x_tiles + tile.x;
+                            tile_sampler.reseed(seed as u64);
+                            let x0: i32 = sample_bounds.p_min.x + tile.x * tile_size;
+                            let x1: i32 = std::cmp::min(x0 + tile_size, sample_bounds.p_max.x);
+                            let y0: i32 = sample_bounds.p_min.y + tile.y * tile_size;
+                            let y1: i32 = std::cmp::min(y0 + tile_size, sample_bounds.p_max.y);
+                            let tile_bounds: Bounds2i =
+                                Bounds2i::new(Point2i { x: x0, y: y0 }, Point2i { x: x1, y: y1 });
+                            // the pixels only provide the sample streams,
+                            // one light path is traced per pixel sample
+                            for p_pixel in &tile_bounds {
+                                tile_sampler.start_pixel(&p_pixel);
+                                loop {
+                                    let time: Float = lerp(
+                                        tile_sampler.get_1d(),
+                                        camera.get_shutter_open(),
+                                        camera.get_shutter_close(),
+                                    );
+                                    let mut light_vertices: Vec<Vertex> =
+                                        Vec::with_capacity((max_depth + 1) as usize);
+                                    let n_light: usize = generate_light_subpath(
+                                        scene,
+                                        &mut tile_sampler,
+                                        max_depth + 1,
+                                        time,
+                                        light_distr,
+                                        &mut light_vertices,
+                                    );
+                                    // connect each vertex after the light
+                                    // source itself to the camera ($t=1$)
+                                    for s in 2..n_light + 1 {
+                                        let mut p_raster: Point2f = Point2f::default();
+                                        if let Some((l, _sampled)) = connect_to_camera(
+                                            scene,
+                                            &light_vertices[s - 1],
+                                            camera,
+                                            &mut tile_sampler,
+                                            &mut p_raster,
+                                        ) {
+                                            if !l.is_black() {
+                                                film.add_splat(&p_raster, &l);
+                                            }
+                                        }
+                                    }
+                                    if !tile_sampler.start_next_sample() {
+                                        break;
+                                    }
+                                }
+                            }
+                            // report the finished tile to the main thread
+                            tile_tx.send(tile).expect(&format!("Failed to send tile"));
+                        }
+                    });
+                }
+                // wait for the tiles to be finished
+                scope.spawn(move || {
+                    for _ in pbr::PbIter::new(0..bq.len()) {
+                        tile_rx.recv().unwrap();
+                    }
+                });
+            });
+        }
+        println!("Rendering finished");
+        film.write_image(1.0 as Float / samples_per_pixel as Float);
+    }
+}
@@ -0,0 +1,532 @@
+// std
+use std;
+use std::f32::consts::SQRT_2;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+// others
+use crossbeam;
+use num_cpus;
+use pbr;
+// pbrt
+use core::camera::Camera;
+use core::geometry::{Bounds2f, Bounds2i, Point2f, Point2i};
+use core::integrator::Integrator;
+use core::integrator::compute_light_power_distribution;
+use core::pbrt::{Float, Options, Spectrum};
+use core::pbrt::erf_inv;
+use core::rng::Rng;
+use core::sampler::Sampler;
+use core::sampling::Distribution1D;
+use core::scene::Scene;
+use integrators::bdpt::{connect_bdpt, generate_camera_subpath, generate_light_subpath, Vertex};
+
+// see mlt.h
+
+pub const CAMERA_STREAM_INDEX: u8 = 0;
+pub const LIGHT_STREAM_INDEX: u8 = 1;
+pub const CONNECTION_STREAM_INDEX: u8 = 2;
+pub const N_SAMPLE_STREAMS: u8 = 3;
+
+/// A single dimension of the primary sample space, together with a
+/// backup of its state before the current mutation.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct PrimarySample {
+    pub value: Float,
+    // MLTSampler Private Methods
+    pub last_modification_iteration: i64,
+    pub value_backup: Float,
+    pub modify_backup: i64,
+}
+
+impl PrimarySample {
+    pub fn backup(&mut self) {
+        self.value_backup = self.value;
+        self.modify_backup = self.last_modification_iteration;
+    }
+    pub fn restore(&mut self) {
+        self.value = self.value_backup;
+        self.last_modification_iteration = self.modify_backup;
+    }
+}
+
+/// Sampler for Primary Sample Space MLT. The sample values are
+/// mutated lazily (when they are requested) and interleaved between
+/// several streams (camera subpath, light subpath, connection).
+#[derive(Clone)]
+pub struct MLTSampler {
+    pub samples_per_pixel: i64,
+    pub rng: Rng,
+    pub sigma: Float,
+    pub large_step_probability: Float,
+    pub stream_count: u8,
+    pub x: Vec<PrimarySample>,
+    pub current_iteration: i64,
+    pub large_step: bool,
+    pub last_large_step_iteration: i64,
+    pub stream_index: u8,
+    pub sample_index: usize,
+    // inherited from class Sampler (see sampler.h)
+    pub current_pixel: Point2i,
+    pub current_pixel_sample_index: i64,
+}
+
+impl MLTSampler {
+    pub fn new(
+        mutations_per_pixel: i64,
+        rng_sequence_index: u64,
+        sigma: Float,
+        large_step_probability: Float,
+        stream_count: u8,
+    ) -> Self {
+        let mut rng: Rng = Rng::new();
+        rng.set_sequence(rng_sequence_index);
+        MLTSampler {
+            samples_per_pixel: mutations_per_pixel,
+            rng: rng,
+            sigma: sigma,
+            large_step_probability: large_step_probability,
+            stream_count: stream_count,
+            x: Vec::new(),
+            current_iteration: 0_i64,
+            large_step: true,
+            last_large_step_iteration: 0_i64,
+            stream_index: 0_u8,
+            sample_index: 0_usize,
+            current_pixel: Point2i::default(),
+            current_pixel_sample_index: 0_i64,
+        }
+    }
+    /// Decides whether the next mutation is a large step (all sample
+    /// values are replaced) or a small step (sample values are
+    /// perturbed).
+    pub fn start_iteration(&mut self) {
+        self.current_iteration += 1;
+        self.large_step = self.rng.uniform_float() < self.large_step_probability;
+    }
+    pub fn accept(&mut self) {
+        if self.large_step {
+            self.last_large_step_iteration = self.current_iteration;
+        }
+    }
+    /// Restores all sample values modified by the rejected mutation.
+    pub fn reject(&mut self) {
+        for xi in self.x.iter_mut() {
+            if xi.last_modification_iteration == self.current_iteration {
+                xi.restore();
+            }
+        }
+        self.current_iteration -= 1;
+    }
+    pub fn start_stream(&mut self, index: u8) {
+        assert!(index < self.stream_count);
+        self.stream_index = index;
+        self.sample_index = 0_usize;
+    }
+    pub fn get_next_index(&mut self) -> usize {
+        let index: usize =
+            self.stream_index as usize + self.stream_count as usize * self.sample_index;
+        self.sample_index += 1;
+        index
+    }
+    fn ensure_ready(&mut self, index: usize) {
+        // enlarge _MLTSampler::x_ if necessary and get current $\VEC{X}_i$
+        if index >= self.x.len() {
+            self.x.resize(index + 1, PrimarySample::default());
+        }
+        let xi: &mut PrimarySample = &mut self.x[index];
+        // reset $\VEC{X}_i$ if a large step took place in the meantime
+        if xi.last_modification_iteration < self.last_large_step_iteration {
+            xi.value = self.rng.uniform_float();
+            xi.last_modification_iteration = self.last_large_step_iteration;
+        }
+        // apply remaining sequence of mutations to _sample_
+        xi.backup();
+        if self.large_step {
+            xi.value = self.rng.uniform_float();
+        } else {
+            let n_small: i64 = self.current_iteration - xi.last_modification_iteration;
+            // apply _n_small_ small step mutations
+
+            // sample the standard normal distribution $N(0, 1)$
+            let normal_sample: Float =
+                SQRT_2 * erf_inv(2.0 as Float * self.rng.uniform_float() - 1.0 as Float);
+            // compute the effective standard deviation and apply
+            // perturbation to $\VEC{X}_i$
+            let eff_sigma: Float = self.sigma * (n_small as Float).sqrt();
+            xi.value += normal_sample * eff_sigma;
+            xi.value -= xi.value.floor();
+        }
+        xi.last_modification_iteration = self.current_iteration;
+    }
+}
+
+impl Sampler for MLTSampler {
+    fn start_pixel(&mut self, p: &Point2i) {
+        self.current_pixel = *p;
+        self.current_pixel_sample_index = 0_i64;
+    }
+    fn get_1d(&mut self) -> Float {
+        // TODO: ProfilePhase _(Prof::GetSample);
+        let index: usize = self.get_next_index();
+        self.ensure_ready(index);
+        self.x[index].value
+    }
+    fn get_2d(&mut self) -> Point2f {
+        // C++: call x first
+        let x = self.get_1d();
+        let y = self.get_1d();
+        Point2f { x: x, y: y }
+    }
+    fn request_2d_array(&mut self, _n: i32) {
+        panic!("MLTSampler::request_2d_array() is not supported");
+    }
+    fn round_count(&self, count: i32) -> i32 {
+        count
+    }
+    fn get_2d_array(&mut self, _n: i32) -> Vec<Point2f> {
+        // no sample arrays were requested
+        Vec::new()
+    }
+    fn start_next_sample(&mut self) -> bool {
+        self.current_pixel_sample_index += 1_i64;
+        self.current_pixel_sample_index < self.samples_per_pixel
+    }
+    fn set_sample_number(&mut self, sample_num: i64) -> bool {
+        self.current_pixel_sample_index = sample_num;
+        self.current_pixel_sample_index < self.samples_per_pixel
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.rng.set_sequence(seed);
+    }
+    fn get_current_pixel(&self) -> Point2i {
+        self.current_pixel
+    }
+    fn get_current_sample_number(&self) -> i64 {
+        self.current_pixel_sample_index
+    }
+    fn get_samples_per_pixel(&self) -> i64 {
+        self.samples_per_pixel
+    }
+}
+
+/// Returns the **MLTSampler** behind a boxed **Sampler** (panics for
+/// any other sampler).
+pub fn get_mlt_sampler(sampler: &mut Box<Sampler + Send + Sync>) -> &mut MLTSampler {
+    sampler
+        .as_any_mut()
+        .downcast_mut::<MLTSampler>()
+        .expect("MLTSampler expected")
+}
+
+/// Metropolis Light Transport (Primary Sample Space MLT on top of
+/// bidirectional path tracing)
+pub struct MLTIntegrator {
+    pub max_depth: u32,
+    pub n_bootstrap: u32,
+    pub n_chains: u32,
+    pub mutations_per_pixel: u32,
+    pub sigma: Float,
+    pub large_step_probability: Float,
+}
+
+impl MLTIntegrator {
+    pub fn new(
+        max_depth: u32,
+        n_bootstrap: u32,
+        n_chains: u32,
+        mutations_per_pixel: u32,
+        sigma: Float,
+        large_step_probability: Float,
+    ) -> Self {
+        MLTIntegrator {
+            max_depth: max_depth,
+            n_bootstrap: n_bootstrap,
+            n_chains: n_chains,
+            mutations_per_pixel: mutations_per_pixel,
+            sigma: sigma,
+            large_step_probability: large_step_probability,
+        }
+    }
+    /// Creates the sampler for the path with the given random number
+    /// sequence index.
+    pub fn create_sampler(&self, rng_sequence_index: u64) -> Box<Sampler + Send + Sync> {
+        Box::new(MLTSampler::new(
+            self.mutations_per_pixel as i64,
+            rng_sequence_index,
+            self.sigma,
+            self.large_step_probability,
+            N_SAMPLE_STREAMS,
+        ))
+    }
+    /// Computes the radiance of a single path with exactly **depth**
+    /// bounces, using one of the BDPT connection strategies. The
+    /// raster position of the path is returned in **p_raster**.
+    pub fn l<'a>(
+        &self,
+        scene: &'a Scene,
+        light_distr: &Arc<Distribution1D>,
+        sampler: &mut Box<Sampler + Send + Sync>,
+        camera: &'a Box<Camera + Send + Sync>,
+        depth: u32,
+        p_raster: &mut Point2f,
+    ) -> Spectrum {
+        get_mlt_sampler(sampler).start_stream(CAMERA_STREAM_INDEX);
+        // determine the number of available strategies and pick a specific one
+        let s: usize;
+        let t: usize;
+        let n_strategies: usize;
+        if depth == 0_u32 {
+            n_strategies = 1;
+            s = 0;
+            t = 2;
+        } else {
+            n_strategies = depth as usize + 2;
+            s = std::cmp::min(
+                (sampler.get_1d() * n_strategies as Float) as usize,
+                n_strategies - 1,
+            );
+            t = n_strategies - s;
+        }
+        // generate a camera subpath with exactly _t_ vertices
+        let mut camera_vertices: Vec<Vertex> = Vec::with_capacity(t);
+        let sample_bounds: Bounds2i = camera.get_film().get_sample_bounds();
+        let sample_bounds_f: Bounds2f = Bounds2f {
+            p_min: Point2f {
+                x: sample_bounds.p_min.x as Float,
+                y: sample_bounds.p_min.y as Float,
+            },
+            p_max: Point2f {
+                x: sample_bounds.p_max.x as Float,
+                y: sample_bounds.p_max.y as Float,
+            },
+        };
+        *p_raster = sample_bounds_f.lerp(&sampler.get_2d());
+        let (n_camera, _p, time) = generate_camera_subpath(
+            scene,
+            sampler,
+            t as u32,
+            camera,
+            p_raster,
+            &mut camera_vertices,
+        );
+        if n_camera != t {
+            return Spectrum::default();
+        }
+        // generate a light subpath with exactly _s_ vertices
+        get_mlt_sampler(sampler).start_stream(LIGHT_STREAM_INDEX);
+        let mut light_vertices: Vec<Vertex> = Vec::with_capacity(s);
+        let n_light: usize = generate_light_subpath(
+            scene,
+            sampler,
+            s as u32,
+            time,
+            light_distr,
+            &mut light_vertices,
+        );
+        if n_light != s {
+            return Spectrum::default();
+        }
+        // execute connection strategy and return the radiance estimate
+        get_mlt_sampler(sampler).start_stream(CONNECTION_STREAM_INDEX);
+        connect_bdpt(
+            scene,
+            &light_vertices,
+            &camera_vertices,
+            s,
+            t,
+            light_distr,
+            camera,
+            sampler,
+            p_raster,
+            None,
+        ) * (n_strategies as Float)
+    }
+}
+
+impl Integrator for MLTIntegrator {
+    fn render(
+        &mut self,
+        scene: &Scene,
+        camera: &Box<Camera + Send + Sync>,
+        _sampler: &mut Box<Sampler + Send + Sync>,
+        options: &Options,
+    ) {
+        // MLTIntegrator::Render (mlt.cpp)
+        let num_cores: usize;
+        if options.num_threads == 0_u8 {
+            num_cores = num_cpus::get();
+        } else {
+            num_cores = options.num_threads as usize;
+        }
+        let film = camera.get_film();
+        let light_distr: Option<Arc<Distribution1D>> = compute_light_power_distribution(scene);
+        if let Some(ref light_distr) = light_distr {
+            let max_depth: u32 = self.max_depth;
+            let n_chains: usize = self.n_chains as usize;
+            let mutations_per_pixel: i64 = self.mutations_per_pixel as i64;
+            // generate bootstrap samples and compute normalization constant $b$
+            let n_bootstrap: usize = self.n_bootstrap as usize;
+            let n_bootstrap_samples: usize = n_bootstrap * (max_depth + 1) as usize;
+            let mut bootstrap_weights: Vec<Float> = vec![0.0 as Float; n_bootstrap_samples];
+            println!(
+                "Generating bootstrap paths with {:?} thread(s) ...",
+                num_cores
+            );
+            {
+                let chunk_size: usize = 4096;
+                let next_index: AtomicUsize = AtomicUsize::new(0);
+                let next_index = &next_index;
+                let integrator = &*self;
+                let bootstrap_weights = &mut bootstrap_weights;
+                crossbeam::scope(|scope| {
+                    let (weight_tx, weight_rx) = mpsc::channel();
+                    // spawn worker threads
+                    for _ in 0..num_cores {
+                        let weight_tx = weight_tx.clone();
+                        scope.spawn(move || loop {
+                            let start: usize = next_index.fetch_add(chunk_size, Ordering::AcqRel);
+                            if start >= n_bootstrap {
+                                break;
+                            }
+                            let end: usize = std::cmp::min(start + chunk_size, n_bootstrap);
+                            let mut weights: Vec<(usize, Float)> =
+                                Vec::with_capacity((end - start) * (max_depth + 1) as usize);
+                            for i in start..end {
+                                for depth in 0..(max_depth + 1) {
+                                    let rng_index: usize =
+                                        i * (max_depth + 1) as usize + depth as usize;
+                                    let mut sampler: Box<Sampler + Send + Sync> =
+                                        integrator.create_sampler(rng_index as u64);
+                                    let mut p_raster: Point2f = Point2f::default();
+                                    let l: Spectrum = integrator.l(
+                                        scene,
+                                        light_distr,
+                                        &mut sampler,
+                                        camera,
+                                        depth,
+                                        &mut p_raster,
+                                    );
+                                    weights.push((rng_index, l.y()));
+                                }
+                            }
+                            weight_tx
+                                .send(weights)
+                                .expect(&format!("Failed to send bootstrap weights"));
+                        });
+                    }
+                    drop(weight_tx);
+                    // collect the bootstrap weights
+                    for weights in weight_rx {
+                        for (rng_index, weight) in weights {
+                            bootstrap_weights[rng_index] = weight;
+                        }
+                    }
+                });
+            }
+            let bootstrap: Distribution1D = Distribution1D::new(bootstrap_weights);
+            let b: Float = bootstrap.func_int * (max_depth + 1) as Float;
+            // run _n_chains_ Markov chains in parallel
+            let n_total_mutations: i64 =
+                mutations_per_pixel * film.get_sample_bounds().area() as i64;
+            println!(
+                "Running {:?} Markov chains with {:?} thread(s) ...",
+                n_chains, num_cores
+            );
+            {
+                let next_chain: AtomicUsize = AtomicUsize::new(0);
+                let next_chain = &next_chain;
+                let integrator = &*self;
+                let bootstrap = &bootstrap;
+                let film = &film;
+                crossbeam::scope(|scope| {
+                    let (chain_tx, chain_rx) = mpsc::channel();
+                    // spawn worker threads
+                    for _ in 0..num_cores {
+                        let chain_tx = chain_tx.clone();
+                        scope.spawn(move || loop {
+                            let i: usize = next_chain.fetch_add(1, Ordering::AcqRel);
+                            if i >= n_chains {
+                                break;
+                            }
+                            let n_chain_mutations: i64 = std::cmp::min(
+                                (i as i64 + 1) * n_total_mutations / n_chains as i64,
+                                n_total_mutations,
+                            ) - i as i64 * n_total_mutations
+                                / n_chains as i64;
+                            // follow {i}th Markov chain for _n_chain_mutations_
+                            let mut rng: Rng = Rng::new();
+                            rng.set_sequence(i as u64);
+                            // select initial state from the set of bootstrap samples
+                            let bootstrap_index: usize =
+                                bootstrap.sample_discrete(rng.uniform_float(), None);
+                            let depth: u32 = (bootstrap_index % (max_depth + 1) as usize) as u32;
+                            // initialize local variables for selected state
+                            let mut sampler: Box<Sampler + Send + Sync> =
+                                integrator.create_sampler(bootstrap_index as u64);
+                            let mut p_current: Point2f = Point2f::default();
+                            let mut l_current: Spectrum = integrator.l(
+                                scene,
+                                light_distr,
+                                &mut sampler,
+                                camera,
+                                depth,
+                                &mut p_current,
+                            );
+                            // run the Markov chain for _n_chain_mutations_ steps
+                            for _j in 0..n_chain_mutations {
+                                get_mlt_sampler(&mut sampler).start_iteration();
+                                let mut p_proposed: Point2f = Point2f::default();
+                                let l_proposed: Spectrum = integrator.l(
+                                    scene,
+                                    light_distr,
+                                    &mut sampler,
+                                    camera,
+                                    depth,
+                                    &mut p_proposed,
+                                );
+                                // compute acceptance probability for proposed sample
+                                let accept: Float =
+                                    (1.0 as Float).min(l_proposed.y() / l_current.y());
+                                // splat both current and proposed samples to _film_
+                                if accept > 0.0 as Float {
+                                    film.add_splat(
+                                        &p_proposed,
+                                        &(l_proposed * accept / l_proposed.y()),
+                                    );
+                                }
+                                film.add_splat(
+                                    &p_current,
+                                    &(l_current * (1.0 as Float - accept) / l_current.y()),
+                                );
+                                // accept or reject the proposal
+                                if rng.uniform_float() < accept {
+                                    p_current = p_proposed;
+                                    l_current = l_proposed;
+                                    get_mlt_sampler(&mut sampler).accept();
+                                } else {
+                                    get_mlt_sampler(&mut sampler).reject();
+                                }
+                            }
+                            chain_tx
+                                .send(i)
+                                .expect(&format!("Failed to send finished chain"));
+                        });
+                    }
+                    drop(chain_tx);
+                    // report progress of the Markov chains
+                    let mut progress = pbr::ProgressBar::new(n_chains as u64);
+                    for _i in chain_rx {
+                        progress.inc();
+                    }
+                    progress.finish();
+                });
+            }
+            // store final image computed with MLT
+            film.write_image(b / mutations_per_pixel as Float);
+        } else {
+            // no light sources, write a black image
+            film.write_image(1.0 as Float);
+        }
+    }
+}
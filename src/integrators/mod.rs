@@ -4,6 +4,7 @@
 //! - AOIntegrator
 //! - BDPTIntegrator
 //! - DirectLightingIntegrator
+//! - LightPathIntegrator
 //! - MLTIntegrator
 //! - PathIntegrator
 //! - SPPMIntegrator
@@ -32,14 +33,63 @@
 //!
 //! ![Direct Lighting](https://www.janwalter.org/assets/cornell_box_v0.1.13.png)
 //!
+//! ## Light Tracing
+//!
+//! The **LightPathIntegrator** only traces paths starting at the
+//! light sources. Every vertex of such a path gets connected to the
+//! camera and the contribution is splatted to the film. This renders
+//! caustics well and is a useful check of the camera's importance
+//! functions, but light sources themselves stay black.
+//!
+//! ## Metropolis Light Transport (MLT)
+//!
+//! The **MLTIntegrator** applies Metropolis sampling to the primary
+//! sample space of bidirectional path tracing. A bootstrap phase
+//! estimates the overall image brightness, then many Markov chains
+//! mutate their paths with small perturbations and occasional large
+//! steps, splatting every path to the film. This concentrates the
+//! work on paths that carry a lot of light, which pays off for hard
+//! indirect lighting, e.g. rooms lit through a small gap.
+//!
 //! ## Path Tracing
 //!
 //! Path tracing incrementally generates paths of scattering events
 //! starting at the camera and ending at light sources in the scene.
 //!
 //! ![Path Tracing](https://www.janwalter.org/assets/cornell_box_v0.2.0_high.png)
+//!
+//! ## Stochastic Progressive Photon Mapping (SPPM)
+//!
+//! Each iteration of the **SPPMIntegrator** first follows a camera
+//! path per pixel to a visible point on a diffuse surface, then
+//! shoots photons from the light sources and accumulates the photons
+//! arriving close to the visible points. The search radius shrinks
+//! from iteration to iteration, so the image converges, even for
+//! caustics seen through glass.
+//!
+//! ## Volumetric Path Tracing
+//!
+//! The **VolPathIntegrator** extends path tracing to scenes with
+//! participating media. At each step the medium the ray travels
+//! through is sampled first, and the path either scatters inside the
+//! medium (using its phase function) or continues at the next
+//! surface. Direct lighting accounts for the transmittance along
+//! shadow rays.
+//!
+//! ## Whitted Ray Tracing
+//!
+//! The **WhittedIntegrator** computes direct lighting from every
+//! light source and recursively traces rays for perfectly specular
+//! reflection and transmission. It ignores all other indirect
+//! illumination, which makes it a cheap preview for scenes with a lot
+//! of glass and mirrors.
 
 pub mod ao;
 pub mod bdpt;
 pub mod directlighting;
+pub mod lightpath;
+pub mod mlt;
 pub mod path;
+pub mod sppm;
+pub mod volpath;
+pub mod whitted;
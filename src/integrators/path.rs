@@ -69,6 +69,7 @@ impl SamplerIntegrator for PathIntegrator {
             t_max: r.t_max,
             time: r.time,
             differential: r.differential,
+            medium: r.medium.clone(),
         };
         let mut specular_bounce: bool = false;
         let mut bounces: u32 = 0_u32;
@@ -100,12 +101,12 @@ impl SamplerIntegrator for PathIntegrator {
                 // compute scattering functions and skip over medium boundaries
                 let mode: TransportMode = TransportMode::Radiance;
                 isect.compute_scattering_functions(&mut ray, true, mode);
-                // if (!isect.bsdf) {
-                //     VLOG(2) << "Skipping intersection due to null bsdf";
-                //     ray = isect.SpawnRay(ray.d);
-                //     bounces--;
-                //     continue;
-                // }
+                if isect.bsdf.is_none() {
+                    // skipping intersection due to null bsdf (without
+                    // counting it as a bounce)
+                    ray = isect.spawn_ray(&ray.d);
+                    continue;
+                }
                 if let Some(ref light_distribution) = self.light_distribution {
                     let distrib: Arc<Distribution1D> = light_distribution.lookup(&isect.p);
                     // Sample illumination from lights to find path contribution.
@@ -203,8 +204,6 @@ impl SamplerIntegrator for PathIntegrator {
                             beta = beta / (1.0 as Float - q);
                             assert!(!(beta.y().is_infinite()));
                         }
-                    } else {
-                        println!("TODO: if let Some(ref bsdf) = isect.bsdf failed");
                     }
                 }
             } else {
@@ -0,0 +1,617 @@
+// std
+use std;
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+// others
+use crossbeam;
+use num_cpus;
+use pbr;
+// pbrt
+use core::camera::{Camera, CameraSample};
+use core::geometry::{Bounds2i, Bounds3f, Normal3f, Point2f, Point2i, Point3f, Point3i, Ray,
+                     Vector2i, Vector3f};
+use core::geometry::{bnd3_expand, bnd3_union_bnd3, nrm_abs_dot_vec3, pnt3_distance_squared,
+                     vec3_abs_dot_nrm, vec3_max_component};
+use core::integrator::Integrator;
+use core::integrator::{compute_light_power_distribution, uniform_sample_one_light};
+use core::interaction::Interaction;
+use core::lowdiscrepancy::radical_inverse;
+use core::material::TransportMode;
+use core::parallel::AtomicFloat;
+use core::pbrt::{Float, Options, Spectrum};
+use core::pbrt::{clamp_t, lerp};
+use core::reflection::{Bsdf, BxdfType};
+use core::sampler::Sampler;
+use core::sampling::Distribution1D;
+use core::scene::Scene;
+use samplers::halton::HaltonSampler;
+use BlockQueue;
+
+// see sppm.h
+
+/// Stochastic Progressive Photon Mapping
+pub struct SPPMIntegrator {
+    pub initial_search_radius: Float,
+    pub n_iterations: i32,
+    pub max_depth: u32,
+    pub photons_per_iteration: i32,
+    pub write_frequency: i32,
+}
+
+impl SPPMIntegrator {
+    pub fn new(n_iterations: i32,
+               photons_per_iteration: i32,
+               max_depth: u32,
+               initial_search_radius: Float,
+               write_frequency: i32)
+               -> Self {
+        SPPMIntegrator {
+            initial_search_radius: initial_search_radius,
+            n_iterations: n_iterations,
+            max_depth: max_depth,
+            photons_per_iteration: photons_per_iteration,
+            write_frequency: write_frequency,
+        }
+    }
+}
+
+impl Integrator for SPPMIntegrator {
+    fn render(
+        &mut self,
+        scene: &Scene,
+        camera: &Box<Camera + Send + Sync>,
+        _sampler: &mut Box<Sampler + Send + Sync>,
+        options: &Options,
+    ) {
+        // SPPMIntegrator::Render (sppm.cpp)
+
+        // initialize _pixel_bounds_ and _pixels_ array for SPPM
+        let film = camera.get_film();
+        let pixel_bounds: Bounds2i = film.cropped_pixel_bounds;
+        let n_pixels: usize = pixel_bounds.area() as usize;
+        let mut pixels: Vec<SPPMPixel> = Vec::with_capacity(n_pixels);
+        for _i in 0..n_pixels {
+            let mut pixel: SPPMPixel = SPPMPixel::default();
+            pixel.radius = self.initial_search_radius;
+            pixels.push(pixel);
+        }
+        let n_iterations: i32 = self.n_iterations;
+        let max_depth: u32 = self.max_depth;
+        let photons_per_iteration: usize = self.photons_per_iteration as usize;
+        let inv_sqrt_spp: Float = 1.0 as Float / (n_iterations as Float).sqrt();
+        // compute _light_distr_ for sampling lights proportional to power
+        let light_distr: Option<Arc<Distribution1D>> = compute_light_power_distribution(scene);
+        // perform _n_iterations_ of SPPM integration
+        let sampler: Box<Sampler + Send + Sync> =
+            Box::new(HaltonSampler::new(n_iterations as i64, pixel_bounds, false));
+        // compute number of tiles to use for SPPM camera pass
+        let pixel_extent: Vector2i = pixel_bounds.diagonal();
+        let tile_size: i32 = 16;
+        let n_tiles: Point2i = Point2i {
+            x: (pixel_extent.x + tile_size - 1) / tile_size,
+            y: (pixel_extent.y + tile_size - 1) / tile_size,
+        };
+        let num_cores: usize;
+        if options.num_threads == 0_u8 {
+            num_cores = num_cpus::get();
+        } else {
+            num_cores = options.num_threads as usize;
+        }
+        println!("Rendering with {:?} thread(s) ...", num_cores);
+        let mut progress = pbr::ProgressBar::new(2 * n_iterations as u64);
+        for iter in 0..n_iterations {
+            // generate SPPM visible points
+            {
+                let block_queue = BlockQueue::new(
+                    (
+                        (n_tiles.x * tile_size) as u32,
+                        (n_tiles.y * tile_size) as u32,
+                    ),
+                    (tile_size as u32, tile_size as u32),
+                    (0, 0),
+                );
+                let bq = &block_queue;
+                let sampler = &sampler;
+                let pixels = &mut pixels;
+                crossbeam::scope(|scope| {
+                    let (vp_tx, vp_rx) = mpsc::channel();
+                    // spawn worker threads
+                    for _ in 0..num_cores {
+                        let vp_tx = vp_tx.clone();
+                        let mut tile_sampler: Box<Sampler + Send + Sync> = sampler.box_clone();
+                        scope.spawn(move || {
+                            while let Some((x, y)) = bq.next() {
+                                // follow camera paths for _tile_ in image for SPPM
+                                let tile: Point2i = Point2i {
+                                    x: x as i32,
+                                    y: y as i32,
+                                };
+                                let seed: i32 = tile.y * n_tiles.x + tile.x;
+                                tile_sampler.reseed(seed as u64);
+                                // compute _tile_bounds_ for SPPM tile
+                                let x0: i32 = pixel_bounds.p_min.x + tile.x * tile_size;
+                                let x1: i32 = std::cmp::min(x0 + tile_size, pixel_bounds.p_max.x);
+                                let y0: i32 = pixel_bounds.p_min.y + tile.y * tile_size;
+                                let y1: i32 = std::cmp::min(y0 + tile_size, pixel_bounds.p_max.y);
+                                let tile_bounds: Bounds2i = Bounds2i::new(
+                                    Point2i { x: x0, y: y0 },
+                                    Point2i { x: x1, y: y1 },
+                                );
+                                // pixel offset, direct lighting and visible point per pixel
+                                let mut tile_points: Vec<(usize, Spectrum, VisiblePoint)> =
+                                    Vec::new();
+                                for p_pixel in &tile_bounds {
+                                    // prepare _tile_sampler_ for _p_pixel_
+                                    tile_sampler.start_pixel(&p_pixel);
+                                    tile_sampler.set_sample_number(iter as i64);
+                                    // generate camera ray for pixel for SPPM
+                                    let camera_sample: CameraSample =
+                                        tile_sampler.get_camera_sample(&p_pixel);
+                                    let mut ray: Ray = Ray::default();
+                                    let ray_weight: Float =
+                                        camera.generate_ray_differential(&camera_sample, &mut ray);
+                                    if ray_weight == 0.0 as Float {
+                                        continue;
+                                    }
+                                    let mut beta: Spectrum = Spectrum::new(ray_weight);
+                                    ray.scale_differentials(inv_sqrt_spp);
+                                    // follow camera ray path until a visible point is created
+
+                                    // get _SPPMPixel_ for _p_pixel_
+                                    let p_pixel_o: Point2i = Point2i {
+                                        x: p_pixel.x - pixel_bounds.p_min.x,
+                                        y: p_pixel.y - pixel_bounds.p_min.y,
+                                    };
+                                    let pixel_offset: usize = (p_pixel_o.x
+                                        + p_pixel_o.y
+                                            * (pixel_bounds.p_max.x - pixel_bounds.p_min.x))
+                                        as usize;
+                                    let mut ld: Spectrum = Spectrum::default();
+                                    let mut vp: VisiblePoint = VisiblePoint::default();
+                                    let mut specular_bounce: bool = false;
+                                    let mut depth: u32 = 0_u32;
+                                    while depth < max_depth {
+                                        // TODO: ++totalPhotonSurfaceInteractions;
+                                        if let Some(mut isect) = scene.intersect(&mut ray) {
+                                            // process SPPM camera ray intersection
+
+                                            // compute BSDF at SPPM camera ray intersection
+                                            let mode: TransportMode = TransportMode::Radiance;
+                                            isect.compute_scattering_functions(&ray, true, mode);
+                                            if isect.bsdf.is_none() {
+                                                ray = isect.spawn_ray(&ray.d);
+                                                continue;
+                                            }
+                                            let bsdf: Arc<Bsdf> = isect.bsdf.clone().unwrap();
+                                            // accumulate direct illumination at
+                                            // SPPM camera ray intersection
+                                            let wo: Vector3f = -ray.d;
+                                            if depth == 0 || specular_bounce {
+                                                ld += beta * isect.le(&wo);
+                                            }
+                                            ld += beta
+                                                * uniform_sample_one_light(
+                                                    &isect,
+                                                    scene,
+                                                    &mut tile_sampler,
+                                                    false,
+                                                    None,
+                                                );
+                                            // possibly create visible point and end camera path
+                                            let is_diffuse: bool = bsdf.num_components(
+                                                BxdfType::BsdfDiffuse as u8
+                                                    | BxdfType::BsdfReflection as u8
+                                                    | BxdfType::BsdfTransmission as u8,
+                                            ) > 0;
+                                            let is_glossy: bool = bsdf.num_components(
+                                                BxdfType::BsdfGlossy as u8
+                                                    | BxdfType::BsdfReflection as u8
+                                                    | BxdfType::BsdfTransmission as u8,
+                                            ) > 0;
+                                            if is_diffuse || (is_glossy && depth == max_depth - 1) {
+                                                vp = VisiblePoint::new(
+                                                    &isect.p,
+                                                    &wo,
+                                                    Some(bsdf.clone()),
+                                                    &beta,
+                                                );
+                                                break;
+                                            }
+                                            // spawn ray from SPPM camera path vertex
+                                            if depth < max_depth - 1 {
+                                                let mut pdf: Float = 0.0 as Float;
+                                                let mut wi: Vector3f = Vector3f::default();
+                                                let mut sampled_type: u8 = 0_u8;
+                                                let f: Spectrum = bsdf.sample_f(
+                                                    &wo,
+                                                    &mut wi,
+                                                    &tile_sampler.get_2d(),
+                                                    &mut pdf,
+                                                    BxdfType::BsdfAll as u8,
+                                                    &mut sampled_type,
+                                                );
+                                                if pdf == 0.0 as Float || f.is_black() {
+                                                    break;
+                                                }
+                                                specular_bounce = (sampled_type
+                                                    & BxdfType::BsdfSpecular as u8)
+                                                    != 0_u8;
+                                                beta *= f * vec3_abs_dot_nrm(&wi, &isect.shading.n)
+                                                    / pdf;
+                                                if beta.y() < 0.25 as Float {
+                                                    let continue_prob: Float =
+                                                        (1.0 as Float).min(beta.y());
+                                                    if tile_sampler.get_1d() > continue_prob {
+                                                        break;
+                                                    }
+                                                    beta = beta / continue_prob;
+                                                }
+                                                ray = isect.spawn_ray(&wi);
+                                            }
+                                        } else {
+                                            // accumulate light contributions for
+                                            // ray with no intersection
+                                            for light in &scene.lights {
+                                                ld += beta * light.le(&mut ray);
+                                            }
+                                            break;
+                                        }
+                                        depth += 1_u32;
+                                    }
+                                    tile_points.push((pixel_offset, ld, vp));
+                                }
+                                // send the visible points through the channel to main thread
+                                vp_tx
+                                    .send(tile_points)
+                                    .expect(&format!("Failed to send visible points"));
+                            }
+                        });
+                    }
+                    // collect the visible points
+                    for _ in 0..bq.len() {
+                        let tile_points = vp_rx.recv().unwrap();
+                        for (pixel_offset, ld, vp) in tile_points {
+                            let pixel: &mut SPPMPixel = &mut pixels[pixel_offset];
+                            pixel.ld += ld;
+                            pixel.vp = vp;
+                        }
+                    }
+                });
+            }
+            progress.inc();
+            // create grid of all SPPM visible points
+            let mut grid_res: [i32; 3] = [1_i32; 3];
+            let mut grid_bounds: Bounds3f = Bounds3f::default();
+            // allocate grid for SPPM visible points
+            let hash_size: usize = n_pixels;
+            let mut grid: Vec<Vec<usize>> = vec![Vec::new(); hash_size];
+            {
+                // compute grid bounds for SPPM visible points
+                let mut max_radius: Float = 0.0 as Float;
+                for pixel in &pixels {
+                    if pixel.vp.beta.is_black() {
+                        continue;
+                    }
+                    let vp_bound: Bounds3f =
+                        bnd3_expand(&Bounds3f::new(pixel.vp.p, pixel.vp.p), pixel.radius);
+                    grid_bounds = bnd3_union_bnd3(&grid_bounds, &vp_bound);
+                    max_radius = max_radius.max(pixel.radius);
+                }
+                if max_radius > 0.0 as Float {
+                    // compute resolution of SPPM grid in each dimension
+                    let diag: Vector3f = grid_bounds.diagonal();
+                    let max_diag: Float = vec3_max_component(&diag);
+                    let base_grid_res: i32 = (max_diag / max_radius) as i32;
+                    assert!(base_grid_res > 0_i32);
+                    for i in 0..3 {
+                        grid_res[i] = std::cmp::max(
+                            (base_grid_res as Float * diag[i as u8] / max_diag) as i32,
+                            1,
+                        );
+                    }
+                }
+                // add visible points to SPPM grid (pbrt does this in
+                // parallel, using a lock-free linked list per grid cell)
+                for pixel_index in 0..n_pixels {
+                    let pixel: &SPPMPixel = &pixels[pixel_index];
+                    if !pixel.vp.beta.is_black() {
+                        // add pixel's visible point to applicable grid cells
+                        let radius: Float = pixel.radius;
+                        let mut p_min: Point3i = Point3i::default();
+                        let mut p_max: Point3i = Point3i::default();
+                        let r: Vector3f = Vector3f {
+                            x: radius,
+                            y: radius,
+                            z: radius,
+                        };
+                        to_grid(&(pixel.vp.p - r), &grid_bounds, &grid_res, &mut p_min);
+                        to_grid(&(pixel.vp.p + r), &grid_bounds, &grid_res, &mut p_max);
+                        for z in p_min.z..(p_max.z + 1) {
+                            for y in p_min.y..(p_max.y + 1) {
+                                for x in p_min.x..(p_max.x + 1) {
+                                    // add visible point to grid cell $(x, y, z)$
+                                    let h: usize = hash(&Point3i { x: x, y: y, z: z }, hash_size);
+                                    grid[h].push(pixel_index);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            // trace photons and accumulate contributions
+            if let Some(ref light_distr) = light_distr {
+                let chunk_size: usize = 8192;
+                let next_photon: AtomicUsize = AtomicUsize::new(0);
+                let next_photon = &next_photon;
+                let pixels = &pixels;
+                let grid = &grid;
+                let grid_bounds = &grid_bounds;
+                let grid_res = &grid_res;
+                crossbeam::scope(|scope| {
+                    // spawn worker threads
+                    for _ in 0..num_cores {
+                        scope.spawn(move || loop {
+                            let start: usize = next_photon.fetch_add(chunk_size, Ordering::AcqRel);
+                            if start >= photons_per_iteration {
+                                break;
+                            }
+                            let end: usize =
+                                std::cmp::min(start + chunk_size, photons_per_iteration);
+                            for photon_index in start..end {
+                                // follow photon path for _photon_index_
+                                let halton_index: u64 = iter as u64 * photons_per_iteration as u64
+                                    + photon_index as u64;
+                                let mut halton_dim: u16 = 0;
+                                // choose light to shoot photon from
+                                let mut light_pdf: Float = 0.0 as Float;
+                                let light_sample: Float = radical_inverse(halton_dim, halton_index);
+                                halton_dim += 1;
+                                let light_num: usize =
+                                    light_distr.sample_discrete(light_sample, Some(&mut light_pdf));
+                                let ref light = scene.lights[light_num];
+                                // compute sample values for photon ray leaving light source
+                                let u_light0: Point2f = Point2f {
+                                    x: radical_inverse(halton_dim, halton_index),
+                                    y: radical_inverse(halton_dim + 1, halton_index),
+                                };
+                                let u_light1: Point2f = Point2f {
+                                    x: radical_inverse(halton_dim + 2, halton_index),
+                                    y: radical_inverse(halton_dim + 3, halton_index),
+                                };
+                                let u_light_time: Float = lerp(
+                                    radical_inverse(halton_dim + 4, halton_index),
+                                    camera.get_shutter_open(),
+                                    camera.get_shutter_close(),
+                                );
+                                halton_dim += 5;
+                                // generate _photon_ray_ from light source and initialize _beta_
+                                let mut photon_ray: Ray = Ray::default();
+                                let mut n_light: Normal3f = Normal3f::default();
+                                let mut pdf_pos: Float = 0.0 as Float;
+                                let mut pdf_dir: Float = 0.0 as Float;
+                                let le: Spectrum = light.sample_le(
+                                    &u_light0,
+                                    &u_light1,
+                                    u_light_time,
+                                    &mut photon_ray,
+                                    &mut n_light,
+                                    &mut pdf_pos,
+                                    &mut pdf_dir,
+                                );
+                                if pdf_pos == 0.0 as Float
+                                    || pdf_dir == 0.0 as Float
+                                    || le.is_black()
+                                {
+                                    continue;
+                                }
+                                let mut beta: Spectrum = (le
+                                    * nrm_abs_dot_vec3(&n_light, &photon_ray.d))
+                                    / (light_pdf * pdf_pos * pdf_dir);
+                                if beta.is_black() {
+                                    continue;
+                                }
+                                // follow photon path through scene and record intersections
+                                let mut depth: u32 = 0_u32;
+                                while depth < max_depth {
+                                    if let Some(mut isect) = scene.intersect(&mut photon_ray) {
+                                        // TODO: ++totalPhotonSurfaceInteractions;
+                                        if depth > 0 {
+                                            // add photon contribution to nearby visible points
+                                            let mut photon_grid_index: Point3i = Point3i::default();
+                                            if to_grid(
+                                                &isect.p,
+                                                grid_bounds,
+                                                grid_res,
+                                                &mut photon_grid_index,
+                                            ) {
+                                                let h: usize = hash(&photon_grid_index, hash_size);
+                                                // add photon contribution to visible points in _grid[h]_
+                                                for pixel_index in &grid[h] {
+                                                    // TODO: ++visiblePointsChecked;
+                                                    let pixel: &SPPMPixel = &pixels[*pixel_index];
+                                                    let radius: Float = pixel.radius;
+                                                    if pnt3_distance_squared(&pixel.vp.p, &isect.p)
+                                                        > radius * radius
+                                                    {
+                                                        continue;
+                                                    }
+                                                    // update _pixel_ $\Phi$ and $M$ for nearby photon
+                                                    let wi: Vector3f = -photon_ray.d;
+                                                    if let Some(ref bsdf) = pixel.vp.bsdf {
+                                                        let phi: Spectrum = beta
+                                                            * bsdf.f(
+                                                                &pixel.vp.wo,
+                                                                &wi,
+                                                                BxdfType::BsdfAll as u8,
+                                                            );
+                                                        for i in 0..3 {
+                                                            pixel.phi[i].add(phi[i]);
+                                                        }
+                                                        pixel.m.fetch_add(1, Ordering::AcqRel);
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        // sample new photon ray direction
+
+                                        // compute BSDF at photon intersection point
+                                        let mode: TransportMode = TransportMode::Importance;
+                                        isect.compute_scattering_functions(&photon_ray, true, mode);
+                                        if isect.bsdf.is_none() {
+                                            photon_ray = isect.spawn_ray(&photon_ray.d);
+                                            continue;
+                                        }
+                                        if let Some(ref photon_bsdf) = isect.bsdf {
+                                            // sample BSDF _fr_ and direction _wi_ for reflected photon
+                                            let mut wi: Vector3f = Vector3f::default();
+                                            let wo: Vector3f = -photon_ray.d;
+                                            let mut pdf: Float = 0.0 as Float;
+                                            let mut flags: u8 = 0_u8;
+                                            // generate _bsdf_sample_ for outgoing photon sample
+                                            let bsdf_sample: Point2f = Point2f {
+                                                x: radical_inverse(halton_dim, halton_index),
+                                                y: radical_inverse(halton_dim + 1, halton_index),
+                                            };
+                                            halton_dim += 2;
+                                            let fr: Spectrum = photon_bsdf.sample_f(
+                                                &wo,
+                                                &mut wi,
+                                                &bsdf_sample,
+                                                &mut pdf,
+                                                BxdfType::BsdfAll as u8,
+                                                &mut flags,
+                                            );
+                                            if fr.is_black() || pdf == 0.0 as Float {
+                                                break;
+                                            }
+                                            let bnew: Spectrum =
+                                                beta * fr * vec3_abs_dot_nrm(&wi, &isect.shading.n)
+                                                    / pdf;
+                                            // possibly terminate photon path with Russian roulette
+                                            let q: Float = (0.0 as Float)
+                                                .max(1.0 as Float - bnew.y() / beta.y());
+                                            if radical_inverse(halton_dim, halton_index) < q {
+                                                break;
+                                            }
+                                            halton_dim += 1;
+                                            beta = bnew / (1.0 as Float - q);
+                                            photon_ray = isect.spawn_ray(&wi);
+                                        }
+                                    } else {
+                                        break;
+                                    }
+                                    depth += 1_u32;
+                                }
+                            }
+                        });
+                    }
+                });
+            }
+            progress.inc();
+            // update pixel values from this pass's photons
+            for p in pixels.iter_mut() {
+                let m: usize = p.m.load(Ordering::SeqCst);
+                if m > 0 {
+                    // update pixel photon count, search radius, and $\tau$ from photons
+                    let gamma: Float = 2.0 as Float / 3.0 as Float;
+                    let n_new: Float = p.n + gamma * m as Float;
+                    let r_new: Float = p.radius * (n_new / (p.n + m as Float)).sqrt();
+                    let mut phi: Spectrum = Spectrum::default();
+                    for j in 0..3 {
+                        phi[j] = Float::from(&p.phi[j]);
+                    }
+                    p.tau = (p.tau + p.vp.beta * phi) * (r_new * r_new) / (p.radius * p.radius);
+                    p.n = n_new;
+                    p.radius = r_new;
+                    p.m.store(0, Ordering::SeqCst);
+                    for j in 0..3 {
+                        p.phi[j] = AtomicFloat::default();
+                    }
+                }
+                // reset _VisiblePoint_ in pixel
+                p.vp.beta = Spectrum::default();
+                p.vp.bsdf = None;
+            }
+            // periodically store SPPM image in film and write image
+            if iter + 1 == n_iterations || ((iter + 1) % self.write_frequency) == 0 {
+                let x0: i32 = pixel_bounds.p_min.x;
+                let x1: i32 = pixel_bounds.p_max.x;
+                let np: u64 = (iter + 1) as u64 * photons_per_iteration as u64;
+                let mut image: Vec<Spectrum> = Vec::with_capacity(n_pixels);
+                for y in pixel_bounds.p_min.y..pixel_bounds.p_max.y {
+                    for x in x0..x1 {
+                        // compute radiance _l_ for SPPM pixel _pixel_
+                        let pixel: &SPPMPixel =
+                            &pixels[((y - pixel_bounds.p_min.y) * (x1 - x0) + (x - x0)) as usize];
+                        let mut l: Spectrum = pixel.ld / (iter + 1) as Float;
+                        l += pixel.tau / (np as Float * PI * pixel.radius * pixel.radius);
+                        image.push(l);
+                    }
+                }
+                film.set_image(&image);
+                film.write_image(1.0 as Float);
+                // TODO: Write SPPM radius image, if requested
+            }
+        }
+        progress.finish();
+    }
+}
+
+// see sppm.cpp
+
+/// The point found by following a camera path until it hits a
+/// diffuse (or glossy) surface.
+#[derive(Default, Clone)]
+pub struct VisiblePoint {
+    pub p: Point3f,
+    pub wo: Vector3f,
+    pub bsdf: Option<Arc<Bsdf>>,
+    pub beta: Spectrum,
+}
+
+impl VisiblePoint {
+    pub fn new(p: &Point3f, wo: &Vector3f, bsdf: Option<Arc<Bsdf>>, beta: &Spectrum) -> Self {
+        VisiblePoint {
+            p: *p,
+            wo: *wo,
+            bsdf: bsdf,
+            beta: *beta,
+        }
+    }
+}
+
+/// Accumulates the photons found around the visible point of a
+/// pixel. **phi** and **m** are updated by several threads during the
+/// photon pass.
+#[derive(Default)]
+pub struct SPPMPixel {
+    pub radius: Float,
+    pub ld: Spectrum,
+    pub vp: VisiblePoint,
+    pub phi: [AtomicFloat; 3],
+    pub m: AtomicUsize,
+    pub n: Float,
+    pub tau: Spectrum,
+}
+
+/// Computes the grid cell **pi** a point falls into and returns
+/// whether the point is inside the grid bounds (**pi** gets clamped
+/// to the grid otherwise).
+pub fn to_grid(p: &Point3f, bounds: &Bounds3f, grid_res: &[i32; 3], pi: &mut Point3i) -> bool {
+    let mut in_bounds: bool = true;
+    let pg: Vector3f = bounds.offset(p);
+    for i in 0..3 {
+        pi[i] = (grid_res[i as usize] as Float * pg[i]) as i32;
+        in_bounds &= pi[i] >= 0 && pi[i] < grid_res[i as usize];
+        pi[i] = clamp_t(pi[i], 0, grid_res[i as usize] - 1);
+    }
+    in_bounds
+}
+
+/// Maps a grid cell to one of **hash_size** hash table entries.
+pub fn hash(p: &Point3i, hash_size: usize) -> usize {
+    ((p.x.wrapping_mul(73856093) ^ p.y.wrapping_mul(19349663) ^ p.z.wrapping_mul(83492791)) as
+     u32 as usize) % hash_size
+}
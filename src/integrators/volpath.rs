@@ -0,0 +1,214 @@
+// std
+use std::borrow::Borrow;
+use std::sync::Arc;
+// pbrt
+use core::geometry::{Bounds2i, Ray, Vector3f};
+use core::geometry::{vec3_abs_dot_nrm, vec3_dot_nrm};
+use core::integrator::SamplerIntegrator;
+use core::integrator::uniform_sample_one_light;
+use core::interaction::{Interaction, MediumInteraction};
+use core::lightdistrib::LightDistribution;
+use core::lightdistrib::create_light_sample_distribution;
+use core::material::TransportMode;
+use core::pbrt::{Float, Spectrum};
+use core::reflection::BxdfType;
+use core::sampler::Sampler;
+use core::sampling::Distribution1D;
+use core::scene::Scene;
+
+// see volpath.h
+
+/// Volumetric Path Tracing (Global Illumination with participating
+/// media)
+pub struct VolPathIntegrator {
+    // inherited from SamplerIntegrator (see integrator.h)
+    pixel_bounds: Bounds2i,
+    // see volpath.h
+    max_depth: u32,
+    rr_threshold: Float, // 1.0
+    light_sample_strategy: String, // "spatial"
+    light_distribution: Option<Arc<LightDistribution + Send + Sync>>,
+}
+
+impl VolPathIntegrator {
+    pub fn new(max_depth: u32,
+               pixel_bounds: Bounds2i,
+               rr_threshold: Float,
+               light_sample_strategy: String)
+               -> Self {
+        VolPathIntegrator {
+            pixel_bounds: pixel_bounds,
+            max_depth: max_depth,
+            rr_threshold: rr_threshold,
+            light_sample_strategy: light_sample_strategy,
+            light_distribution: None,
+        }
+    }
+}
+
+impl SamplerIntegrator for VolPathIntegrator {
+    fn preprocess(&mut self, scene: &Scene, _sampler: &mut Box<Sampler + Send + Sync>) {
+        self.light_distribution =
+            create_light_sample_distribution(self.light_sample_strategy.clone(), scene);
+    }
+    fn li(&self,
+          r: &mut Ray,
+          scene: &Scene,
+          sampler: &mut Box<Sampler + Send + Sync>,
+          // arena: &mut Arena,
+          _depth: i32)
+          -> Spectrum {
+        // TODO: ProfilePhase p(Prof::SamplerIntegratorLi);
+        let mut l: Spectrum = Spectrum::default();
+        let mut beta: Spectrum = Spectrum::new(1.0 as Float);
+        let mut ray: Ray = Ray {
+            o: r.o,
+            d: r.d,
+            t_max: r.t_max,
+            time: r.time,
+            differential: r.differential,
+            medium: r.medium.clone(),
+        };
+        let mut specular_bounce: bool = false;
+        let mut bounces: u32 = 0_u32;
+        // Added after book publication: etaScale tracks the
+        // accumulated effect of radiance scaling due to rays passing
+        // through refractive boundaries (see the derivation on p. 527
+        // of the third edition). We track this value in order to
+        // remove it from beta when we apply Russian roulette.
+        let mut eta_scale: Float = 1.0;
+        loop {
+            // intersect _ray_ with scene and store intersection in _isect_
+            let found_intersection = scene.intersect(&mut ray);
+            // sample the participating medium, if present
+            let mut mi_opt: Option<MediumInteraction> = None;
+            if let Some(medium) = ray.medium.clone() {
+                let (spectrum, mi) = medium.sample(&ray, sampler);
+                beta *= spectrum;
+                mi_opt = mi;
+            }
+            if beta.is_black() {
+                break;
+            }
+            // handle an interaction with a medium or a surface
+            if let Some(mi) = mi_opt {
+                // terminate path if _maxDepth_ was reached
+                if bounces >= self.max_depth {
+                    break;
+                }
+                // TODO: ++volume_interactions;
+                // handle scattering at point in medium for volumetric path tracer
+                if let Some(ref light_distribution) = self.light_distribution {
+                    let distrib: Arc<Distribution1D> = light_distribution.lookup(&mi.p);
+                    l += beta *
+                         uniform_sample_one_light(&mi,
+                                                  scene,
+                                                  sampler,
+                                                  true,
+                                                  Some(Arc::borrow(&distrib)));
+                }
+                let wo: Vector3f = -ray.d;
+                let mut wi: Vector3f = Vector3f::default();
+                if let Some(ref phase) = mi.phase {
+                    phase.sample_p(&wo, &mut wi, &sampler.get_2d());
+                }
+                ray = mi.spawn_ray(&wi);
+                specular_bounce = false;
+            } else {
+                // TODO: ++surface_interactions;
+                // handle scattering at point on surface for volumetric path tracer
+
+                // possibly add emitted light at intersection
+                if bounces == 0 || specular_bounce {
+                    if let Some(ref isect) = found_intersection {
+                        l += beta * isect.le(&-ray.d);
+                    } else {
+                        for light in &scene.infinite_lights {
+                            l += beta * light.le(&mut ray);
+                        }
+                    }
+                }
+                // terminate path if ray escaped or _maxDepth_ was reached
+                if bounces >= self.max_depth {
+                    break;
+                }
+                if let Some(mut isect) = found_intersection {
+                    // compute scattering functions and skip over medium boundaries
+                    let mode: TransportMode = TransportMode::Radiance;
+                    isect.compute_scattering_functions(&mut ray, true, mode);
+                    if isect.bsdf.is_none() {
+                        // skipping intersection due to null bsdf (without
+                        // counting it as a bounce)
+                        ray = isect.spawn_ray(&ray.d);
+                        continue;
+                    }
+                    // sample illumination from lights to find attenuated
+                    // path contribution
+                    if let Some(ref light_distribution) = self.light_distribution {
+                        let distrib: Arc<Distribution1D> = light_distribution.lookup(&isect.p);
+                        l += beta *
+                             uniform_sample_one_light(&isect,
+                                                      scene,
+                                                      sampler,
+                                                      true,
+                                                      Some(Arc::borrow(&distrib)));
+                    }
+                    // sample BSDF to get new path direction
+                    if let Some(ref bsdf) = isect.bsdf {
+                        let wo: Vector3f = -ray.d;
+                        let mut wi: Vector3f = Vector3f::default();
+                        let mut pdf: Float = 0.0 as Float;
+                        let bsdf_flags: u8 = BxdfType::BsdfAll as u8;
+                        let mut sampled_type: u8 = u8::max_value(); // != 0
+                        let f: Spectrum = bsdf.sample_f(&wo,
+                                                        &mut wi,
+                                                        &sampler.get_2d(),
+                                                        &mut pdf,
+                                                        bsdf_flags,
+                                                        &mut sampled_type);
+                        if f.is_black() || pdf == 0.0 as Float {
+                            break;
+                        }
+                        beta *= (f * vec3_abs_dot_nrm(&wi, &isect.shading.n)) / pdf;
+                        assert!(!(beta.y().is_infinite()));
+                        specular_bounce = (sampled_type & BxdfType::BsdfSpecular as u8) != 0_u8;
+                        if ((sampled_type & BxdfType::BsdfSpecular as u8) != 0_u8) &&
+                           ((sampled_type & BxdfType::BsdfTransmission as u8) != 0_u8) {
+                            let eta: Float = bsdf.eta;
+                            // Update the term that tracks radiance
+                            // scaling for refraction depending on
+                            // whether the ray is entering or leaving
+                            // the medium.
+                            if vec3_dot_nrm(&wo, &isect.n) > 0.0 as Float {
+                                eta_scale *= eta * eta;
+                            } else {
+                                eta_scale *= 1.0 as Float / (eta * eta);
+                            }
+                        }
+                        ray = isect.spawn_ray(&wi);
+                        // TODO: Account for attenuated subsurface scattering, if applicable
+                    }
+                } else {
+                    // terminate path if ray escaped
+                    break;
+                }
+            }
+            // Possibly terminate the path with Russian roulette.
+            // Factor out radiance scaling due to refraction in rr_beta.
+            let rr_beta: Spectrum = beta * eta_scale;
+            if rr_beta.max_component_value() < self.rr_threshold && bounces > 3 {
+                let q: Float = (0.05 as Float).max(1.0 as Float - rr_beta.max_component_value());
+                if sampler.get_1d() < q {
+                    break;
+                }
+                beta = beta / (1.0 as Float - q);
+                assert!(!(beta.y().is_infinite()));
+            }
+            bounces += 1_u32;
+        }
+        l
+    }
+    fn get_pixel_bounds(&self) -> Bounds2i {
+        self.pixel_bounds
+    }
+}
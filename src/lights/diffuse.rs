@@ -7,6 +7,7 @@ use core::geometry::{Normal3f, Point2f, Ray, Vector3f};
 use core::geometry::{nrm_abs_dot_vec3, nrm_dot_vec3, vec3_coordinate_system, vec3_normalize};
 use core::interaction::{Interaction, InteractionCommon};
 use core::light::{AreaLight, Light, LightFlags, VisibilityTester};
+use core::medium::MediumInterface;
 use core::pbrt::{Float, Spectrum};
 use core::rng::FLOAT_ONE_MINUS_EPSILON;
 use core::sampling::{cosine_hemisphere_pdf, cosine_sample_hemisphere};
@@ -24,7 +25,7 @@ pub struct DiffuseAreaLight {
     // inherited from class Light (see light.h)
     flags: u8,
     n_samples: i32,
-    // TODO: const MediumInterface mediumInterface;
+    medium_interface: MediumInterface,
     // light_to_world: Transform,
     // world_to_light: Transform,
 }
@@ -32,6 +33,7 @@ pub struct DiffuseAreaLight {
 impl DiffuseAreaLight {
     pub fn new(
         _light_to_world: &Transform,
+        medium_interface: &MediumInterface,
         l_emit: &Spectrum,
         n_samples: i32,
         shape: Arc<Shape + Send + Sync>,
@@ -46,7 +48,7 @@ impl DiffuseAreaLight {
             // inherited from class Light (see light.h)
             flags: LightFlags::Area as u8,
             n_samples: std::cmp::max(1_i32, n_samples),
-            // TODO: const MediumInterface mediumInterface;
+            medium_interface: medium_interface.clone(),
             // light_to_world: *light_to_world,
             // world_to_light: Transform::inverse(*light_to_world),
         }
@@ -63,28 +65,16 @@ impl Light for DiffuseAreaLight {
         vis: &mut VisibilityTester,
     ) -> Spectrum {
         // TODO: ProfilePhase _(Prof::LightSample);
-        let p_shape: InteractionCommon = self.shape.sample_with_ref_point(&iref, &*u, pdf);
-        // TODO: iref.mediumInterface = mediumInterface;
+        let mut p_shape: InteractionCommon = self.shape.sample_with_ref_point(&iref, &*u, pdf);
+        p_shape.medium_interface = self.medium_interface.clone();
         if *pdf == 0.0 as Float || (p_shape.p - iref.p).length_squared() == 0.0 as Float {
             *pdf = 0.0 as Float;
             return Spectrum::default();
         }
         let new_wi: Vector3f = vec3_normalize(&(p_shape.p - iref.p));
         *wi = new_wi;
-        vis.p0 = InteractionCommon {
-            p: iref.p,
-            time: iref.time,
-            p_error: iref.p_error,
-            wo: iref.wo,
-            n: iref.n,
-        };
-        vis.p1 = InteractionCommon {
-            p: p_shape.p,
-            time: p_shape.time,
-            p_error: p_shape.p_error,
-            wo: p_shape.wo,
-            n: p_shape.n,
-        };
+        vis.p0 = iref.clone();
+        vis.p1 = p_shape.clone();
         self.l(&p_shape, &-new_wi)
     }
     fn power(&self) -> Spectrum {
@@ -120,8 +110,8 @@ impl Light for DiffuseAreaLight {
         // TODO: ProfilePhase _(Prof::LightSample);
 
         // sample a point on the area light's _Shape_, _p_shape_
-        let ic: InteractionCommon = self.shape.sample(u1, pdf_pos);
-        // TODO: p_shape.mediumInterface = mediumInterface;
+        let mut ic: InteractionCommon = self.shape.sample(u1, pdf_pos);
+        ic.medium_interface = self.medium_interface.clone();
         *n_light = ic.n;
         // sample a cosine-weighted outgoing direction _w_ for area light
         let mut w: Vector3f;
@@ -7,6 +7,7 @@ use core::geometry::{Bounds3f, Normal3f, Point2f, Point3f, Ray, Vector3f};
 use core::geometry::{vec3_coordinate_system, vec3_normalize};
 use core::interaction::{Interaction, InteractionCommon};
 use core::light::{Light, LightFlags, VisibilityTester};
+use core::medium::MediumInterface;
 use core::pbrt::{Float, Spectrum};
 use core::sampling::concentric_sample_disk;
 use core::scene::Scene;
@@ -24,7 +25,7 @@ pub struct DistantLight {
     // inherited from class Light (see light.h)
     flags: u8,
     n_samples: i32,
-    // TODO: const MediumInterface mediumInterface;
+    // distant lights are never inside a medium
     light_to_world: Transform,
     world_to_light: Transform,
 }
@@ -59,25 +60,21 @@ impl Light for DistantLight {
         let p_outside: Point3f =
             iref.p + self.w_light * (2.0 as Float * *self.world_radius.read().unwrap());
         *vis = VisibilityTester {
-            p0: InteractionCommon {
-                p: iref.p,
-                time: iref.time,
-                p_error: iref.p_error,
-                wo: iref.wo,
-                n: iref.n,
-            },
+            p0: iref.clone(),
             p1: InteractionCommon {
                 p: p_outside,
                 time: iref.time,
                 p_error: Vector3f::default(),
                 wo: Vector3f::default(),
                 n: Normal3f::default(),
+                medium_interface: MediumInterface::default(),
             },
         };
         self.l
     }
     fn power(&self) -> Spectrum {
-        Spectrum::default()
+        let world_radius: Float = *self.world_radius.read().unwrap();
+        self.l * (PI * world_radius * world_radius)
     }
     /// Some of the **DistanceLight** methods need to know the bounds
     /// of the scene. Because lights are created before the scene
@@ -130,6 +127,7 @@ impl Light for DistantLight {
             t_max: std::f32::INFINITY,
             time: time,
             differential: None,
+            medium: None,
         };
         *n_light = Normal3f::from(ray.d);
         *pdf_pos = 1.0 as Float / (PI * world_radius * world_radius);
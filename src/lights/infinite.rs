@@ -16,6 +16,7 @@ use core::geometry::{spherical_phi, spherical_theta, vec3_coordinate_system, vec
 use core::interaction::{Interaction, InteractionCommon};
 use core::mipmap::{ImageWrap, MipMap};
 use core::light::{Light, LightFlags, VisibilityTester};
+use core::medium::MediumInterface;
 use core::pbrt::{INV_2_PI, INV_PI};
 use core::pbrt::{Float, Spectrum};
 use core::sampling::Distribution2D;
@@ -56,7 +57,7 @@ pub struct InfiniteAreaLight {
     // inherited from class Light (see light.h)
     flags: u8,
     n_samples: i32,
-    // TODO: const MediumInterface mediumInterface;
+    // infinite lights are never inside a medium
     light_to_world: Transform,
     world_to_light: Transform,
 }
@@ -329,19 +330,14 @@ impl Light for InfiniteAreaLight {
         // return radiance value for infinite light direction
         let world_radius: Float = *self.world_radius.read().unwrap();
         *vis = VisibilityTester {
-            p0: InteractionCommon {
-                p: iref.p,
-                time: iref.time,
-                p_error: iref.p_error,
-                wo: iref.wo,
-                n: iref.n,
-            },
+            p0: iref.clone(),
             p1: InteractionCommon {
                 p: iref.p + *wi * (2.0 as Float * world_radius),
                 time: iref.time,
                 p_error: Vector3f::default(),
                 wo: Vector3f::default(),
                 n: Normal3f::default(),
+                medium_interface: MediumInterface::default(),
             },
         };
         // TODO: SpectrumType::Illuminant
@@ -443,6 +439,7 @@ impl Light for InfiniteAreaLight {
             t_max: std::f32::INFINITY,
             time: time,
             differential: None,
+            medium: None,
         };
         // compute _InfiniteAreaLight_ ray PDFs
         if sin_theta == 0.0 as Float {
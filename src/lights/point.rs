@@ -1,10 +1,12 @@
 // std
 use std;
+use std::f32::consts::PI;
 // pbrt
 use core::geometry::{Normal3f, Point2f, Point3f, Ray, Vector3f};
 use core::geometry::{pnt3_distance_squared, vec3_normalize};
 use core::interaction::{Interaction, InteractionCommon};
 use core::light::{Light, LightFlags, VisibilityTester};
+use core::medium::MediumInterface;
 use core::pbrt::{Float, Spectrum};
 use core::sampling::{uniform_sample_sphere, uniform_sphere_pdf};
 use core::scene::Scene;
@@ -12,7 +14,7 @@ use core::transform::Transform;
 
 // see point.h
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct PointLight {
     // private data (see point.h)
     pub p_light: Point3f,
@@ -20,15 +22,21 @@ pub struct PointLight {
     // inherited from class Light (see light.h)
     flags: u8,
     n_samples: i32,
+    medium_interface: MediumInterface,
 }
 
 impl PointLight {
-    pub fn new(light_to_world: &Transform, i: &Spectrum) -> Self {
+    pub fn new(
+        light_to_world: &Transform,
+        medium_interface: &MediumInterface,
+        i: &Spectrum,
+    ) -> Self {
         PointLight {
             p_light: light_to_world.transform_point(&Point3f::default()),
             i: *i,
             flags: LightFlags::DeltaPosition as u8,
             n_samples: 1_i32,
+            medium_interface: medium_interface.clone(),
         }
     }
 }
@@ -46,25 +54,20 @@ impl Light for PointLight {
         *wi = vec3_normalize(&(self.p_light - iref.p));
         *pdf = 1.0 as Float;
         *vis = VisibilityTester {
-            p0: InteractionCommon {
-                p: iref.p,
-                time: iref.time,
-                p_error: iref.p_error,
-                wo: iref.wo,
-                n: iref.n,
-            },
+            p0: iref.clone(),
             p1: InteractionCommon {
                 p: self.p_light,
                 time: iref.time,
                 p_error: Vector3f::default(),
                 wo: Vector3f::default(),
                 n: Normal3f::default(),
+                medium_interface: self.medium_interface.clone(),
             },
         };
         self.i / pnt3_distance_squared(&self.p_light, &iref.p)
     }
     fn power(&self) -> Spectrum {
-        Spectrum::default()
+        self.i * (4.0 as Float * PI)
     }
     fn preprocess(&self, _scene: &Scene) {}
     /// Default implementation returns no emitted radiance for a ray
@@ -80,18 +83,19 @@ impl Light for PointLight {
         u1: &Point2f,
         _u2: &Point2f,
         time: Float,
-        _ray: &mut Ray,
+        ray: &mut Ray,
         n_light: &mut Normal3f,
         pdf_pos: &mut Float,
         pdf_dir: &mut Float,
     ) -> Spectrum {
         // TODO: ProfilePhase _(Prof::LightSample);
-        let ray: Ray = Ray {
+        *ray = Ray {
             o: self.p_light,
             d: uniform_sample_sphere(u1),
             t_max: std::f32::INFINITY,
             time: time,
             differential: None,
+            medium: self.medium_interface.inside.clone(),
         };
         *n_light = Normal3f::from(ray.d);
         *pdf_pos = 1.0 as Float;
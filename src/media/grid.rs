@@ -0,0 +1,283 @@
+// std
+use std::sync::Arc;
+// pbrt
+use core::geometry::{Bounds3f, Point3f, Point3i, Ray, Vector3f};
+use core::geometry::vec3_normalize;
+use core::interaction::MediumInteraction;
+use core::medium::{HenyeyGreenstein, Medium};
+use core::pbrt::{Float, Spectrum};
+use core::pbrt::lerp;
+use core::sampler::Sampler;
+use core::transform::Transform;
+
+// see grid.h
+
+/// A medium with densities stored at the positions of a regular
+/// **nx** x **ny** x **nz** grid, spanning the unit cube in medium
+/// space.
+pub struct GridDensityMedium {
+    pub sigma_a: Spectrum,
+    pub sigma_s: Spectrum,
+    pub g: Float,
+    pub nx: i32,
+    pub ny: i32,
+    pub nz: i32,
+    pub world_to_medium: Transform,
+    pub density: Vec<Float>,
+    pub sigma_t: Float,
+    pub inv_max_density: Float,
+}
+
+impl GridDensityMedium {
+    /// The attenuation coefficient **sigma_a + sigma_s** has to be
+    /// spectrally uniform (only its first channel is used).
+    pub fn new(
+        sigma_a: &Spectrum,
+        sigma_s: &Spectrum,
+        g: Float,
+        nx: i32,
+        ny: i32,
+        nz: i32,
+        medium_to_world: &Transform,
+        d: Vec<Float>,
+    ) -> Self {
+        assert_eq!((nx * ny * nz) as usize, d.len());
+        let sigma_t: Float = (*sigma_a + *sigma_s)[0];
+        let mut max_density: Float = 0.0 as Float;
+        for i in 0..d.len() {
+            max_density = max_density.max(d[i]);
+        }
+        GridDensityMedium {
+            sigma_a: *sigma_a,
+            sigma_s: *sigma_s,
+            g: g,
+            nx: nx,
+            ny: ny,
+            nz: nz,
+            world_to_medium: Transform::inverse(medium_to_world),
+            density: d,
+            sigma_t: sigma_t,
+            inv_max_density: 1.0 as Float / max_density,
+        }
+    }
+    /// Density at a grid position (zero outside of the grid).
+    pub fn d(&self, p: &Point3i) -> Float {
+        if p.x < 0 || p.x >= self.nx || p.y < 0 || p.y >= self.ny || p.z < 0 || p.z >= self.nz {
+            return 0.0 as Float;
+        }
+        self.density[((p.z * self.ny + p.y) * self.nx + p.x) as usize]
+    }
+    /// Trilinearly interpolated density at a point in medium space.
+    pub fn density(&self, p: &Point3f) -> Float {
+        // compute voxel coordinates and offsets for _p_
+        let p_samples: Point3f = Point3f {
+            x: p.x * self.nx as Float - 0.5 as Float,
+            y: p.y * self.ny as Float - 0.5 as Float,
+            z: p.z * self.nz as Float - 0.5 as Float,
+        };
+        let pi: Point3i = Point3i {
+            x: p_samples.x.floor() as i32,
+            y: p_samples.y.floor() as i32,
+            z: p_samples.z.floor() as i32,
+        };
+        let d: Vector3f = Vector3f {
+            x: p_samples.x - pi.x as Float,
+            y: p_samples.y - pi.y as Float,
+            z: p_samples.z - pi.z as Float,
+        };
+        // trilinearly interpolate density values to compute local density
+        let d00: Float = lerp(d.x, self.d(&pi), self.d(&self.offset(&pi, 1, 0, 0)));
+        let d10: Float = lerp(
+            d.x,
+            self.d(&self.offset(&pi, 0, 1, 0)),
+            self.d(&self.offset(&pi, 1, 1, 0)),
+        );
+        let d01: Float = lerp(
+            d.x,
+            self.d(&self.offset(&pi, 0, 0, 1)),
+            self.d(&self.offset(&pi, 1, 0, 1)),
+        );
+        let d11: Float = lerp(
+            d.x,
+            self.d(&self.offset(&pi, 0, 1, 1)),
+            self.d(&self.offset(&pi, 1, 1, 1)),
+        );
+        let d0: Float = lerp(d.y, d00, d10);
+        let d1: Float = lerp(d.y, d01, d11);
+        lerp(d.z, d0, d1)
+    }
+    fn offset(&self, p: &Point3i, dx: i32, dy: i32, dz: i32) -> Point3i {
+        Point3i {
+            x: p.x + dx,
+            y: p.y + dy,
+            z: p.z + dz,
+        }
+    }
+    /// Transforms the ray into medium space (with a normalized
+    /// direction) and returns it with the parametric range within the
+    /// grid, if any.
+    fn medium_ray(&self, r_world: &Ray) -> Option<(Ray, Float, Float)> {
+        let ray: Ray = self.world_to_medium.transform_ray(&Ray {
+            o: r_world.o,
+            d: vec3_normalize(&r_world.d),
+            t_max: r_world.t_max * r_world.d.length(),
+            time: r_world.time,
+            differential: None,
+            medium: None,
+        });
+        // compute $[\tmin, \tmax]$ interval of _ray_'s overlap with medium bounds
+        let b: Bounds3f = Bounds3f {
+            p_min: Point3f {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            p_max: Point3f {
+                x: 1.0,
+                y: 1.0,
+                z: 1.0,
+            },
+        };
+        let mut t_min: Float = 0.0 as Float;
+        let mut t_max: Float = 0.0 as Float;
+        if b.intersect_b(&ray, &mut t_min, &mut t_max) {
+            Some((ray, t_min, t_max))
+        } else {
+            None
+        }
+    }
+}
+
+impl Medium for GridDensityMedium {
+    fn tr(&self, r_world: &Ray, sampler: &mut Box<Sampler + Send + Sync>) -> Spectrum {
+        // TODO: ProfilePhase _(Prof::MediumTr);
+        if let Some((ray, t_min, t_max)) = self.medium_ray(r_world) {
+            // perform ratio tracking to estimate the transmittance value
+            let mut tr: Float = 1.0 as Float;
+            let mut t: Float = t_min;
+            loop {
+                t -= (1.0 as Float - sampler.get_1d()).ln() * self.inv_max_density / self.sigma_t;
+                if t >= t_max {
+                    break;
+                }
+                let density: Float = self.density(&ray.position(t));
+                tr *= 1.0 as Float - (0.0 as Float).max(density * self.inv_max_density);
+                // added after book publication: when transmittance gets
+                // low, start applying Russian roulette to terminate
+                // sampling
+                let rr_threshold: Float = 0.1 as Float;
+                if tr < rr_threshold {
+                    let q: Float = (0.05 as Float).max(1.0 as Float - tr);
+                    if sampler.get_1d() < q {
+                        return Spectrum::default();
+                    }
+                    tr /= 1.0 as Float - q;
+                }
+            }
+            Spectrum::new(tr)
+        } else {
+            Spectrum::new(1.0 as Float)
+        }
+    }
+    fn sample(
+        &self,
+        r_world: &Ray,
+        sampler: &mut Box<Sampler + Send + Sync>,
+    ) -> (Spectrum, Option<MediumInteraction>) {
+        // TODO: ProfilePhase _(Prof::MediumSample);
+        if let Some((ray, t_min, t_max)) = self.medium_ray(r_world) {
+            // run delta-tracking iterations to sample a medium interaction
+            let mut t: Float = t_min;
+            loop {
+                t -= (1.0 as Float - sampler.get_1d()).ln() * self.inv_max_density / self.sigma_t;
+                if t >= t_max {
+                    break;
+                }
+                if self.density(&ray.position(t)) * self.inv_max_density > sampler.get_1d() {
+                    // populate _mi_ with medium interaction information and return
+                    let mi: MediumInteraction = MediumInteraction::new(
+                        &r_world.position(t),
+                        &-r_world.d,
+                        r_world.time,
+                        r_world.medium.clone(),
+                        Some(Arc::new(HenyeyGreenstein::new(self.g))),
+                    );
+                    return (self.sigma_s / self.sigma_t, Some(mi));
+                }
+            }
+        }
+        (Spectrum::new(1.0 as Float), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use samplers::random::RandomSampler;
+
+    fn ray_along_x(y: Float, z: Float) -> Ray {
+        Ray {
+            o: Point3f { x: -1.0, y: y, z: z },
+            d: Vector3f { x: 2.0, y: 0.0, z: 0.0 },
+            t_max: 1.5,
+            time: 0.0,
+            differential: None,
+            medium: None,
+        }
+    }
+
+    #[test]
+    fn trilinear_density() {
+        // 2 x 1 x 1 voxels with centers at x = 0.25 and x = 0.75
+        let medium: GridDensityMedium = GridDensityMedium::new(
+            &Spectrum::new(0.5),
+            &Spectrum::new(0.5),
+            0.0,
+            2,
+            1,
+            1,
+            &Transform::default(),
+            vec![1.0, 3.0],
+        );
+        let density = |x: Float| medium.density(&Point3f { x: x, y: 0.5, z: 0.5 });
+        assert_eq!(density(0.25), 1.0);
+        assert_eq!(density(0.75), 3.0);
+        assert_eq!(density(0.5), 2.0);
+        assert_eq!(density(0.625), 2.5);
+        // towards the boundary, the density falls off to zero
+        assert_eq!(density(0.0), 0.5);
+        assert_eq!(density(1.0), 1.5);
+        assert_eq!(medium.density(&Point3f { x: 0.25, y: 0.0, z: 0.5 }), 0.5);
+    }
+
+    #[test]
+    fn ratio_tracking_transmittance() {
+        // constant grid values, the grid covers the cube [1, 3]^3 in world space
+        let medium_to_world: Transform = Transform::translate(&Vector3f { x: 1.0, y: 1.0, z: 1.0 })
+            * Transform::scale(2.0, 2.0, 2.0);
+        let medium: GridDensityMedium = GridDensityMedium::new(
+            &Spectrum::new(0.2),
+            &Spectrum::new(0.3),
+            0.0,
+            4,
+            4,
+            4,
+            &medium_to_world,
+            vec![1.0; 64],
+        );
+        let mut sampler: Box<Sampler + Send + Sync> = Box::new(RandomSampler::new(1));
+        sampler.reseed(0);
+        // the ray enters the grid at x = 1 and ends at x = 2, the
+        // density rises linearly from 0.5 at the boundary to 1 at the
+        // first voxel center (x = 1.25)
+        let optical_depth: Float = 0.5 * (0.25 * 0.75 + 0.75);
+        let n: usize = 20000;
+        let mut tr: Float = 0.0;
+        for _ in 0..n {
+            tr += medium.tr(&ray_along_x(2.0, 2.0), &mut sampler)[0] / n as Float;
+        }
+        assert!((tr - (-optical_depth).exp()).abs() < 1e-2);
+        // rays which miss the grid aren't attenuated
+        assert_eq!(medium.tr(&ray_along_x(4.0, 2.0), &mut sampler)[0], 1.0);
+    }
+}
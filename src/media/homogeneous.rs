@@ -0,0 +1,156 @@
+// std
+use std;
+use std::sync::Arc;
+// pbrt
+use core::geometry::{Point3f, Ray};
+use core::interaction::MediumInteraction;
+use core::medium::{HenyeyGreenstein, Medium};
+use core::pbrt::{Float, Spectrum};
+use core::sampler::Sampler;
+
+// see homogeneous.h
+
+pub struct HomogeneousMedium {
+    pub sigma_a: Spectrum,
+    pub sigma_s: Spectrum,
+    pub sigma_t: Spectrum,
+    pub g: Float,
+}
+
+impl HomogeneousMedium {
+    pub fn new(sigma_a: &Spectrum, sigma_s: &Spectrum, g: Float) -> Self {
+        HomogeneousMedium {
+            sigma_a: *sigma_a,
+            sigma_s: *sigma_s,
+            sigma_t: *sigma_s + *sigma_a,
+            g: g,
+        }
+    }
+}
+
+impl Medium for HomogeneousMedium {
+    fn tr(&self, ray: &Ray, _sampler: &mut Box<Sampler + Send + Sync>) -> Spectrum {
+        // TODO: ProfilePhase _(Prof::MediumTr);
+        (self.sigma_t * -(ray.t_max * ray.d.length()).min(std::f32::MAX)).exp()
+    }
+    fn sample(
+        &self,
+        ray: &Ray,
+        sampler: &mut Box<Sampler + Send + Sync>,
+    ) -> (Spectrum, Option<MediumInteraction>) {
+        // TODO: ProfilePhase _(Prof::MediumSample);
+        // sample a channel and distance along the ray
+        let n_spectrum_samples: usize = 3; // RGB
+        let channel: usize = ((sampler.get_1d() * n_spectrum_samples as Float) as usize)
+            .min(n_spectrum_samples - 1);
+        let dist: Float = -((1.0 as Float - sampler.get_1d()).ln()) / self.sigma_t[channel];
+        let t: Float = (dist / ray.d.length()).min(ray.t_max);
+        let sampled_medium: bool = t < ray.t_max;
+        let mut mi: Option<MediumInteraction> = None;
+        if sampled_medium {
+            // the ray carries this medium
+            let p: Point3f = ray.position(t);
+            mi = Some(MediumInteraction::new(
+                &p,
+                &-ray.d,
+                ray.time,
+                ray.medium.clone(),
+                Some(Arc::new(HenyeyGreenstein::new(self.g))),
+            ));
+        }
+        // compute the transmittance and sampling density
+        let tr: Spectrum = (self.sigma_t * -(t.min(std::f32::MAX) * ray.d.length())).exp();
+        // return weighting factor for scattering from homogeneous medium
+        let density: Spectrum = if sampled_medium {
+            self.sigma_t * tr
+        } else {
+            tr
+        };
+        let mut pdf: Float = 0.0 as Float;
+        for i in 0..n_spectrum_samples {
+            pdf += density[i];
+        }
+        pdf *= 1.0 as Float / n_spectrum_samples as Float;
+        if pdf == 0.0 as Float {
+            assert!(tr.is_black());
+            pdf = 1.0 as Float;
+        }
+        if sampled_medium {
+            (tr * self.sigma_s / pdf, mi)
+        } else {
+            (tr / pdf, mi)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::geometry::{vec3_dot_vec3, Point2f, Vector3f};
+    use core::medium::PhaseFunction;
+    use core::sampling::{uniform_sample_sphere, uniform_sphere_pdf};
+    use samplers::random::RandomSampler;
+
+    /// Stratified samples of the unit square.
+    fn grid_samples(n: usize) -> Vec<Point2f> {
+        let mut samples: Vec<Point2f> = Vec::with_capacity(n * n);
+        for y in 0..n {
+            for x in 0..n {
+                samples.push(Point2f {
+                    x: (x as Float + 0.5 as Float) / n as Float,
+                    y: (y as Float + 0.5 as Float) / n as Float,
+                });
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn transmittance() {
+        let sigma_a: Spectrum = Spectrum::rgb(0.1, 0.2, 0.3);
+        let sigma_s: Spectrum = Spectrum::rgb(0.4, 0.2, 0.0);
+        let medium: HomogeneousMedium = HomogeneousMedium::new(&sigma_a, &sigma_s, 0.0);
+        let mut sampler: Box<Sampler + Send + Sync> = Box::new(RandomSampler::new(1));
+        // the distance is t_max times the length of the direction
+        let ray: Ray = Ray {
+            o: Point3f::default(),
+            d: Vector3f { x: 0.0, y: 3.0, z: 4.0 },
+            t_max: 0.5,
+            time: 0.0,
+            differential: None,
+            medium: None,
+        };
+        let tr: Spectrum = medium.tr(&ray, &mut sampler);
+        for (i, sigma_t) in [0.5 as Float, 0.4, 0.3].iter().enumerate() {
+            assert!((tr[i] - (-sigma_t * 2.5 as Float).exp()).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn henyey_greenstein_sample_p() {
+        let wo: Vector3f = Vector3f { x: 0.0, y: 0.6, z: 0.8 };
+        for g in &[-0.7 as Float, -0.3, 0.0, 0.5, 0.9] {
+            let phase: HenyeyGreenstein = HenyeyGreenstein::new(*g);
+            let samples: Vec<Point2f> = grid_samples(64);
+            let mut mean_cos_theta: Float = 0.0;
+            for u in &samples {
+                let mut wi: Vector3f = Vector3f::default();
+                let value: Float = phase.sample_p(&wo, &mut wi, u);
+                // the returned value is the phase function (and PDF) of wi
+                assert!((wi.length() - 1.0).abs() < 1e-4);
+                assert!((value - phase.p(&wo, &wi)).abs() < 1e-3 * value.max(1.0));
+                mean_cos_theta += vec3_dot_vec3(&wo, &wi) / samples.len() as Float;
+            }
+            // wo points away from the scattering point, so forward
+            // scattering (g > 0) means wi is close to -wo
+            assert!((mean_cos_theta + g).abs() < 1e-2);
+            // the phase function integrates to one over the sphere
+            let mut integral: Float = 0.0;
+            for u in &samples {
+                let wi: Vector3f = uniform_sample_sphere(u);
+                integral += phase.p(&wo, &wi) / uniform_sphere_pdf() / samples.len() as Float;
+            }
+            assert!((integral - 1.0).abs() < 2e-2);
+        }
+    }
+}
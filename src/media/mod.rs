@@ -0,0 +1,22 @@
+//! Participating media fill a region of space (defined by shapes
+//! with a **MediumInterface**) and absorb and scatter light passing
+//! through it.
+//!
+//! - GridDensityMedium
+//! - HomogeneousMedium
+//!
+//! ## Homogeneous Medium
+//!
+//! A **HomogeneousMedium** has constant absorption and scattering
+//! coefficients throughout its extent, which makes the transmittance
+//! along a ray easy to compute (Beer's law).
+//!
+//! ## 3D Grids
+//!
+//! A **GridDensityMedium** stores medium densities at a regular 3D
+//! grid of positions, which are trilinearly interpolated. Medium
+//! interactions are sampled with delta tracking and the transmittance
+//! is estimated with ratio tracking.
+
+pub mod grid;
+pub mod homogeneous;
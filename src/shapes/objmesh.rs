@@ -0,0 +1,607 @@
+// std
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+// pbrt
+use core::fileutil::open_file;
+use core::geometry::{Normal3f, Point2f, Point3f, Vector3f};
+use core::material::Material;
+use core::mipmap::ImageWrap;
+use core::paramset::ParamSet;
+use core::pbrt::{Float, Spectrum};
+use core::shape::Shape;
+use core::texture::{Texture, UVMapping2D};
+use core::transform::Transform;
+use materials::matte::MatteMaterial;
+use materials::plastic::PlasticMaterial;
+use shapes::triangle::{Triangle, TriangleMesh};
+use textures::constant::ConstantTexture;
+use textures::imagemap::ImageTexture;
+
+/// A material of an MTL file (only the parameters we can map onto
+/// **MatteMaterial** and **PlasticMaterial**).
+struct MtlMaterial {
+    kd: [Float; 3],
+    ks: [Float; 3],
+    ns: Float,
+    map_kd: Option<PathBuf>,
+}
+
+impl Default for MtlMaterial {
+    fn default() -> Self {
+        MtlMaterial {
+            kd: [0.8 as Float; 3],
+            ks: [0.0 as Float; 3],
+            ns: 0.0 as Float,
+            map_kd: None,
+        }
+    }
+}
+
+/// Faces which share group and material become one triangle mesh.
+struct ObjGroup {
+    material: Option<String>,
+    /// Vertex, uv and normal index (-1 if missing) of each corner.
+    corners: Vec<(i64, i64, i64)>,
+    /// Triangles (three corners each, as offsets into **corners**).
+    triangles: Vec<usize>,
+}
+
+fn parse_floats(tokens: &[&str], n: usize, line: usize) -> Result<Vec<Float>, String> {
+    if tokens.len() < n {
+        return Err(format!("objmesh: {} values expected in line {}", n, line));
+    }
+    let mut floats: Vec<Float> = Vec::with_capacity(n);
+    for token in &tokens[0..n] {
+        match Float::from_str(token) {
+            Ok(float) => floats.push(float),
+            Err(_) => {
+                return Err(format!("objmesh: number expected in line {}, found {:?}", line, token))
+            }
+        }
+    }
+    Ok(floats)
+}
+
+/// OBJ indices start at 1, negative indices count from the end of
+/// the list. Missing indices (e.g. the uv index of **v//vn**) are
+/// returned as -1.
+fn parse_index(token: &str, n: usize, line: usize) -> Result<i64, String> {
+    if token.is_empty() {
+        return Ok(-1);
+    }
+    let index: i64 = match i64::from_str(token) {
+        Ok(index) => index,
+        Err(_) => {
+            return Err(format!("objmesh: index expected in line {}, found {:?}", line, token))
+        }
+    };
+    let resolved: i64 = if index < 0 { n as i64 + index } else { index - 1 };
+    if resolved < 0 || resolved >= n as i64 {
+        return Err(format!("objmesh: index {} out of range in line {}", index, line));
+    }
+    Ok(resolved)
+}
+
+fn read_mtl_file(path: &Path,
+                 materials: &mut HashMap<String, MtlMaterial>,
+                 warnings: &mut Vec<String>) {
+    let buf_reader: Box<BufRead> = match open_file(path) {
+        Ok(buf_reader) => buf_reader,
+        Err(_) => {
+            warnings.push(format!("objmesh: Couldn't open MTL file {:?}", path));
+            return;
+        }
+    };
+    let directory: PathBuf = path.parent().map(|p| p.to_path_buf()).unwrap_or(PathBuf::new());
+    let mut current: Option<String> = None;
+    for (number, line) in buf_reader.lines().enumerate() {
+        let line: String = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.is_empty() || tokens[0].starts_with('#') {
+            continue;
+        }
+        if tokens[0] == "newmtl" {
+            let name: String = tokens[1..].join(" ");
+            materials.insert(name.clone(), MtlMaterial::default());
+            current = Some(name);
+            continue;
+        }
+        let material: &mut MtlMaterial = match current {
+            Some(ref name) => materials.get_mut(name).unwrap(),
+            None => continue,
+        };
+        match tokens[0] {
+            "Kd" | "Ks" => {
+                match parse_floats(&tokens[1..], 3, number + 1) {
+                    Ok(rgb) => {
+                        let color: [Float; 3] = [rgb[0], rgb[1], rgb[2]];
+                        if tokens[0] == "Kd" {
+                            material.kd = color;
+                        } else {
+                            material.ks = color;
+                        }
+                    }
+                    Err(message) => warnings.push(message),
+                }
+            }
+            "Ns" => {
+                match parse_floats(&tokens[1..], 1, number + 1) {
+                    Ok(ns) => material.ns = ns[0],
+                    Err(message) => warnings.push(message),
+                }
+            }
+            "map_Kd" => {
+                // the filename comes last (after options like -s or -o)
+                if let Some(filename) = tokens.last() {
+                    material.map_kd = Some(directory.join(filename));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn create_material(material: &MtlMaterial,
+                   warnings: &mut Vec<String>)
+                   -> Arc<Material + Send + Sync> {
+    let mut kd: Arc<Texture<Spectrum> + Send + Sync> =
+        Arc::new(ConstantTexture::new(Spectrum::from_rgb(&material.kd)));
+    if let Some(ref map_kd) = material.map_kd {
+        if map_kd.is_file() {
+            let mapping = Box::new(UVMapping2D {
+                                       su: 1.0,
+                                       sv: 1.0,
+                                       du: 0.0,
+                                       dv: 0.0,
+                                   });
+            kd = Arc::new(ImageTexture::new(mapping,
+                                            String::from(map_kd.to_str().unwrap()),
+                                            false,
+                                            8.0,
+                                            ImageWrap::Repeat,
+                                            1.0,
+                                            true));
+        } else {
+            warnings.push(format!("objmesh: Texture {:?} not found, using \"Kd\"", map_kd));
+        }
+    }
+    if material.ks.iter().any(|k| *k > 0.0 as Float) {
+        // Phong exponent to microfacet roughness (alpha)
+        let alpha: Float = (2.0 as Float / (material.ns + 2.0 as Float)).sqrt();
+        let ks = Arc::new(ConstantTexture::new(Spectrum::from_rgb(&material.ks)));
+        let roughness = Arc::new(ConstantTexture::new(alpha));
+        Arc::new(PlasticMaterial::new(kd, ks, roughness, false))
+    } else {
+        let sigma = Arc::new(ConstantTexture::new(0.0 as Float));
+        Arc::new(MatteMaterial::new(kd, sigma))
+    }
+}
+
+/// Reads a Wavefront OBJ file. Each group (**g** or **o**) and
+/// material (**usemtl**) becomes a triangle mesh of its own; polygons
+/// are triangulated as fans. If **usemtl** is true (the default), the
+/// materials of the referenced MTL files are returned as well (**None**
+/// for faces without material, which use the current material).
+pub fn create_obj_mesh(o2w: &Transform,
+                       w2o: &Transform,
+                       reverse_orientation: bool,
+                       params: &ParamSet,
+                       filename: String,
+                       warnings: &mut Vec<String>)
+                       -> Result<(Vec<Arc<Shape + Send + Sync>>,
+                                  Vec<Option<Arc<Material + Send + Sync>>>),
+                                 String> {
+    let use_mtl: bool = params.find_one_bool(String::from("usemtl"), true);
+    // OBJ files might be gzip-compressed
+    let buf_reader: Box<BufRead> = match open_file(Path::new(&filename)) {
+        Ok(buf_reader) => buf_reader,
+        Err(_) => return Err(format!("Couldn't open OBJ file {:?}", filename)),
+    };
+    let directory: PathBuf = Path::new(&filename)
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or(PathBuf::new());
+    let mut p: Vec<Point3f> = Vec::new();
+    let mut uvs: Vec<Point2f> = Vec::new();
+    let mut n: Vec<Normal3f> = Vec::new();
+    let mut mtl_materials: HashMap<String, MtlMaterial> = HashMap::new();
+    let mut groups: Vec<ObjGroup> = Vec::new();
+    // group of (group name, material name)
+    let mut group_index: HashMap<(String, Option<String>), usize> = HashMap::new();
+    let mut group_name: String = String::new();
+    let mut material_name: Option<String> = None;
+    let mut current: Option<usize> = None;
+    for (number, line) in buf_reader.lines().enumerate() {
+        let line: String = match line {
+            Ok(line) => line,
+            Err(_) => return Err(format!("Unable to read OBJ file {:?}", filename)),
+        };
+        let number: usize = number + 1;
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.is_empty() || tokens[0].starts_with('#') {
+            continue;
+        }
+        match tokens[0] {
+            "v" => {
+                let v: Vec<Float> = parse_floats(&tokens[1..], 3, number)?;
+                p.push(Point3f {
+                           x: v[0],
+                           y: v[1],
+                           z: v[2],
+                       });
+            }
+            "vt" => {
+                let vt: Vec<Float> = parse_floats(&tokens[1..], 2, number)?;
+                uvs.push(Point2f { x: vt[0], y: vt[1] });
+            }
+            "vn" => {
+                let vn: Vec<Float> = parse_floats(&tokens[1..], 3, number)?;
+                n.push(Normal3f {
+                           x: vn[0],
+                           y: vn[1],
+                           z: vn[2],
+                       });
+            }
+            "g" | "o" => {
+                group_name = tokens[1..].join(" ");
+                current = None;
+            }
+            "usemtl" => {
+                material_name = Some(tokens[1..].join(" "));
+                current = None;
+            }
+            "mtllib" => {
+                if use_mtl {
+                    for mtl_file in &tokens[1..] {
+                        read_mtl_file(&directory.join(mtl_file), &mut mtl_materials, warnings);
+                    }
+                }
+            }
+            "f" => {
+                if tokens.len() < 4 {
+                    return Err(format!("objmesh: Face with less than 3 vertices in line {}",
+                                       number));
+                }
+                let index: usize = match current {
+                    Some(index) => index,
+                    None => {
+                        let key: (String, Option<String>) = (group_name.clone(),
+                                                             material_name.clone());
+                        let next: usize = groups.len();
+                        let index: usize = *group_index.entry(key).or_insert(next);
+                        if index == next {
+                            groups.push(ObjGroup {
+                                            material: material_name.clone(),
+                                            corners: Vec::new(),
+                                            triangles: Vec::new(),
+                                        });
+                        }
+                        current = Some(index);
+                        index
+                    }
+                };
+                let group: &mut ObjGroup = &mut groups[index];
+                let first: usize = group.corners.len();
+                for corner in &tokens[1..] {
+                    // v, v/vt, v//vn or v/vt/vn
+                    let indices: Vec<&str> = corner.split('/').collect();
+                    let vi: i64 = parse_index(indices[0], p.len(), number)?;
+                    if vi < 0 {
+                        return Err(format!("objmesh: Vertex index missing in line {}", number));
+                    }
+                    let ti: i64 = if indices.len() > 1 {
+                        parse_index(indices[1], uvs.len(), number)?
+                    } else {
+                        -1
+                    };
+                    let ni: i64 = if indices.len() > 2 {
+                        parse_index(indices[2], n.len(), number)?
+                    } else {
+                        -1
+                    };
+                    group.corners.push((vi, ti, ni));
+                }
+                for i in first + 1..group.corners.len() - 1 {
+                    group.triangles.push(first);
+                    group.triangles.push(i);
+                    group.triangles.push(i + 1);
+                }
+            }
+            _ => {
+                // ignore smoothing groups, lines, points, ...
+            }
+        }
+    }
+    let mut materials: HashMap<String, Arc<Material + Send + Sync>> = HashMap::new();
+    let mut shapes: Vec<Arc<Shape + Send + Sync>> = Vec::new();
+    let mut shape_materials: Vec<Option<Arc<Material + Send + Sync>>> = Vec::new();
+    for group in groups {
+        // corners with the same indices share a mesh vertex
+        let mut vertex_index: HashMap<(i64, i64, i64), usize> = HashMap::new();
+        let mut vertices: Vec<(i64, i64, i64)> = Vec::new();
+        let mut tm_vertex_indices: Vec<usize> = Vec::with_capacity(group.triangles.len());
+        for corner in group.triangles {
+            let key: (i64, i64, i64) = group.corners[corner];
+            let next: usize = vertices.len();
+            let index: usize = *vertex_index.entry(key).or_insert(next);
+            if index == next {
+                vertices.push(key);
+            }
+            tm_vertex_indices.push(index);
+        }
+        // normals and uvs are only used if all corners have them
+        let has_uvs: bool = vertices.iter().all(|v| v.1 >= 0);
+        let has_normals: bool = vertices.iter().all(|v| v.2 >= 0);
+        // transform mesh vertices (and normals) to world space
+        let p_ws: Vec<Point3f> = vertices.iter()
+            .map(|v| o2w.transform_point(&p[v.0 as usize]))
+            .collect();
+        let mut n_ws: Vec<Normal3f> = Vec::new();
+        if has_normals {
+            n_ws = vertices.iter().map(|v| o2w.transform_normal(&n[v.2 as usize])).collect();
+        }
+        let mut uv: Vec<Point2f> = Vec::new();
+        if has_uvs {
+            uv = vertices.iter().map(|v| uvs[v.1 as usize]).collect();
+        }
+        let s_ws: Vec<Vector3f> = Vec::new();
+        let mesh = Arc::new(TriangleMesh::new(*o2w,
+                                              *w2o,
+                                              reverse_orientation,
+                                              false, // transform_swaps_handedness
+                                              tm_vertex_indices.len() / 3, // n_triangles
+                                              tm_vertex_indices,
+                                              vertices.len(),
+                                              p_ws, // in world space
+                                              s_ws, // in world space
+                                              n_ws, // in world space
+                                              uv));
+        let mut mtl: Option<Arc<Material + Send + Sync>> = None;
+        if let Some(ref name) = group.material {
+            if materials.contains_key(name) {
+                mtl = materials.get(name).cloned();
+            } else if let Some(mtl_material) = mtl_materials.get(name) {
+                let material: Arc<Material + Send + Sync> = create_material(mtl_material, warnings);
+                materials.insert(name.clone(), material.clone());
+                mtl = Some(material);
+            } else if use_mtl {
+                warnings.push(format!("objmesh: Material \"{}\" not found in MTL files", name));
+            }
+        }
+        for id in 0..mesh.n_triangles {
+            let triangle = Arc::new(Triangle::new(mesh.object_to_world,
+                                                  mesh.world_to_object,
+                                                  mesh.reverse_orientation,
+                                                  mesh.clone(),
+                                                  id));
+            shapes.push(triangle.clone());
+            shape_materials.push(mtl.clone());
+        }
+    }
+    Ok((shapes, shape_materials))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::f32;
+    use std::fs;
+    use std::io::Write;
+    use std::process;
+    use super::*;
+    use core::geometry::Ray;
+    use core::interaction::SurfaceInteraction;
+    use core::material::TransportMode;
+    use core::reflection::{Bsdf, BxdfType};
+
+    fn obj_dir(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir: PathBuf = env::temp_dir().join(format!("pbrt_objmesh_{}_{}", test, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for &(name, content) in files {
+            fs::File::create(dir.join(name)).unwrap().write_all(content.as_bytes()).unwrap();
+        }
+        dir
+    }
+
+    fn load(test: &str,
+            files: &[(&str, &str)])
+            -> (Vec<Arc<Shape + Send + Sync>>,
+                Vec<Option<Arc<Material + Send + Sync>>>,
+                Vec<String>) {
+        let dir: PathBuf = obj_dir(test, files);
+        let filename: String = String::from(dir.join("mesh.obj").to_str().unwrap());
+        let identity: Transform = Transform::default();
+        let mut warnings: Vec<String> = Vec::new();
+        let (shapes, materials) = create_obj_mesh(&identity,
+                                                  &identity,
+                                                  false,
+                                                  &ParamSet::default(),
+                                                  filename,
+                                                  &mut warnings)
+            .unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        (shapes, materials, warnings)
+    }
+
+    fn bound_xy(shape: &Arc<Shape + Send + Sync>) -> (Float, Float, Float, Float) {
+        let bound = shape.world_bound();
+        (bound.p_min.x, bound.p_min.y, bound.p_max.x, bound.p_max.y)
+    }
+
+    fn hit(shape: &Arc<Shape + Send + Sync>, x: Float, y: Float) -> SurfaceInteraction {
+        let ray: Ray = Ray {
+            o: Point3f { x: x, y: y, z: 5.0 },
+            d: Vector3f {
+                x: 0.0,
+                y: 0.0,
+                z: -1.0,
+            },
+            t_max: f32::INFINITY,
+            time: 0.0,
+            differential: None,
+            medium: None,
+        };
+        let (si, _t) = shape.intersect(&ray).unwrap();
+        si
+    }
+
+    #[test]
+    fn fan_triangulation() {
+        let (shapes, _, _) = load("fan",
+                                  &[("mesh.obj",
+                                     "v 0 0 0\nv 2 0 0\nv 3 1 0\nv 1 3 0\nv -1 1 0\n\
+                                      f 1 2 3 4 5\n\
+                                      f 1 2 3 4\n")]);
+        // a pentagon and a quad
+        assert_eq!(shapes.len(), 5);
+        // every triangle of a fan shares the first corner
+        assert_eq!(bound_xy(&shapes[0]), (0.0, 0.0, 3.0, 1.0));
+        assert_eq!(bound_xy(&shapes[1]), (0.0, 0.0, 3.0, 3.0));
+        assert_eq!(bound_xy(&shapes[2]), (-1.0, 0.0, 1.0, 3.0));
+        assert_eq!(bound_xy(&shapes[3]), (0.0, 0.0, 3.0, 1.0));
+        assert_eq!(bound_xy(&shapes[4]), (0.0, 0.0, 3.0, 3.0));
+    }
+
+    #[test]
+    fn negative_indices() {
+        let (shapes, _, _) = load("negative",
+                                  &[("mesh.obj",
+                                     "v 0 0 0\nv 1 0 0\nv 0 1 0\n\
+                                      f -3 -2 -1\n\
+                                      v 5 5 0\nv 6 5 0\nv 5 6 0\n\
+                                      f -3 -2 -1\n\
+                                      f 1 -2 3\n")]);
+        assert_eq!(shapes.len(), 3);
+        assert_eq!(bound_xy(&shapes[0]), (0.0, 0.0, 1.0, 1.0));
+        assert_eq!(bound_xy(&shapes[1]), (5.0, 5.0, 6.0, 6.0));
+        assert_eq!(bound_xy(&shapes[2]), (0.0, 0.0, 6.0, 5.0));
+    }
+
+    #[test]
+    fn uv_and_normal_indices() {
+        let (shapes, _, _) = load("corners",
+                                  &[("mesh.obj",
+                                     "v 0 0 0\nv 1 0 0\nv 0 1 0\n\
+                                      vt 0.5 0.5\nvt 0.75 0.5\nvt 0.5 0.75\n\
+                                      vn 0 0.6 0.8\n\
+                                      g v\nf 1 2 3\n\
+                                      g vt\nf 1/1 2/2 3/3\n\
+                                      g vn\nf 1//1 2//1 3//1\n\
+                                      g all\nf 1/1/1 2/2/1 3/3/1\n\
+                                      g mixed\nf 1/1/1 2/2 3/3/1\n")]);
+        assert_eq!(shapes.len(), 5);
+        let uv_and_normal = |shape: &Arc<Shape + Send + Sync>| {
+            let si: SurfaceInteraction = hit(shape, 0.25, 0.25);
+            (si.uv.x, si.uv.y, si.shading.n.y.abs())
+        };
+        // default uvs are (0, 0), (1, 0) and (1, 1), the default
+        // shading normal is the geometric normal
+        assert_eq!(uv_and_normal(&shapes[0]), (0.5, 0.25, 0.0));
+        assert_eq!(uv_and_normal(&shapes[1]), (0.5625, 0.5625, 0.0));
+        let (u, v, ny) = uv_and_normal(&shapes[2]);
+        assert_eq!((u, v), (0.5, 0.25));
+        assert!((ny - 0.6).abs() < 1e-5);
+        let (u, v, ny) = uv_and_normal(&shapes[3]);
+        assert_eq!((u, v), (0.5625, 0.5625));
+        assert!((ny - 0.6).abs() < 1e-5);
+        // normals are only used if all corners have them
+        assert_eq!(uv_and_normal(&shapes[4]), (0.5625, 0.5625, 0.0));
+    }
+
+    #[test]
+    fn meshes_per_group_and_material() {
+        let (shapes, materials, warnings) =
+            load("groups",
+                 &[("mesh.mtl", "newmtl red\nKd 1 0 0\nnewmtl blue\nKd 0 0 1\n"),
+                   ("mesh.obj",
+                    "mtllib mesh.mtl\n\
+                     v 0 0 0\nv 1 0 0\nv 0 1 0\nv 5 5 0\nv 6 5 0\nv 5 6 0\n\
+                     f 1 2 3\n\
+                     g a\nusemtl red\nf 1 2 3\n\
+                     usemtl blue\nf 1 2 3\n\
+                     g b\nusemtl red\nf 1 2 3\n\
+                     g a\nusemtl red\nf 4 5 6\n\
+                     usemtl green\nf 1 2 3\n")]);
+        assert_eq!(shapes.len(), 6);
+        // meshes are created in order of their first face
+        assert!(materials[0].is_none());
+        let red: &Arc<Material + Send + Sync> = materials[1].as_ref().unwrap();
+        let blue: &Arc<Material + Send + Sync> = materials[3].as_ref().unwrap();
+        assert!(!Arc::ptr_eq(red, blue));
+        // group "a" with material "red" collects faces from both blocks
+        assert!(Arc::ptr_eq(red, materials[2].as_ref().unwrap()));
+        assert_eq!(bound_xy(&shapes[1]), (0.0, 0.0, 1.0, 1.0));
+        assert_eq!(bound_xy(&shapes[2]), (5.0, 5.0, 6.0, 6.0));
+        // group "b" shares the material, but not the mesh
+        assert!(Arc::ptr_eq(red, materials[4].as_ref().unwrap()));
+        assert!(materials[5].is_none());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("\"green\""));
+    }
+
+    fn bsdf_at(material: &Arc<Material + Send + Sync>, si: &SurfaceInteraction) -> Arc<Bsdf> {
+        let mut si: SurfaceInteraction = si.clone();
+        material.compute_scattering_functions(&mut si, TransportMode::Radiance, true);
+        si.bsdf.unwrap()
+    }
+
+    #[test]
+    fn mtl_materials() {
+        let dir: PathBuf = obj_dir("mtl",
+                                   &[("mesh.mtl",
+                                      "# comment\n\
+                                       newmtl matte\nKd 0.5 0.25 0.125\n\
+                                       newmtl shiny\nKd 0.2 0.2 0.2\nKs 0.3 0.3 0.3\nNs 30\n")]);
+        let mut mtl_materials: HashMap<String, MtlMaterial> = HashMap::new();
+        let mut warnings: Vec<String> = Vec::new();
+        read_mtl_file(&dir.join("mesh.mtl"), &mut mtl_materials, &mut warnings);
+        let _ = fs::remove_dir_all(&dir);
+        assert!(warnings.is_empty());
+        assert_eq!(mtl_materials.len(), 2);
+        assert_eq!(mtl_materials["matte"].kd, [0.5, 0.25, 0.125]);
+        assert_eq!(mtl_materials["matte"].ks, [0.0; 3]);
+        assert_eq!(mtl_materials["shiny"].ks, [0.3; 3]);
+        assert_eq!(mtl_materials["shiny"].ns, 30.0);
+        let (shapes, _, _) = load("mtl_surface",
+                                  &[("mesh.obj", "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n")]);
+        let si: SurfaceInteraction = hit(&shapes[0], 0.25, 0.25);
+        let wo: Vector3f = si.wo;
+        // looking straight down, the specular lobe depends strongly on roughness
+        let wi: Vector3f = wo;
+        // without "Ks" an MTL material becomes matte
+        let matte: Arc<Material + Send + Sync> = create_material(&mtl_materials["matte"],
+                                                                 &mut warnings);
+        let bsdf: Arc<Bsdf> = bsdf_at(&matte, &si);
+        assert_eq!(bsdf.num_components(BxdfType::BsdfAll as u8), 1);
+        let f: Spectrum = bsdf.f(&wo, &wi, BxdfType::BsdfAll as u8);
+        let expected: Spectrum = Spectrum::from_rgb(&[0.5, 0.25, 0.125]) / f32::consts::PI;
+        for i in 0..3 {
+            assert!((f.c[i] - expected.c[i]).abs() < 1e-5);
+        }
+        // with "Ks" it becomes plastic, "Ns" is mapped to roughness
+        let shiny: Arc<Material + Send + Sync> = create_material(&mtl_materials["shiny"],
+                                                                 &mut warnings);
+        let bsdf: Arc<Bsdf> = bsdf_at(&shiny, &si);
+        assert_eq!(bsdf.num_components(BxdfType::BsdfAll as u8), 2);
+        let plastic = |roughness: Float| -> Spectrum {
+            let material: Arc<Material + Send + Sync> =
+                Arc::new(PlasticMaterial::new(Arc::new(ConstantTexture::new(Spectrum::new(0.2))),
+                                              Arc::new(ConstantTexture::new(Spectrum::new(0.3))),
+                                              Arc::new(ConstantTexture::new(roughness)),
+                                              false));
+            bsdf_at(&material, &si).f(&wo, &wi, BxdfType::BsdfAll as u8)
+        };
+        let f: Spectrum = bsdf.f(&wo, &wi, BxdfType::BsdfAll as u8);
+        let expected: Spectrum = plastic((2.0 as Float / 32.0 as Float).sqrt());
+        assert!((f.c[0] - expected.c[0]).abs() < 1e-5);
+        assert!((f.c[0] - plastic(0.5).c[0]).abs() > 1e-3);
+        assert!(warnings.is_empty());
+    }
+}
@@ -2,9 +2,8 @@ extern crate ply_rs;
 
 // std
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufReader;
-use std::path::PathBuf;
+use std::io::BufRead;
+use std::path::Path;
 use std::string::String;
 use std::sync::Arc;
 use std::vec::Vec;
@@ -12,6 +11,7 @@ use std::vec::Vec;
 use ply_rs::parser;
 use ply_rs::ply;
 // pbrt
+use core::fileutil::open_file;
 use core::geometry::{Normal3f, Point2f, Point3f, Vector3f};
 use core::paramset::ParamSet;
 use core::pbrt::Float;
@@ -20,47 +20,68 @@ use core::texture::Texture;
 use core::transform::Transform;
 use shapes::triangle::{Triangle, TriangleMesh};
 
-pub fn create_ply_mesh(o2w: &Transform,
-                       w2o: &Transform,
-                       reverse_orientation: bool,
-                       params: &ParamSet,
-                       _float_textures: HashMap<String, Arc<Texture<Float> + Send + Sync>>,
-                       search_directory: Option<&Box<PathBuf>>)
-                       -> Vec<Arc<Shape + Send + Sync>> {
-    let mut filename: String = params.find_one_string(String::from("filename"), String::new());
-    if let Some(ref search_directory) = search_directory {
-        let mut path_buf: PathBuf = PathBuf::from("/");
-        path_buf.push(search_directory.as_ref());
-        path_buf.push(filename);
-        filename = String::from(path_buf.to_str().unwrap());
-    }
-    let result = File::open(&filename);
+/// The vertices and faces of a PLY file (in object space).
+struct PlyMesh {
+    p: Vec<Point3f>,
+    n: Vec<Normal3f>,
+    uvs: Vec<Point2f>,
+    /// Three per triangle (quads are split).
+    vertex_indices: Vec<usize>,
+    /// One per triangle (or none).
+    face_indices: Vec<usize>,
+}
+
+fn read_ply_file(filename: &str) -> Result<PlyMesh, String> {
+    // PLY files might be gzip-compressed
+    let result = open_file(Path::new(&filename));
     if result.is_err() {
-        panic!("Couldn't open PLY file {:?}", filename);
+        return Err(format!("Couldn't open PLY file {:?}", filename));
     }
-    let f = result.unwrap();
-    let mut buf_reader = BufReader::new(f);
+    let mut buf_reader: Box<BufRead> = result.unwrap();
     let p = parser::Parser::<ply::DefaultElement>::new();
     // header
     let result = p.read_header(&mut buf_reader);
     if result.is_err() {
-        panic!("Unable to read the header of PLY file  {:?}", filename);
+        return Err(format!("Unable to read the header of PLY file  {:?}", filename));
     }
     let header = result.unwrap();
-    // println!("header = {:?}", header);
+    // check the layout before reading the payload
+    match header.elements.get("vertex") {
+        Some(vertex) => {
+            for coordinate in &["x", "y", "z"] {
+                if !vertex.properties.contains_key(*coordinate) {
+                    return Err(format!("plymesh: Vertex coordinate {:?} missing in {:?}",
+                                       coordinate,
+                                       filename));
+                }
+            }
+        }
+        None => return Err(format!("plymesh: No vertices in {:?}", filename)),
+    }
+    match header.elements.get("face") {
+        Some(face) => {
+            if !face.properties.contains_key("vertex_indices") &&
+               !face.properties.contains_key("vertex_index") {
+                return Err(format!("plymesh: Faces without \"vertex_indices\" in {:?}",
+                                   filename));
+            }
+        }
+        None => {
+            return Err(format!("plymesh: No faces in {:?} (triangle strips are not supported)",
+                               filename))
+        }
+    }
     // payload
     let result = p.read_payload(&mut buf_reader, &header);
     if result.is_err() {
-        panic!("Unable to read the payload of PLY file  {:?}", filename);
+        return Err(format!("Unable to read the payload of PLY file  {:?}", filename));
     }
     let payload = result.unwrap();
-    // println!("payload = {:?}", payload);
     let mut p: Vec<Point3f> = Vec::new();
     let mut n: Vec<Normal3f> = Vec::new();
     let mut uvs: Vec<Point2f> = Vec::new();
-    let mut has_normals: bool = false;
-    let mut has_uvs: bool = false;
     let mut tm_vertex_indices: Vec<usize> = Vec::new();
+    let mut face_indices: Vec<usize> = Vec::new();
     for (name, list) in payload.into_iter() {
         match name.as_ref() {
             "vertex" => {
@@ -68,146 +89,379 @@ pub fn create_ply_mesh(o2w: &Transform,
                     let mut pnt: Point3f = Point3f::default();
                     let mut nrm: Normal3f = Normal3f::default();
                     let mut pt2: Point2f = Point2f::default();
+                    let mut has_normal: bool = false;
+                    let mut has_uv: bool = false;
                     for (name2, list2) in elem.into_iter() {
+                        // other properties (e.g. colors) are ignored
+                        let value: Float = match property_to_float(&list2) {
+                            Some(value) => value,
+                            None => continue,
+                        };
                         match name2.as_ref() {
-                            "x" => {
-                                if let ply::Property::Float(x) = list2 {
-                                    pnt.x = x;
-                                }
-                            },
-                            "y" => {
-                                if let ply::Property::Float(y) = list2 {
-                                    pnt.y = y;
-                                }
-                            },
-                            "z" => {
-                                if let ply::Property::Float(z) = list2 {
-                                    pnt.z = z;
-                                }
-                            },
+                            "x" => pnt.x = value,
+                            "y" => pnt.y = value,
+                            "z" => pnt.z = value,
                             "nx" => {
-                                has_normals = true;
-                                if let ply::Property::Float(x) = list2 {
-                                    nrm.x = x;
-                                }
-                            },
+                                has_normal = true;
+                                nrm.x = value;
+                            }
                             "ny" => {
-                                has_normals = true;
-                                if let ply::Property::Float(y) = list2 {
-                                    nrm.y = y;
-                                }
-                            },
+                                has_normal = true;
+                                nrm.y = value;
+                            }
                             "nz" => {
-                                has_normals = true;
-                                if let ply::Property::Float(z) = list2 {
-                                    nrm.z = z;
-                                }
-                            },
-                            "u" => {
-                                has_uvs = true;
-                                if let ply::Property::Float(x) = list2 {
-                                    pt2.x = x;
-                                }
-                            },
-                            "v" => {
-                                has_uvs = true;
-                                if let ply::Property::Float(y) = list2 {
-                                    pt2.y = y;
-                                }
-                            },
-                            _ => {
-                                println!("name2 = {:?}", name2);
-                                unreachable!();
-                            },
+                                has_normal = true;
+                                nrm.z = value;
+                            }
+                            "u" | "s" | "texture_u" | "texture_s" => {
+                                has_uv = true;
+                                pt2.x = value;
+                            }
+                            "v" | "t" | "texture_v" | "texture_t" => {
+                                has_uv = true;
+                                pt2.y = value;
+                            }
+                            _ => {}
                         }
                     }
                     p.push(pnt);
-                    if has_normals {
+                    if has_normal {
                         n.push(nrm);
                     }
-                    if has_uvs {
+                    if has_uv {
                         uvs.push(pt2);
                     }
                 }
             }
             "face" => {
                 for elem in list.into_iter() {
+                    let mut vertex_indices: Vec<usize> = Vec::new();
+                    let mut face_index: Option<usize> = None;
                     for (name2, list2) in elem.into_iter() {
                         match name2.as_ref() {
-                            "vertex_indices" => {
-                                if let ply::Property::ListInt(li) = list2 {
-                                    let mut vertex_indices: Vec<usize> = Vec::new();
-                                    for i in li.into_iter() {
-                                        vertex_indices.push(i as usize);
-                                    }
-                                    // println!("vertex_indices = {:?}", vertex_indices);
-                                    if vertex_indices.len() != 3 {
-                                        if vertex_indices.len() == 4 {
-                                            // handle quads (split it into 2 triangles)
-                                            let v1 = vertex_indices[0];
-                                            let v3 = vertex_indices[2];
-                                            let v4 = vertex_indices[3];
-                                            vertex_indices.push(v1);
-                                            vertex_indices.push(v3);
-                                            vertex_indices.push(v4);
-                                        } else {
-                                            panic!("plymesh: Ignoring face with {} vertices (only triangles and quads are supported!)",
-                                                   vertex_indices.len());
-                                        }
+                            "vertex_indices" | "vertex_index" => {
+                                match property_to_indices(&list2) {
+                                    Some(indices) => vertex_indices = indices,
+                                    None => {
+                                        return Err(format!("plymesh: Property {:?} in {:?} \
+                                                            isn't a list of integers",
+                                                           name2,
+                                                           filename));
                                     }
-                                    // now we can add the indices to the triangle mesh vertex indices
-                                    for vi in vertex_indices {
-                                        tm_vertex_indices.push(vi);
+                                }
+                            }
+                            "face_indices" => {
+                                match property_to_int(&list2) {
+                                    Some(index) if index >= 0 => face_index = Some(index as usize),
+                                    _ => {
+                                        return Err(format!("plymesh: Property \"face_indices\" \
+                                                            in {:?} isn't a (positive) integer",
+                                                           filename));
                                     }
                                 }
                             }
-                            _ => unreachable!(),
+                            _ => {}
+                        }
+                    }
+                    let n_triangles: usize = match vertex_indices.len() {
+                        3 => {
+                            tm_vertex_indices.extend_from_slice(&vertex_indices);
+                            1
+                        }
+                        4 => {
+                            // handle quads (split it into 2 triangles)
+                            tm_vertex_indices.extend_from_slice(&vertex_indices[0..3]);
+                            tm_vertex_indices.push(vertex_indices[0]);
+                            tm_vertex_indices.push(vertex_indices[2]);
+                            tm_vertex_indices.push(vertex_indices[3]);
+                            2
+                        }
+                        count => {
+                            return Err(format!("plymesh: Face with {} vertices in {:?} (only \
+                                                triangles and quads are supported!)",
+                                               count,
+                                               filename));
+                        }
+                    };
+                    if let Some(face_index) = face_index {
+                        for _ in 0..n_triangles {
+                            face_indices.push(face_index);
                         }
                     }
                 }
             }
-            _ => unreachable!(),
+            _ => {
+                // other elements (e.g. edges or materials) are ignored
+            }
         }
     }
-    // for i in 0..p.len() {
-    //     println!("{:?}: {:?}", i, p[i]);
-    // }
-    // println!("tm_vertex_indices = {:?}", tm_vertex_indices);
+    for vi in &tm_vertex_indices {
+        if *vi >= p.len() {
+            return Err(format!("plymesh: Vertex index {} out of range in {:?} ({} vertices)",
+                               vi,
+                               filename,
+                               p.len()));
+        }
+    }
+    if !face_indices.is_empty() && face_indices.len() != tm_vertex_indices.len() / 3 {
+        return Err(format!("plymesh: \"face_indices\" in {:?} are given for some faces only",
+                           filename));
+    }
+    if !uvs.is_empty() && uvs.len() != p.len() {
+        return Err(format!("plymesh: Number of uvs in {:?} doesn't match number of vertices",
+                           filename));
+    }
+    if !n.is_empty() && n.len() != p.len() {
+        return Err(format!("plymesh: Number of normals in {:?} doesn't match number of vertices",
+                           filename));
+    }
+    Ok(PlyMesh {
+        p: p,
+        n: n,
+        uvs: uvs,
+        vertex_indices: tm_vertex_indices,
+        face_indices: face_indices,
+    })
+}
+
+pub fn create_ply_mesh(o2w: &Transform,
+                       w2o: &Transform,
+                       reverse_orientation: bool,
+                       _params: &ParamSet,
+                       _float_textures: HashMap<String, Arc<Texture<Float> + Send + Sync>>,
+                       filename: String)
+                       -> Result<Vec<Arc<Shape + Send + Sync>>, String> {
+    let ply: PlyMesh = read_ply_file(&filename)?;
     let mut n_ws: Vec<Normal3f> = Vec::new();
-    if !n.is_empty() {
-        assert!(n.len() == p.len());
+    if !ply.n.is_empty() {
         // transform normals to world space
-        let n_normals: usize = n.len();
+        let n_normals: usize = ply.n.len();
         for i in 0..n_normals {
-            n_ws.push(o2w.transform_normal(&n[i]));
+            n_ws.push(o2w.transform_normal(&ply.n[i]));
         }
     }
     // transform mesh vertices to world space
     let mut p_ws: Vec<Point3f> = Vec::new();
-    let n_vertices: usize = p.len();
+    let n_vertices: usize = ply.p.len();
     for i in 0..n_vertices {
-        p_ws.push(o2w.transform_point(&p[i]));
-    }
-    let s_ws: Vec<Vector3f> = Vec::new(); // TODO
-    let mesh = Arc::new(TriangleMesh::new(*o2w,
-                                          *w2o,
-                                          reverse_orientation,
-                                          false, // transform_swaps_handedness
-                                          tm_vertex_indices.len() / 3, // n_triangles
-                                          tm_vertex_indices,
-                                          n_vertices,
-                                          p_ws, // in world space
-                                          s_ws, // in world space
-                                          n_ws, // in world space
-                                          uvs));
+        p_ws.push(o2w.transform_point(&ply.p[i]));
+    }
+    // PLY files don't store tangents
+    let s_ws: Vec<Vector3f> = Vec::new();
+    let mut mesh: TriangleMesh = TriangleMesh::new(*o2w,
+                                                   *w2o,
+                                                   reverse_orientation,
+                                                   false, // transform_swaps_handedness
+                                                   ply.vertex_indices.len() / 3, // n_triangles
+                                                   ply.vertex_indices,
+                                                   n_vertices,
+                                                   p_ws, // in world space
+                                                   s_ws, // in world space
+                                                   n_ws, // in world space
+                                                   ply.uvs);
+    // kept for per-face (Ptex) lookups
+    mesh.face_indices = ply.face_indices;
+    let mesh = Arc::new(mesh);
     let mut shapes: Vec<Arc<Shape + Send + Sync>> = Vec::new();
     for id in 0..mesh.n_triangles {
         let triangle = Arc::new(Triangle::new(mesh.object_to_world,
                                               mesh.world_to_object,
-                                              mesh.transform_swaps_handedness,
+                                              mesh.reverse_orientation,
                                               mesh.clone(),
                                               id));
         shapes.push(triangle.clone());
     }
-    shapes
+    Ok(shapes)
+}
+
+/// Scalar properties of any integer type.
+fn property_to_int(property: &ply::Property) -> Option<i64> {
+    match *property {
+        ply::Property::Char(v) => Some(v as i64),
+        ply::Property::UChar(v) => Some(v as i64),
+        ply::Property::Short(v) => Some(v as i64),
+        ply::Property::UShort(v) => Some(v as i64),
+        ply::Property::Int(v) => Some(v as i64),
+        ply::Property::UInt(v) => Some(v as i64),
+        _ => None,
+    }
+}
+
+/// Scalar properties of any type (ASCII and binary files might use
+/// different ones).
+fn property_to_float(property: &ply::Property) -> Option<Float> {
+    match *property {
+        ply::Property::Float(v) => Some(v as Float),
+        ply::Property::Double(v) => Some(v as Float),
+        _ => property_to_int(property).map(|v| v as Float),
+    }
+}
+
+/// Lists of (non-negative) integers of any type.
+fn property_to_indices(property: &ply::Property) -> Option<Vec<usize>> {
+    let indices: Vec<i64> = match *property {
+        ply::Property::ListChar(ref l) => l.iter().map(|v| *v as i64).collect(),
+        ply::Property::ListUChar(ref l) => l.iter().map(|v| *v as i64).collect(),
+        ply::Property::ListShort(ref l) => l.iter().map(|v| *v as i64).collect(),
+        ply::Property::ListUShort(ref l) => l.iter().map(|v| *v as i64).collect(),
+        ply::Property::ListInt(ref l) => l.iter().map(|v| *v as i64).collect(),
+        ply::Property::ListUInt(ref l) => l.iter().map(|v| *v as i64).collect(),
+        _ => return None,
+    };
+    if indices.iter().any(|i| *i < 0) {
+        return None;
+    }
+    Some(indices.into_iter().map(|i| i as usize).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::process;
+    use super::*;
+
+    fn read(test: &str, content: &[u8]) -> Result<PlyMesh, String> {
+        let dir: PathBuf = env::temp_dir().join(format!("pbrt_plymesh_{}_{}", test, process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path: PathBuf = dir.join("mesh.ply");
+        fs::File::create(&path).unwrap().write_all(content).unwrap();
+        let result: Result<PlyMesh, String> = read_ply_file(path.to_str().unwrap());
+        let _ = fs::remove_dir_all(&dir);
+        result
+    }
+
+    fn ascii_ply(vertex_properties: &[&str],
+                 vertices: &[&str],
+                 face_properties: &[&str],
+                 faces: &[&str])
+                 -> String {
+        let mut ply: String = format!("ply\nformat ascii 1.0\nelement vertex {}\n", vertices.len());
+        for property in vertex_properties {
+            ply += &format!("property {}\n", property);
+        }
+        ply += &format!("element face {}\n", faces.len());
+        for property in face_properties {
+            ply += &format!("property {}\n", property);
+        }
+        ply += "end_header\n";
+        for line in vertices.iter().chain(faces.iter()) {
+            ply += &format!("{}\n", line);
+        }
+        ply
+    }
+
+    const XYZ: [&str; 3] = ["float x", "float y", "float z"];
+    const TRIANGLE: [&str; 3] = ["0 0 0", "1 0 0", "0 1 0"];
+    const VERTEX_INDICES: [&str; 1] = ["list uchar int vertex_indices"];
+
+    #[test]
+    fn uv_property_names() {
+        let names: [(&str, &str); 4] =
+            [("u", "v"), ("s", "t"), ("texture_u", "texture_v"), ("texture_s", "texture_t")];
+        for &(u, v) in &names {
+            let u: String = format!("float {}", u);
+            let v: String = format!("float {}", v);
+            let ply: String = ascii_ply(&["float x", "float y", "float z", &u, &v],
+                                        &["0 0 0 0.5 0.25", "1 0 0 1 0.25", "0 1 0 0.5 1"],
+                                        &VERTEX_INDICES,
+                                        &["3 0 1 2"]);
+            let mesh: PlyMesh = read("uv", ply.as_bytes()).unwrap();
+            let uvs: Vec<(Float, Float)> = mesh.uvs.iter().map(|uv| (uv.x, uv.y)).collect();
+            assert_eq!(uvs, vec![(0.5, 0.25), (1.0, 0.25), (0.5, 1.0)]);
+            assert!(mesh.n.is_empty());
+        }
+        let ply: String = ascii_ply(&XYZ, &TRIANGLE, &VERTEX_INDICES, &["3 0 1 2"]);
+        let mesh: PlyMesh = read("no_uv", ply.as_bytes()).unwrap();
+        assert!(mesh.uvs.is_empty());
+    }
+
+    #[test]
+    fn normals() {
+        let ply: String = ascii_ply(&["float x", "float y", "float z", "float nx", "float ny",
+                                      "float nz"],
+                                    &["0 0 0 0 0 1", "1 0 0 0 0.6 0.8", "0 1 0 1 0 0"],
+                                    &VERTEX_INDICES,
+                                    &["3 0 1 2"]);
+        let mesh: PlyMesh = read("normals", ply.as_bytes()).unwrap();
+        let n: Vec<(Float, Float, Float)> = mesh.n.iter().map(|n| (n.x, n.y, n.z)).collect();
+        assert_eq!(n, vec![(0.0, 0.0, 1.0), (0.0, 0.6, 0.8), (1.0, 0.0, 0.0)]);
+    }
+
+    #[test]
+    fn quads_are_split() {
+        let ply: String = ascii_ply(&XYZ,
+                                    &["0 0 0", "1 0 0", "1 1 0", "0 1 0"],
+                                    &VERTEX_INDICES,
+                                    &["4 0 1 2 3", "3 3 2 1"]);
+        let mesh: PlyMesh = read("quads", ply.as_bytes()).unwrap();
+        assert_eq!(mesh.vertex_indices, vec![0, 1, 2, 0, 2, 3, 3, 2, 1]);
+        let ply: String = ascii_ply(&XYZ,
+                                    &["0 0 0", "1 0 0", "1 1 0", "0 1 0", "0 2 0"],
+                                    &VERTEX_INDICES,
+                                    &["5 0 1 2 3 4"]);
+        let message: String = read("pentagon", ply.as_bytes()).err().unwrap();
+        assert!(message.contains("Face with 5 vertices"));
+    }
+
+    #[test]
+    fn face_indices() {
+        let face_properties: [&str; 2] = ["list uchar int vertex_indices", "int face_indices"];
+        let ply: String = ascii_ply(&XYZ,
+                                    &["0 0 0", "1 0 0", "1 1 0", "0 1 0"],
+                                    &face_properties,
+                                    &["3 0 1 2 7", "4 0 1 2 3 3"]);
+        let mesh: PlyMesh = read("face_indices", ply.as_bytes()).unwrap();
+        // one per triangle, quads have two
+        assert_eq!(mesh.face_indices, vec![7, 3, 3]);
+        let ply: String = ascii_ply(&XYZ, &TRIANGLE, &VERTEX_INDICES, &["3 0 1 2"]);
+        let mesh: PlyMesh = read("no_face_indices", ply.as_bytes()).unwrap();
+        assert!(mesh.face_indices.is_empty());
+        let ply: String = ascii_ply(&XYZ, &TRIANGLE, &face_properties, &["3 0 1 2 -1"]);
+        let message: String = read("negative_face_index", ply.as_bytes()).err().unwrap();
+        assert!(message.contains("\"face_indices\""));
+    }
+
+    #[test]
+    fn big_endian_integer_properties() {
+        let mut ply: Vec<u8> = Vec::new();
+        ply.extend_from_slice(b"ply\nformat binary_big_endian 1.0\n\
+                                element vertex 4\n\
+                                property short x\nproperty int y\nproperty float z\n\
+                                property uchar u\nproperty ushort v\n\
+                                element face 2\n\
+                                property list uchar ushort vertex_indices\n\
+                                property uint face_indices\n\
+                                end_header\n");
+        for &(x, y, z, u, v) in &[(0i16, 0i32, 0.5f32, 0u8, 0u16),
+                                  (2, 0, 0.5, 1, 0),
+                                  (2, -2, 0.5, 1, 1),
+                                  (0, -2, 0.5, 0, 1)] {
+            ply.extend_from_slice(&x.to_be_bytes());
+            ply.extend_from_slice(&y.to_be_bytes());
+            ply.extend_from_slice(&z.to_be_bytes());
+            ply.push(u);
+            ply.extend_from_slice(&v.to_be_bytes());
+        }
+        ply.push(4);
+        for i in &[0u16, 1, 2, 3] {
+            ply.extend_from_slice(&i.to_be_bytes());
+        }
+        ply.extend_from_slice(&5u32.to_be_bytes());
+        ply.push(3);
+        for i in &[0u16, 2, 3] {
+            ply.extend_from_slice(&i.to_be_bytes());
+        }
+        ply.extend_from_slice(&9u32.to_be_bytes());
+        let mesh: PlyMesh = read("big_endian", &ply).unwrap();
+        let p: Vec<(Float, Float, Float)> = mesh.p.iter().map(|p| (p.x, p.y, p.z)).collect();
+        assert_eq!(p,
+                   vec![(0.0, 0.0, 0.5), (2.0, 0.0, 0.5), (2.0, -2.0, 0.5), (0.0, -2.0, 0.5)]);
+        let uvs: Vec<(Float, Float)> = mesh.uvs.iter().map(|uv| (uv.x, uv.y)).collect();
+        assert_eq!(uvs, vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+        assert_eq!(mesh.vertex_indices, vec![0, 1, 2, 0, 2, 3, 0, 2, 3]);
+        assert_eq!(mesh.face_indices, vec![5, 5, 9]);
+    }
 }
@@ -33,6 +33,8 @@ pub struct TriangleMesh {
     pub s: Vec<Vector3f>,
     /// an optional vector of paramtric (u, v) values (texture coordinates)
     pub uv: Vec<Point2f>,
+    /// an optional vector of face indices, one per triangle (can be empty)
+    pub face_indices: Vec<usize>,
     // TODO: std::shared_ptr<Texture<Float>> alphaMask, shadowAlphaMask;
     // inherited from class Shape (see shape.h)
     pub object_to_world: Transform, // TODO: not pub?
@@ -69,6 +71,7 @@ impl TriangleMesh {
             n: n,
             s: s,
             uv: uv,
+            face_indices: Vec::new(),
         }
     }
 }